@@ -19,26 +19,26 @@
     },
 };
 
-pub struct Analyzer<'reader, R: Read + Seek> {
+pub struct Analyzer<R: Read + Seek> {
     pub file_name: String,
     pub copyright: Option<Copyright>,
     pub package_name: Option<PackageName>,
     pub publisher: Option<Publisher>,
     pub installers: Vec<Installer>,
-    pub zip: Option<Zip<&'reader mut R>>,
+    pub zip: Option<Zip<R>>,
 }
 
-impl<'reader, R: Read + Seek> Analyzer<'reader, R> {
-    pub fn new(reader: &'reader mut R, file_name: &str) -> Result<Self> {
+impl<R: Read + Seek> Analyzer<R> {
+    pub fn new(mut reader: R, file_name: &str) -> Result<Self> {
         let extension = Utf8Path::new(file_name)
             .extension()
             .unwrap_or_default()
             .to_ascii_lowercase();
 
         let installers = match extension.as_str() {
-            MSI => Msi::new(reader)?.installers(),
-            MSIX | APPX => Msix::new(reader)?.installers(),
-            MSIX_BUNDLE | APPX_BUNDLE => MsixBundle::new(reader)?.installers(),
+            MSI => Msi::new(&mut reader)?.installers(),
+            MSIX | APPX => Msix::new(&mut reader)?.installers(),
+            MSIX_BUNDLE | APPX_BUNDLE => MsixBundle::new(&mut reader)?.installers(),
             ZIP => {
                 let mut scoped_zip = Zip::new(reader)?;
                 let installers = mem::take(&mut scoped_zip.installers);
@@ -49,7 +49,7 @@ pub fn new(reader: &'reader mut R, file_name: &str) -> Result<Self> {
                 });
             }
             EXE => {
-                let mut exe = Exe::new(reader)?;
+                let mut exe = Exe::new(&mut reader)?;
                 return Ok(Self {
                     installers: exe.installers(),
                     copyright: exe
@@ -76,7 +76,18 @@ pub fn new(reader: &'reader mut R, file_name: &str) -> Result<Self> {
     }
 }
 
-impl<R: Read + Seek> Default for Analyzer<'_, R> {
+/// Returns whether installers can be analyzed from a file with the given name, going by its
+/// extension in the same way as [`Analyzer::new`].
+pub fn is_supported(file_name: &str) -> bool {
+    Utf8Path::new(file_name)
+        .extension()
+        .is_some_and(|extension| {
+            [EXE, MSI, MSIX, APPX, MSIX_BUNDLE, APPX_BUNDLE, ZIP]
+                .contains(&extension.to_ascii_lowercase().as_str())
+        })
+}
+
+impl<R: Read + Seek> Default for Analyzer<R> {
     fn default() -> Self {
         Self {
             file_name: String::default(),
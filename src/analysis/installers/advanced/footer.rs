@@ -121,8 +121,6 @@ fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
 
 #[cfg(test)]
 mod tests {
-    use std::mem::offset_of;
-
     use super::Footer;
 
     #[test]
@@ -3,5 +3,5 @@
 pub mod installers;
 mod r#trait;
 
-pub use analyzer::Analyzer;
+pub use analyzer::{Analyzer, is_supported};
 pub use r#trait::Installers;
@@ -1,7 +1,7 @@
 use std::{
     fs::File,
     io,
-    io::{Read, Seek, SeekFrom},
+    io::{Read, Seek, SeekFrom, Write},
 };
 
 use anstream::stdout;
@@ -9,16 +9,36 @@
 use clap::Parser;
 use color_eyre::{Result, eyre::ensure};
 use sha2::{Digest, Sha256, digest::Output};
-use winget_types::Sha256String;
+use winget_types::{
+    ManifestType, PackageIdentifier, PackageVersion, Sha256String,
+    installer::{Installer, InstallerManifest},
+    locale::{Copyright, DefaultLocaleManifest, PackageName, Publisher},
+    version::VersionManifest,
+};
 
-use crate::{analysis::Analyzer, manifests::print_manifest};
+use crate::{
+    analysis::Analyzer,
+    download::Downloader,
+    download_file::{process_files, process_installers},
+    manifests::{
+        Manifests, Url,
+        diff::ManifestValues,
+        json::{ManifestFormat, to_json},
+        print_manifest,
+        singleton::build_singleton_string,
+    },
+};
 
 /// Analyzes a file and outputs information about it
+///
+/// If a URL is given and the file does not need to be hashed, only the parts of the file needed
+/// for analysis are fetched using HTTP range requests when the server supports them
 #[derive(Parser)]
 #[clap(visible_alias = "analyse")]
 pub struct Analyze {
-    #[arg(value_parser = is_valid_file, value_hint = clap::ValueHint::FilePath)]
-    file_path: Utf8PathBuf,
+    /// A path to a local file or an HTTP(S) URL
+    #[arg(value_parser = parse_source, value_hint = clap::ValueHint::AnyPath)]
+    source: Source,
 
     #[cfg(not(debug_assertions))]
     /// Hash the file and include it in the `InstallerSha256` field
@@ -39,33 +59,159 @@ pub struct Analyze {
     /// Skip hashing the file
     #[arg(long = "no-hash", alias = "no-sha256")]
     _no_hash: bool,
+
+    /// Output a singleton manifest with the installer instead of only the installer. The locale
+    /// fields that can't be read from the file are filled with placeholders
+    #[arg(long, requires_all = ["identifier", "version"])]
+    singleton: bool,
+
+    /// The package identifier of the singleton manifest
+    #[arg(long, requires = "singleton")]
+    identifier: Option<PackageIdentifier>,
+
+    /// The package version of the singleton manifest
+    #[arg(long, requires = "singleton")]
+    version: Option<PackageVersion>,
+
+    /// Format to output the installers or singleton manifest in
+    #[arg(long, value_enum, default_value_t)]
+    format: ManifestFormat,
+}
+
+/// The installers and package metadata read from a file.
+#[derive(Default)]
+struct Analysis {
+    installers: Vec<Installer>,
+    publisher: Option<Publisher>,
+    package_name: Option<PackageName>,
+    copyright: Option<Copyright>,
+}
+
+impl<R: Read + Seek> From<Analyzer<R>> for Analysis {
+    fn from(analyzer: Analyzer<R>) -> Self {
+        Self {
+            installers: analyzer.installers,
+            publisher: analyzer.publisher,
+            package_name: analyzer.package_name,
+            copyright: analyzer.copyright,
+        }
+    }
+}
+
+#[derive(Clone)]
+enum Source {
+    File(Utf8PathBuf),
+    Url(Url),
 }
 
 impl Analyze {
-    pub fn run(self) -> Result<()> {
-        let mut file = File::open(&self.file_path)?;
-        let file_name = self
-            .file_path
-            .file_name()
-            .unwrap_or_else(|| self.file_path.as_str());
-        let mut installers = Analyzer::new(&mut file, file_name)?.installers;
+    pub async fn run(self) -> Result<()> {
+        let analysis = match self.source {
+            Source::File(ref file_path) => self.analyze_file(file_path)?,
+            Source::Url(ref url) => self.analyze_url(url).await?,
+        };
+        let yaml = match (self.identifier, self.version) {
+            (Some(identifier), Some(version)) if self.singleton => {
+                singleton(analysis, identifier, version)?
+            }
+            _ => match analysis.installers.as_slice() {
+                [installer] => serde_yaml::to_string(installer)?,
+                installers => serde_yaml::to_string(installers)?,
+            },
+        };
+        let mut lock = stdout().lock();
+        match self.format {
+            ManifestFormat::Yaml => print_manifest(&mut lock, &yaml),
+            ManifestFormat::Json => write!(lock, "{}", to_json(&yaml)?)?,
+        }
+        Ok(())
+    }
+
+    fn analyze_file(&self, file_path: &Utf8Path) -> Result<Analysis> {
+        let mut file = File::open(file_path)?;
+        let file_name = file_path.file_name().unwrap_or_else(|| file_path.as_str());
+        let mut analysis = Analysis::from(Analyzer::new(&mut file, file_name)?);
         if self.hash {
             file.seek(SeekFrom::Start(0))?;
             let sha_256 = Sha256String::from_digest(&sha256_digest(file)?);
-            for installer in &mut installers {
+            for installer in &mut analysis.installers {
                 installer.sha_256 = sha_256.clone();
             }
         }
-        let yaml = match installers.as_slice() {
-            [installer] => serde_yaml::to_string(installer)?,
-            installers => serde_yaml::to_string(installers)?,
-        };
-        let mut lock = stdout().lock();
-        print_manifest(&mut lock, &yaml);
-        Ok(())
+        Ok(analysis)
+    }
+
+    async fn analyze_url(&self, url: &Url) -> Result<Analysis> {
+        let downloader = Downloader::new()?;
+
+        // Hashing requires the whole file so only use range requests when skipping the hash
+        if !self.hash
+            && let Some(reader) = downloader.open_ranged(url.clone()).await?
+        {
+            let file_name = reader.file_name().to_owned();
+            let last_modified = reader.last_modified();
+            let mut analysis = Analysis::from(
+                tokio::task::spawn_blocking(move || Analyzer::new(reader, &file_name)).await??,
+            );
+            process_installers(
+                url,
+                &mut analysis.installers,
+                &Sha256String::default(),
+                last_modified,
+            );
+            return Ok(analysis);
+        }
+
+        let files = downloader.download([url.clone()]).await?;
+        let mut analysis = process_files(files)
+            .into_values()
+            .map(Analysis::from)
+            .next()
+            .unwrap_or_default();
+        if !self.hash {
+            for installer in &mut analysis.installers {
+                installer.sha_256 = Sha256String::default();
+            }
+        }
+        Ok(analysis)
     }
 }
 
+/// Writes the analysis of a file as a singleton manifest.
+fn singleton(
+    analysis: Analysis,
+    identifier: PackageIdentifier,
+    version: PackageVersion,
+) -> Result<String> {
+    let default_locale = DefaultLocaleManifest {
+        package_identifier: identifier.clone(),
+        package_version: version.clone(),
+        publisher: analysis.publisher.unwrap_or_default(),
+        package_name: analysis.package_name.unwrap_or_default(),
+        copyright: analysis.copyright,
+        ..DefaultLocaleManifest::default()
+    };
+    let manifests = Manifests {
+        version: VersionManifest {
+            package_identifier: identifier.clone(),
+            package_version: version.clone(),
+            default_locale: default_locale.package_locale.clone(),
+            manifest_type: ManifestType::Version,
+            manifest_version: default_locale.manifest_version,
+        },
+        installer: InstallerManifest {
+            package_identifier: identifier,
+            package_version: version,
+            installers: analysis.installers,
+            ..InstallerManifest::default()
+        },
+        default_locale,
+        locales: Vec::new(),
+    };
+
+    build_singleton_string(&ManifestValues::new(&manifests), None)
+}
+
 fn sha256_digest<R: Read>(mut reader: R) -> io::Result<Output<Sha256>> {
     let mut digest = Sha256::new();
     let mut buffer = [0; 1 << 13];
@@ -81,6 +227,19 @@ fn sha256_digest<R: Read>(mut reader: R) -> io::Result<Output<Sha256>> {
     Ok(digest.finalize())
 }
 
+fn parse_source(input: &str) -> Result<Source> {
+    const HTTP: &str = "http";
+    const HTTPS: &str = "https";
+
+    if let Ok(url) = input.parse::<Url>()
+        && matches!(url.scheme(), HTTP | HTTPS)
+    {
+        return Ok(Source::Url(url));
+    }
+
+    is_valid_file(input).map(Source::File)
+}
+
 fn is_valid_file(path: &str) -> Result<Utf8PathBuf> {
     let path = Utf8Path::new(path);
     ensure!(path.exists(), "{path} does not exist");
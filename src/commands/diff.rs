@@ -0,0 +1,110 @@
+use std::io::Write;
+
+use anstream::println;
+use camino::Utf8PathBuf;
+use clap::Parser;
+use color_eyre::Result;
+use owo_colors::OwoColorize;
+use secrecy::SecretString;
+use serde::Serialize;
+use tokio::try_join;
+use winget_types::{PackageIdentifier, PackageVersion};
+
+use crate::{
+    github::{GitHubError, client::GitHub},
+    manifests::diff::{FieldDiff, ManifestDiff},
+    repository::{LocalCheckout, ManifestRepository},
+    token::TokenManager,
+};
+
+/// Show the fields that changed between two versions of a package
+///
+/// Installers are matched between the versions by their URL, architecture and scope, and every
+/// field of every manifest that was added, removed or changed is listed.
+#[derive(Parser)]
+pub struct Diff {
+    /// The package's unique identifier
+    #[arg()]
+    package_identifier: PackageIdentifier,
+
+    /// The version to compare from
+    #[arg()]
+    from: PackageVersion,
+
+    /// The version to compare to
+    #[arg()]
+    to: PackageVersion,
+
+    /// Output the changed fields as JSON
+    #[arg(long)]
+    json: bool,
+
+    /// Local clone of the upstream repository to read manifests from instead of using the GitHub
+    /// API
+    #[arg(long, env = "KOMAC_CHECKOUT", value_hint = clap::ValueHint::DirPath)]
+    checkout: Option<Utf8PathBuf>,
+
+    /// GitHub personal access token with the `public_repo` scope
+    #[arg(short, long, env = "GITHUB_TOKEN", hide_env_values = true)]
+    token: Option<SecretString>,
+}
+
+#[derive(Serialize)]
+struct DiffOutput<'a> {
+    identifier: &'a PackageIdentifier,
+    from: &'a PackageVersion,
+    to: &'a PackageVersion,
+    changes: &'a [FieldDiff],
+}
+
+impl Diff {
+    pub async fn run(mut self) -> Result<()> {
+        let diff = if let Some(checkout) = &self.checkout {
+            self.diff(&LocalCheckout::open(checkout).await?).await?
+        } else {
+            let token_manager = TokenManager::handle(self.token.take()).await?;
+            self.diff(&GitHub::new(&token_manager)?).await?
+        };
+
+        if self.json {
+            let mut stdout = anstream::stdout().lock();
+            serde_json::to_writer_pretty(
+                &mut stdout,
+                &DiffOutput {
+                    identifier: &self.package_identifier,
+                    from: &self.from,
+                    to: &self.to,
+                    changes: &diff.fields,
+                },
+            )?;
+            writeln!(stdout)?;
+            return Ok(());
+        }
+
+        println!(
+            "{} {} → {}",
+            self.package_identifier.bold(),
+            self.from,
+            self.to
+        );
+        if diff.is_empty() {
+            println!("No fields changed");
+        } else {
+            diff.print();
+        }
+
+        Ok(())
+    }
+
+    async fn diff<R: ManifestRepository>(
+        &self,
+        repository: &R,
+    ) -> Result<ManifestDiff, GitHubError> {
+        let (from, to) = try_join!(
+            repository.get_manifests(&self.package_identifier, &self.from),
+            repository.get_manifests(&self.package_identifier, &self.to),
+        )?;
+
+        Ok(ManifestDiff::new(&from, &to))
+    }
+}
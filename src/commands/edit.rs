@@ -0,0 +1,429 @@
+use std::{
+    num::{NonZeroU32, NonZeroUsize},
+    ops::{Bound, RangeBounds},
+    str::FromStr,
+};
+
+use anstream::println;
+use camino::Utf8PathBuf;
+use clap::Parser;
+use color_eyre::eyre::{Result, bail};
+use indicatif::ProgressBar;
+use itertools::Itertools;
+use owo_colors::OwoColorize;
+use secrecy::SecretString;
+use thiserror::Error;
+use tracing::warn;
+use winget_types::{PackageIdentifier, PackageVersion, PackageVersionError};
+
+use crate::{
+    commands::utils::{SPINNER_TICK_RATE, check_permissions, write_changes_to_dir},
+    github::{
+        client::GitHub,
+        upstream::Upstream,
+        utils::{PackagePath, pull_request::pr_changes},
+    },
+    manifests::{
+        diff::{ManifestDiff, ManifestValues},
+        edit::{EditError, FieldEdit, apply},
+        json::ManifestFormat,
+    },
+    prompts::text::confirm_prompt,
+    repository::{CheckoutArgs, ManifestRepository, VersionsEdit, parse_manifests},
+    token::TokenManager,
+};
+
+/// Set fields in the manifests of existing versions of a package
+///
+/// Locale fields are set in the default locale and in any other locale that repeated its value, and
+/// installer fields are set at the root of the installer manifest and in any installer that
+/// overrides them. Every changed field is reported before the manifests are written or submitted.
+/// Versions whose manifest version doesn't have a field that is being set are skipped.
+#[derive(Parser)]
+pub struct Edit {
+    /// The package's unique identifier
+    #[arg()]
+    package_identifier: PackageIdentifier,
+
+    /// A field to set, such as `License=MIT`. An empty value removes the field
+    #[arg(long = "set", value_name = "FIELD=VALUE", required = true)]
+    edits: Vec<FieldEdit>,
+
+    /// The versions to edit, such as `1.2.3`, `1.0..2.0`, `1.0..=2.0`, `1.0..` or `..2.0`
+    /// [default: every version]
+    #[arg(long, value_name = "RANGE")]
+    versions: Option<VersionRange>,
+
+    /// The most files to change in one pull request. Edits to more files are split into several
+    /// pull requests of whole versions
+    ///
+    /// The default of 100 is Komac's own choice rather than a limit of winget-pkgs, as pull
+    /// requests that change many files are slow to validate and review.
+    #[arg(long, value_name = "FILES", default_value_t = NonZeroUsize::new(100).unwrap())]
+    max_files_per_pr: NonZeroUsize,
+
+    /// List of issues that editing these versions would resolve
+    #[arg(long)]
+    resolves: Vec<NonZeroU32>,
+
+    /// Automatically submit a pull request
+    #[arg(short, long)]
+    submit: bool,
+
+    /// Directory to output the manifests to
+    #[arg(short, long, env = "OUTPUT_DIRECTORY", value_hint = clap::ValueHint::DirPath)]
+    output: Option<Utf8PathBuf>,
+
+    /// Open pull request link automatically
+    #[arg(long, env = "OPEN_PR")]
+    open_pr: bool,
+
+    /// Run without writing or submitting
+    #[arg(long, env = "DRY_RUN")]
+    dry_run: bool,
+
+    #[command(flatten)]
+    checkout: CheckoutArgs,
+
+    /// GitHub personal access token with the `public_repo` scope
+    #[arg(short, long, env = "GITHUB_TOKEN", hide_env_values = true)]
+    token: Option<SecretString>,
+}
+
+impl Edit {
+    pub async fn run(mut self) -> Result<()> {
+        let token_manager = TokenManager::handle(self.token.take()).await?;
+        let github = GitHub::new(&token_manager)?;
+
+        match self.checkout.open(&github).await? {
+            Some(checkout) => self.edit(&checkout).await,
+            None => {
+                if !self.dry_run {
+                    check_permissions(&github).await?;
+                }
+                self.edit(&github).await
+            }
+        }
+    }
+
+    async fn edit<R: ManifestRepository>(&self, repository: &R) -> Result<()> {
+        let identifier = &self.package_identifier;
+        let versions = repository
+            .get_versions(identifier)
+            .await?
+            .into_iter()
+            .filter(|version| {
+                self.versions
+                    .as_ref()
+                    .is_none_or(|range| range.contains(version))
+            })
+            .collect::<Vec<_>>();
+
+        if versions.is_empty() {
+            bail!(
+                "No versions of {identifier} in {} match the given range",
+                Upstream::get()
+            );
+        }
+
+        let mut edited = Vec::new();
+        for version in versions {
+            let files = repository.get_manifest_files(identifier, &version).await?;
+            let package_path = PackagePath::new(identifier, Some(&version), None);
+            let mut manifests = parse_manifests(identifier, &package_path, &files)?;
+            let previous = ManifestValues::new(&manifests);
+
+            // The manifests are written as they were before the edits too, so that only the files
+            // that the edits changed are submitted
+            let unedited = pr_changes()
+                .package_identifier(identifier)
+                .manifests(&manifests)
+                .package_path(&package_path)
+                .original(&files)
+                .create()?;
+            match apply(&mut manifests, &self.edits) {
+                Err(error @ EditError::FieldVersion { .. }) => {
+                    warn!(
+                        "Skipping {identifier} {version}: {error}. Migrate it to a newer manifest \
+                         version with `komac migrate` first"
+                    );
+                    continue;
+                }
+                result => result?,
+            }
+
+            let diff = ManifestDiff::between(&previous, &ManifestValues::new(&manifests));
+            if diff.is_empty() {
+                continue;
+            }
+
+            println!("{}", format!("{identifier} {version}").bold());
+            diff.print();
+            println!();
+
+            let changes = pr_changes()
+                .package_identifier(identifier)
+                .manifests(&manifests)
+                .package_path(&package_path)
+                .original(&files)
+                .create()?
+                .into_iter()
+                .filter(|change| !unedited.contains(change))
+                .collect::<Vec<_>>();
+            edited.push((version, changes));
+        }
+
+        if edited.is_empty() {
+            println!("No versions of {identifier} needed to be edited");
+            return Ok(());
+        }
+
+        if let Some(output) = &self.output {
+            let changes = edited
+                .iter()
+                .flat_map(|(_, changes)| changes.iter().cloned())
+                .collect::<Vec<_>>();
+            write_changes_to_dir(&changes, output, ManifestFormat::Yaml).await?;
+            println!(
+                "{} written all manifest files to {output}",
+                "Successfully".green()
+            );
+        }
+
+        if self.dry_run {
+            return Ok(());
+        }
+
+        let should_submit = self.submit
+            || confirm_prompt(&format!(
+                "Would you like to make a pull request to edit {} of {identifier}?",
+                version_count(edited.len())
+            ))?;
+
+        if !should_submit {
+            return Ok(());
+        }
+
+        let batches = batch(edited, self.max_files_per_pr.get());
+        if batches.len() > 1 {
+            println!(
+                "The edits change more than {} files, so they will be split into {} pull requests",
+                self.max_files_per_pr,
+                batches.len()
+            );
+        }
+
+        for batch in batches {
+            let (versions, changes) = batch.into_iter().fold(
+                (Vec::new(), Vec::new()),
+                |(mut versions, mut changes), (version, version_changes)| {
+                    versions.push(version);
+                    changes.extend(version_changes);
+                    (versions, changes)
+                },
+            );
+            let summary = self.summary(versions.len());
+
+            // Create an indeterminate progress bar to show as a pull request is being created
+            let pr_progress = ProgressBar::new_spinner().with_message(format!(
+                "Creating a pull request to edit {} of {identifier}",
+                version_count(versions.len())
+            ));
+            pr_progress.enable_steady_tick(SPINNER_TICK_RATE);
+
+            let submission = repository
+                .edit_versions(
+                    VersionsEdit::builder()
+                        .identifier(identifier)
+                        .versions(&versions)
+                        .changes(changes)
+                        .summary(&summary)
+                        .issue_resolves(&self.resolves)
+                        .build(),
+                )
+                .await?;
+
+            pr_progress.finish_and_clear();
+
+            submission.print_success();
+
+            if self.open_pr
+                && let Some(url) = submission.url()
+            {
+                open::that(url.as_str())?;
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Describes the edits in the body of a pull request, such as ``Sets `License` to `MIT` in 3
+    /// versions of Package.Identifier``.
+    fn summary(&self, versions: usize) -> String {
+        let edits = self
+            .edits
+            .iter()
+            .map(|edit| match &edit.value {
+                Some(value) => format!("Sets `{}` to `{value}`", edit.field),
+                None => format!("Removes `{}`", edit.field),
+            })
+            .join(", ");
+        format!(
+            "{edits} in {} of {}",
+            version_count(versions),
+            self.package_identifier
+        )
+    }
+}
+
+/// Splits the changes to each version into batches of whole versions that each change at most
+/// `max_files` files, apart from a version that changes more files than that on its own.
+fn batch<T>(
+    edited: Vec<(PackageVersion, Vec<T>)>,
+    max_files: usize,
+) -> Vec<Vec<(PackageVersion, Vec<T>)>> {
+    let mut batches = Vec::<Vec<(PackageVersion, Vec<T>)>>::new();
+    let mut files = 0;
+    for (version, changes) in edited {
+        match batches.last_mut() {
+            Some(batch) if files + changes.len() <= max_files => {
+                files += changes.len();
+                batch.push((version, changes));
+            }
+            _ => {
+                files = changes.len();
+                batches.push(vec![(version, changes)]);
+            }
+        }
+    }
+    batches
+}
+
+fn version_count(versions: usize) -> String {
+    if versions == 1 {
+        String::from("1 version")
+    } else {
+        format!("{versions} versions")
+    }
+}
+
+/// A range of package versions, such as `1.0..2.0`, or a single version.
+#[derive(Clone, Debug, Eq, PartialEq)]
+struct VersionRange {
+    start: Bound<PackageVersion>,
+    end: Bound<PackageVersion>,
+}
+
+impl VersionRange {
+    fn contains(&self, version: &PackageVersion) -> bool {
+        RangeBounds::contains(&(self.start.as_ref(), self.end.as_ref()), version)
+    }
+}
+
+#[derive(Debug, Error)]
+enum VersionRangeError {
+    #[error("A version range must have a start or an end")]
+    Unbounded,
+    #[error(transparent)]
+    Version(#[from] PackageVersionError),
+}
+
+impl FromStr for VersionRange {
+    type Err = VersionRangeError;
+
+    fn from_str(range: &str) -> Result<Self, Self::Err> {
+        let Some((start, end)) = range.split_once("..") else {
+            let version = range.trim().parse::<PackageVersion>()?;
+            return Ok(Self {
+                start: Bound::Included(version.clone()),
+                end: Bound::Included(version),
+            });
+        };
+
+        let (end, inclusive) = end
+            .strip_prefix('=')
+            .map_or((end, false), |end| (end, true));
+        let (start, end) = (start.trim(), end.trim());
+        if start.is_empty() && end.is_empty() {
+            return Err(VersionRangeError::Unbounded);
+        }
+
+        Ok(Self {
+            start: match start {
+                "" => Bound::Unbounded,
+                start => Bound::Included(start.parse()?),
+            },
+            end: match end {
+                "" => Bound::Unbounded,
+                end if inclusive => Bound::Included(end.parse()?),
+                end => Bound::Excluded(end.parse()?),
+            },
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rstest::rstest;
+    use winget_types::PackageVersion;
+
+    use super::{VersionRange, batch};
+
+    #[rstest]
+    #[case("1.2.3", &["1.2.3"])]
+    #[case("1.0..2.0", &["1.0", "1.2.3"])]
+    #[case("1.0..=2.0", &["1.0", "1.2.3", "2.0"])]
+    #[case("1.2..", &["1.2.3", "2.0", "10.0"])]
+    #[case("..2.0", &["0.9", "1.0", "1.2.3"])]
+    #[case("..=1.0", &["0.9", "1.0"])]
+    fn version_range_contains(#[case] range: &str, #[case] expected: &[&str]) {
+        const VERSIONS: [&str; 5] = ["0.9", "1.0", "1.2.3", "2.0", "10.0"];
+
+        let range = range.parse::<VersionRange>().unwrap();
+        let mut contained = VERSIONS
+            .into_iter()
+            .map(|version| version.parse::<PackageVersion>().unwrap())
+            .filter(|version| range.contains(version))
+            .collect::<Vec<_>>();
+        contained.sort();
+
+        let mut expected = expected
+            .iter()
+            .map(|version| version.parse::<PackageVersion>().unwrap())
+            .collect::<Vec<_>>();
+        expected.sort();
+
+        assert_eq!(contained, expected);
+    }
+
+    #[test]
+    fn rejects_unbounded_version_range() {
+        assert!("..".parse::<VersionRange>().is_err());
+    }
+
+    #[test]
+    fn batches_whole_versions() {
+        let edited = [3, 40, 40, 30, 120, 5]
+            .into_iter()
+            .enumerate()
+            .map(|(version, files)| {
+                (
+                    PackageVersion::new(version.to_string()).unwrap(),
+                    vec![(); files],
+                )
+            })
+            .collect::<Vec<_>>();
+
+        let batches = batch(edited, 100)
+            .into_iter()
+            .map(|batch| {
+                batch
+                    .iter()
+                    .map(|(_, files)| files.len())
+                    .collect::<Vec<_>>()
+            })
+            .collect::<Vec<_>>();
+
+        assert_eq!(batches, [vec![3, 40, 40], vec![30], vec![120], vec![5]]);
+    }
+}
@@ -0,0 +1,163 @@
+use std::fs;
+
+use anstream::println;
+use camino::{Utf8Path, Utf8PathBuf};
+use clap::Parser;
+use color_eyre::{Result, eyre::bail};
+use owo_colors::OwoColorize;
+use secrecy::SecretString;
+use winget_types::{PackageIdentifier, PackageVersion};
+
+use crate::{
+    commands::utils::{read_manifest_files, write_changes_to_dir},
+    github::client::GitHub,
+    manifests::{
+        diagnostic::print_diagnostics, format::format, json::ManifestFormat, print_changes,
+        validation::ManifestFile,
+    },
+    repository::{LocalCheckout, ManifestRepository},
+    token::TokenManager,
+};
+
+/// Rewrite manifests in the layout that Komac writes them in
+///
+/// Fields are ordered as in the schema, quoting and line endings are normalised, and values that
+/// are the same for every installer are moved to the root of the installer manifest. Comments
+/// other than those at the top of a manifest are removed.
+#[derive(Parser)]
+pub struct Fmt {
+    /// A directory of manifests which is searched recursively, a manifest file, or a package
+    /// identifier in the upstream repository
+    #[arg(value_name = "DIRECTORY|IDENTIFIER", value_hint = clap::ValueHint::AnyPath)]
+    target: String,
+
+    /// The package version to format when formatting a package from the upstream repository
+    /// [default: the latest version]
+    #[arg(short = 'v', long = "version")]
+    package_version: Option<PackageVersion>,
+
+    /// Check that the manifests are formatted without changing them, exiting with a non-zero code
+    /// if any are not
+    #[arg(long)]
+    check: bool,
+
+    /// Directory to write the formatted manifests of a package from the upstream repository to.
+    /// They are printed otherwise
+    #[arg(short, long, value_hint = clap::ValueHint::DirPath)]
+    output: Option<Utf8PathBuf>,
+
+    /// Local clone of the upstream repository to read manifests from instead of using the GitHub
+    /// API
+    #[arg(long, env = "KOMAC_CHECKOUT", value_hint = clap::ValueHint::DirPath)]
+    checkout: Option<Utf8PathBuf>,
+
+    /// GitHub personal access token with the `public_repo` scope
+    #[arg(short, long, env = "GITHUB_TOKEN", hide_env_values = true)]
+    token: Option<SecretString>,
+}
+
+impl Fmt {
+    pub async fn run(mut self) -> Result<()> {
+        let path = Utf8Path::new(&self.target);
+        let is_local = path.exists();
+        let files = if is_local {
+            read_manifest_files(path)?
+        } else if let Some(checkout) = &self.checkout {
+            self.get_manifest_files(&LocalCheckout::open(checkout).await?)
+                .await?
+        } else {
+            let token_manager = TokenManager::handle(self.token.take()).await?;
+            self.get_manifest_files(&GitHub::new(&token_manager)?)
+                .await?
+        };
+
+        let mut diagnostics = Vec::new();
+        let mut formatted = Vec::new();
+        for file in &files {
+            match format(file) {
+                Ok(text) => formatted.push((file, text)),
+                Err(diagnostic) => diagnostics.push(diagnostic),
+            }
+        }
+        let unformatted = formatted
+            .iter()
+            .filter(|(file, text)| file.text != *text)
+            .collect::<Vec<_>>();
+
+        if !diagnostics.is_empty() {
+            print_diagnostics(anstream::stdout().lock(), &diagnostics)?;
+        }
+
+        if self.check {
+            for (file, _) in &unformatted {
+                println!("{} {}", "Would reformat".yellow(), file.path);
+            }
+            if !unformatted.is_empty() || !diagnostics.is_empty() {
+                bail!(
+                    "{} of {} files are not formatted",
+                    unformatted.len() + diagnostics.len(),
+                    files.len()
+                );
+            }
+            println!("{} files are formatted", files.len());
+            return Ok(());
+        }
+
+        if is_local {
+            for (file, text) in &unformatted {
+                fs::write(&file.path, text)?;
+                println!("{} {}", "Reformatted".green(), file.path);
+            }
+            println!(
+                "{} reformatted, {} already formatted",
+                unformatted.len(),
+                formatted.len() - unformatted.len()
+            );
+        } else if let Some(output) = &self.output {
+            let changes = formatted
+                .iter()
+                .map(|(file, text)| (file.path.to_string(), text.clone()))
+                .collect::<Vec<_>>();
+            write_changes_to_dir(&changes, output, ManifestFormat::Yaml).await?;
+            println!(
+                "{} written all manifest files to {output}",
+                "Successfully".green()
+            );
+        } else {
+            print_changes(formatted.iter().map(|(_, text)| text));
+        }
+
+        if !diagnostics.is_empty() {
+            bail!(
+                "{} of {} files could not be formatted",
+                diagnostics.len(),
+                files.len()
+            );
+        }
+
+        Ok(())
+    }
+
+    /// Gets the manifest files of the latest or specified version of a package.
+    async fn get_manifest_files<R: ManifestRepository>(
+        &self,
+        repository: &R,
+    ) -> Result<Vec<ManifestFile>> {
+        let identifier = self.target.parse::<PackageIdentifier>()?;
+        let mut versions = repository.get_versions(&identifier).await?;
+        let version = self
+            .package_version
+            .clone()
+            .unwrap_or_else(|| versions.pop_last().unwrap_or_else(|| unreachable!()));
+
+        Ok(repository
+            .get_manifest_files(&identifier, &version)
+            .await?
+            .into_iter()
+            .map(|file| ManifestFile {
+                path: Utf8PathBuf::from(file.name),
+                text: file.text,
+            })
+            .collect())
+    }
+}
@@ -0,0 +1,123 @@
+use std::fs;
+
+use anstream::println;
+use camino::Utf8PathBuf;
+use clap::Parser;
+use color_eyre::{Result, eyre::bail};
+use owo_colors::OwoColorize;
+
+use crate::{
+    commands::utils::yaml_file_paths,
+    manifests::{
+        diagnostic::print_diagnostics,
+        lint::{LintRules, Rule, RuleLevel, apply_fixes, lint, parse_rule_level},
+        validation::ManifestFile,
+    },
+};
+
+/// Check manifests against the style conventions of winget-pkgs moderators
+///
+/// Each rule can be set to off, warning or error in the configuration file under `lint.rules`, or
+/// with `--rule`. Exits with a non-zero code if any problem is an error.
+#[derive(Parser)]
+pub struct Lint {
+    /// A manifest file, or a directory of manifests which is searched recursively
+    #[arg(value_hint = clap::ValueHint::AnyPath, required_unless_present = "list_rules")]
+    path: Option<Utf8PathBuf>,
+
+    /// Fix the problems that can be fixed safely, writing the changes back to the files
+    #[arg(long)]
+    fix: bool,
+
+    /// Set the level of a rule, overriding the configuration file
+    #[arg(long = "rule", value_name = "RULE=LEVEL", value_parser = parse_rule_level)]
+    rules: Vec<(Rule, RuleLevel)>,
+
+    /// List the rules with their IDs and levels
+    #[arg(long, exclusive = true)]
+    list_rules: bool,
+}
+
+impl Lint {
+    pub fn run(self) -> Result<()> {
+        let mut rules = LintRules::get().clone();
+        for (rule, level) in self.rules {
+            rules.insert(rule, level);
+        }
+
+        if self.list_rules {
+            for rule in Rule::all() {
+                println!(
+                    "{:<24} {:<7} {}{}",
+                    rule.id().bold(),
+                    rules.level(rule),
+                    rule.description(),
+                    if rule.is_fixable() { " (fixable)" } else { "" }
+                );
+            }
+            return Ok(());
+        }
+
+        let Some(path) = self.path else {
+            return Ok(());
+        };
+
+        let paths = yaml_file_paths(&path)?;
+        if paths.is_empty() {
+            bail!("No manifests were found in {path}");
+        }
+
+        let mut diagnostics = Vec::new();
+        let mut fixed = 0;
+        let mut fixable = 0;
+        for path in paths {
+            let text = fs::read_to_string(&path)?;
+            let file = ManifestFile { path, text };
+            let findings = lint(&file, &rules);
+
+            let fixes = findings
+                .iter()
+                .filter_map(|finding| finding.fix.as_ref())
+                .collect::<Vec<_>>();
+            let fixed_text = (self.fix && !fixes.is_empty())
+                .then(|| apply_fixes(&file, fixes.iter().copied()))
+                .flatten();
+
+            if let Some(text) = fixed_text {
+                fs::write(&file.path, text)?;
+                fixed += fixes.len();
+                diagnostics.extend(
+                    findings
+                        .into_iter()
+                        .filter(|finding| finding.fix.is_none())
+                        .map(|finding| finding.diagnostic),
+                );
+            } else {
+                fixable += fixes.len();
+                diagnostics.extend(findings.into_iter().map(|finding| finding.diagnostic));
+            }
+        }
+
+        print_diagnostics(anstream::stdout().lock(), &diagnostics)?;
+
+        if fixed > 0 {
+            println!(
+                "Fixed {fixed} {}",
+                if fixed == 1 { "problem" } else { "problems" }
+            );
+        }
+        if fixable > 0 && !self.fix {
+            println!(
+                "{fixable} {} can be fixed with {}",
+                if fixable == 1 { "problem" } else { "problems" },
+                "--fix".bold()
+            );
+        }
+
+        if diagnostics.iter().any(|diagnostic| diagnostic.is_error()) {
+            bail!("Linting failed");
+        }
+
+        Ok(())
+    }
+}
@@ -1,12 +1,17 @@
 use std::io::Write;
 
+use camino::Utf8PathBuf;
 use clap::{Args, Parser};
 use color_eyre::Result;
 use owo_colors::OwoColorize;
 use secrecy::SecretString;
 use winget_types::PackageIdentifier;
 
-use crate::{github::client::GitHub, token::TokenManager};
+use crate::{
+    github::client::GitHub,
+    repository::{LocalCheckout, ManifestRepository},
+    token::TokenManager,
+};
 
 /// Lists all versions for a given package
 #[derive(Parser)]
@@ -22,6 +27,10 @@ pub struct ListVersions {
     #[arg(long)]
     count: bool,
 
+    /// Local clone of the upstream repository to read versions from instead of using the GitHub API
+    #[arg(long, env = "KOMAC_CHECKOUT", value_hint = clap::ValueHint::DirPath)]
+    checkout: Option<Utf8PathBuf>,
+
     /// GitHub personal access token with the `public_repo` scope
     #[arg(short, long, env = "GITHUB_TOKEN", hide_env_values = true)]
     token: Option<SecretString>,
@@ -45,10 +54,17 @@ struct OutputType {
 
 impl ListVersions {
     pub async fn run(self) -> Result<()> {
-        let token_manager = TokenManager::handle(self.token).await?;
-        let github = GitHub::new(token_manager)?;
-
-        let versions = github.get_versions(&self.package_identifier).await?;
+        let versions = if let Some(checkout) = self.checkout {
+            LocalCheckout::open(&checkout)
+                .await?
+                .get_versions(&self.package_identifier)
+                .await?
+        } else {
+            let token_manager = TokenManager::handle(self.token).await?;
+            GitHub::new(token_manager)?
+                .get_versions(&self.package_identifier)
+                .await?
+        };
 
         let mut stdout_lock = anstream::stdout().lock();
         match self.output_type {
@@ -0,0 +1,213 @@
+use anstream::println;
+use camino::{Utf8Path, Utf8PathBuf};
+use clap::Parser;
+use color_eyre::eyre::Result;
+use indicatif::ProgressBar;
+use owo_colors::OwoColorize;
+use secrecy::SecretString;
+use winget_types::{ManifestVersion, PackageIdentifier, PackageVersion};
+
+use crate::{
+    commands::utils::{
+        SPINNER_TICK_RATE, SubmitOption, check_permissions, read_manifest_files,
+        write_changes_to_dir,
+    },
+    github::client::GitHub,
+    manifests::{
+        json::ManifestFormat,
+        migration::{Migration, migrate},
+        validation::ManifestFile,
+    },
+    repository::{CheckoutArgs, ManifestRepository, VersionAddition},
+    token::TokenManager,
+};
+
+/// Migrate a package version's manifests to another manifest version
+///
+/// Fields that changed between manifest versions are converted, and every changed field is
+/// reported before the manifests are written or submitted.
+#[derive(Parser)]
+pub struct Migrate {
+    /// A package identifier in the upstream repository, or a directory containing the manifests of
+    /// a single package version
+    #[arg(value_name = "IDENTIFIER|DIRECTORY", value_hint = clap::ValueHint::AnyPath)]
+    target: String,
+
+    /// The package version to migrate when migrating a package from the upstream repository
+    /// [default: the latest version]
+    #[arg(short = 'v', long = "version")]
+    package_version: Option<PackageVersion>,
+
+    /// The manifest version to migrate to
+    #[arg(long, default_value_t = ManifestVersion::DEFAULT)]
+    to: ManifestVersion,
+
+    /// Submit a pull request with the migrated manifests. Manifests migrated from a directory are
+    /// otherwise written back to it
+    #[arg(short, long)]
+    submit: bool,
+
+    /// Directory to output the manifests to
+    #[arg(short, long, env = "OUTPUT_DIRECTORY", value_hint = clap::ValueHint::DirPath)]
+    output: Option<Utf8PathBuf>,
+
+    /// Open pull request link automatically
+    #[arg(long, env = "OPEN_PR")]
+    open_pr: bool,
+
+    /// Run without writing or submitting
+    #[arg(long, env = "DRY_RUN")]
+    dry_run: bool,
+
+    #[command(flatten)]
+    checkout: CheckoutArgs,
+
+    /// GitHub personal access token with the `public_repo` scope
+    #[arg(short, long, env = "GITHUB_TOKEN", hide_env_values = true)]
+    token: Option<SecretString>,
+}
+
+impl Migrate {
+    pub async fn run(mut self) -> Result<()> {
+        let directory = Utf8Path::new(&self.target);
+        let local_migration = if directory.is_dir() {
+            let migration = migrate(&read_manifest_files(directory)?, self.to)?;
+            if !self.report(&migration) {
+                return Ok(());
+            }
+
+            if !self.submit {
+                if !self.dry_run {
+                    let output = self.output.as_deref().unwrap_or(directory);
+                    write_changes_to_dir(&migration.changes, output, ManifestFormat::Yaml).await?;
+                    println!(
+                        "{} written all manifest files to {output}",
+                        "Successfully".green()
+                    );
+                }
+                return Ok(());
+            }
+
+            Some(migration)
+        } else {
+            None
+        };
+
+        let token_manager = TokenManager::handle(self.token.take()).await?;
+        let github = GitHub::new(&token_manager)?;
+
+        match self.checkout.open(&github).await? {
+            Some(checkout) => self.submit(local_migration, &checkout).await,
+            None => {
+                if !self.dry_run {
+                    check_permissions(&github).await?;
+                }
+                self.submit(local_migration, &github).await
+            }
+        }
+    }
+
+    async fn submit<R: ManifestRepository>(
+        &self,
+        migration: Option<Migration>,
+        repository: &R,
+    ) -> Result<()> {
+        let mut migration = match migration {
+            Some(migration) => migration,
+            None => {
+                let identifier = self.target.parse::<PackageIdentifier>()?;
+                let mut versions = repository.get_versions(&identifier).await?;
+                let version = match &self.package_version {
+                    Some(version) => version.clone(),
+                    None => versions.pop_last().unwrap_or_else(|| unreachable!()),
+                };
+
+                let files = repository
+                    .get_manifest_files(&identifier, &version)
+                    .await?
+                    .into_iter()
+                    .map(|file| ManifestFile {
+                        path: Utf8PathBuf::from(file.name),
+                        text: file.text,
+                    })
+                    .collect::<Vec<_>>();
+
+                let migration = migrate(&files, self.to)?;
+                if !self.report(&migration) {
+                    return Ok(());
+                }
+                migration
+            }
+        };
+
+        let identifier = &migration.identifier;
+        let version = &migration.version;
+
+        let submit_option = SubmitOption::prompt(
+            &mut migration.changes,
+            identifier,
+            version,
+            self.submit,
+            self.dry_run,
+        )?;
+
+        if let Some(output) = &self.output {
+            write_changes_to_dir(&migration.changes, output, ManifestFormat::Yaml).await?;
+            println!(
+                "{} written all manifest files to {output}",
+                "Successfully".green()
+            );
+        }
+
+        if submit_option.is_exit() {
+            return Ok(());
+        }
+
+        let versions = repository.get_versions(identifier).await.ok();
+
+        // Create an indeterminate progress bar to show as a pull request is being created
+        let pr_progress = ProgressBar::new_spinner().with_message(format!(
+            "Creating a pull request to migrate {identifier} {version} to manifest version {}",
+            self.to
+        ));
+        pr_progress.enable_steady_tick(SPINNER_TICK_RATE);
+
+        let submission = repository
+            .add_version(
+                VersionAddition::builder()
+                    .identifier(identifier)
+                    .version(version)
+                    .maybe_versions(versions.as_ref())
+                    .changes(migration.changes)
+                    .build(),
+            )
+            .await?;
+
+        pr_progress.finish_and_clear();
+
+        submission.print_success();
+
+        if self.open_pr
+            && let Some(url) = submission.url()
+        {
+            open::that(url.as_str())?;
+        }
+
+        Ok(())
+    }
+
+    /// Prints the fields that changed, returning `false` if nothing needed to be migrated.
+    fn report(&self, migration: &Migration) -> bool {
+        if migration.report.is_empty() {
+            println!(
+                "{} {} is already on manifest version {}",
+                migration.identifier, migration.version, self.to
+            );
+            return false;
+        }
+
+        migration.print_report();
+        println!();
+        true
+    }
+}
@@ -1,8 +1,15 @@
 pub mod analyze;
 pub mod cleanup;
 pub mod complete;
+pub mod diff;
+pub mod edit;
+pub mod fmt;
+pub mod lint;
 pub mod list_versions;
+pub mod migrate;
 pub mod new_version;
+pub mod prs;
+pub mod rate_limit;
 pub mod remove_dead_versions;
 pub mod remove_version;
 pub mod show_version;
@@ -11,3 +18,4 @@
 pub mod token;
 pub mod update_version;
 pub mod utils;
+pub mod validate;
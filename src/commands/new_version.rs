@@ -7,7 +7,7 @@
 use anstream::println;
 use camino::Utf8PathBuf;
 use clap::Parser;
-use color_eyre::eyre::Result;
+use color_eyre::eyre::{Report, Result, bail};
 use indicatif::ProgressBar;
 use inquire::CustomType;
 use ordinal::Ordinal;
@@ -33,22 +33,29 @@
 
 use crate::{
     commands::utils::{
-        SPINNER_TICK_RATE, SubmitOption, prompt_existing_pull_request, write_changes_to_dir,
+        SPINNER_TICK_RATE, SubmitOption, check_permissions, fetch_release_notes,
+        prompt_existing_pull_request, write_changes_to_dir,
     },
-    download::Downloader,
+    download::{ByteRate, Downloader},
     download_file::process_files,
     github::{
-        GITHUB_HOST,
         client::GitHub,
+        host::GitHubHost,
         utils::{PackagePath, pull_request::pr_changes},
     },
-    manifests::{Manifests, Url},
+    manifests::{
+        Manifests, Url, diff::ManifestValues, json::ManifestFormat,
+        singleton::build_singleton_string,
+    },
+    metadata,
     prompts::{
         check_prompt, handle_inquire_error,
         list::list_prompt,
         radio_prompt,
         text::{confirm_prompt, optional_prompt, required_prompt},
     },
+    release_notes::ReleaseNotesSource,
+    repository::{CheckoutArgs, ManifestRepository, VersionAddition},
     token::TokenManager,
 };
 
@@ -68,6 +75,33 @@ pub struct NewVersion {
     #[arg(short, long, num_args = 1.., value_hint = clap::ValueHint::Url)]
     urls: Vec<Url>,
 
+    /// Install modes that the installers support: `interactive`, `silent` or
+    /// `silentWithProgress`. Pass the flag without values for none
+    #[arg(long, num_args = 0.., value_parser = parse_install_mode)]
+    install_modes: Option<Vec<InstallModes>>,
+
+    /// Exit codes other than 0 that mean the installer succeeded. Pass the flag without values for
+    /// none
+    #[arg(long, num_args = 0..)]
+    success_codes: Option<Vec<InstallerSuccessCode>>,
+
+    /// What happens when a newer version is installed over this one: `install`,
+    /// `uninstallPrevious` or `deny`
+    #[arg(long, value_parser = parse_upgrade_behavior)]
+    upgrade_behavior: Option<UpgradeBehavior>,
+
+    /// Commands that the package adds. Pass the flag without values for none
+    #[arg(long, num_args = 0..)]
+    commands: Option<Vec<Command>>,
+
+    /// Protocols that the package handles. Pass the flag without values for none
+    #[arg(long, num_args = 0..)]
+    protocols: Option<Vec<Protocol>>,
+
+    /// File extensions that the package opens. Pass the flag without values for none
+    #[arg(long, num_args = 0..)]
+    file_extensions: Option<Vec<FileExtension>>,
+
     #[arg(long)]
     package_locale: Option<LanguageTag>,
 
@@ -110,13 +144,30 @@ pub struct NewVersion {
     #[arg(long)]
     description: Option<Description>,
 
+    /// Tags to describe the package by. Pass the flag without values for none
+    #[arg(long, num_args = 0..)]
+    tags: Option<Vec<Tag>>,
+
     #[arg(long, value_hint = clap::ValueHint::Url)]
     release_notes_url: Option<ReleaseNotesUrl>,
 
+    /// Where to read the release notes from instead of the GitHub release: `changelog`,
+    /// `changelog:<PATH|URL>`, `gitlab:<URL>`, `gitea:<URL>` or `feed:<URL>`
+    #[arg(long, value_name = "SOURCE")]
+    release_notes_source: Option<ReleaseNotesSource>,
+
     /// Number of installers to download at the same time
     #[arg(long, default_value_t = NonZeroUsize::new(num_cpus::get()).unwrap())]
     concurrent_downloads: NonZeroUsize,
 
+    /// Maximum number of installers to download from the same host at the same time
+    #[arg(long)]
+    max_connections_per_host: Option<NonZeroUsize>,
+
+    /// Maximum combined download speed, such as `20MB/s` or `512KiB/s`
+    #[arg(long, env = "KOMAC_MAX_RATE")]
+    max_rate: Option<ByteRate>,
+
     /// List of issues that adding this package or version would resolve
     #[arg(long)]
     resolves: Vec<NonZeroU32>,
@@ -137,6 +188,15 @@ pub struct NewVersion {
     #[arg(short, long, env = "OUTPUT_DIRECTORY", value_hint = clap::ValueHint::DirPath)]
     output: Option<Utf8PathBuf>,
 
+    /// Output a single singleton manifest instead of separate manifests. Only packages with one
+    /// installer and no additional locales can be written as a singleton manifest
+    #[arg(long, requires = "output")]
+    singleton: bool,
+
+    /// Format to write the manifests to the output directory in
+    #[arg(long, value_enum, default_value_t, requires = "output")]
+    format: ManifestFormat,
+
     /// Open pull request link automatically
     #[arg(long, env = "OPEN_PR")]
     open_pr: bool,
@@ -149,19 +209,34 @@ pub struct NewVersion {
     #[arg(long, env)]
     skip_pr_check: bool,
 
+    #[command(flatten)]
+    checkout: CheckoutArgs,
+
     /// GitHub personal access token with the `public_repo` scope
     #[arg(short, long, env = "GITHUB_TOKEN", hide_env_values = true)]
     token: Option<SecretString>,
 }
 
 impl NewVersion {
-    pub async fn run(self) -> Result<()> {
-        let token_manager = TokenManager::handle(self.token).await?;
+    pub async fn run(mut self) -> Result<()> {
+        let token_manager = TokenManager::handle(self.token.take()).await?;
         let github = GitHub::new(token_manager)?;
 
+        match self.checkout.open(&github).await? {
+            Some(checkout) => self.create(&github, &checkout).await,
+            None => {
+                if !self.dry_run {
+                    check_permissions(&github).await?;
+                }
+                self.create(&github, &github).await
+            }
+        }
+    }
+
+    async fn create<R: ManifestRepository>(self, github: &GitHub, repository: &R) -> Result<()> {
         let package_identifier = required_prompt(self.package_identifier, None::<&str>)?;
 
-        let versions = github.get_versions(&package_identifier).await.ok();
+        let versions = repository.get_versions(&package_identifier).await.ok();
 
         let latest_version = versions.as_ref().and_then(BTreeSet::last);
 
@@ -170,7 +245,7 @@ pub async fn run(self) -> Result<()> {
         }
 
         let manifests =
-            latest_version.map(|version| github.get_manifests(&package_identifier, version));
+            latest_version.map(|version| repository.get_manifests(&package_identifier, version));
 
         let package_version = required_prompt(self.package_version, None::<&str>)?;
 
@@ -208,21 +283,28 @@ pub async fn run(self) -> Result<()> {
 
         let github_values = tokio::spawn({
             let github = github.clone();
-            let github_url = urls
-                .iter()
-                .find(|url| url.host_str() == Some(GITHUB_HOST))
-                .cloned();
+            let urls = urls.clone();
+            let package_version = package_version.clone();
             async move {
-                github_url
-                    .map(|url| github.get_all_values_from_url(url.into_inner()))
-                    .unwrap_or_default()
-                    .await
+                match urls.iter().find(|url| GitHubHost::from_url(url).is_some()) {
+                    Some(url) => Ok(github
+                        .get_all_values_from_url(url.clone().into_inner())
+                        .await
+                        .transpose()?),
+                    None => {
+                        let urls = urls.iter().map(|url| &***url).collect::<Vec<_>>();
+                        Ok::<_, Report>(metadata::get_values_or_warn(&urls, &package_version).await)
+                    }
+                }
             }
         });
 
-        let downloader = Downloader::new_with_concurrent(self.concurrent_downloads)?;
-        let mut files = downloader.download(urls.iter().cloned()).await?;
-        let mut download_results = process_files(&mut files).await?;
+        let downloader = Downloader::new_with_concurrent(self.concurrent_downloads)?
+            .with_max_connections_per_host(self.max_connections_per_host)
+            .with_max_rate(self.max_rate)
+            .with_redirect_prompt(true);
+        let files = downloader.download(urls.iter().cloned()).await?;
+        let mut download_results = process_files(files);
 
         let mut installers = Vec::new();
         for analyzer in &mut download_results.values_mut() {
@@ -285,18 +367,37 @@ pub async fn run(self) -> Result<()> {
                 .any(|installer| installer.r#type == Some(InstallerType::Inno))
             {
                 InstallModes::all()
+            } else if let Some(install_modes) = self.install_modes {
+                install_modes
+                    .into_iter()
+                    .fold(InstallModes::empty(), |modes, mode| modes | mode)
             } else {
                 check_prompt::<InstallModes>()?
             },
-            success_codes: list_prompt::<InstallerSuccessCode>()?,
-            upgrade_behavior: Some(radio_prompt::<UpgradeBehavior>()?),
-            commands: list_prompt::<Command>()?,
-            protocols: list_prompt::<Protocol>()?,
+            success_codes: match self.success_codes {
+                Some(success_codes) => success_codes.into_iter().collect(),
+                None => list_prompt::<InstallerSuccessCode>()?,
+            },
+            upgrade_behavior: Some(match self.upgrade_behavior {
+                Some(upgrade_behavior) => upgrade_behavior,
+                None => radio_prompt::<UpgradeBehavior>()?,
+            }),
+            commands: match self.commands {
+                Some(commands) => commands.into_iter().collect(),
+                None => list_prompt::<Command>()?,
+            },
+            protocols: match self.protocols {
+                Some(protocols) => protocols.into_iter().collect(),
+                None => list_prompt::<Protocol>()?,
+            },
             file_extensions: if installers
                 .iter()
                 .all(|installer| installer.file_extensions.is_empty())
             {
-                list_prompt::<FileExtension>()?
+                match self.file_extensions {
+                    Some(file_extensions) => file_extensions.into_iter().collect(),
+                    None => list_prompt::<FileExtension>()?,
+                }
             } else {
                 BTreeSet::new()
             },
@@ -305,9 +406,23 @@ pub async fn run(self) -> Result<()> {
             ..InstallerManifest::default()
         };
 
-        let mut github_values = match github_values.await? {
-            Some(future) => Some(future?),
-            None => None,
+        let mut github_values = github_values.await??;
+
+        let (release_notes, release_notes_url) = match fetch_release_notes(
+            self.release_notes_source.as_ref(),
+            github,
+            &urls,
+            &package_version,
+        )
+        .await
+        {
+            Some(sourced) => (Some(sourced.notes), sourced.url),
+            None => github_values.as_mut().map_or((None, None), |values| {
+                (
+                    values.release_notes.take(),
+                    values.release_notes_url.clone(),
+                )
+            }),
         };
 
         let default_locale_manifest = DefaultLocaleManifest {
@@ -328,7 +443,9 @@ pub async fn run(self) -> Result<()> {
             )?,
             publisher_url: optional_prompt(
                 self.publisher_url,
-                github_values.as_ref().map(|values| &values.publisher_url),
+                github_values
+                    .as_ref()
+                    .and_then(|values| values.publisher_url.as_ref()),
             )?,
             publisher_support_url: optional_prompt(
                 self.publisher_support_url,
@@ -376,22 +493,16 @@ pub async fn run(self) -> Result<()> {
             )?,
             description: optional_prompt(self.description, None::<&str>)?,
             moniker: optional_prompt(self.moniker, None::<&str>)?,
-            tags: match github_values
-                .as_mut()
-                .map(|values| mem::take(&mut values.topics))
-            {
-                Some(topics) => topics,
+            tags: match self.tags.map(BTreeSet::from_iter).or_else(|| {
+                github_values
+                    .as_mut()
+                    .map(|values| mem::take(&mut values.topics))
+            }) {
+                Some(tags) => tags,
                 None => list_prompt::<Tag>()?,
             },
-            release_notes: github_values
-                .as_mut()
-                .and_then(|values| values.release_notes.take()),
-            release_notes_url: optional_prompt(
-                self.release_notes_url,
-                github_values
-                    .as_ref()
-                    .and_then(|values| values.release_notes_url.as_ref()),
-            )?,
+            release_notes,
+            release_notes_url: optional_prompt(self.release_notes_url, release_notes_url.as_ref())?,
             manifest_type: ManifestType::DefaultLocale,
             ..DefaultLocaleManifest::default()
         };
@@ -443,7 +554,20 @@ pub async fn run(self) -> Result<()> {
         )?;
 
         if let Some(output) = self.output.map(|out| out.join(package_path.as_str())) {
-            write_changes_to_dir(&changes, output.as_path()).await?;
+            if self.singleton {
+                let singleton = build_singleton_string(
+                    &ManifestValues::from_changes(&changes),
+                    self.created_with.as_deref(),
+                )?;
+                write_changes_to_dir(
+                    &[(format!("{package_identifier}.yaml"), singleton)],
+                    output.as_path(),
+                    self.format,
+                )
+                .await?;
+            } else {
+                write_changes_to_dir(&changes, output.as_path(), self.format).await?;
+            }
             println!(
                 "{} written all manifest files to {output}",
                 "Successfully".green()
@@ -460,26 +584,52 @@ pub async fn run(self) -> Result<()> {
         ));
         pr_progress.enable_steady_tick(SPINNER_TICK_RATE);
 
-        let pull_request = github
-            .add_version()
-            .identifier(&package_identifier)
-            .version(&package_version)
-            .maybe_versions(versions.as_ref())
-            .changes(changes)
-            .issue_resolves(&self.resolves)
-            .maybe_created_with(self.created_with.as_deref())
-            .maybe_created_with_url(self.created_with_url.as_ref())
-            .send()
+        let submission = repository
+            .add_version(
+                VersionAddition::builder()
+                    .identifier(&package_identifier)
+                    .version(&package_version)
+                    .maybe_versions(versions.as_ref())
+                    .changes(changes)
+                    .issue_resolves(&self.resolves)
+                    .maybe_created_with(self.created_with.as_deref())
+                    .maybe_created_with_url(self.created_with_url.as_ref())
+                    .build(),
+            )
             .await?;
 
         pr_progress.finish_and_clear();
 
-        pull_request.print_success();
+        submission.print_success();
 
-        if self.open_pr {
-            open::that(pull_request.url().as_str())?;
+        if self.open_pr
+            && let Some(url) = submission.url()
+        {
+            open::that(url.as_str())?;
         }
 
         Ok(())
     }
 }
+
+fn parse_install_mode(input: &str) -> Result<InstallModes> {
+    match input {
+        "interactive" => Ok(InstallModes::INTERACTIVE),
+        "silent" => Ok(InstallModes::SILENT),
+        "silentWithProgress" => Ok(InstallModes::SILENT_WITH_PROGRESS),
+        _ => bail!(
+            "Install mode did not match any of `interactive`, `silent` or `silentWithProgress`"
+        ),
+    }
+}
+
+fn parse_upgrade_behavior(input: &str) -> Result<UpgradeBehavior> {
+    match input {
+        "install" => Ok(UpgradeBehavior::Install),
+        "uninstallPrevious" => Ok(UpgradeBehavior::UninstallPrevious),
+        "deny" => Ok(UpgradeBehavior::Deny),
+        _ => {
+            bail!("Upgrade behavior did not match any of `install`, `uninstallPrevious` or `deny`")
+        }
+    }
+}
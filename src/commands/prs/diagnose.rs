@@ -0,0 +1,581 @@
+use std::{collections::BTreeSet, fmt, sync::LazyLock};
+
+use anstream::println;
+use clap::Parser;
+use color_eyre::eyre::{Result, bail};
+use indicatif::ProgressBar;
+use itertools::Itertools;
+use owo_colors::OwoColorize;
+use regex::Regex;
+use secrecy::SecretString;
+use winget_types::{
+    PackageIdentifier, PackageVersion,
+    installer::{
+        Installer,
+        switches::{InstallerSwitches, SilentSwitch, SilentWithProgressSwitch},
+    },
+};
+
+use crate::{
+    commands::utils::{SPINNER_TICK_RATE, SubmitOption, amendable_pull_request, environment::CI},
+    download::Downloader,
+    download_file::process_files,
+    github::{
+        client::GitHub,
+        utils::{PackagePath, pull_request::pr_changes},
+    },
+    manifests::Manifests,
+    prompts::text::required_prompt,
+    repository::parse_manifests,
+    terminal::{Hyperlinkable, SUPPORTS_HYPERLINKS},
+    token::TokenManager,
+};
+
+/// Reads the validation pipeline's labels and comments on one of your pull requests to work out
+/// why it failed, suggesting or applying a fix
+#[derive(Parser)]
+pub struct Diagnose {
+    /// The number of the pull request to diagnose
+    #[arg(value_parser = clap::value_parser!(i32).range(1..))]
+    number: i32,
+
+    /// Apply the suggested fixes by amending the pull request
+    #[arg(long)]
+    fix: bool,
+
+    /// Automatically submit the fixes without asking for confirmation
+    #[arg(short, long, requires = "fix")]
+    submit: bool,
+
+    /// GitHub personal access token with the `public_repo` scope
+    #[arg(short, long, env = "GITHUB_TOKEN", hide_env_values = true)]
+    token: Option<SecretString>,
+}
+
+impl Diagnose {
+    pub async fn run(mut self) -> Result<()> {
+        let token_manager = TokenManager::handle(self.token.take()).await?;
+        let github = GitHub::new(&token_manager)?;
+
+        let feedback = github.get_pull_request_feedback(self.number).await?;
+        let diagnosis = Diagnosis::new(feedback.label_names(), feedback.feedback());
+
+        println!(
+            "{} {}",
+            format_args!("#{}", self.number)
+                .hyperlink(&feedback.url)
+                .bold(),
+            feedback.title
+        );
+        if !*SUPPORTS_HYPERLINKS {
+            println!("{}", feedback.url.blue());
+        }
+
+        if diagnosis.failures.is_empty() {
+            println!("No validation failures were found");
+            return Ok(());
+        }
+
+        for failure in &diagnosis.failures {
+            println!("{} {failure}", "•".red());
+            println!("  {}", failure.remediation().dimmed());
+        }
+
+        if !diagnosis.logs.is_empty() {
+            println!("Validation logs:");
+            for log in &diagnosis.logs {
+                println!("  {}", log.blue());
+            }
+        }
+
+        if !diagnosis
+            .failures
+            .iter()
+            .any(|failure| failure.is_fixable())
+        {
+            return Ok(());
+        }
+
+        if !self.fix {
+            println!(
+                "Run {} to apply the fixes to the pull request",
+                format!("komac prs diagnose {} --fix", self.number).blue()
+            );
+            return Ok(());
+        }
+
+        let Some((identifier, version)) = package_from_paths(feedback.file_paths()) else {
+            bail!("#{} does not add an installer manifest", self.number)
+        };
+
+        self.fix(&github, &identifier, &version, &diagnosis.failures)
+            .await
+    }
+
+    async fn fix(
+        &self,
+        github: &GitHub,
+        identifier: &PackageIdentifier,
+        version: &PackageVersion,
+        failures: &BTreeSet<Failure>,
+    ) -> Result<()> {
+        let (pull_request, fork_owner) = amendable_pull_request(github, self.number).await?;
+        let Some(head_ref) = &pull_request.head_ref else {
+            bail!("The branch of #{} no longer exists", self.number)
+        };
+
+        let package_path = PackagePath::new(identifier, Some(version), None);
+        let files = github
+            .get_directory_content_with_text()
+            .owner(&fork_owner)
+            .branch_name(&head_ref.name)
+            .path(&package_path)
+            .call()
+            .await?
+            .collect::<Vec<_>>();
+        let mut manifests = parse_manifests(identifier, &package_path, &files)?;
+
+        if failures.contains(&Failure::HashMismatch) || failures.contains(&Failure::ArpMismatch) {
+            reanalyze_installers(&mut manifests, failures).await?;
+        }
+
+        if failures.contains(&Failure::InstallerFailed) {
+            if *CI {
+                println!(
+                    "Skipping changing the installer switches as they cannot be prompted for in CI"
+                );
+            } else {
+                change_silent_switches(&mut manifests)?;
+            }
+        }
+
+        manifests.installer.optimize();
+
+        let mut changes = pr_changes()
+            .package_identifier(identifier)
+            .manifests(&manifests)
+            .package_path(&package_path)
+            .create()?;
+
+        let submit_option =
+            SubmitOption::prompt(&mut changes, identifier, version, self.submit, false)?;
+
+        if submit_option.is_exit() {
+            return Ok(());
+        }
+
+        // Create an indeterminate progress bar to show as the pull request is being amended
+        let pr_progress =
+            ProgressBar::new_spinner().with_message(format!("Amending #{}", self.number));
+        pr_progress.enable_steady_tick(SPINNER_TICK_RATE);
+
+        let amended = github
+            .amend_pull_request()
+            .pull_request(&pull_request)
+            .fork_owner(&fork_owner)
+            .identifier(identifier)
+            .version(version)
+            .changes(changes)
+            .send()
+            .await?;
+
+        pr_progress.finish_and_clear();
+
+        match amended {
+            Some(amended) => amended.print_updated(),
+            None => println!(
+                "The fixes did not change any manifests so #{} was left unchanged",
+                self.number
+            ),
+        }
+
+        Ok(())
+    }
+}
+
+/// Downloads the installers again to update their hashes or Apps and Features entries.
+async fn reanalyze_installers(
+    manifests: &mut Manifests,
+    failures: &BTreeSet<Failure>,
+) -> Result<()> {
+    let urls = manifests
+        .installer
+        .installers
+        .iter()
+        .map(|installer| installer.url.clone())
+        .unique()
+        .collect::<Vec<_>>();
+    let files = Downloader::new()?.download(urls).await?;
+    let analyzed = process_files(files)
+        .into_values()
+        .flat_map(|analyzer| analyzer.installers)
+        .collect::<Vec<_>>();
+
+    apply_reanalysis(manifests, &analyzed, failures);
+
+    Ok(())
+}
+
+/// Updates the installers from the installers found when downloading them again.
+///
+/// Installers without a matching analyzed installer, such as when the URL redirected elsewhere,
+/// are left as they are.
+fn apply_reanalysis(
+    manifests: &mut Manifests,
+    analyzed: &[Installer],
+    failures: &BTreeSet<Failure>,
+) {
+    // Entries at the root of the manifest apply to every installer, so move them onto the
+    // installers before replacing them. They are moved back to the root if they end up the same.
+    let root_entries = std::mem::take(&mut manifests.installer.apps_and_features_entries);
+
+    for installer in &mut manifests.installer.installers {
+        if installer.apps_and_features_entries.is_empty() {
+            installer
+                .apps_and_features_entries
+                .clone_from(&root_entries);
+        }
+
+        let Some(analyzed) = analyzed
+            .iter()
+            .filter(|analyzed| analyzed.url == installer.url)
+            .find_or_first(|analyzed| analyzed.architecture == installer.architecture)
+        else {
+            continue;
+        };
+
+        if failures.contains(&Failure::HashMismatch) {
+            installer.sha_256 = analyzed.sha_256.clone();
+        }
+
+        if failures.contains(&Failure::ArpMismatch)
+            && !analyzed.apps_and_features_entries.is_empty()
+        {
+            installer
+                .apps_and_features_entries
+                .clone_from(&analyzed.apps_and_features_entries);
+            for entry in &mut installer.apps_and_features_entries {
+                entry.deduplicate(&manifests.default_locale);
+            }
+        }
+    }
+}
+
+/// Prompts for new silent switches and replaces the existing ones with them.
+fn change_silent_switches(manifests: &mut Manifests) -> Result<()> {
+    let switches = &manifests.installer.switches;
+    let silent = required_prompt(None, switches.silent().map(SilentSwitch::to_string))?;
+    let silent_with_progress = required_prompt(
+        None,
+        switches
+            .silent_with_progress()
+            .map(SilentWithProgressSwitch::to_string),
+    )?;
+
+    manifests.installer.switches = with_silent_switches(
+        &manifests.installer.switches,
+        &silent,
+        &silent_with_progress,
+    )?;
+    for installer in &mut manifests.installer.installers {
+        if installer.switches.silent().is_some()
+            || installer.switches.silent_with_progress().is_some()
+        {
+            installer.switches =
+                with_silent_switches(&installer.switches, &silent, &silent_with_progress)?;
+        }
+    }
+
+    Ok(())
+}
+
+/// Returns a copy of the switches with the silent switches replaced, keeping all the others.
+fn with_silent_switches(
+    switches: &InstallerSwitches,
+    silent: &SilentSwitch,
+    silent_with_progress: &SilentWithProgressSwitch,
+) -> serde_yaml::Result<InstallerSwitches> {
+    // The switches can only be set through their builder, which would require every other switch
+    // to be copied across, so they are replaced through their serialized form instead
+    let mut value = serde_yaml::to_value(switches)?;
+    if let serde_yaml::Value::Mapping(mapping) = &mut value {
+        mapping.insert("Silent".into(), silent.to_string().into());
+        mapping.insert(
+            "SilentWithProgress".into(),
+            silent_with_progress.to_string().into(),
+        );
+    }
+    serde_yaml::from_value(value)
+}
+
+/// Finds the package identifier and version from the path of the installer manifest changed by
+/// a pull request.
+fn package_from_paths<'a, I>(mut paths: I) -> Option<(PackageIdentifier, PackageVersion)>
+where
+    I: Iterator<Item = &'a str>,
+{
+    paths.find_map(|path| {
+        let (directory, file_name) = path.rsplit_once('/')?;
+        let identifier = file_name.strip_suffix(".installer.yaml")?;
+        let (_, version) = directory.rsplit_once('/')?;
+        Some((identifier.parse().ok()?, version.parse().ok()?))
+    })
+}
+
+/// A reason the validation pipeline rejected a pull request.
+#[derive(Clone, Copy, Debug, Eq, Ord, PartialEq, PartialOrd)]
+pub enum Failure {
+    HashMismatch,
+    InstallerFailed,
+    UrlUnreachable,
+    ArpMismatch,
+}
+
+impl Failure {
+    /// Phrases used in the validation pipeline's comments for each failure, matched without
+    /// regard to case.
+    const COMMENT_PHRASES: [(&'static str, Self); 11] = [
+        ("hash mismatch", Self::HashMismatch),
+        ("sha256 does not match", Self::HashMismatch),
+        ("installer hash does not match", Self::HashMismatch),
+        ("installation failed", Self::InstallerFailed),
+        ("failed to install", Self::InstallerFailed),
+        ("exited with code", Self::InstallerFailed),
+        ("404 (not found)", Self::UrlUnreachable),
+        (
+            "response status code does not indicate success",
+            Self::UrlUnreachable,
+        ),
+        ("could not be downloaded", Self::UrlUnreachable),
+        ("appsandfeaturesentries", Self::ArpMismatch),
+        ("arp entries", Self::ArpMismatch),
+    ];
+
+    fn from_label(label: &str) -> Option<Self> {
+        match label {
+            "Validation-Hash-Verification-Failed" => Some(Self::HashMismatch),
+            "Validation-Installation-Error"
+            | "Validation-Executable-Error"
+            | "Validation-Unattended-Failed"
+            | "Validation-Shell-Execute" => Some(Self::InstallerFailed),
+            "URL-Validation-Error" | "Validation-HTTP-Error" => Some(Self::UrlUnreachable),
+            "Manifest-AppsAndFeaturesVersion-Error" | "Validation-Missing-ARP-Entry" => {
+                Some(Self::ArpMismatch)
+            }
+            _ => None,
+        }
+    }
+
+    fn from_comment(comment: &str) -> impl Iterator<Item = Self> {
+        let comment = comment.to_ascii_lowercase();
+        Self::COMMENT_PHRASES
+            .into_iter()
+            .filter(move |(phrase, _)| comment.contains(phrase))
+            .map(|(_, failure)| failure)
+    }
+
+    /// Returns `true` if the failure can be fixed by amending the pull request.
+    pub const fn is_fixable(self) -> bool {
+        !matches!(self, Self::UrlUnreachable)
+    }
+
+    pub const fn as_str(self) -> &'static str {
+        match self {
+            Self::HashMismatch => "The hash of an installer does not match the manifest",
+            Self::InstallerFailed => "An installer failed to install silently",
+            Self::UrlUnreachable => "An installer URL could not be downloaded",
+            Self::ArpMismatch => {
+                "The installed Apps and Features entries do not match the manifest"
+            }
+        }
+    }
+
+    pub const fn remediation(self) -> &'static str {
+        match self {
+            Self::HashMismatch => "Download the installers again and update their hashes",
+            Self::InstallerFailed => {
+                "Check the logs for the installer's exit code and change its silent switches"
+            }
+            Self::UrlUnreachable => {
+                "Check the URL still works, or replace it using `komac update --amend-pr`"
+            }
+            Self::ArpMismatch => {
+                "Update AppsAndFeaturesEntries from the installers' Apps and Features entries"
+            }
+        }
+    }
+}
+
+impl fmt::Display for Failure {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.as_str().fmt(f)
+    }
+}
+
+/// The failures found in a pull request's labels and comments, along with any links to the
+/// validation pipeline's logs.
+#[derive(Debug, Default, Eq, PartialEq)]
+pub struct Diagnosis {
+    pub failures: BTreeSet<Failure>,
+    pub logs: Vec<String>,
+}
+
+impl Diagnosis {
+    pub fn new<'a, L, C>(labels: L, comments: C) -> Self
+    where
+        L: IntoIterator<Item = &'a str>,
+        C: IntoIterator<Item = &'a str>,
+    {
+        static AZURE_DEVOPS_LINK: LazyLock<Regex> = LazyLock::new(|| {
+            Regex::new(r#"https://dev\.azure\.com/[^\s)\]>"']+"#).unwrap_or_else(|_| unreachable!())
+        });
+
+        let mut diagnosis = Self {
+            failures: labels.into_iter().filter_map(Failure::from_label).collect(),
+            logs: Vec::new(),
+        };
+
+        for comment in comments {
+            diagnosis.failures.extend(Failure::from_comment(comment));
+            for link in AZURE_DEVOPS_LINK.find_iter(comment) {
+                let link = link.as_str().trim_end_matches(['.', ',']);
+                if !diagnosis.logs.iter().any(|log| log == link) {
+                    diagnosis.logs.push(link.to_owned());
+                }
+            }
+        }
+
+        diagnosis
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeSet;
+
+    use indoc::indoc;
+    use rstest::rstest;
+
+    use super::{Diagnosis, Failure, apply_reanalysis, package_from_paths};
+    use crate::manifests::Manifests;
+
+    #[rstest]
+    #[case("Validation-Hash-Verification-Failed", Some(Failure::HashMismatch))]
+    #[case("Validation-Executable-Error", Some(Failure::InstallerFailed))]
+    #[case("URL-Validation-Error", Some(Failure::UrlUnreachable))]
+    #[case("Manifest-AppsAndFeaturesVersion-Error", Some(Failure::ArpMismatch))]
+    #[case("Moderator-Approved", None)]
+    fn failure_from_label(#[case] label: &str, #[case] expected: Option<Failure>) {
+        assert_eq!(Failure::from_label(label), expected);
+    }
+
+    #[test]
+    fn diagnosis_from_comments() {
+        const COMMENT: &str = indoc! {"
+            Validation Pipeline Run [WinGetSvc-Validation-123-456](https://dev.azure.com/shine-oss/winget-pkgs/_build/results?buildId=123&view=logs).
+
+            Installer hash does not match the value in the manifest. The installation failed with
+            exit code 1603.
+        "};
+
+        let diagnosis = Diagnosis::new(["Needs-Author-Feedback"], [COMMENT]);
+
+        assert_eq!(
+            diagnosis,
+            Diagnosis {
+                failures: BTreeSet::from([Failure::HashMismatch, Failure::InstallerFailed]),
+                logs: vec![String::from(
+                    "https://dev.azure.com/shine-oss/winget-pkgs/_build/results?buildId=123&view=logs"
+                )],
+            }
+        );
+    }
+
+    #[test]
+    fn package_from_installer_manifest_path() {
+        let (identifier, version) = package_from_paths(
+            [
+                "manifests/p/Package/Identifier/1.2.3/Package.Identifier.locale.en-US.yaml",
+                "manifests/p/Package/Identifier/1.2.3/Package.Identifier.installer.yaml",
+            ]
+            .into_iter(),
+        )
+        .unwrap();
+
+        assert_eq!(identifier.as_str(), "Package.Identifier");
+        assert_eq!(version.as_str(), "1.2.3");
+    }
+
+    #[test]
+    fn reanalysis_keeps_root_entries_for_unmatched_installers() {
+        let mut manifests = Manifests {
+            installer: serde_yaml::from_str(indoc! {"
+                PackageIdentifier: Package.Identifier
+                PackageVersion: 1.2.3
+                AppsAndFeaturesEntries:
+                - DisplayName: Package
+                Installers:
+                - Architecture: x64
+                  InstallerType: msi
+                  InstallerUrl: https://example.com/installer-x64.msi
+                  InstallerSha256: 0000000000000000000000000000000000000000000000000000000000000000
+                - Architecture: arm64
+                  InstallerType: msi
+                  InstallerUrl: https://example.com/installer-arm64.msi
+                  InstallerSha256: 1111111111111111111111111111111111111111111111111111111111111111
+                ManifestType: installer
+                ManifestVersion: 1.10.0
+            "})
+            .unwrap(),
+            default_locale: serde_yaml::from_str(indoc! {"
+                PackageIdentifier: Package.Identifier
+                PackageVersion: 1.2.3
+                PackageLocale: en-US
+                Publisher: Publisher
+                PackageName: Package
+                License: MIT
+                ShortDescription: A package
+                ManifestType: defaultLocale
+                ManifestVersion: 1.10.0
+            "})
+            .unwrap(),
+            locales: Vec::new(),
+            version: serde_yaml::from_str(indoc! {"
+                PackageIdentifier: Package.Identifier
+                PackageVersion: 1.2.3
+                DefaultLocale: en-US
+                ManifestType: version
+                ManifestVersion: 1.10.0
+            "})
+            .unwrap(),
+        };
+        let analyzed = vec![
+            serde_yaml::from_str(indoc! {"
+                Architecture: x64
+                InstallerType: msi
+                InstallerUrl: https://example.com/installer-x64.msi
+                InstallerSha256: 2222222222222222222222222222222222222222222222222222222222222222
+            "})
+            .unwrap(),
+        ];
+        let root_entries = manifests.installer.apps_and_features_entries.clone();
+
+        apply_reanalysis(
+            &mut manifests,
+            &analyzed,
+            &BTreeSet::from([Failure::HashMismatch]),
+        );
+
+        let [x64, arm64] = manifests.installer.installers.as_slice() else {
+            panic!("expected two installers");
+        };
+        assert_eq!(x64.sha_256, analyzed[0].sha_256);
+        assert_eq!(
+            arm64.sha_256.to_string(),
+            "1111111111111111111111111111111111111111111111111111111111111111"
+        );
+        for installer in [x64, arm64] {
+            assert_eq!(installer.apps_and_features_entries, root_entries);
+        }
+    }
+}
@@ -0,0 +1,232 @@
+mod diagnose;
+mod status;
+
+use std::{collections::BTreeMap, io::Write, time::Duration};
+
+use chrono::{Local, TimeDelta, Utc};
+use clap::{Parser, Subcommand};
+use color_eyre::Result;
+use diagnose::Diagnose;
+use indicatif::ProgressBar;
+use itertools::Itertools;
+use owo_colors::OwoColorize;
+use secrecy::SecretString;
+use status::{PullRequestStatus, Transition, colored_label, state_name};
+use tracing::warn;
+
+use crate::{
+    commands::utils::SPINNER_TICK_RATE,
+    github::{client::GitHub, graphql::types::PullRequestState, upstream::Upstream},
+    terminal::{Hyperlinkable, SUPPORTS_HYPERLINKS},
+    token::TokenManager,
+};
+
+/// Lists your open and recently closed pull requests to the upstream repository along with their
+/// validation status, labels and reviews
+#[derive(Parser)]
+#[clap(visible_alias = "pull-requests")]
+#[command(args_conflicts_with_subcommands = true)]
+pub struct PullRequests {
+    #[command(subcommand)]
+    command: Option<PullRequestsCommand>,
+
+    /// Number of days to include closed and merged pull requests from
+    #[arg(long, default_value_t = 7)]
+    days: u16,
+
+    /// Output the pull requests as JSON, or each transition as a line of JSON when watching
+    #[arg(long)]
+    json: bool,
+
+    /// Keep checking for changes every given number of seconds and print each transition
+    #[arg(
+        long,
+        value_name = "SECONDS",
+        num_args = 0..=1,
+        default_missing_value = "60",
+        value_parser = clap::value_parser!(u64).range(10..)
+    )]
+    watch: Option<u64>,
+
+    /// GitHub personal access token with the `public_repo` scope
+    #[arg(short, long, env = "GITHUB_TOKEN", hide_env_values = true)]
+    token: Option<SecretString>,
+}
+
+#[derive(Subcommand)]
+enum PullRequestsCommand {
+    Diagnose(Diagnose),
+}
+
+impl PullRequests {
+    pub async fn run(mut self) -> Result<()> {
+        if let Some(PullRequestsCommand::Diagnose(diagnose)) = self.command {
+            return diagnose.run().await;
+        }
+
+        let token_manager = TokenManager::handle(self.token.take()).await?;
+        let github = GitHub::new(&token_manager)?;
+        let author = github.get_username().await?;
+
+        let pb = ProgressBar::new_spinner().with_message(format!(
+            "Retrieving pull requests by {author} to {}",
+            Upstream::get()
+        ));
+        pb.enable_steady_tick(SPINNER_TICK_RATE);
+
+        let mut statuses = self.fetch(&github, &author).await?;
+
+        pb.finish_and_clear();
+
+        let mut stdout = anstream::stdout().lock();
+        if self.json {
+            if self.watch.is_none() {
+                serde_json::to_writer_pretty(
+                    &mut stdout,
+                    &statuses.values().rev().collect::<Vec<_>>(),
+                )?;
+                writeln!(stdout)?;
+            }
+        } else if statuses.is_empty() {
+            writeln!(
+                stdout,
+                "{author} has no open pull requests or any closed in the last {} days",
+                self.days
+            )?;
+        } else {
+            // Show the most recent pull requests first
+            for status in statuses.values().rev() {
+                print_status(&mut stdout, status)?;
+            }
+        }
+        drop(stdout);
+
+        let Some(interval) = self.watch.map(Duration::from_secs) else {
+            return Ok(());
+        };
+
+        loop {
+            tokio::time::sleep(interval).await;
+
+            // A failed poll is usually transient, so keep watching rather than exiting
+            let current = match self.fetch(&github, &author).await {
+                Ok(current) => current,
+                Err(error) => {
+                    warn!("Failed to check pull requests: {error}");
+                    continue;
+                }
+            };
+
+            let mut stdout = anstream::stdout().lock();
+            for status in current.values() {
+                let transitions = statuses.get(&status.number).map_or_else(
+                    || {
+                        vec![Transition::New {
+                            number: status.number,
+                            title: &status.title,
+                        }]
+                    },
+                    |previous| status.transitions_from(previous),
+                );
+
+                for transition in transitions {
+                    if self.json {
+                        serde_json::to_writer(&mut stdout, &transition)?;
+                        writeln!(stdout)?;
+                    } else {
+                        writeln!(
+                            stdout,
+                            "{} {transition}",
+                            Local::now().format("%H:%M:%S").dimmed()
+                        )?;
+                    }
+                }
+            }
+
+            statuses = current;
+        }
+    }
+
+    async fn fetch(
+        &self,
+        github: &GitHub,
+        author: &str,
+    ) -> Result<BTreeMap<i32, PullRequestStatus>> {
+        let closed_since = Utc::now().date_naive() - TimeDelta::days(i64::from(self.days));
+
+        Ok(github
+            .get_authored_pull_requests(author, closed_since)
+            .await?
+            .into_iter()
+            .map(|pull_request| (pull_request.number, PullRequestStatus::from(pull_request)))
+            .collect())
+    }
+}
+
+fn print_status<W: Write>(stdout: &mut W, status: &PullRequestStatus) -> std::io::Result<()> {
+    let state = match status.state {
+        PullRequestState::Open if status.draft => "draft".dimmed().to_string(),
+        PullRequestState::Open => state_name(status.state).green().to_string(),
+        PullRequestState::Merged => state_name(status.state).purple().to_string(),
+        PullRequestState::Closed => state_name(status.state).red().to_string(),
+    };
+
+    writeln!(
+        stdout,
+        "{} {} ({state})",
+        format_args!("#{}", status.number)
+            .hyperlink(&status.url)
+            .bold(),
+        status.title,
+    )?;
+
+    if let Some(checks) = status.checks {
+        write!(stdout, "  Checks: ")?;
+        if checks.is_failure() {
+            write!(stdout, "{}", checks.red())?;
+        } else {
+            write!(stdout, "{checks}")?;
+        }
+        if !status.failed_checks.is_empty() {
+            write!(stdout, " ({})", status.failed_checks.join(", "))?;
+        }
+        writeln!(stdout)?;
+    }
+
+    if !status.labels.is_empty() {
+        writeln!(
+            stdout,
+            "  Labels: {}",
+            status
+                .labels
+                .iter()
+                .map(|label| colored_label(label))
+                .join(", ")
+        )?;
+    }
+
+    if status.state == PullRequestState::Open {
+        write!(stdout, "  Review: ")?;
+        if let Some(review_decision) = status.review_decision {
+            write!(stdout, "{review_decision}, ")?;
+        }
+        writeln!(
+            stdout,
+            "{} {}, {}",
+            status.comments,
+            if status.comments == 1 {
+                "comment"
+            } else {
+                "comments"
+            },
+            status.mergeable
+        )?;
+    }
+
+    // If the terminal doesn't support hyperlinks, print the pull request's URL on a new line
+    if !*SUPPORTS_HYPERLINKS {
+        writeln!(stdout, "  {}", status.url)?;
+    }
+
+    Ok(())
+}
@@ -0,0 +1,326 @@
+use std::fmt;
+
+use chrono::{DateTime, Utc};
+use owo_colors::OwoColorize;
+use serde::Serialize;
+use url::Url;
+
+use crate::github::graphql::{
+    get_authored_pull_requests::{PullRequest, StatusCheckRollupContext},
+    types::{MergeableState, PullRequestReviewDecision, PullRequestState, StatusState},
+};
+
+/// A snapshot of where a pull request is in the validation and review process.
+#[derive(Clone, Debug, Eq, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PullRequestStatus {
+    pub number: i32,
+    pub title: String,
+    pub url: Url,
+    pub state: PullRequestState,
+    pub draft: bool,
+    pub checks: Option<StatusState>,
+    pub failed_checks: Vec<String>,
+    pub labels: Vec<String>,
+    pub comments: i32,
+    pub review_decision: Option<PullRequestReviewDecision>,
+    pub mergeable: MergeableState,
+    pub updated_at: DateTime<Utc>,
+}
+
+impl From<PullRequest> for PullRequestStatus {
+    fn from(pull_request: PullRequest) -> Self {
+        let (checks, failed_checks) = pull_request
+            .status_check_rollup
+            .map(|rollup| {
+                let failed_checks = rollup
+                    .contexts
+                    .nodes
+                    .iter()
+                    .filter_map(StatusCheckRollupContext::failed_name)
+                    .map(str::to_owned)
+                    .collect();
+                (Some(rollup.state), failed_checks)
+            })
+            .unwrap_or_default();
+
+        Self {
+            number: pull_request.number,
+            title: pull_request.title,
+            url: pull_request.url,
+            state: pull_request.state,
+            draft: pull_request.is_draft,
+            checks,
+            failed_checks,
+            labels: pull_request
+                .labels
+                .map(|labels| labels.nodes.into_iter().map(|label| label.name).collect())
+                .unwrap_or_default(),
+            comments: pull_request.total_comments_count.unwrap_or_default(),
+            review_decision: pull_request.review_decision,
+            mergeable: pull_request.mergeable,
+            updated_at: pull_request.updated_at,
+        }
+    }
+}
+
+impl PullRequestStatus {
+    /// Returns every change between a previous snapshot of this pull request and this one.
+    pub fn transitions_from<'a>(&'a self, previous: &'a Self) -> Vec<Transition<'a>> {
+        let number = self.number;
+        let mut transitions = Vec::new();
+
+        if self.state != previous.state {
+            transitions.push(Transition::State {
+                number,
+                from: previous.state,
+                to: self.state,
+            });
+        }
+
+        if self.checks != previous.checks {
+            transitions.push(Transition::Checks {
+                number,
+                from: previous.checks,
+                to: self.checks,
+                failed: &self.failed_checks,
+            });
+        }
+
+        transitions.extend(
+            self.labels
+                .iter()
+                .filter(|label| !previous.labels.contains(label))
+                .map(|label| Transition::LabelAdded { number, label }),
+        );
+        transitions.extend(
+            previous
+                .labels
+                .iter()
+                .filter(|label| !self.labels.contains(label))
+                .map(|label| Transition::LabelRemoved { number, label }),
+        );
+
+        if self.comments > previous.comments {
+            transitions.push(Transition::Comments {
+                number,
+                new: self.comments - previous.comments,
+            });
+        }
+
+        if self.review_decision != previous.review_decision {
+            transitions.push(Transition::ReviewDecision {
+                number,
+                from: previous.review_decision,
+                to: self.review_decision,
+            });
+        }
+
+        if self.mergeable != previous.mergeable {
+            transitions.push(Transition::Mergeable {
+                number,
+                from: previous.mergeable,
+                to: self.mergeable,
+            });
+        }
+
+        transitions
+    }
+}
+
+/// A change to a pull request between two polls of `komac prs --watch`.
+#[derive(Debug, Eq, PartialEq, Serialize)]
+#[serde(
+    tag = "event",
+    rename_all = "camelCase",
+    rename_all_fields = "camelCase"
+)]
+pub enum Transition<'a> {
+    New {
+        number: i32,
+        title: &'a str,
+    },
+    State {
+        number: i32,
+        from: PullRequestState,
+        to: PullRequestState,
+    },
+    Checks {
+        number: i32,
+        from: Option<StatusState>,
+        to: Option<StatusState>,
+        failed: &'a [String],
+    },
+    LabelAdded {
+        number: i32,
+        label: &'a str,
+    },
+    LabelRemoved {
+        number: i32,
+        label: &'a str,
+    },
+    Comments {
+        number: i32,
+        new: i32,
+    },
+    ReviewDecision {
+        number: i32,
+        from: Option<PullRequestReviewDecision>,
+        to: Option<PullRequestReviewDecision>,
+    },
+    Mergeable {
+        number: i32,
+        from: MergeableState,
+        to: MergeableState,
+    },
+}
+
+impl fmt::Display for Transition<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        const NONE: &str = "none";
+
+        match *self {
+            Self::New { number, title } => write!(f, "#{number} {title}"),
+            Self::State { number, from, to } => {
+                write!(f, "#{number} {} → {}", state_name(from), state_name(to))
+            }
+            Self::Checks {
+                number,
+                from,
+                to,
+                failed,
+            } => {
+                write!(
+                    f,
+                    "#{number} checks {} → {}",
+                    from.map_or(NONE, StatusState::as_str),
+                    to.map_or(NONE, StatusState::as_str)
+                )?;
+                if !failed.is_empty() {
+                    write!(f, " ({})", failed.join(", "))?;
+                }
+                Ok(())
+            }
+            Self::LabelAdded { number, label } => write!(f, "#{number} labelled {label}"),
+            Self::LabelRemoved { number, label } => write!(f, "#{number} unlabelled {label}"),
+            Self::Comments { number, new: 1 } => write!(f, "#{number} has a new comment"),
+            Self::Comments { number, new } => write!(f, "#{number} has {new} new comments"),
+            Self::ReviewDecision { number, from, to } => write!(
+                f,
+                "#{number} review {} → {}",
+                from.map_or(NONE, PullRequestReviewDecision::as_str),
+                to.map_or(NONE, PullRequestReviewDecision::as_str)
+            ),
+            Self::Mergeable { number, from, to } => write!(f, "#{number} {from} → {to}"),
+        }
+    }
+}
+
+pub const fn state_name(state: PullRequestState) -> &'static str {
+    match state {
+        PullRequestState::Closed => "closed",
+        PullRequestState::Merged => "merged",
+        PullRequestState::Open => "open",
+    }
+}
+
+/// Colors a label by whether it means the pull request is progressing or needs attention.
+pub fn colored_label(label: &str) -> String {
+    if label == "Moderator-Approved"
+        || label == "Validation-Completed"
+        || label == "Azure-Pipeline-Passed"
+    {
+        label.green().to_string()
+    } else if label.starts_with("Validation-")
+        && (label.ends_with("-Error") || label.ends_with("-Failed"))
+    {
+        label.red().to_string()
+    } else if label.starts_with("Needs-") {
+        label.yellow().to_string()
+    } else {
+        label.to_owned()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::DateTime;
+    use url::Url;
+
+    use super::{PullRequestStatus, Transition};
+    use crate::github::graphql::types::{MergeableState, PullRequestState, StatusState};
+
+    fn status() -> PullRequestStatus {
+        PullRequestStatus {
+            number: 1,
+            title: String::from("New version: Package.Identifier version 1.2.3"),
+            url: Url::parse("https://github.com/microsoft/winget-pkgs/pull/1").unwrap(),
+            state: PullRequestState::Open,
+            draft: false,
+            checks: Some(StatusState::Pending),
+            failed_checks: Vec::new(),
+            labels: vec![String::from("Azure-Pipeline-Passed")],
+            comments: 1,
+            review_decision: None,
+            mergeable: MergeableState::Mergeable,
+            updated_at: DateTime::UNIX_EPOCH,
+        }
+    }
+
+    #[test]
+    fn no_transitions() {
+        let status = status();
+
+        assert!(status.transitions_from(&status.clone()).is_empty());
+    }
+
+    #[test]
+    fn transitions() {
+        let previous = status();
+        let current = PullRequestStatus {
+            checks: Some(StatusState::Failure),
+            failed_checks: vec![String::from("Azure Pipelines")],
+            labels: vec![
+                String::from("Needs-Author-Feedback"),
+                String::from("Validation-Executable-Error"),
+            ],
+            comments: 3,
+            ..previous.clone()
+        };
+
+        let transitions = current.transitions_from(&previous);
+
+        assert_eq!(
+            transitions,
+            [
+                Transition::Checks {
+                    number: 1,
+                    from: Some(StatusState::Pending),
+                    to: Some(StatusState::Failure),
+                    failed: &[String::from("Azure Pipelines")],
+                },
+                Transition::LabelAdded {
+                    number: 1,
+                    label: "Needs-Author-Feedback",
+                },
+                Transition::LabelAdded {
+                    number: 1,
+                    label: "Validation-Executable-Error",
+                },
+                Transition::LabelRemoved {
+                    number: 1,
+                    label: "Azure-Pipeline-Passed",
+                },
+                Transition::Comments { number: 1, new: 2 },
+            ]
+        );
+        assert_eq!(
+            transitions[0].to_string(),
+            "#1 checks pending → failure (Azure Pipelines)"
+        );
+        assert_eq!(
+            serde_json::to_string(&transitions[1]).unwrap(),
+            r#"{"event":"labelAdded","number":1,"label":"Needs-Author-Feedback"}"#
+        );
+    }
+}
@@ -0,0 +1,92 @@
+use std::io::Write;
+
+use chrono::{Local, Utc};
+use clap::Parser;
+use color_eyre::Result;
+use indicatif::{HumanDuration, ProgressBar};
+use owo_colors::OwoColorize;
+use secrecy::SecretString;
+
+use crate::{
+    commands::utils::SPINNER_TICK_RATE,
+    github::{client::GitHub, upstream::Upstream},
+    token::TokenManager,
+};
+
+/// Shows how much of each of GitHub's rate limits remains and when they reset
+///
+/// The REST API counts requests against the `core` resource, while the GraphQL API counts points
+/// against the `graphql` resource, where each query costs at least one point. Komac waits for a
+/// rate limit to reset when it runs out during a command.
+#[derive(Parser)]
+#[clap(visible_alias = "limits")]
+pub struct ShowRateLimit {
+    /// Show every rate limit resource rather than only the ones Komac uses
+    #[arg(short, long)]
+    all: bool,
+
+    /// Output the rate limits as JSON
+    #[arg(long)]
+    json: bool,
+
+    /// GitHub personal access token with the `public_repo` scope
+    #[arg(short, long, env = "GITHUB_TOKEN", hide_env_values = true)]
+    token: Option<SecretString>,
+}
+
+impl ShowRateLimit {
+    /// The rate limit resources that Komac's requests count against.
+    const USED_RESOURCES: [&'static str; 2] = ["core", "graphql"];
+
+    pub async fn run(self) -> Result<()> {
+        let token_manager = TokenManager::handle(self.token).await?;
+        let github = GitHub::new(&token_manager)?;
+
+        let pb = ProgressBar::new_spinner().with_message(format!(
+            "Retrieving rate limits from {}",
+            Upstream::get().host().rest_api_url()
+        ));
+        pb.enable_steady_tick(SPINNER_TICK_RATE);
+
+        let mut budgets = github.get_rate_limits().await?;
+
+        pb.finish_and_clear();
+
+        if !self.all {
+            budgets.retain(|resource, _| Self::USED_RESOURCES.contains(&resource.as_str()));
+        }
+
+        let mut stdout = anstream::stdout().lock();
+
+        if self.json {
+            serde_json::to_writer_pretty(&mut stdout, &budgets)?;
+            writeln!(stdout)?;
+            return Ok(());
+        }
+
+        let width = budgets.keys().map(String::len).max().unwrap_or_default();
+        let now = Utc::now();
+
+        for (resource, budget) in &budgets {
+            let remaining = format!("{}/{}", budget.remaining, budget.limit);
+            let remaining = if budget.remaining == 0 {
+                remaining.red().to_string()
+            } else if budget.remaining * 10 < budget.limit {
+                remaining.yellow().to_string()
+            } else {
+                remaining.green().to_string()
+            };
+
+            let resets_in = (budget.reset - now).to_std().unwrap_or_default();
+
+            writeln!(
+                stdout,
+                "{resource:width$}  {remaining} remaining, resets in {} at {}",
+                HumanDuration(resets_in),
+                budget.reset.with_timezone(&Local).format("%H:%M:%S"),
+            )?;
+        }
+
+        Ok(())
+    }
+}
@@ -18,6 +18,7 @@
 
 use crate::{
     commands::utils::{RateLimit, SPINNER_SLOW_TICK_RATE},
+    fixtures::Dispatch,
     github::client::GitHub,
     prompts::text::confirm_prompt,
     token::{TokenManager, default_headers},
@@ -189,7 +190,7 @@ pub async fn run(self) -> Result<()> {
                         .map(|url| {
                             client
                                 .head((*url).clone())
-                                .send()
+                                .dispatch()
                                 .map_ok(|response| (url, response.status()))
                         })
                         .buffered(2)
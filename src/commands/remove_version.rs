@@ -3,19 +3,19 @@
 use anstream::println;
 use clap::Parser;
 use color_eyre::eyre::{Result, bail};
-use futures_util::TryFutureExt;
 use inquire::{
     Text,
     validator::{MaxLengthValidator, MinLengthValidator},
 };
 use owo_colors::OwoColorize;
 use secrecy::SecretString;
-use tokio::try_join;
 use winget_types::{PackageIdentifier, PackageVersion};
 
 use crate::{
-    github::{WINGET_PKGS_FULL_NAME, client::GitHub},
+    commands::utils::check_permissions,
+    github::{client::GitHub, upstream::Upstream},
     prompts::{handle_inquire_error, text::confirm_prompt},
+    repository::{CheckoutArgs, ManifestRepository, VersionRemoval},
     token::TokenManager,
 };
 
@@ -50,6 +50,9 @@ pub struct RemoveVersion {
     #[arg(long, env = "OPEN_PR")]
     open_pr: bool,
 
+    #[command(flatten)]
+    checkout: CheckoutArgs,
+
     /// GitHub personal access token with the `public_repo` scope
     #[arg(short, long, env = "GITHUB_TOKEN", hide_env_values = true)]
     token: Option<SecretString>,
@@ -59,8 +62,8 @@ impl RemoveVersion {
     const MIN_REASON_LENGTH: usize = 4;
     const MAX_REASON_LENGTH: usize = 1000;
 
-    pub async fn run(self) -> Result<()> {
-        let token_manager = TokenManager::handle(self.token).await?;
+    pub async fn run(mut self) -> Result<()> {
+        let token_manager = TokenManager::handle(self.token.take()).await?;
 
         if !self.no_warning {
             println!(
@@ -71,19 +74,24 @@ pub async fn run(self) -> Result<()> {
 
         let github = GitHub::new(&token_manager)?;
 
-        let (fork, winget_pkgs, versions) = try_join!(
-            github
-                .get_username()
-                .and_then(|current_user| github.get_winget_pkgs().owner(current_user).send()),
-            github.get_winget_pkgs().send(),
-            github.get_versions(&self.package_identifier)
-        )?;
+        match self.checkout.open(&github).await? {
+            Some(checkout) => self.remove(&checkout).await,
+            None => {
+                check_permissions(&github).await?;
+                self.remove(&github).await
+            }
+        }
+    }
+
+    async fn remove<R: ManifestRepository>(self, repository: &R) -> Result<()> {
+        let versions = repository.get_versions(&self.package_identifier).await?;
 
         if !versions.contains(&self.package_version) {
             bail!(
-                "{} version {} does not exist in {WINGET_PKGS_FULL_NAME}",
+                "{} version {} does not exist in {}",
                 self.package_identifier,
                 self.package_version,
+                Upstream::get(),
             );
         }
 
@@ -113,19 +121,21 @@ pub async fn run(self) -> Result<()> {
             return Ok(());
         }
 
-        let pull_request = github
-            .remove_version()
-            .identifier(&self.package_identifier)
-            .version(&self.package_version)
-            .reason(&deletion_reason)
-            .fork(&fork)
-            .winget_pkgs(&winget_pkgs)
-            .issue_resolves(&self.resolves)
-            .send()
+        let submission = repository
+            .remove_version(
+                VersionRemoval::builder()
+                    .identifier(&self.package_identifier)
+                    .version(&self.package_version)
+                    .reason(&deletion_reason)
+                    .issue_resolves(&self.resolves)
+                    .build(),
+            )
             .await?;
 
-        if self.open_pr {
-            open::that(pull_request.url().as_str())?;
+        if self.open_pr
+            && let Some(url) = submission.url()
+        {
+            open::that(url.as_str())?;
         }
 
         Ok(())
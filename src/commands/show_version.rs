@@ -1,9 +1,16 @@
+use anstream::println;
+use camino::Utf8PathBuf;
 use clap::Parser;
 use color_eyre::Result;
 use secrecy::SecretString;
 use winget_types::{PackageIdentifier, PackageVersion};
 
-use crate::{github::client::GitHub, manifests::print_changes, token::TokenManager};
+use crate::{
+    github::{GitHubError, client::GitHub},
+    manifests::{Manifests, json::ManifestFormat, print_changes},
+    repository::{LocalCheckout, ManifestRepository},
+    token::TokenManager,
+};
 
 /// Output the manifests for a given package and version
 #[expect(clippy::struct_excessive_bools)]
@@ -33,28 +40,29 @@ pub struct ShowVersion {
     #[arg(long)]
     version_manifest: bool,
 
+    /// Format to output the manifests in. Multiple manifests are output as a JSON array
+    #[arg(long, value_enum, default_value_t)]
+    format: ManifestFormat,
+
+    /// Local clone of the upstream repository to read manifests from instead of using the GitHub
+    /// API
+    #[arg(long, env = "KOMAC_CHECKOUT", value_hint = clap::ValueHint::DirPath)]
+    checkout: Option<Utf8PathBuf>,
+
     /// GitHub personal access token with the `public_repo` scope
     #[arg(short, long, env = "GITHUB_TOKEN", hide_env_values = true)]
     token: Option<SecretString>,
 }
 
 impl ShowVersion {
-    pub async fn run(self) -> Result<()> {
-        let token_manager = TokenManager::handle(self.token).await?;
-        let github = GitHub::new(&token_manager)?;
-
-        // Get a list of all versions for the given package
-        let mut versions = github.get_versions(&self.package_identifier).await?;
-
-        // Get the manifests for the latest or specified version
-        let manifests = github
-            .get_manifests(
-                &self.package_identifier,
-                &self
-                    .package_version
-                    .unwrap_or_else(|| versions.pop_last().unwrap_or_else(|| unreachable!())),
-            )
-            .await?;
+    pub async fn run(mut self) -> Result<()> {
+        let manifests = if let Some(checkout) = &self.checkout {
+            self.get_manifests(&LocalCheckout::open(checkout).await?)
+                .await?
+        } else {
+            let token_manager = TokenManager::handle(self.token.take()).await?;
+            self.get_manifests(&GitHub::new(&token_manager)?).await?
+        };
 
         let all = matches!(
             (
@@ -68,25 +76,56 @@ pub async fn run(self) -> Result<()> {
 
         let mut contents = Vec::new();
         if all || self.installer_manifest {
-            contents.push(serde_yaml::to_string(&manifests.installer)?);
+            contents.push(serde_yaml::to_value(&manifests.installer)?);
         }
         if all || self.default_locale_manifest {
-            contents.push(serde_yaml::to_string(&manifests.default_locale)?);
+            contents.push(serde_yaml::to_value(&manifests.default_locale)?);
         }
         if all || self.locale_manifests {
             contents.extend(
                 manifests
                     .locales
                     .into_iter()
-                    .flat_map(|locale_manifest| serde_yaml::to_string(&locale_manifest)),
+                    .flat_map(|locale_manifest| serde_yaml::to_value(&locale_manifest)),
             );
         }
         if all || self.version_manifest {
-            contents.push(serde_yaml::to_string(&manifests.version)?);
+            contents.push(serde_yaml::to_value(&manifests.version)?);
         }
 
-        print_changes(contents);
+        match self.format {
+            ManifestFormat::Yaml => print_changes(
+                contents
+                    .iter()
+                    .map(serde_yaml::to_string)
+                    .collect::<Result<Vec<_>, _>>()?,
+            ),
+            ManifestFormat::Json => println!(
+                "{}",
+                match contents.as_slice() {
+                    [manifest] => serde_json::to_string_pretty(manifest)?,
+                    manifests => serde_json::to_string_pretty(manifests)?,
+                }
+            ),
+        }
 
         Ok(())
     }
+    /// Gets the manifests for the latest or specified version
+    async fn get_manifests<R: ManifestRepository>(
+        &self,
+        repository: &R,
+    ) -> Result<Manifests, GitHubError> {
+        // Get a list of all versions for the given package
+        let mut versions = repository.get_versions(&self.package_identifier).await?;
+
+        let version = self
+            .package_version
+            .clone()
+            .unwrap_or_else(|| versions.pop_last().unwrap_or_else(|| unreachable!()));
+
+        repository
+            .get_manifests(&self.package_identifier, &version)
+            .await
+    }
 }
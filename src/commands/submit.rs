@@ -9,17 +9,24 @@
 use itertools::Itertools;
 use owo_colors::OwoColorize;
 use secrecy::SecretString;
-use walkdir::WalkDir;
 use winget_types::{GenericManifest, ManifestType, ManifestVersion};
 
 use crate::{
-    commands::utils::{RateLimit, SPINNER_TICK_RATE, SubmitOption},
+    commands::utils::{
+        RateLimit, SPINNER_TICK_RATE, SubmitOption, check_permissions, manifest_file_paths,
+    },
     github::{
         client::GitHub,
         utils::{PackagePath, pull_request::pr_changes},
     },
-    manifests::{Manifests, manifest::Manifest},
+    manifests::{
+        Manifests,
+        json::{from_json, is_json},
+        manifest::Manifest,
+        singleton::{is_singleton, split},
+    },
     prompts::handle_inquire_error,
+    repository::{CheckoutArgs, ManifestRepository, Submission, VersionAddition},
     token::TokenManager,
 };
 
@@ -53,6 +60,9 @@ pub struct Submit {
     #[arg(long, env = "DRY_RUN")]
     dry_run: bool,
 
+    #[command(flatten)]
+    checkout: CheckoutArgs,
+
     /// GitHub personal access token with the `public_repo` scope
     #[arg(short, long, env = "GITHUB_TOKEN", hide_env_values = true)]
     token: Option<SecretString>,
@@ -62,14 +72,25 @@ impl Submit {
     pub async fn run(mut self) -> Result<()> {
         let token_manager = TokenManager::handle(self.token.take()).await?;
 
-        let yaml_entries = self.get_yaml_file_paths()?;
+        let manifest_entries = manifest_file_paths(&self.path)?;
 
-        let packages = yaml_entries
+        let packages = manifest_entries
             .iter()
             .flat_map(|path| {
                 // Read file to string so we can read it twice - once for the manifest type and
                 // second for the full manifest
-                let manifest = io::read_to_string(File::open(path)?)?;
+                let mut manifest = io::read_to_string(File::open(path)?)?;
+
+                // JSON manifests are converted to YAML so that they are read like any other
+                if is_json(path) {
+                    manifest = from_json(&manifest)?;
+                }
+
+                // A singleton manifest holds the installer, default locale and version manifests
+                // in one file, so it is split into them to be submitted as separate files
+                if is_singleton(&manifest) {
+                    return Ok::<Vec<Manifest>, eyre::Error>(Vec::from(split(&manifest)?));
+                }
 
                 // Deserialize the manifest into just the manifest type so that it can be determined
                 // which manifest to properly deserialize into
@@ -83,8 +104,9 @@ pub async fn run(mut self) -> Result<()> {
                     ManifestType::Locale => Manifest::Locale(serde_yaml::from_str(&manifest)?),
                     ManifestType::Version => Manifest::Version(serde_yaml::from_str(&manifest)?),
                 };
-                Ok::<Manifest, eyre::Error>(manifest)
+                Ok(vec![manifest])
             })
+            .flatten()
             .chunk_by(|manifest| {
                 // Group manifests by both the package identifier and the package version
                 (
@@ -138,10 +160,26 @@ pub async fn run(mut self) -> Result<()> {
                 .map_err(handle_inquire_error)?,
         };
 
-        let rate_limit = RateLimit::new(self.fast);
-
         let github = GitHub::new(token_manager)?;
 
+        match self.checkout.open(&github).await? {
+            Some(checkout) => self.submit(manifests, &checkout).await,
+            None => {
+                if !self.dry_run {
+                    check_permissions(&github).await?;
+                }
+                self.submit(manifests, &github).await
+            }
+        }
+    }
+
+    async fn submit<R: ManifestRepository>(
+        &self,
+        manifests: Vec<Manifests>,
+        repository: &R,
+    ) -> Result<()> {
+        let rate_limit = RateLimit::new(self.fast);
+
         for mut manifest in manifests {
             let identifier = &manifest.version.package_identifier;
             let version = &manifest.version.package_version;
@@ -174,7 +212,7 @@ pub async fn run(mut self) -> Result<()> {
                 continue;
             }
 
-            let versions = github.get_versions(identifier).await.ok();
+            let versions = repository.get_versions(identifier).await.ok();
 
             rate_limit.wait().await;
 
@@ -184,40 +222,34 @@ pub async fn run(mut self) -> Result<()> {
             ));
             pr_progress.enable_steady_tick(SPINNER_TICK_RATE);
 
-            let pull_request = github
-                .add_version()
-                .identifier(identifier)
-                .version(version)
-                .maybe_versions(versions.as_ref())
-                .changes(changes)
-                .issue_resolves(&self.resolves)
-                .send()
+            let submission = repository
+                .add_version(
+                    VersionAddition::builder()
+                        .identifier(identifier)
+                        .version(version)
+                        .maybe_versions(versions.as_ref())
+                        .changes(changes)
+                        .issue_resolves(&self.resolves)
+                        .build(),
+                )
                 .await?;
 
-            rate_limit.record().await;
+            // Only pull requests count towards GitHub's limit, not commits to a local checkout
+            if let Submission::PullRequest(_) = submission {
+                rate_limit.record().await;
+            }
 
             pr_progress.finish_and_clear();
 
-            pull_request.print_success();
+            submission.print_success();
 
-            if self.open_pr {
-                open::that(pull_request.url().as_str())?;
+            if self.open_pr
+                && let Some(url) = submission.url()
+            {
+                open::that(url.as_str())?;
             }
         }
 
         Ok(())
     }
-
-    fn get_yaml_file_paths(&self) -> walkdir::Result<Vec<Utf8PathBuf>> {
-        WalkDir::new(&self.path)
-            .into_iter()
-            .filter_map_ok(|entry| {
-                entry
-                    .path()
-                    .extension()
-                    .is_some_and(|ext| ext.eq_ignore_ascii_case("yaml"))
-                    .then(|| Utf8PathBuf::from_path_buf(entry.into_path()).ok())?
-            })
-            .collect::<walkdir::Result<Vec<_>>>()
-    }
 }
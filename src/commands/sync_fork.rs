@@ -11,12 +11,12 @@
 
 use crate::{
     commands::utils::{SPINNER_TICK_RATE, environment::VHS},
-    github::{WINGET_PKGS, WINGET_PKGS_FULL_NAME, client::GitHub},
+    github::{client::GitHub, upstream::Upstream},
     terminal::Hyperlinkable,
     token::TokenManager,
 };
 
-/// Merges changes from microsoft/winget-pkgs into the fork repository
+/// Merges changes from the upstream repository into the fork repository
 #[derive(Parser)]
 #[clap(visible_aliases = ["sync-fork", "merge-upstream"])]
 pub struct SyncFork {
@@ -95,8 +95,8 @@ async fn vhs() -> Result<()> {
         let merge_message = format!(
             "{} upstream commits from {} into {}",
             random_range(50..=500),
-            WINGET_PKGS_FULL_NAME.blue(),
-            format_args!("octocat/{WINGET_PKGS}").blue()
+            Upstream::get().blue(),
+            format_args!("octocat/{}", Upstream::get().repo()).blue()
         );
 
         let pb = ProgressBar::new_spinner().with_message(format!("Merging {merge_message}"));
@@ -1,6 +1,6 @@
 use clap::{Args, Subcommand};
 
-use crate::commands::token::{remove::RemoveToken, update::UpdateToken};
+use crate::commands::token::{login::Login, remove::RemoveToken, update::UpdateToken};
 
 #[derive(Args)]
 pub struct TokenArgs {
@@ -9,6 +9,7 @@ pub struct TokenArgs {
 }
 #[derive(Subcommand)]
 pub enum TokenCommands {
+    Login(Login),
     Update(UpdateToken),
     Remove(RemoveToken),
 }
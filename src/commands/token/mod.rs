@@ -1,3 +1,4 @@
 pub mod commands;
+pub mod login;
 pub mod remove;
 pub mod update;
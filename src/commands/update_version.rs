@@ -9,7 +9,7 @@
 use camino::Utf8PathBuf;
 use clap::Parser;
 use color_eyre::eyre::{Error, Result, bail};
-use futures_util::TryFutureExt;
+use futures_util::{FutureExt, TryFutureExt, future::try_join_all};
 use indicatif::ProgressBar;
 use itertools::Itertools;
 use owo_colors::OwoColorize;
@@ -23,20 +23,25 @@
 };
 
 use crate::{
-    analysis::installers::Zip,
+    analysis::{installers::Zip, is_supported},
     commands::utils::{
-        SPINNER_TICK_RATE, SubmitOption, prompt_existing_pull_request, write_changes_to_dir,
+        ExistingPullRequestOption, SPINNER_TICK_RATE, SubmitOption, amendable_pull_request,
+        check_permissions, fetch_release_notes, write_changes_to_dir,
     },
-    download::Downloader,
+    download::{ByteRate, Downloader},
     download_file::process_files,
     github::{
-        GITHUB_HOST, GitHubError, WINGET_PKGS_FULL_NAME,
         client::{GitHub, GitHubValues},
-        graphql::get_existing_pull_request::PullRequest,
-        utils::{PackagePath, pull_request::pr_changes},
+        graphql::{get_existing_pull_request::PullRequest, get_pull_request},
+        host::GitHubHost,
+        upstream::Upstream,
+        utils::{ManifestSnapshot, PackagePath, pull_request::pr_changes},
     },
-    manifests::Url,
+    manifests::{Url, json::ManifestFormat},
     match_installers::match_installers,
+    metadata,
+    release_notes::ReleaseNotesSource,
+    repository::{CheckoutArgs, ManifestRepository, VersionAddition},
     token::TokenManager,
     traits::{LocaleExt, path::NormalizePath},
 };
@@ -61,6 +66,14 @@ pub struct UpdateVersion {
     #[arg(long, default_value_t = NonZeroUsize::new(num_cpus::get()).unwrap())]
     concurrent_downloads: NonZeroUsize,
 
+    /// Maximum number of installers to download from the same host at the same time
+    #[arg(long)]
+    max_connections_per_host: Option<NonZeroUsize>,
+
+    /// Maximum combined download speed, such as `20MB/s` or `512KiB/s`
+    #[arg(long, env = "KOMAC_MAX_RATE")]
+    max_rate: Option<ByteRate>,
+
     /// List of issues that updating this package would resolve
     #[arg(long)]
     resolves: Vec<NonZeroU32>,
@@ -73,6 +86,11 @@ pub struct UpdateVersion {
     #[arg(long, value_hint = clap::ValueHint::Url)]
     release_notes_url: Option<ReleaseNotesUrl>,
 
+    /// Where to read the release notes from instead of the GitHub release: `changelog`,
+    /// `changelog:<PATH|URL>`, `gitlab:<URL>`, `gitea:<URL>` or `feed:<URL>`
+    #[arg(long, value_name = "SOURCE")]
+    release_notes_source: Option<ReleaseNotesSource>,
+
     /// Name of external tool that invoked Komac
     #[arg(long, env = "KOMAC_CREATED_WITH")]
     created_with: Option<String>,
@@ -85,6 +103,10 @@ pub struct UpdateVersion {
     #[arg(short, long, env = "OUTPUT_DIRECTORY", value_hint = clap::ValueHint::DirPath)]
     output: Option<Utf8PathBuf>,
 
+    /// Format to write the manifests to the output directory in
+    #[arg(long, value_enum, default_value_t, requires = "output")]
+    format: ManifestFormat,
+
     /// Open pull request link automatically
     #[arg(long, env = "OPEN_PR")]
     open_pr: bool,
@@ -101,6 +123,13 @@ pub struct UpdateVersion {
     #[arg(long, env)]
     skip_pr_check: bool,
 
+    /// Commit the manifests to an existing pull request's branch instead of creating a new one
+    #[arg(long, value_name = "NUMBER", value_parser = clap::value_parser!(i32).range(1..))]
+    amend_pr: Option<i32>,
+
+    #[command(flatten)]
+    checkout: CheckoutArgs,
+
     /// GitHub personal access token with the `public_repo` scope
     #[arg(short, long, env = "GITHUB_TOKEN", hide_env_values = true)]
     token: Option<SecretString>,
@@ -111,8 +140,20 @@ pub async fn run(mut self) -> Result<()> {
         let token_manager = TokenManager::handle(self.token.take()).await?;
         let github = GitHub::new(&token_manager)?;
 
+        match self.checkout.open(&github).await? {
+            Some(checkout) => self.update(&github, &checkout).await,
+            None => {
+                if !self.dry_run {
+                    check_permissions(&github).await?;
+                }
+                self.update(&github, &github).await
+            }
+        }
+    }
+
+    async fn update<R: ManifestRepository>(self, github: &GitHub, repository: &R) -> Result<()> {
         let (versions, existing_pr) = try_join!(
-            github.get_versions(&self.package_identifier),
+            repository.get_versions(&self.package_identifier),
             github.get_existing_pull_request(&self.package_identifier, &self.package_version),
         )?;
 
@@ -124,20 +165,41 @@ pub async fn run(mut self) -> Result<()> {
 
         let replace_version = self.resolve_replace_version(&versions, latest_version)?;
 
-        if self.should_abort_for_existing_pr(existing_pr)? {
-            return Ok(());
-        }
-
-        let downloader = Downloader::new_with_concurrent(self.concurrent_downloads)?;
-        let (mut manifests, mut github_values, mut files) = try_join!(
-            github
+        let amend_number = match self.amend_pr {
+            Some(number) => Some(number),
+            None => match self.existing_pr_option(existing_pr)? {
+                Some((ExistingPullRequestOption::Abort, _)) => return Ok(()),
+                Some((ExistingPullRequestOption::Amend, number)) => Some(number),
+                Some((ExistingPullRequestOption::CreateNew, _)) | None => None,
+            },
+        };
+        let amend = match amend_number {
+            Some(number) => Some(amendable_pull_request(github, number).await?),
+            None => None,
+        };
+
+        let downloader = Downloader::new_with_concurrent(self.concurrent_downloads)?
+            .with_max_connections_per_host(self.max_connections_per_host)
+            .with_max_rate(self.max_rate);
+        check_urls(&downloader, &self.urls).await?;
+        let (mut manifests, mut github_values, sourced_release_notes, files) = try_join!(
+            repository
                 .get_manifests(&self.package_identifier, latest_version)
                 .map_err(Error::new),
-            self.fetch_github_values(&github).map_err(Error::new),
+            self.fetch_values(github),
+            fetch_release_notes(
+                self.release_notes_source.as_ref(),
+                github,
+                &self.urls,
+                &self.package_version
+            )
+            .map(Ok),
             downloader.download(self.urls.iter().cloned()),
         )?;
 
-        let mut download_results = process_files(&mut files).await?;
+        let previous = ManifestSnapshot::new(&manifests);
+
+        let mut download_results = process_files(files);
         let installer_results = download_results
             .iter_mut()
             .flat_map(|(_url, analyzer)| mem::take(&mut analyzer.installers))
@@ -225,6 +287,12 @@ pub async fn run(mut self) -> Result<()> {
             self.release_notes_url.as_ref(),
         );
 
+        if let Some(sourced) = sourced_release_notes {
+            manifests.default_locale.release_notes = Some(sourced.notes);
+            manifests.default_locale.release_notes_url =
+                self.release_notes_url.clone().or(sourced.url);
+        }
+
         manifests.locales.iter_mut().for_each(|locale| {
             locale.update(
                 &self.package_version,
@@ -244,6 +312,15 @@ pub async fn run(mut self) -> Result<()> {
             .maybe_created_with(self.created_with.as_deref())
             .create()?;
 
+        if let Some(current) = ManifestSnapshot::from_changes(&changes) {
+            let diff = current.diff(&previous);
+            if !diff.is_empty() {
+                println!("{} {latest_version}:", "Changes since".bold());
+                diff.print();
+                println!();
+            }
+        }
+
         let submit_option = SubmitOption::prompt(
             &mut changes,
             &self.package_identifier,
@@ -257,7 +334,7 @@ pub async fn run(mut self) -> Result<()> {
             .as_ref()
             .map(|out| out.join(package_path.as_str()))
         {
-            write_changes_to_dir(&changes, output.as_path()).await?;
+            write_changes_to_dir(&changes, output.as_path(), self.format).await?;
             println!(
                 "{} written all manifest files to {output}",
                 "Successfully".green()
@@ -268,6 +345,12 @@ pub async fn run(mut self) -> Result<()> {
             return Ok(());
         }
 
+        if let Some((pull_request, fork_owner)) = amend {
+            return self
+                .amend(github, &pull_request, &fork_owner, changes, &previous)
+                .await;
+        }
+
         // Create an indeterminate progress bar to show as a pull request is being created
         let pr_progress = ProgressBar::new_spinner().with_message(format!(
             "Creating a pull request for {} {}",
@@ -275,25 +358,30 @@ pub async fn run(mut self) -> Result<()> {
         ));
         pr_progress.enable_steady_tick(SPINNER_TICK_RATE);
 
-        let pull_request = github
-            .add_version()
-            .identifier(&self.package_identifier)
-            .version(&self.package_version)
-            .versions(&versions)
-            .changes(changes)
-            .maybe_replace_version(replace_version)
-            .issue_resolves(&self.resolves)
-            .maybe_created_with(self.created_with.as_deref())
-            .maybe_created_with_url(self.created_with_url.as_ref())
-            .send()
+        let submission = repository
+            .add_version(
+                VersionAddition::builder()
+                    .identifier(&self.package_identifier)
+                    .version(&self.package_version)
+                    .versions(&versions)
+                    .changes(changes)
+                    .maybe_replace_version(replace_version)
+                    .issue_resolves(&self.resolves)
+                    .maybe_created_with(self.created_with.as_deref())
+                    .maybe_created_with_url(self.created_with_url.as_ref())
+                    .previous(&previous)
+                    .build(),
+            )
             .await?;
 
         pr_progress.finish_and_clear();
 
-        pull_request.print_success();
+        submission.print_success();
 
-        if self.open_pr {
-            open::that(pull_request.url().as_str())?;
+        if self.open_pr
+            && let Some(url) = submission.url()
+        {
+            open::that(url.as_str())?;
         }
 
         Ok(())
@@ -321,47 +409,100 @@ fn resolve_replace_version<'a>(
             && let Some(closest) = version.closest(versions)
         {
             bail!(
-                "Replacement version {version} does not exist in {WINGET_PKGS_FULL_NAME}. The closest version is {closest}"
+                "Replacement version {version} does not exist in {}. The closest version is {closest}",
+                Upstream::get(),
             )
         }
 
         Ok(replace_version)
     }
 
-    fn should_abort_for_existing_pr<T>(&self, existing_pr: T) -> Result<bool>
-    where
-        T: Into<Option<PullRequest>>,
-    {
-        if let Some(ref pull_request) = existing_pr.into()
-            && !self.skip_pr_check
-            && !self.dry_run
-            && !prompt_existing_pull_request(
-                &self.package_identifier,
-                &self.package_version,
-                pull_request,
-            )?
-        {
-            return Ok(true);
+    /// Prompts for what to do about an existing pull request, returning the chosen option along
+    /// with the pull request's number.
+    fn existing_pr_option(
+        &self,
+        existing_pr: Option<PullRequest>,
+    ) -> Result<Option<(ExistingPullRequestOption, i32)>> {
+        if self.skip_pr_check || self.dry_run {
+            return Ok(None);
         }
 
-        Ok(false)
+        existing_pr
+            .map(|pull_request| {
+                ExistingPullRequestOption::prompt(
+                    &self.package_identifier,
+                    &self.package_version,
+                    &pull_request,
+                )
+                .map(|option| (option, pull_request.number))
+            })
+            .transpose()
     }
 
-    async fn fetch_github_values(
+    async fn amend(
         &self,
         github: &GitHub,
-    ) -> Result<Option<GitHubValues>, GitHubError> {
+        pull_request: &get_pull_request::PullRequest,
+        fork_owner: &str,
+        changes: Vec<(String, String)>,
+        previous: &ManifestSnapshot,
+    ) -> Result<()> {
+        // Create an indeterminate progress bar to show as the pull request is being amended
+        let pr_progress = ProgressBar::new_spinner().with_message(format!(
+            "Amending #{} with {} {}",
+            pull_request.number, self.package_identifier, self.package_version
+        ));
+        pr_progress.enable_steady_tick(SPINNER_TICK_RATE);
+
+        let amended = github
+            .amend_pull_request()
+            .pull_request(pull_request)
+            .fork_owner(fork_owner)
+            .identifier(&self.package_identifier)
+            .version(&self.package_version)
+            .changes(changes)
+            .issue_resolves(&self.resolves)
+            .maybe_created_with(self.created_with.as_deref())
+            .maybe_created_with_url(self.created_with_url.as_ref())
+            .previous(previous)
+            .send()
+            .await?;
+
+        pr_progress.finish_and_clear();
+
+        let Some(amended) = amended else {
+            println!(
+                "#{} already contains these manifests so it was left unchanged",
+                pull_request.number
+            );
+            println!("{}", pull_request.url.blue());
+            return Ok(());
+        };
+
+        amended.print_updated();
+
+        if self.open_pr {
+            open::that(amended.url().as_str())?;
+        }
+
+        Ok(())
+    }
+
+    /// Reads the package's metadata from its GitHub repository, or from the forge that hosts the
+    /// installers if none of them are released on GitHub.
+    async fn fetch_values(&self, github: &GitHub) -> Result<Option<GitHubValues>> {
         if let Some(url) = self
             .urls
             .iter()
-            .find(|url| url.host_str() == Some(GITHUB_HOST))
+            .find(|url| GitHubHost::from_url(url).is_some())
         {
-            github
+            Ok(github
                 .get_all_values_from_url(url.clone().into_inner())
                 .await
-                .transpose()
+                .transpose()?)
         } else {
-            Ok(None)
+            let urls = self.urls.iter().map(|url| &***url).collect::<Vec<_>>();
+            Ok(metadata::get_values_or_warn(&urls, &self.package_version).await)
         }
     }
 }
@@ -399,3 +540,25 @@ fn fix_relative_paths<R: Read + Seek>(
         })
         .collect::<BTreeSet<_>>()
 }
+
+/// Checks that every URL can be reached and is a file type that installers can be analyzed from
+/// before any installer is downloaded in full.
+///
+/// Only the start of each file is read, through a range request. Files from servers that don't
+/// support range requests are left to be checked when they are downloaded.
+async fn check_urls(downloader: &Downloader, urls: &[Url]) -> Result<()> {
+    let readers = try_join_all(urls.iter().map(|url| downloader.open_ranged(url.clone()))).await?;
+
+    for (url, reader) in urls.iter().zip(readers) {
+        if let Some(reader) = reader
+            && !is_supported(reader.file_name())
+        {
+            bail!(
+                "{url} is {}, which installers can't be analyzed from",
+                reader.file_name()
+            );
+        }
+    }
+
+    Ok(())
+}
@@ -0,0 +1,72 @@
+use std::fmt;
+
+use color_eyre::Result;
+use inquire::Select;
+use winget_types::{PackageIdentifier, PackageVersion};
+
+use super::{print_existing_pull_request, prompt_existing_pull_request};
+use crate::{
+    commands::utils::environment::CI, github::graphql::get_existing_pull_request::PullRequest,
+    prompts::handle_inquire_error,
+};
+
+/// What to do when a pull request already exists for the package version being updated.
+#[derive(Clone, Copy, Eq, PartialEq)]
+pub enum ExistingPullRequestOption {
+    Amend,
+    CreateNew,
+    Abort,
+}
+
+impl ExistingPullRequestOption {
+    /// Asks whether to amend the existing pull request, create a new one alongside it, or abort.
+    ///
+    /// Amending is only offered when the existing pull request is still open.
+    pub fn prompt(
+        identifier: &PackageIdentifier,
+        version: &PackageVersion,
+        pull_request: &PullRequest,
+    ) -> Result<Self> {
+        if !pull_request.is_open() {
+            return Ok(
+                if prompt_existing_pull_request(identifier, version, pull_request)? {
+                    Self::CreateNew
+                } else {
+                    Self::Abort
+                },
+            );
+        }
+
+        print_existing_pull_request(identifier, version, pull_request);
+        if *CI {
+            // Exit instead of proceeding in CI environments
+            Ok(Self::Abort)
+        } else {
+            Ok(
+                Select::new("What would you like to do?", Self::all().into())
+                    .prompt()
+                    .map_err(handle_inquire_error)?,
+            )
+        }
+    }
+
+    /// Returns an array of all the existing pull request options.
+    #[inline]
+    pub const fn all() -> [Self; 3] {
+        [Self::Amend, Self::CreateNew, Self::Abort]
+    }
+
+    pub const fn as_str(self) -> &'static str {
+        match self {
+            Self::Amend => "Amend the existing pull request",
+            Self::CreateNew => "Create a new pull request",
+            Self::Abort => "Abort",
+        }
+    }
+}
+
+impl fmt::Display for ExistingPullRequestOption {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.as_str().fmt(f)
+    }
+}
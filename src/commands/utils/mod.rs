@@ -1,24 +1,34 @@
 pub mod environment;
+mod existing_pull_request;
 mod rate_limit;
 mod submit_option;
 
 use std::time::Duration;
 
 use anstream::println;
-use camino::Utf8Path;
+use camino::{Utf8Path, Utf8PathBuf};
 use chrono::Local;
-use color_eyre::Result;
+use color_eyre::{Result, eyre::bail};
+pub use existing_pull_request::ExistingPullRequestOption;
 use futures_util::{StreamExt, TryStreamExt, stream};
 use inquire::error::InquireResult;
+use itertools::Itertools;
 use owo_colors::OwoColorize;
 pub use rate_limit::RateLimit;
 pub use submit_option::SubmitOption;
-use tokio::{fs, fs::File, io::AsyncWriteExt};
+use tokio::{fs, fs::File, io::AsyncWriteExt, try_join};
+use walkdir::WalkDir;
 use winget_types::{PackageIdentifier, PackageVersion};
 
 use crate::{
-    commands::utils::environment::CI, github::graphql::get_existing_pull_request::PullRequest,
+    commands::utils::environment::CI,
+    github::{
+        client::GitHub,
+        graphql::{get_existing_pull_request::PullRequest, get_pull_request},
+    },
+    manifests::{Url, json::ManifestFormat, validation::ManifestFile},
     prompts::text::confirm_prompt,
+    release_notes::{ReleaseNotesSource, SourcedReleaseNotes},
 };
 
 pub const SPINNER_TICK_RATE: Duration = Duration::from_millis(50);
@@ -30,6 +40,20 @@ pub fn prompt_existing_pull_request(
     version: &PackageVersion,
     pull_request: &PullRequest,
 ) -> InquireResult<bool> {
+    print_existing_pull_request(identifier, version, pull_request);
+    if *CI {
+        // Exit instead of proceeding in CI environments
+        Ok(false)
+    } else {
+        confirm_prompt("Would you like to proceed?")
+    }
+}
+
+fn print_existing_pull_request(
+    identifier: &PackageIdentifier,
+    version: &PackageVersion,
+    pull_request: &PullRequest,
+) {
     let created_at = pull_request.created_at.with_timezone(&Local);
     println!(
         "There is already {} pull request for {identifier} {version} that was created on {} at {}",
@@ -38,21 +62,100 @@ pub fn prompt_existing_pull_request(
         created_at.time()
     );
     println!("{}", pull_request.url.blue());
-    if *CI {
-        // Exit instead of proceeding in CI environments
-        Ok(false)
-    } else {
-        confirm_prompt("Would you like to proceed?")
+}
+
+/// Checks that the token has the scopes or permissions it needs to push to the user's fork, so that
+/// a missing permission is reported before any installers are downloaded.
+pub async fn check_permissions(github: &GitHub) -> Result<()> {
+    let owner = github.get_username().await?;
+    let missing = github.missing_permissions(&owner).await?;
+    if !missing.is_empty() {
+        bail!(
+            "The GitHub token can't be used to open pull requests:\n{}",
+            missing
+                .iter()
+                .map(|permission| format!("  • {permission}"))
+                .join("\n")
+        )
+    }
+
+    Ok(())
+}
+
+/// Gets a pull request by its number and checks that it can be amended, returning it along with
+/// the owner of the fork its branch is in.
+pub async fn amendable_pull_request(
+    github: &GitHub,
+    number: i32,
+) -> Result<(get_pull_request::PullRequest, String)> {
+    let (pull_request, current_user) =
+        try_join!(github.get_pull_request(number), github.get_username())?;
+
+    if !pull_request.is_open() {
+        bail!(
+            "#{number} is {} pull request and can no longer be amended",
+            pull_request.state
+        )
+    }
+
+    if pull_request.head_ref.is_none() {
+        bail!("The branch of #{number} no longer exists")
+    }
+
+    match pull_request.head_repository_owner {
+        Some(ref owner) if owner.login == current_user => {}
+        _ => bail!("#{number} was not opened from {current_user}'s fork so it cannot be amended"),
+    }
+
+    Ok((pull_request, current_user))
+}
+
+/// Reads the release notes of a package version from a source if one was given, warning if the
+/// source couldn't be read or has no notes for the version.
+///
+/// Returning `None` falls back to the release notes from GitHub, so a source that is unreachable
+/// or malformed doesn't stop a manifest from being made after its installers were downloaded.
+pub async fn fetch_release_notes(
+    source: Option<&ReleaseNotesSource>,
+    github: &GitHub,
+    urls: &[Url],
+    version: &PackageVersion,
+) -> Option<SourcedReleaseNotes> {
+    let source = source?;
+
+    let urls = urls.iter().map(|url| &***url).collect::<Vec<_>>();
+    match source.fetch(github, &urls, version).await {
+        Ok(Some(release_notes)) => Some(release_notes),
+        Ok(None) => {
+            println!(
+                "{} No release notes for {version} were found in {source}",
+                "Warning:".yellow()
+            );
+            None
+        }
+        Err(error) => {
+            println!(
+                "{} Failed to read release notes from {source}: {error}",
+                "Warning:".yellow()
+            );
+            None
+        }
     }
 }
 
-pub async fn write_changes_to_dir(changes: &[(String, String)], output: &Utf8Path) -> Result<()> {
+/// Writes the manifests to a directory in a format, converting them from YAML if needed.
+pub async fn write_changes_to_dir(
+    changes: &[(String, String)],
+    output: &Utf8Path,
+    format: ManifestFormat,
+) -> Result<()> {
     fs::create_dir_all(output).await?;
     stream::iter(changes.iter())
         .map(|(path, content)| async move {
             if let Some(file_name) = Utf8Path::new(path).file_name() {
-                let mut file = File::create(output.join(file_name)).await?;
-                file.write_all(content.as_bytes()).await?;
+                let path = output.join(file_name).with_extension(format.extension());
+                let mut file = File::create(path).await?;
+                file.write_all(format.convert(content)?.as_bytes()).await?;
             }
             Ok::<(), color_eyre::eyre::Error>(())
         })
@@ -60,3 +163,47 @@ pub async fn write_changes_to_dir(changes: &[(String, String)], output: &Utf8Pat
         .try_collect()
         .await
 }
+
+/// Reads every YAML file in a directory and its subdirectories, or a single YAML file.
+pub fn read_manifest_files(path: &Utf8Path) -> Result<Vec<ManifestFile>> {
+    let files = yaml_file_paths(path)?
+        .into_iter()
+        .map(|path| {
+            let text = std::fs::read_to_string(&path)?;
+            Ok(ManifestFile { path, text })
+        })
+        .collect::<Result<Vec<_>>>()?;
+
+    if files.is_empty() {
+        bail!("No manifests were found in {path}");
+    }
+
+    Ok(files)
+}
+
+/// Returns the paths of all the YAML files in a directory and its subdirectories.
+pub fn yaml_file_paths(path: &Utf8Path) -> walkdir::Result<Vec<Utf8PathBuf>> {
+    file_paths(path, &["yaml"])
+}
+
+/// Returns the paths of all the YAML and JSON files in a directory and its subdirectories.
+pub fn manifest_file_paths(path: &Utf8Path) -> walkdir::Result<Vec<Utf8PathBuf>> {
+    file_paths(path, &["yaml", "json"])
+}
+
+fn file_paths(path: &Utf8Path, extensions: &[&str]) -> walkdir::Result<Vec<Utf8PathBuf>> {
+    WalkDir::new(path)
+        .into_iter()
+        .filter_map_ok(|entry| {
+            entry
+                .path()
+                .extension()
+                .is_some_and(|ext| {
+                    extensions
+                        .iter()
+                        .any(|extension| ext.eq_ignore_ascii_case(extension))
+                })
+                .then(|| Utf8PathBuf::from_path_buf(entry.into_path()).ok())?
+        })
+        .collect::<walkdir::Result<Vec<_>>>()
+}
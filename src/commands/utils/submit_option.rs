@@ -1,11 +1,19 @@
 use std::fmt;
 
+use camino::Utf8PathBuf;
 use color_eyre::Result;
 use inquire::Select;
 use winget_types::{PackageIdentifier, PackageVersion};
 
 use crate::{
-    commands::utils::environment::VHS, editor::Editor, manifests::print_changes,
+    commands::utils::environment::VHS,
+    editor::Editor,
+    manifests::{
+        diagnostic::print_diagnostics,
+        lint::{LintRules, lint},
+        print_changes,
+        validation::ManifestFile,
+    },
     prompts::handle_inquire_error,
 };
 
@@ -27,6 +35,8 @@ pub fn prompt(
         let mut submit_option;
 
         loop {
+            print_lint_diagnostics(changes);
+
             let changes_iter = changes.iter().map(|(_, content)| content.as_str());
             if *VHS {
                 print_changes(changes_iter.take(1));
@@ -91,6 +101,27 @@ pub const fn as_str(self) -> &'static str {
     }
 }
 
+/// Lints the changes and prints any problems that were found, so that they are shown above the
+/// manifest preview.
+fn print_lint_diagnostics(changes: &[(String, String)]) {
+    let diagnostics = changes
+        .iter()
+        .flat_map(|(path, content)| {
+            let file = ManifestFile {
+                path: Utf8PathBuf::from(path),
+                text: content.clone(),
+            };
+            lint(&file, LintRules::get())
+        })
+        .map(|finding| finding.diagnostic)
+        .collect::<Vec<_>>();
+
+    if !diagnostics.is_empty() {
+        let _ = print_diagnostics(anstream::stdout().lock(), &diagnostics);
+        anstream::println!();
+    }
+}
+
 impl fmt::Display for SubmitOption {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         self.as_str().fmt(f)
@@ -0,0 +1,62 @@
+use std::{collections::BTreeMap, fs};
+
+use camino::Utf8PathBuf;
+use clap::Parser;
+use color_eyre::{Result, eyre::bail};
+
+use crate::{
+    commands::utils::yaml_file_paths,
+    manifests::{
+        diagnostic::print_diagnostics,
+        validation::{ManifestFile, validate},
+    },
+};
+
+/// Validate manifests against their schema and winget's rules without submitting them
+///
+/// Manifests are grouped into package versions by the directory they are in, like
+/// `winget validate`. Exits with a non-zero code if any manifest has an error.
+#[derive(Parser)]
+pub struct Validate {
+    /// A directory of manifests, which is searched recursively
+    #[arg(value_hint = clap::ValueHint::DirPath)]
+    path: Utf8PathBuf,
+}
+
+impl Validate {
+    pub fn run(self) -> Result<()> {
+        let mut package_versions = BTreeMap::<Utf8PathBuf, Vec<ManifestFile>>::new();
+        for path in yaml_file_paths(&self.path)? {
+            let text = fs::read_to_string(&path)?;
+            let directory = path.parent().map(ToOwned::to_owned).unwrap_or_default();
+            package_versions
+                .entry(directory)
+                .or_default()
+                .push(ManifestFile { path, text });
+        }
+
+        if package_versions.is_empty() {
+            bail!("No manifests were found in {}", self.path);
+        }
+
+        let diagnostics = package_versions
+            .values()
+            .map(|files| validate(files))
+            .collect::<Vec<_>>();
+
+        print_diagnostics(anstream::stdout().lock(), &diagnostics.concat())?;
+
+        let invalid = diagnostics
+            .iter()
+            .filter(|diagnostics| diagnostics.iter().any(|diagnostic| diagnostic.is_error()))
+            .count();
+        if invalid > 0 {
+            bail!(
+                "{invalid} of {} package versions failed validation",
+                package_versions.len()
+            );
+        }
+
+        Ok(())
+    }
+}
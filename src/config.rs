@@ -0,0 +1,424 @@
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    env, fs, io,
+};
+
+use camino::{Utf8Path, Utf8PathBuf};
+use clap::Args;
+use color_eyre::eyre::{Result, WrapErr};
+use serde::Deserialize;
+
+use crate::{
+    github::{
+        host::GitHubHost,
+        upstream::Upstream,
+        utils::{BodySection, PullRequestSettings},
+    },
+    manifests::lint::{LintRules, Rule, RuleLevel},
+};
+
+/// Settings that are read from komac's configuration file.
+///
+/// The file is YAML and is read from the path in `--config` or `KOMAC_CONFIG`, or otherwise from
+/// `komac/config.yaml` in the user's configuration directory if it exists. Command line arguments
+/// take precedence over the file.
+///
+/// ```yaml
+/// upstream:
+///   host: ghe.example.com
+///   owner: contoso
+///   repo: winget-manifests
+///   branch: main
+///   manifest-root: manifests
+/// pull-request:
+///   draft: true
+///   body-template: pull-request-template.md
+///   sections: [release-notes, installers]
+///   reviewers: [octocat]
+/// lint:
+///   rules:
+///     tag-format: error
+///     msi-product-code: off
+/// ```
+#[derive(Default, Deserialize)]
+#[serde(default, rename_all = "kebab-case", deny_unknown_fields)]
+pub struct Config {
+    pub upstream: UpstreamConfig,
+    pub pull_request: PullRequestConfig,
+    pub lint: LintConfig,
+}
+
+impl Config {
+    const DIRECTORY: &'static str = "komac";
+    const FILE_NAME: &'static str = "config.yaml";
+
+    /// Loads the configuration file from an explicit path, or from the default path if it exists.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if an explicit path does not exist, or if the file could not be read or
+    /// parsed.
+    pub fn load(path: Option<&Utf8Path>) -> Result<Self> {
+        let (path, required) = match path {
+            Some(path) => (path.to_owned(), true),
+            None => match Self::default_path() {
+                Some(path) => (path, false),
+                None => return Ok(Self::default()),
+            },
+        };
+
+        let contents = match fs::read_to_string(&path) {
+            Ok(contents) => contents,
+            Err(error) if error.kind() == io::ErrorKind::NotFound && !required => {
+                return Ok(Self::default());
+            }
+            Err(error) => {
+                return Err(error).wrap_err_with(|| format!("Failed to read {path}"));
+            }
+        };
+
+        serde_yaml::from_str(&contents).wrap_err_with(|| format!("Failed to parse {path}"))
+    }
+
+    /// Returns the default path of the configuration file, which is `%APPDATA%\komac\config.yaml`
+    /// on Windows and `$XDG_CONFIG_HOME/komac/config.yaml` or `~/.config/komac/config.yaml`
+    /// elsewhere.
+    fn default_path() -> Option<Utf8PathBuf> {
+        let directory = if cfg!(windows) {
+            env::var("APPDATA").ok().map(Utf8PathBuf::from)
+        } else {
+            env::var("XDG_CONFIG_HOME")
+                .ok()
+                .filter(|directory| !directory.is_empty())
+                .map(Utf8PathBuf::from)
+                .or_else(|| {
+                    env::var("HOME")
+                        .ok()
+                        .map(|home| Utf8PathBuf::from(home).join(".config"))
+                })
+        }?;
+
+        Some(directory.join(Self::DIRECTORY).join(Self::FILE_NAME))
+    }
+}
+
+/// The repository that every command operates against, settable per invocation or in the
+/// configuration file.
+#[derive(Args, Default, Deserialize)]
+#[serde(default, rename_all = "kebab-case", deny_unknown_fields)]
+pub struct UpstreamConfig {
+    /// Host of the GitHub Enterprise Server that the upstream repository is on [default:
+    /// github.com]
+    #[arg(
+        long = "github-host",
+        global = true,
+        env = "KOMAC_GITHUB_HOST",
+        value_name = "HOST"
+    )]
+    pub host: Option<String>,
+
+    /// Owner of the upstream manifests repository [default: microsoft]
+    #[arg(
+        long = "upstream-owner",
+        global = true,
+        env = "KOMAC_UPSTREAM_OWNER",
+        value_name = "OWNER"
+    )]
+    pub owner: Option<String>,
+
+    /// Name of the upstream manifests repository and its forks [default: winget-pkgs]
+    #[arg(
+        long = "upstream-repo",
+        global = true,
+        env = "KOMAC_UPSTREAM_REPO",
+        value_name = "REPO"
+    )]
+    pub repo: Option<String>,
+
+    /// Branch of the upstream repository to read manifests from and open pull requests against
+    /// [default: the repository's default branch]
+    #[arg(
+        long = "upstream-branch",
+        global = true,
+        env = "KOMAC_UPSTREAM_BRANCH",
+        value_name = "BRANCH"
+    )]
+    pub branch: Option<String>,
+
+    /// Directory in the upstream repository that manifests are stored in [default: manifests]
+    #[arg(
+        long,
+        global = true,
+        env = "KOMAC_MANIFEST_ROOT",
+        value_name = "DIRECTORY"
+    )]
+    pub manifest_root: Option<String>,
+}
+
+impl UpstreamConfig {
+    /// Fills in any unset values from another configuration.
+    #[must_use]
+    pub fn or(self, other: Self) -> Self {
+        Self {
+            host: self.host.or(other.host),
+            owner: self.owner.or(other.owner),
+            repo: self.repo.or(other.repo),
+            branch: self.branch.or(other.branch),
+            manifest_root: self.manifest_root.or(other.manifest_root),
+        }
+    }
+}
+
+impl From<UpstreamConfig> for Upstream {
+    fn from(config: UpstreamConfig) -> Self {
+        let default = Self::default();
+        Self::new(
+            config.owner.as_deref().unwrap_or(default.owner()),
+            config.repo.as_deref().unwrap_or(default.repo()),
+            config.branch,
+            config
+                .manifest_root
+                .as_deref()
+                .unwrap_or(default.manifest_root()),
+        )
+        .with_host(config.host.map(GitHubHost::new).unwrap_or_default())
+    }
+}
+
+/// How pull requests are opened and what their bodies contain, settable per invocation or in the
+/// configuration file.
+#[derive(Args, Default, Deserialize)]
+#[serde(default, rename_all = "kebab-case", deny_unknown_fields)]
+pub struct PullRequestConfig {
+    /// Open pull requests as drafts
+    #[arg(
+        long,
+        global = true,
+        num_args = 0,
+        default_missing_value = "true",
+        overrides_with = "no_draft"
+    )]
+    pub draft: Option<bool>,
+
+    /// Open pull requests as ready for review, even if the configuration file opens them as drafts
+    #[arg(long, global = true, overrides_with = "draft")]
+    #[serde(skip)]
+    pub no_draft: bool,
+
+    /// Path to a Markdown template for the body of pull requests for new or updated versions
+    ///
+    /// The template can contain {identifier}, {version}, {urls}, {hashes}, {changed_fields},
+    /// {resolves}, {created_with}, {release_notes}, {installers}, {diff} and {reviewers}.
+    #[arg(
+        long,
+        global = true,
+        env = "KOMAC_BODY_TEMPLATE",
+        value_name = "FILE",
+        value_hint = clap::ValueHint::FilePath
+    )]
+    pub body_template: Option<Utf8PathBuf>,
+
+    /// Extra sections to add to the default pull request body
+    #[arg(
+        long = "body-section",
+        global = true,
+        value_enum,
+        value_delimiter = ','
+    )]
+    pub sections: Vec<BodySection>,
+
+    /// GitHub users to mention in the body of pull requests
+    #[arg(
+        long = "mention",
+        global = true,
+        value_delimiter = ',',
+        value_name = "USER"
+    )]
+    pub reviewers: Vec<String>,
+}
+
+impl PullRequestConfig {
+    /// Fills in any unset values from another configuration.
+    #[must_use]
+    pub fn or(self, other: Self) -> Self {
+        Self {
+            draft: if self.no_draft {
+                Some(false)
+            } else {
+                self.draft
+            }
+            .or(other.draft),
+            no_draft: false,
+            body_template: self.body_template.or(other.body_template),
+            sections: if self.sections.is_empty() {
+                other.sections
+            } else {
+                self.sections
+            },
+            reviewers: if self.reviewers.is_empty() {
+                other.reviewers
+            } else {
+                self.reviewers
+            },
+        }
+    }
+}
+
+impl TryFrom<PullRequestConfig> for PullRequestSettings {
+    type Error = color_eyre::Report;
+
+    fn try_from(config: PullRequestConfig) -> Result<Self> {
+        let template = config
+            .body_template
+            .map(|path| {
+                fs::read_to_string(&path)
+                    .wrap_err_with(|| format!("Failed to read the body template at {path}"))
+            })
+            .transpose()?;
+
+        Ok(Self {
+            draft: config.draft.unwrap_or_default(),
+            template,
+            sections: config.sections.into_iter().collect::<BTreeSet<_>>(),
+            reviewers: config.reviewers,
+        })
+    }
+}
+
+/// The level of each lint rule, which is only settable in the configuration file. Rules that
+/// aren't listed are warnings.
+#[derive(Default, Deserialize)]
+#[serde(default, rename_all = "kebab-case", deny_unknown_fields)]
+pub struct LintConfig {
+    pub rules: BTreeMap<Rule, RuleLevel>,
+}
+
+impl From<LintConfig> for LintRules {
+    fn from(config: LintConfig) -> Self {
+        Self::new(config.rules)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::iter;
+
+    use clap::Parser;
+    use indoc::indoc;
+    use rstest::rstest;
+
+    use super::{Config, PullRequestConfig, UpstreamConfig};
+    use crate::{
+        github::{host::GitHubHost, upstream::Upstream, utils::BodySection},
+        manifests::lint::{LintRules, Rule, RuleLevel},
+    };
+
+    #[test]
+    fn parse_config() {
+        let config = serde_yaml::from_str::<Config>(indoc! {"
+            upstream:
+              host: ghe.contoso.com
+              owner: contoso
+              branch: main
+              manifest-root: /packages/
+        "})
+        .unwrap();
+
+        assert_eq!(
+            Upstream::from(config.upstream),
+            Upstream::new(
+                "contoso",
+                "winget-pkgs",
+                Some("main".to_owned()),
+                "packages"
+            )
+            .with_host(GitHubHost::new("ghe.contoso.com"))
+        );
+    }
+
+    #[test]
+    fn arguments_take_precedence() {
+        let arguments = UpstreamConfig {
+            owner: Some("fabrikam".to_owned()),
+            ..UpstreamConfig::default()
+        };
+        let file = UpstreamConfig {
+            owner: Some("contoso".to_owned()),
+            repo: Some("manifests".to_owned()),
+            ..UpstreamConfig::default()
+        };
+
+        assert_eq!(
+            Upstream::from(arguments.or(file)),
+            Upstream::new("fabrikam", "manifests", None, "manifests")
+        );
+    }
+
+    #[test]
+    fn parse_pull_request_config() {
+        let config = serde_yaml::from_str::<Config>(indoc! {"
+            pull-request:
+              draft: true
+              sections: [release-notes, installers]
+              reviewers: [octocat]
+        "})
+        .unwrap();
+
+        let PullRequestConfig {
+            draft,
+            body_template,
+            sections,
+            reviewers,
+            ..
+        } = config.pull_request.or(PullRequestConfig::default());
+
+        assert_eq!(draft, Some(true));
+        assert!(body_template.is_none());
+        assert_eq!(
+            sections,
+            [BodySection::ReleaseNotes, BodySection::Installers]
+        );
+        assert_eq!(reviewers, ["octocat"]);
+    }
+
+    #[rstest]
+    #[case(&[], Some(true))]
+    #[case(&["--draft"], Some(true))]
+    #[case(&["--no-draft"], Some(false))]
+    #[case(&["--draft", "--no-draft"], Some(false))]
+    #[case(&["--no-draft", "--draft"], Some(true))]
+    fn draft_arguments_override_config(#[case] args: &[&str], #[case] expected: Option<bool>) {
+        #[derive(Parser)]
+        struct Cli {
+            #[command(flatten)]
+            pull_request: PullRequestConfig,
+        }
+
+        let file = PullRequestConfig {
+            draft: Some(true),
+            ..PullRequestConfig::default()
+        };
+        let arguments = Cli::try_parse_from(iter::once("komac").chain(args.iter().copied()))
+            .unwrap()
+            .pull_request;
+
+        assert_eq!(arguments.or(file).draft, expected);
+    }
+
+    #[test]
+    fn parse_lint_config() {
+        let config = serde_yaml::from_str::<Config>(indoc! {"
+            lint:
+              rules:
+                tag-format: error
+                msi-product-code: off
+        "})
+        .unwrap();
+
+        let rules = LintRules::from(config.lint);
+
+        assert_eq!(rules.level(Rule::TagFormat), RuleLevel::Error);
+        assert_eq!(rules.level(Rule::MsiProductCode), RuleLevel::Off);
+        assert_eq!(rules.level(Rule::InsecureUrl), RuleLevel::Warning);
+    }
+}
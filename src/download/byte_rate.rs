@@ -0,0 +1,102 @@
+use std::{fmt, num::NonZeroU64, str::FromStr};
+
+use indicatif::DecimalBytes;
+use thiserror::Error;
+
+/// A transfer rate in bytes per second, such as `20MB/s` or `512KiB/s`.
+///
+/// Decimal units (`KB`, `MB`, `GB`) are multiples of 1000 and binary units (`KiB`, `MiB`, `GiB`)
+/// are multiples of 1024. The unit and the `/s` suffix are optional, so `1500` is 1500 bytes per
+/// second.
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub struct ByteRate(NonZeroU64);
+
+#[derive(Debug, Error, Eq, PartialEq)]
+pub enum ByteRateError {
+    #[error("Rate must be a number followed by an optional unit, such as `20MB/s`")]
+    InvalidNumber,
+    #[error("Unknown unit `{0}`. Expected one of B, KB, MB, GB, KiB, MiB, or GiB")]
+    UnknownUnit(String),
+    #[error("Rate must be at least 1 byte per second")]
+    Zero,
+}
+
+impl ByteRate {
+    /// Returns the rate in bytes per second.
+    #[inline]
+    pub const fn get(self) -> u64 {
+        self.0.get()
+    }
+}
+
+impl FromStr for ByteRate {
+    type Err = ByteRateError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let s = s.trim();
+        let s = s.strip_suffix("/s").unwrap_or(s).trim_end();
+
+        let unit_start = s
+            .find(|char: char| !char.is_ascii_digit() && char != '.')
+            .unwrap_or(s.len());
+        let (number, unit) = s.split_at(unit_start);
+
+        let number = number
+            .parse::<f64>()
+            .map_err(|_| ByteRateError::InvalidNumber)?;
+
+        let multiplier: u64 = match unit.trim().to_ascii_lowercase().as_str() {
+            "" | "b" => 1,
+            "k" | "kb" => 1000,
+            "m" | "mb" => 1000_u64.pow(2),
+            "g" | "gb" => 1000_u64.pow(3),
+            "ki" | "kib" => 1 << 10,
+            "mi" | "mib" => 1 << 20,
+            "gi" | "gib" => 1 << 30,
+            _ => return Err(ByteRateError::UnknownUnit(unit.trim().to_owned())),
+        };
+
+        #[expect(
+            clippy::cast_possible_truncation,
+            clippy::cast_precision_loss,
+            clippy::cast_sign_loss,
+            reason = "fractional bytes per second are meaningless and the number is never negative"
+        )]
+        let bytes = (number * multiplier as f64) as u64;
+
+        NonZeroU64::new(bytes).map(Self).ok_or(ByteRateError::Zero)
+    }
+}
+
+impl fmt::Display for ByteRate {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}/s", DecimalBytes(self.get()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rstest::rstest;
+
+    use super::{ByteRate, ByteRateError};
+
+    #[rstest]
+    #[case("1500", 1500)]
+    #[case("20MB/s", 20_000_000)]
+    #[case("20 MB/s", 20_000_000)]
+    #[case("1.5mb", 1_500_000)]
+    #[case("512KiB/s", 512 * 1024)]
+    #[case("2GiB", 2 << 30)]
+    fn parse_byte_rate(#[case] input: &str, #[case] expected: u64) {
+        assert_eq!(input.parse::<ByteRate>().unwrap().get(), expected);
+    }
+
+    #[rstest]
+    #[case("", ByteRateError::InvalidNumber)]
+    #[case("MB/s", ByteRateError::InvalidNumber)]
+    #[case("0", ByteRateError::Zero)]
+    #[case("20TB/s", ByteRateError::UnknownUnit("TB".to_owned()))]
+    fn invalid_byte_rate(#[case] input: &str, #[case] expected: ByteRateError) {
+        assert_eq!(input.parse::<ByteRate>(), Err(expected));
+    }
+}
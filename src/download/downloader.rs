@@ -1,9 +1,10 @@
-use std::{fmt, num::NonZeroUsize};
+use std::{fmt, io, num::NonZeroUsize};
 
-use chrono::DateTime;
+use chrono::{DateTime, NaiveDate};
 use color_eyre::{Result, eyre::bail};
 use futures_util::{StreamExt, TryStreamExt, stream};
 use indicatif::{MultiProgress, ProgressBar, ProgressStyle};
+use inquire::{Select, error::InquireResult};
 use itertools::{Itertools, Position};
 use reqwest::{
     Client,
@@ -11,21 +12,28 @@
         CONTENT_DISPOSITION, CONTENT_TYPE, DNT, GetAll, HeaderMap, HeaderValue, LAST_MODIFIED,
         USER_AGENT,
     },
+    redirect::Policy,
 };
 use sha2::{Digest, Sha256};
 use thiserror::Error;
-use tokio::{
-    io::{AsyncWriteExt, BufWriter},
-    sync::mpsc,
-    try_join,
-};
+use tokio::try_join;
+use tracing::{debug, info, warn};
 use winget_types::Sha256String;
 
-use super::{Download, DownloadedFile};
+use super::{
+    ByteRate, Download, DownloadedFile, GrowingFile, RangeReader,
+    redirect_chain::RedirectChain,
+    scheduler::{Scheduler, Throttle},
+};
+use crate::{analysis::Analyzer, fixtures::Dispatch, prompts::handle_inquire_error};
 
 pub struct Downloader {
     client: Client,
+    redirect_client: Client,
     concurrent_downloads: NonZeroUsize,
+    max_connections_per_host: Option<NonZeroUsize>,
+    throttle: Option<Throttle>,
+    prompt_for_redirects: bool,
 }
 
 impl Downloader {
@@ -49,7 +57,6 @@ impl Downloader {
     /// initialized, or the resolver cannot load the system configuration.
     ///
     /// [`ClientBuilder::build`]: reqwest::ClientBuilder::build
-    #[expect(unused)]
     pub fn new() -> reqwest::Result<Self> {
         Self::new_with_concurrent(
             num_cpus::get()
@@ -72,20 +79,75 @@ pub fn new_with_concurrent(concurrent_downloads: NonZeroUsize) -> reqwest::Resul
                 .default_headers(Self::headers())
                 .referer(false)
                 .build()?,
+            redirect_client: Client::builder()
+                .default_headers(Self::headers())
+                .referer(false)
+                .redirect(Policy::none())
+                .build()?,
             concurrent_downloads,
+            max_connections_per_host: None,
+            throttle: None,
+            prompt_for_redirects: false,
         })
     }
 
+    /// Sets the maximum number of files to download from the same host at the same time.
+    #[must_use]
+    pub const fn with_max_connections_per_host(
+        mut self,
+        max_connections_per_host: Option<NonZeroUsize>,
+    ) -> Self {
+        self.max_connections_per_host = max_connections_per_host;
+        self
+    }
+
+    /// Sets the maximum combined download speed of all downloads.
+    #[must_use]
+    pub fn with_max_rate(mut self, max_rate: Option<ByteRate>) -> Self {
+        self.throttle = max_rate.map(Throttle::new);
+        self
+    }
+
+    /// Sets whether to ask which URL to use when a download redirects to other URLs.
+    ///
+    /// Otherwise, the most stable URL in the redirect chain is used automatically.
+    #[must_use]
+    pub const fn with_redirect_prompt(mut self, prompt_for_redirects: bool) -> Self {
+        self.prompt_for_redirects = prompt_for_redirects;
+        self
+    }
+
     pub async fn download<I, D>(&self, downloads: I) -> Result<Vec<DownloadedFile>>
     where
         I: IntoIterator<Item = D>,
         D: Into<Download>,
     {
+        let redirect_chains = stream::iter(downloads.into_iter().map(D::into).unique())
+            .map(|download| self.resolve(download))
+            .buffered(self.concurrent_downloads.get())
+            .collect::<Vec<_>>()
+            .await;
+
+        let mut downloads = redirect_chains
+            .into_iter()
+            .map(|(download, redirect_chain)| {
+                let len = redirect_chain.as_ref().and_then(RedirectChain::len);
+                self.choose_url(download, redirect_chain)
+                    .map(|download| (download, len))
+            })
+            .collect::<InquireResult<Vec<_>>>()?;
+
+        // Download the smallest files first so that their analysis can start as early as possible
+        downloads.sort_by_key(|&(_, len)| len.unwrap_or(u64::MAX));
+
+        let scheduler = Scheduler::new(self.concurrent_downloads, self.max_connections_per_host);
         let multi_progress = MultiProgress::new();
 
-        let downloaded_files = stream::iter(downloads.into_iter().map(D::into).unique())
-            .map(|download| self.fetch(&self.client, download, &multi_progress))
-            .buffer_unordered(self.concurrent_downloads.get())
+        // Every download is started at once and then waits for the scheduler to let it run, so
+        // that downloads waiting on a busy host do not hold up downloads from other hosts
+        let downloaded_files = stream::iter(downloads)
+            .map(|(download, _)| self.fetch(&self.client, download, &scheduler, &multi_progress))
+            .buffer_unordered(usize::MAX)
             .try_collect::<Vec<_>>()
             .await?;
 
@@ -94,6 +156,95 @@ pub async fn download<I, D>(&self, downloads: I) -> Result<Vec<DownloadedFile>>
         Ok(downloaded_files)
     }
 
+    /// Opens a download for random access through HTTP range requests, without downloading it in
+    /// full.
+    ///
+    /// Returns `None` if the server does not support range requests.
+    pub async fn open_ranged<D>(&self, download: D) -> reqwest::Result<Option<RangeReader>>
+    where
+        D: Into<Download>,
+    {
+        let (mut download, redirect_chain) = self.resolve(download.into()).await;
+
+        if let Some(redirect_chain) = redirect_chain {
+            let hop = &redirect_chain.hops()[redirect_chain.preferred()];
+            download.set_url(hop.url.clone());
+        }
+
+        RangeReader::new(self.client.clone(), &download).await
+    }
+
+    /// Upgrades a download to HTTPS if possible and follows its redirects.
+    ///
+    /// Failing to follow the redirects is not fatal as the download itself may still succeed, so
+    /// the redirect chain is omitted in that case.
+    async fn resolve(&self, mut download: Download) -> (Download, Option<RedirectChain>) {
+        download.upgrade_to_https(&self.client).await;
+
+        let redirect_chain = download
+            .redirect_chain(&self.redirect_client)
+            .await
+            .inspect_err(|error| debug!(%error, "Failed to follow redirects for {download}"))
+            .ok();
+
+        (download, redirect_chain)
+    }
+
+    /// Chooses which URL from a download's redirect chain to store in the manifest, either by
+    /// prompting or by using the preferred URL.
+    fn choose_url(
+        &self,
+        mut download: Download,
+        redirect_chain: Option<RedirectChain>,
+    ) -> InquireResult<Download> {
+        let Some(redirect_chain) = redirect_chain else {
+            return Ok(download);
+        };
+
+        let preferred = redirect_chain.preferred();
+        let index = if self.prompt_for_redirects && redirect_chain.is_redirected() {
+            Select::new(
+                &format!("{download} redirects. Which URL should be used in the manifest?"),
+                redirect_chain.hops().iter().collect(),
+            )
+            .with_starting_cursor(preferred)
+            .raw_prompt()
+            .map_err(handle_inquire_error)?
+            .index
+        } else {
+            preferred
+        };
+
+        Self::warn_if_unstable(&download, &redirect_chain, index);
+        download.set_url(redirect_chain.hops()[index].url.clone());
+
+        Ok(download)
+    }
+
+    fn warn_if_unstable(download: &Download, redirect_chain: &RedirectChain, index: usize) {
+        let hop = &redirect_chain.hops()[index];
+        if hop.is_expiring() {
+            warn!(
+                "{} is signed and will expire, so the manifest will stop working once it does",
+                hop.url
+            );
+        } else if let Some(alternative) = redirect_chain.versioned_alternative(index) {
+            warn!(
+                "{} does not appear to point to a specific version, so the manifest may break when a newer version is released. Consider using {} instead",
+                hop.url, alternative.url
+            );
+        } else if redirect_chain.is_redirected() && !hop.is_versioned() {
+            // Nothing in the chain points to a specific version, which `update` otherwise gives no
+            // sign of as it doesn't prompt for which URL to use
+            warn!(
+                "{} does not appear to point to a specific version, and neither does any URL it redirects through, so the manifest may break when a newer version is released",
+                hop.url
+            );
+        } else if hop.url != ***download.url() {
+            info!("Using {} instead of {download}", hop.url);
+        }
+    }
+
     fn headers() -> HeaderMap {
         const MICROSOFT_DELIVERY_OPTIMIZATION: HeaderValue =
             HeaderValue::from_static("Microsoft-Delivery-Optimization/10.1");
@@ -125,14 +276,13 @@ fn check_content_types(
     pub async fn fetch(
         &self,
         client: &Client,
-        mut download: Download,
+        download: Download,
+        scheduler: &Scheduler,
         multi_progress: &MultiProgress,
     ) -> Result<DownloadedFile> {
-        download.convert_to_github_versioned().await?;
-
-        download.upgrade_to_https(client).await;
+        let _permit = scheduler.acquire(&download).await;
 
-        let res = client.get((***download.url()).clone()).send().await?;
+        let res = client.get((***download.url()).clone()).dispatch().await?;
 
         if let Err(err) = res.error_for_status_ref() {
             bail!(
@@ -149,12 +299,7 @@ pub async fn fetch(
             .file_name(res.url(), res.headers().get(CONTENT_DISPOSITION))
             .into_owned();
 
-        let last_modified = res
-            .headers()
-            .get(LAST_MODIFIED)
-            .and_then(|last_modified| last_modified.to_str().ok())
-            .and_then(|last_modified| DateTime::parse_from_rfc2822(last_modified).ok())
-            .map(|date_time| date_time.date_naive());
+        let last_modified = last_modified(res.headers());
 
         let progress_bar = match res.content_length() {
             Some(len) => ProgressBar::new(len).with_style(
@@ -169,20 +314,22 @@ pub async fn fetch(
         let progress =
             multi_progress.add(progress_bar.with_message(format!("Downloading {download}")));
 
-        // Create a temporary file
-        let temp_file = tempfile::tempfile()?;
-        let file = tokio::fs::File::from_std(temp_file.try_clone()?);
-        let mut buf_writer = BufWriter::new(file);
+        // Create a temporary file that can be analyzed while it is still being downloaded
+        let (mut file_writer, file_reader) = GrowingFile::new(res.content_length())?;
+
+        // Create a thread for analyzing the file as soon as enough bytes are present
+        let analysis = tokio::task::spawn_blocking({
+            let file_name = file_name.clone();
+            move || Analyzer::new(file_reader, &file_name)
+        });
 
         // Create a thread for writing to the file
-        let (write_sender, mut write_receiver) = mpsc::unbounded_channel::<bytes::Bytes>();
-        let writer = tokio::spawn(async move {
-            while let Some(chunk) = write_receiver.recv().await {
-                buf_writer.write_all(&chunk).await?;
+        let (write_sender, write_receiver) = crossbeam_channel::unbounded::<bytes::Bytes>();
+        let writer = tokio::task::spawn_blocking(move || {
+            while let Ok(chunk) = write_receiver.recv() {
+                file_writer.write_chunk(&chunk)?;
             }
-
-            buf_writer.flush().await?;
-            buf_writer.shutdown().await
+            Ok::<_, io::Error>(file_writer)
         });
 
         // Create a thread for hashing the downloaded bytes
@@ -199,6 +346,9 @@ pub async fn fetch(
 
         // Download the chunks asynchronously
         while let Some(chunk) = stream.next().await.transpose()? {
+            if let Some(throttle) = &self.throttle {
+                throttle.consume(chunk.len()).await;
+            }
             progress.inc(chunk.len() as u64);
             hash_sender.send(chunk.clone())?;
             write_sender.send(chunk)?;
@@ -208,18 +358,28 @@ pub async fn fetch(
         drop(hash_sender);
 
         let sha_256 = match try_join!(writer, hasher)? {
-            (Ok(()), sha_256) => sha_256,
+            (Ok(file_writer), sha_256) => {
+                file_writer.finish()?;
+                sha_256
+            }
             (Err(err), _) => return Err(err.into()),
         };
 
+        // Show that the remaining analysis is still running after the download completed
+        if !analysis.is_finished() {
+            progress.set_message(format!("Analyzing {file_name}"));
+        }
+
+        let analyzer = analysis.await??;
+
         progress.finish();
 
         Ok(DownloadedFile {
             url: download.into_url(),
-            file: temp_file,
             sha_256: Sha256String::from_digest(&sha_256),
             file_name,
             last_modified,
+            analyzer,
         })
     }
 }
@@ -267,3 +427,12 @@ fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         )
     }
 }
+
+/// Gets the date that a file was last modified from a `Last-Modified` header.
+pub(super) fn last_modified(headers: &HeaderMap) -> Option<NaiveDate> {
+    headers
+        .get(LAST_MODIFIED)
+        .and_then(|last_modified| last_modified.to_str().ok())
+        .and_then(|last_modified| DateTime::parse_from_rfc2822(last_modified).ok())
+        .map(|date_time| date_time.date_naive())
+}
@@ -1,14 +1,13 @@
-use std::fs::File;
-
 use chrono::NaiveDate;
 use winget_types::Sha256String;
 
-use crate::manifests::Url;
+use super::GrowingFile;
+use crate::{analysis::Analyzer, manifests::Url};
 
 pub struct DownloadedFile {
-    pub file: File,
     pub url: Url,
     pub sha_256: Sha256String,
     pub file_name: String,
     pub last_modified: Option<NaiveDate>,
+    pub analyzer: Analyzer<GrowingFile>,
 }
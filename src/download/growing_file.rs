@@ -0,0 +1,256 @@
+use std::{
+    cmp,
+    fs::File,
+    io,
+    io::{Read, Seek, SeekFrom, Write},
+    sync::{Arc, Condvar, Mutex, MutexGuard},
+};
+
+use tempfile::{NamedTempFile, TempPath};
+
+/// A temporary file that is still being written to by a download.
+///
+/// The download writes to the file through a [`GrowingFileWriter`] while a [`GrowingFile`] reads
+/// from it through a separate handle with its own cursor. Reads past what has been written so far
+/// block until enough bytes are present, the download finishes, or the download fails. This
+/// allows installers to be analyzed while they are still being downloaded.
+pub struct GrowingFile {
+    file: File,
+    position: u64,
+    len: Option<u64>,
+    shared: Arc<Shared>,
+}
+
+pub struct GrowingFileWriter {
+    file: File,
+    shared: Arc<Shared>,
+}
+
+struct Shared {
+    progress: Mutex<Progress>,
+    condvar: Condvar,
+    _path: TempPath,
+}
+
+#[derive(Copy, Clone)]
+struct Progress {
+    written: u64,
+    state: State,
+}
+
+#[derive(Copy, Clone, Eq, PartialEq)]
+enum State {
+    Downloading,
+    Complete,
+    Failed,
+}
+
+impl GrowingFile {
+    /// Creates a new temporary file, returning a writer for the download and a reader for the
+    /// analysis.
+    ///
+    /// If the length of the download is known ahead of time, it allows seeking relative to the end
+    /// of the file without waiting for the download to complete.
+    pub fn new(len: Option<u64>) -> io::Result<(GrowingFileWriter, Self)> {
+        let temp_file = NamedTempFile::new()?;
+        let reader = temp_file.reopen()?;
+        let (file, path) = temp_file.into_parts();
+
+        let shared = Arc::new(Shared {
+            progress: Mutex::new(Progress {
+                written: 0,
+                state: State::Downloading,
+            }),
+            condvar: Condvar::new(),
+            _path: path,
+        });
+
+        Ok((
+            GrowingFileWriter {
+                file,
+                shared: Arc::clone(&shared),
+            },
+            Self {
+                file: reader,
+                position: 0,
+                len,
+                shared,
+            },
+        ))
+    }
+
+    /// Returns the total length of the file, waiting for the download to complete if the length
+    /// was not known ahead of time.
+    fn len(&self) -> io::Result<u64> {
+        match self.len {
+            Some(len) => Ok(len),
+            None => self.shared.wait_until(u64::MAX),
+        }
+    }
+}
+
+impl Read for GrowingFile {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        if buf.is_empty() {
+            return Ok(0);
+        }
+
+        let written = self.shared.wait_until(self.position + 1)?;
+
+        if written <= self.position {
+            // The download has completed and the position is at or beyond the end of the file
+            return Ok(0);
+        }
+
+        let available = usize::try_from(written - self.position).unwrap_or(usize::MAX);
+        let len = cmp::min(buf.len(), available);
+        let read = self.file.read(&mut buf[..len])?;
+        self.position += read as u64;
+        Ok(read)
+    }
+}
+
+impl Seek for GrowingFile {
+    fn seek(&mut self, pos: SeekFrom) -> io::Result<u64> {
+        let position = match pos {
+            SeekFrom::Start(offset) => Some(offset),
+            SeekFrom::End(offset) => self.len()?.checked_add_signed(offset),
+            SeekFrom::Current(offset) => self.position.checked_add_signed(offset),
+        }
+        .ok_or_else(|| {
+            io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "invalid seek to a negative or overflowing position",
+            )
+        })?;
+
+        self.position = self.file.seek(SeekFrom::Start(position))?;
+        Ok(self.position)
+    }
+
+    fn stream_position(&mut self) -> io::Result<u64> {
+        Ok(self.position)
+    }
+}
+
+impl GrowingFileWriter {
+    /// Writes a downloaded chunk to the file and wakes any readers waiting for it.
+    pub fn write_chunk(&mut self, chunk: &[u8]) -> io::Result<()> {
+        self.file.write_all(chunk)?;
+
+        let mut progress = self.shared.lock();
+        progress.written += chunk.len() as u64;
+        drop(progress);
+
+        self.shared.condvar.notify_all();
+        Ok(())
+    }
+
+    /// Marks the download as complete so that readers see the end of the file.
+    ///
+    /// If the writer is dropped without being finished, the download is considered to have failed
+    /// and any waiting readers return an error.
+    pub fn finish(mut self) -> io::Result<()> {
+        self.file.flush()?;
+        self.shared.set_state(State::Complete);
+        Ok(())
+    }
+}
+
+impl Drop for GrowingFileWriter {
+    fn drop(&mut self) {
+        self.shared.set_state(State::Failed);
+    }
+}
+
+impl Shared {
+    fn lock(&self) -> MutexGuard<'_, Progress> {
+        self.progress
+            .lock()
+            .unwrap_or_else(std::sync::PoisonError::into_inner)
+    }
+
+    fn set_state(&self, state: State) {
+        let mut progress = self.lock();
+        if progress.state == State::Downloading {
+            progress.state = state;
+        }
+        drop(progress);
+
+        self.condvar.notify_all();
+    }
+
+    /// Blocks until at least `offset` bytes have been written or the download has completed,
+    /// returning the number of bytes written.
+    fn wait_until(&self, offset: u64) -> io::Result<u64> {
+        let progress = self
+            .condvar
+            .wait_while(self.lock(), |progress| {
+                progress.written < offset && progress.state == State::Downloading
+            })
+            .unwrap_or_else(std::sync::PoisonError::into_inner);
+
+        match progress.state {
+            State::Failed if progress.written < offset => Err(io::Error::new(
+                io::ErrorKind::UnexpectedEof,
+                "the download failed before the requested bytes were received",
+            )),
+            _ => Ok(progress.written),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{
+        io::{Read, Seek, SeekFrom},
+        thread,
+    };
+
+    use super::GrowingFile;
+
+    #[test]
+    fn read_waits_for_written_bytes() {
+        let (mut writer, mut reader) = GrowingFile::new(Some(6)).unwrap();
+
+        let handle = thread::spawn(move || {
+            let mut buffer = Vec::new();
+            reader.read_to_end(&mut buffer).unwrap();
+            buffer
+        });
+
+        writer.write_chunk(b"abc").unwrap();
+        writer.write_chunk(b"def").unwrap();
+        writer.finish().unwrap();
+
+        assert_eq!(handle.join().unwrap(), b"abcdef");
+    }
+
+    #[test]
+    fn seek_from_end_with_known_length() {
+        let (mut writer, mut reader) = GrowingFile::new(Some(6)).unwrap();
+
+        let handle = thread::spawn(move || {
+            reader.seek(SeekFrom::End(-2)).unwrap();
+            let mut buffer = [0; 2];
+            reader.read_exact(&mut buffer).unwrap();
+            buffer
+        });
+
+        writer.write_chunk(b"abcdef").unwrap();
+        writer.finish().unwrap();
+
+        assert_eq!(&handle.join().unwrap(), b"ef");
+    }
+
+    #[test]
+    fn dropped_writer_fails_pending_reads() {
+        let (mut writer, mut reader) = GrowingFile::new(None).unwrap();
+
+        writer.write_chunk(b"abc").unwrap();
+        drop(writer);
+
+        let mut buffer = [0; 4];
+        assert!(reader.read_exact(&mut buffer).is_err());
+    }
+}
@@ -1,17 +1,26 @@
+mod byte_rate;
 mod downloader;
 mod file;
+mod growing_file;
+mod range_reader;
+mod redirect_chain;
+mod scheduler;
 
 use std::{borrow::Cow, fmt};
 
+pub use byte_rate::ByteRate;
 use camino::Utf8Path;
 use const_format::formatcp;
 pub use downloader::Downloader;
 pub use file::DownloadedFile;
-use reqwest::{Client, ClientBuilder, Response, header::HeaderValue, redirect::Policy};
+pub use growing_file::GrowingFile;
+pub use range_reader::RangeReader;
+use redirect_chain::RedirectChain;
+use reqwest::{Client, Response, header::HeaderValue};
 use uuid::Uuid;
 use winget_types::installer::VALID_FILE_EXTENSIONS;
 
-use crate::{github::GITHUB_HOST, manifests::Url};
+use crate::{fixtures::Dispatch, manifests::Url};
 
 #[derive(Clone, Debug, Eq, PartialEq, PartialOrd, Ord, Hash)]
 pub struct Download(Url);
@@ -104,7 +113,7 @@ pub async fn upgrade_to_https(&mut self, client: &Client) {
 
             if client
                 .head((**self.0).clone())
-                .send()
+                .dispatch()
                 .await
                 .and_then(Response::error_for_status)
                 .is_err()
@@ -114,34 +123,17 @@ pub async fn upgrade_to_https(&mut self, client: &Client) {
         }
     }
 
-    pub async fn convert_to_github_versioned(&mut self) -> reqwest::Result<()> {
-        const LATEST: &str = "latest";
-        const DOWNLOAD: &str = "download";
-        const MAX_HOPS: u8 = 2;
-
-        if self.0.host_str() != Some(GITHUB_HOST) {
-            return Ok(());
-        }
+    /// Follows the redirects of the download's URL, recording each URL along the way.
+    ///
+    /// The client must not follow redirects itself.
+    pub async fn redirect_chain(&self, client: &Client) -> reqwest::Result<RedirectChain> {
+        RedirectChain::resolve(client, (**self.0).clone()).await
+    }
 
-        if let Some(mut segments) = self.0.path_segments() {
-            // If the 4th and 5th segments are 'latest' and 'download', it's a vanity URL
-            if segments.nth(3) == Some(LATEST) && segments.next() == Some(DOWNLOAD) {
-                // Create a client that will redirect only once
-                let limited_redirect_client = ClientBuilder::new()
-                    .redirect(Policy::limited(MAX_HOPS as usize))
-                    .build()?;
-
-                // If there was a redirect error because max hops were reached, as intended, set the
-                // original vanity URL to the redirected versioned URL
-                if let Err(error) = limited_redirect_client.head(self.as_str()).send().await
-                    && error.is_redirect()
-                    && let Some(final_url) = error.url()
-                {
-                    **self.0 = final_url.clone();
-                }
-            }
-        }
-        Ok(())
+    /// Replaces the download's URL with another, such as a hop from its redirect chain, while
+    /// keeping any architecture override.
+    pub fn set_url(&mut self, url: url::Url) {
+        **self.0 = url;
     }
 
     /// Returns the serialization of the download's URL.
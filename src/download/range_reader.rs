@@ -0,0 +1,339 @@
+use std::{
+    cmp, io,
+    io::{Read, Seek, SeekFrom},
+    ops::RangeInclusive,
+};
+
+use bytes::Bytes;
+use chrono::NaiveDate;
+use indexmap::IndexMap;
+use reqwest::{
+    Client, Response, StatusCode,
+    header::{
+        CONTENT_DISPOSITION, CONTENT_RANGE, ETAG, HeaderMap, HeaderValue, IF_RANGE, LAST_MODIFIED,
+        RANGE,
+    },
+};
+use tokio::runtime::Handle;
+
+use super::{Download, downloader::last_modified};
+use crate::fixtures::Dispatch;
+
+/// A reader over a remote file that fetches only the parts that are read using HTTP range
+/// requests.
+///
+/// The file is split into fixed-size blocks which are fetched on demand and kept in a bounded
+/// least-recently-used cache. This allows installers to be analyzed without downloading them in
+/// full when only their headers are needed, such as PE headers, `VS_VERSION_INFO`, or an MSIX's
+/// `AppxManifest.xml` found through the zip central directory.
+///
+/// Reading blocks on the runtime that the reader was created in, so it must be used from a
+/// blocking thread such as one spawned by [`tokio::task::spawn_blocking`].
+pub struct RangeReader {
+    client: Client,
+    url: url::Url,
+    file_name: String,
+    len: u64,
+    last_modified: Option<NaiveDate>,
+    /// The strong `ETag` or `Last-Modified` value of the first response, which is sent as
+    /// `If-Range` so that blocks can't come from a different version of the file.
+    validator: Option<HeaderValue>,
+    position: u64,
+    blocks: IndexMap<u64, Bytes>,
+    runtime: Handle,
+}
+
+impl RangeReader {
+    /// The size of each block that is fetched from the server.
+    const BLOCK_SIZE: u64 = 1 << 16;
+
+    /// The maximum number of blocks to keep in the cache at once.
+    const MAX_CACHED_BLOCKS: usize = 1 << 8;
+
+    /// The maximum number of blocks to fetch in a single range request.
+    const MAX_BLOCKS_PER_REQUEST: u64 = 1 << 5;
+
+    /// Opens a remote file for random access.
+    ///
+    /// The first block is requested up front to check whether the server supports range requests.
+    /// If it does not, or it returns a different number of bytes than requested, `None` is returned
+    /// and the file has to be downloaded in full instead.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the request fails or the server returns an error status.
+    pub async fn new(client: Client, download: &Download) -> reqwest::Result<Option<Self>> {
+        let response = client
+            .get(download.as_str())
+            .header(RANGE, format!("bytes=0-{}", Self::BLOCK_SIZE - 1))
+            .dispatch()
+            .await?
+            .error_for_status()?;
+
+        if response.status() != StatusCode::PARTIAL_CONTENT {
+            return Ok(None);
+        }
+
+        let Some(len) = total_length(&response) else {
+            return Ok(None);
+        };
+
+        let url = response.url().clone();
+        let file_name = download
+            .file_name(&url, response.headers().get(CONTENT_DISPOSITION))
+            .into_owned();
+        let last_modified = last_modified(response.headers());
+        let validator = validator(response.headers());
+
+        let mut reader = Self {
+            client,
+            url,
+            file_name,
+            len,
+            last_modified,
+            validator,
+            position: 0,
+            blocks: IndexMap::new(),
+            runtime: Handle::current(),
+        };
+
+        let block = response.bytes().await?;
+        if reader.check_block(0, &block).is_err() {
+            return Ok(None);
+        }
+        reader.blocks.insert(0, block);
+
+        Ok(Some(reader))
+    }
+
+    /// Returns the file name of the remote file.
+    #[inline]
+    pub fn file_name(&self) -> &str {
+        &self.file_name
+    }
+
+    /// Returns the date that the remote file was last modified, if the server sent it.
+    #[inline]
+    pub const fn last_modified(&self) -> Option<NaiveDate> {
+        self.last_modified
+    }
+
+    /// Returns the byte range covered by a block, clamped to the end of the file.
+    fn block_range(&self, index: u64) -> RangeInclusive<u64> {
+        let start = index * Self::BLOCK_SIZE;
+        start..=cmp::min(start + Self::BLOCK_SIZE, self.len) - 1
+    }
+
+    /// Checks that a block has as many bytes as its range covers.
+    fn check_block(&self, index: u64, block: &Bytes) -> io::Result<()> {
+        let range = self.block_range(index);
+        let expected = range.end() - range.start() + 1;
+        if block.len() as u64 == expected {
+            Ok(())
+        } else {
+            Err(io::Error::new(
+                io::ErrorKind::UnexpectedEof,
+                format!(
+                    "expected {expected} bytes from {} for bytes {}-{} but received {}",
+                    self.url,
+                    range.start(),
+                    range.end(),
+                    block.len()
+                ),
+            ))
+        }
+    }
+
+    /// Ensures that the blocks from `first` up to `last` are cached, fetching the first run of
+    /// missing blocks in a single range request. Blocks after that run are fetched on later reads.
+    fn fetch_blocks(&mut self, first: u64, last: u64) -> io::Result<()> {
+        // Mark the cached blocks as recently used so they are not evicted by the fetched blocks
+        for index in first..=last {
+            self.touch(index);
+        }
+
+        let Some(first_missing) = (first..=last).find(|index| !self.blocks.contains_key(index))
+        else {
+            return Ok(());
+        };
+
+        let last_missing = (first_missing..=last)
+            .take_while(|index| !self.blocks.contains_key(index))
+            .last()
+            .unwrap_or(first_missing);
+
+        let start = *self.block_range(first_missing).start();
+        let end = *self.block_range(last_missing).end();
+
+        let (status, bytes) = self
+            .runtime
+            .block_on(async {
+                let mut request = self
+                    .client
+                    .get(self.url.clone())
+                    .header(RANGE, format!("bytes={start}-{end}"));
+                if let Some(validator) = &self.validator {
+                    request = request.header(IF_RANGE, validator);
+                }
+                let response = request.dispatch().await?.error_for_status()?;
+                Ok::<_, reqwest::Error>((response.status(), response.bytes().await?))
+            })
+            .map_err(io::Error::other)?;
+
+        // The server sends the whole file instead of the range if it changed since it was opened
+        if status != StatusCode::PARTIAL_CONTENT {
+            return Err(io::Error::other(format!(
+                "{} changed while it was being read",
+                self.url
+            )));
+        }
+
+        for index in first_missing..=last_missing {
+            let block_start = *self.block_range(index).start() - start;
+            let block_end = *self.block_range(index).end() - start + 1;
+            let block = usize::try_from(block_start)
+                .ok()
+                .zip(usize::try_from(block_end).ok())
+                .and_then(|(block_start, block_end)| bytes.get(block_start..block_end))
+                .map(|block| bytes.slice_ref(block))
+                .unwrap_or_default();
+            self.check_block(index, &block)?;
+            self.insert(index, block);
+        }
+
+        Ok(())
+    }
+
+    /// Marks a block as the most recently used.
+    fn touch(&mut self, index: u64) {
+        if let Some(position) = self.blocks.get_index_of(&index) {
+            self.blocks.move_index(position, self.blocks.len() - 1);
+        }
+    }
+
+    /// Inserts a block into the cache, evicting the least recently used block if the cache is full.
+    fn insert(&mut self, index: u64, block: Bytes) {
+        if self.blocks.len() >= Self::MAX_CACHED_BLOCKS {
+            self.blocks.shift_remove_index(0);
+        }
+        self.blocks.insert(index, block);
+    }
+}
+
+impl Read for RangeReader {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        if buf.is_empty() || self.position >= self.len {
+            return Ok(0);
+        }
+
+        let end = cmp::min(self.position + buf.len() as u64, self.len) - 1;
+        let first = self.position / Self::BLOCK_SIZE;
+        let last = cmp::min(
+            end / Self::BLOCK_SIZE,
+            first + Self::MAX_BLOCKS_PER_REQUEST - 1,
+        );
+
+        self.fetch_blocks(first, last)?;
+
+        let mut read = 0;
+        for index in first..=last {
+            let Some(block) = self.blocks.get(&index) else {
+                break;
+            };
+            self.check_block(index, block)?;
+            let offset = usize::try_from(self.position - index * Self::BLOCK_SIZE)
+                .unwrap_or_else(|_| unreachable!());
+            let count = cmp::min(block.len() - offset, buf.len() - read);
+            buf[read..read + count].copy_from_slice(&block[offset..offset + count]);
+            read += count;
+            self.position += count as u64;
+            if read == buf.len() {
+                break;
+            }
+        }
+
+        Ok(read)
+    }
+}
+
+impl Seek for RangeReader {
+    fn seek(&mut self, pos: SeekFrom) -> io::Result<u64> {
+        self.position = match pos {
+            SeekFrom::Start(offset) => Some(offset),
+            SeekFrom::End(offset) => self.len.checked_add_signed(offset),
+            SeekFrom::Current(offset) => self.position.checked_add_signed(offset),
+        }
+        .ok_or_else(|| {
+            io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "invalid seek to a negative or overflowing position",
+            )
+        })?;
+
+        Ok(self.position)
+    }
+
+    fn stream_position(&mut self) -> io::Result<u64> {
+        Ok(self.position)
+    }
+}
+
+/// Gets the value to send as `If-Range` from a response: its `ETag` if it is strong, as weak ones
+/// can't be used for range requests, or otherwise its `Last-Modified` date.
+fn validator(headers: &HeaderMap) -> Option<HeaderValue> {
+    headers
+        .get(ETAG)
+        .filter(|etag| !etag.as_bytes().starts_with(b"W/"))
+        .or_else(|| headers.get(LAST_MODIFIED))
+        .cloned()
+}
+
+/// Gets the total length of a file from a `Content-Range` header, such as `bytes 0-1023/146515`.
+pub(super) fn total_length(response: &Response) -> Option<u64> {
+    response
+        .headers()
+        .get(CONTENT_RANGE)?
+        .to_str()
+        .ok()?
+        .rsplit_once('/')?
+        .1
+        .parse()
+        .ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use reqwest::header::{ETAG, HeaderMap, HeaderValue, LAST_MODIFIED};
+    use rstest::rstest;
+
+    use super::validator;
+
+    #[rstest]
+    #[case(
+        Some(r#""abc""#),
+        Some("Thu, 15 Jan 2026 09:30:00 GMT"),
+        Some(r#""abc""#)
+    )]
+    #[case(
+        Some(r#"W/"abc""#),
+        Some("Thu, 15 Jan 2026 09:30:00 GMT"),
+        Some("Thu, 15 Jan 2026 09:30:00 GMT")
+    )]
+    #[case(Some(r#"W/"abc""#), None, None)]
+    #[case(None, None, None)]
+    fn if_range_validator(
+        #[case] etag: Option<&'static str>,
+        #[case] last_modified: Option<&'static str>,
+        #[case] expected: Option<&'static str>,
+    ) {
+        let mut headers = HeaderMap::new();
+        if let Some(etag) = etag {
+            headers.insert(ETAG, HeaderValue::from_static(etag));
+        }
+        if let Some(last_modified) = last_modified {
+            headers.insert(LAST_MODIFIED, HeaderValue::from_static(last_modified));
+        }
+
+        assert_eq!(validator(&headers), expected.map(HeaderValue::from_static));
+    }
+}
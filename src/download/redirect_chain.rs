@@ -0,0 +1,311 @@
+use std::{fmt, sync::LazyLock};
+
+use owo_colors::OwoColorize;
+use regex::Regex;
+use reqwest::{
+    Client, Response,
+    header::{CONTENT_LENGTH, LOCATION, RANGE},
+};
+use url::Url;
+
+use super::range_reader::total_length;
+use crate::fixtures::Dispatch;
+
+/// The chain of redirects followed from a URL to the file it eventually serves.
+///
+/// Vendors commonly publish "latest" links, such as GitHub's `releases/latest/download`,
+/// SourceForge's `files/latest/download`, `aka.ms`, or `go.microsoft.com/fwlink`, that redirect
+/// through one or more intermediate URLs before reaching a versioned file or a signed CDN URL.
+/// Storing a vanity URL in a manifest makes it break as soon as a new version is released, and
+/// storing a signed URL makes it break once the signature expires, so the chain is recorded to
+/// find the most stable URL.
+#[derive(Debug)]
+pub struct RedirectChain {
+    hops: Vec<Hop>,
+    len: Option<u64>,
+}
+
+/// A single URL in a [`RedirectChain`].
+#[derive(Debug)]
+pub struct Hop {
+    pub url: Url,
+}
+
+impl RedirectChain {
+    /// The maximum number of redirects to follow, matching reqwest's default redirect policy.
+    const MAX_REDIRECTS: usize = 10;
+
+    /// Follows the redirects from a URL one hop at a time, recording each URL along the way.
+    ///
+    /// The client must not follow redirects itself. A `HEAD` request is made for each hop, falling
+    /// back to a `GET` request for a single byte if the server rejects `HEAD` requests.
+    pub async fn resolve(client: &Client, url: Url) -> reqwest::Result<Self> {
+        let mut hops = Vec::new();
+        let mut len = None;
+        let mut next = Some(url);
+
+        while let Some(url) = next.take() {
+            let response = Self::request(client, &url).await?;
+
+            if response.status().is_success() {
+                len = total_length(&response).or_else(|| {
+                    response
+                        .headers()
+                        .get(CONTENT_LENGTH)
+                        .and_then(|content_length| content_length.to_str().ok())
+                        .and_then(|content_length| content_length.parse().ok())
+                });
+            } else if response.status().is_redirection() && hops.len() < Self::MAX_REDIRECTS {
+                next = response
+                    .headers()
+                    .get(LOCATION)
+                    .and_then(|location| location.to_str().ok())
+                    .and_then(|location| url.join(location).ok())
+                    .filter(|location| hops.iter().all(|hop: &Hop| hop.url != *location));
+            }
+
+            hops.push(Hop { url });
+        }
+
+        Ok(Self { hops, len })
+    }
+
+    async fn request(client: &Client, url: &Url) -> reqwest::Result<Response> {
+        let response = client.head(url.clone()).dispatch().await?;
+
+        if response.status().is_client_error() || response.status().is_server_error() {
+            return client
+                .get(url.clone())
+                .header(RANGE, "bytes=0-0")
+                .dispatch()
+                .await;
+        }
+
+        Ok(response)
+    }
+
+    /// Returns the URLs in the chain, starting with the original URL.
+    #[inline]
+    pub fn hops(&self) -> &[Hop] {
+        &self.hops
+    }
+
+    /// Returns the index of the hop that is best suited to be stored in a manifest.
+    ///
+    /// The first versioned hop that does not expire is preferred. If there is none, the first hop
+    /// that does not expire is used, falling back to the original URL.
+    pub fn preferred(&self) -> usize {
+        self.hops
+            .iter()
+            .position(|hop| hop.is_versioned() && !hop.is_expiring())
+            .or_else(|| self.hops.iter().position(|hop| !hop.is_expiring()))
+            .unwrap_or_default()
+    }
+
+    /// Returns a versioned hop that does not expire if the hop at the given index is not
+    /// versioned.
+    ///
+    /// This is only `Some` when the chain offered a more stable URL than the one that was chosen,
+    /// so URLs without a version that never redirected anywhere better are not reported.
+    pub fn versioned_alternative(&self, index: usize) -> Option<&Hop> {
+        if self.hops.get(index)?.is_versioned() {
+            return None;
+        }
+
+        self.hops
+            .iter()
+            .enumerate()
+            .find(|&(position, hop)| position != index && hop.is_versioned() && !hop.is_expiring())
+            .map(|(_, hop)| hop)
+    }
+
+    /// Returns the size of the file that the chain ends at, if the server reported it.
+    #[inline]
+    pub const fn len(&self) -> Option<u64> {
+        self.len
+    }
+
+    /// Returns `true` if the chain has any redirects.
+    #[inline]
+    pub const fn is_redirected(&self) -> bool {
+        self.hops.len() > 1
+    }
+}
+
+impl Hop {
+    /// Query parameters that are used to sign a URL so that it is only valid for a limited time.
+    ///
+    /// These cover AWS S3 and `CloudFront`, Google Cloud Storage, Azure shared access signatures,
+    /// and Akamai token authentication.
+    const SIGNED_QUERY_PARAMETERS: [&str; 11] = [
+        "x-amz-signature",
+        "x-amz-expires",
+        "x-amz-security-token",
+        "x-goog-signature",
+        "x-goog-expires",
+        "signature",
+        "expires",
+        "key-pair-id",
+        "sig",
+        "se",
+        "hdnts",
+    ];
+
+    /// Returns `true` if the URL is signed with query parameters that make it expire.
+    pub fn is_expiring(&self) -> bool {
+        self.url.query_pairs().any(|(key, _)| {
+            Self::SIGNED_QUERY_PARAMETERS
+                .iter()
+                .any(|parameter| key.eq_ignore_ascii_case(parameter))
+        })
+    }
+
+    /// Returns `true` if the URL appears to point to a specific version rather than whatever the
+    /// latest version is.
+    ///
+    /// A URL is considered versioned if a path segment looks like a version, such as `v1.2.3` or
+    /// `setup-1.2.exe`, and no path segment is `latest`.
+    pub fn is_versioned(&self) -> bool {
+        static VERSION_REGEX: LazyLock<Regex> = LazyLock::new(|| {
+            Regex::new(r"(?i)^v?\d+([._-]\d+)*$|\d+\.\d+").unwrap_or_else(|_| unreachable!())
+        });
+        const LATEST: &str = "latest";
+
+        let Some(segments) = self.url.path_segments() else {
+            return false;
+        };
+
+        let mut versioned = false;
+        for segment in segments {
+            if segment.eq_ignore_ascii_case(LATEST) {
+                return false;
+            }
+            versioned |= VERSION_REGEX.is_match(segment);
+        }
+        versioned
+    }
+}
+
+impl fmt::Display for Hop {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.url)?;
+        if self.is_expiring() {
+            write!(f, " {}", "(expiring)".red())
+        } else if self.is_versioned() {
+            write!(f, " {}", "(versioned)".green())
+        } else {
+            write!(f, " {}", "(not versioned)".yellow())
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rstest::rstest;
+
+    use super::{Hop, RedirectChain};
+
+    fn hop(url: &str) -> Hop {
+        Hop {
+            url: url.parse().unwrap(),
+        }
+    }
+
+    #[rstest]
+    #[case(
+        "https://github.com/owner/repo/releases/download/v1.2.3/setup.exe",
+        true
+    )]
+    #[case("https://example.com/downloads/1.2/setup.exe", true)]
+    #[case("https://example.com/downloads/setup-1.2.3-x64.msi", true)]
+    #[case(
+        "https://github.com/owner/repo/releases/latest/download/setup-1.2.3.exe",
+        false
+    )]
+    #[case(
+        "https://sourceforge.net/projects/example/files/latest/download",
+        false
+    )]
+    #[case("https://aka.ms/example", false)]
+    #[case("https://download.example.com/setup.exe", false)]
+    fn versioned(#[case] url: &str, #[case] expected: bool) {
+        assert_eq!(hop(url).is_versioned(), expected);
+    }
+
+    #[rstest]
+    #[case(
+        "https://bucket.s3.amazonaws.com/setup.exe?X-Amz-Algorithm=AWS4-HMAC-SHA256&X-Amz-Signature=abc",
+        true
+    )]
+    #[case(
+        "https://account.blob.core.windows.net/setup.exe?sv=2020&se=2030&sig=abc",
+        true
+    )]
+    #[case(
+        "https://d111.cloudfront.net/setup.exe?Expires=1&Signature=a&Key-Pair-Id=b",
+        true
+    )]
+    #[case(
+        "https://downloads.sourceforge.net/project/example/1.2/setup.exe?ts=1",
+        false
+    )]
+    fn expiring(#[case] url: &str, #[case] expected: bool) {
+        assert_eq!(hop(url).is_expiring(), expected);
+    }
+
+    #[test]
+    fn prefers_first_stable_versioned_hop() {
+        let chain = RedirectChain {
+            hops: vec![
+                hop("https://github.com/owner/repo/releases/latest/download/setup.exe"),
+                hop("https://github.com/owner/repo/releases/download/v1.2.3/setup.exe"),
+                hop(
+                    "https://release-assets.githubusercontent.com/github-production-release-asset/1/2?X-Amz-Expires=300&X-Amz-Signature=abc",
+                ),
+            ],
+            len: None,
+        };
+
+        assert_eq!(chain.preferred(), 1);
+    }
+
+    #[test]
+    fn keeps_original_without_versioned_hop() {
+        let chain = RedirectChain {
+            hops: vec![
+                hop("https://aka.ms/example"),
+                hop("https://download.example.com/setup.exe"),
+            ],
+            len: None,
+        };
+
+        assert_eq!(chain.preferred(), 0);
+    }
+
+    #[test]
+    fn versioned_alternative_when_unversioned_hop_chosen() {
+        let chain = RedirectChain {
+            hops: vec![
+                hop("https://github.com/owner/repo/releases/latest/download/setup.exe"),
+                hop("https://github.com/owner/repo/releases/download/v1.2.3/setup.exe"),
+            ],
+            len: None,
+        };
+
+        assert_eq!(
+            chain.versioned_alternative(0).map(|hop| hop.url.as_str()),
+            Some("https://github.com/owner/repo/releases/download/v1.2.3/setup.exe")
+        );
+        assert!(chain.versioned_alternative(1).is_none());
+    }
+
+    #[test]
+    fn no_versioned_alternative_without_redirect() {
+        let chain = RedirectChain {
+            hops: vec![hop("https://download.example.com/setup.exe")],
+            len: None,
+        };
+
+        assert!(chain.versioned_alternative(0).is_none());
+    }
+}
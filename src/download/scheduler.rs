@@ -0,0 +1,130 @@
+use std::{
+    collections::HashMap,
+    num::NonZeroUsize,
+    sync::{Arc, Mutex},
+    time::{Duration, Instant},
+};
+
+use tokio::{
+    sync::{OwnedSemaphorePermit, Semaphore, SemaphorePermit},
+    time::sleep,
+};
+
+use super::{ByteRate, Download};
+
+/// Limits how many downloads run at the same time, both in total and for each host.
+///
+/// Permits are handed out in the order they are requested, so downloads start in the order they
+/// were scheduled in.
+pub struct Scheduler {
+    slots: Semaphore,
+    max_per_host: Option<NonZeroUsize>,
+    hosts: Mutex<HashMap<String, Arc<Semaphore>>>,
+}
+
+/// Allows a download to run until it is dropped.
+pub struct Permit<'scheduler> {
+    _host: Option<OwnedSemaphorePermit>,
+    _slot: SemaphorePermit<'scheduler>,
+}
+
+impl Scheduler {
+    pub fn new(concurrent_downloads: NonZeroUsize, max_per_host: Option<NonZeroUsize>) -> Self {
+        Self {
+            slots: Semaphore::new(concurrent_downloads.get()),
+            max_per_host,
+            hosts: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Waits until the download is allowed to start.
+    ///
+    /// The permit for the download's host is acquired before the overall slot so that downloads
+    /// waiting on a busy host do not hold up downloads from other hosts.
+    pub async fn acquire(&self, download: &Download) -> Permit<'_> {
+        let host = match (self.max_per_host, download.url().host_str()) {
+            (Some(max_per_host), Some(host)) => {
+                let semaphore = Arc::clone(
+                    self.hosts
+                        .lock()
+                        .unwrap_or_else(std::sync::PoisonError::into_inner)
+                        .entry(host.to_owned())
+                        .or_insert_with(|| Arc::new(Semaphore::new(max_per_host.get()))),
+                );
+                Some(
+                    semaphore
+                        .acquire_owned()
+                        .await
+                        .unwrap_or_else(|_| unreachable!("host semaphores are never closed")),
+                )
+            }
+            _ => None,
+        };
+
+        let slot = self
+            .slots
+            .acquire()
+            .await
+            .unwrap_or_else(|_| unreachable!("the download semaphore is never closed"));
+
+        Permit {
+            _host: host,
+            _slot: slot,
+        }
+    }
+}
+
+/// Limits the combined throughput of all downloads using a token bucket.
+///
+/// Up to one second's worth of bytes can be downloaded in a burst, after which downloads are
+/// paused until enough time has passed to stay under the rate.
+pub struct Throttle {
+    rate: ByteRate,
+    bucket: Mutex<Bucket>,
+}
+
+struct Bucket {
+    available: f64,
+    updated: Instant,
+}
+
+impl Throttle {
+    pub fn new(rate: ByteRate) -> Self {
+        Self {
+            rate,
+            bucket: Mutex::new(Bucket {
+                available: Self::as_f64(rate.get()),
+                updated: Instant::now(),
+            }),
+        }
+    }
+
+    /// Accounts for a downloaded chunk, waiting if the rate has been exceeded.
+    pub async fn consume(&self, bytes: usize) {
+        let rate = Self::as_f64(self.rate.get());
+
+        let wait = {
+            let mut bucket = self
+                .bucket
+                .lock()
+                .unwrap_or_else(std::sync::PoisonError::into_inner);
+            let now = Instant::now();
+            let refilled = now.duration_since(bucket.updated).as_secs_f64() * rate;
+            bucket.available = (bucket.available + refilled).min(rate) - Self::as_f64(bytes as u64);
+            bucket.updated = now;
+            (bucket.available < 0.0).then(|| Duration::from_secs_f64(-bucket.available / rate))
+        };
+
+        if let Some(wait) = wait {
+            sleep(wait).await;
+        }
+    }
+
+    #[expect(
+        clippy::cast_precision_loss,
+        reason = "byte counts are far below where f64 loses precision"
+    )]
+    const fn as_f64(bytes: u64) -> f64 {
+        bytes as f64
+    }
+}
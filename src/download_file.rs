@@ -1,46 +1,56 @@
-use std::{
-    collections::HashMap,
-    io::{Read, Seek},
-    mem,
-};
+use std::{collections::HashMap, mem};
 
-use color_eyre::eyre::Result;
-use futures_util::{StreamExt, TryStreamExt, stream};
+use chrono::NaiveDate;
 use tracing::debug;
-use winget_types::{installer::Architecture, url::DecodedUrl};
+use winget_types::{
+    Sha256String,
+    installer::{Architecture, Installer},
+    url::DecodedUrl,
+};
 
-use crate::{analysis::Analyzer, download::DownloadedFile};
+use crate::{
+    analysis::Analyzer,
+    download::{DownloadedFile, GrowingFile},
+    manifests::Url,
+};
+
+pub fn process_files(files: Vec<DownloadedFile>) -> HashMap<DecodedUrl, Analyzer<GrowingFile>> {
+    files
+        .into_iter()
+        .map(
+            |DownloadedFile {
+                 mut url,
+                 sha_256,
+                 file_name,
+                 last_modified,
+                 analyzer: mut file_analyzer,
+             }| {
+                process_installers(&url, &mut file_analyzer.installers, &sha_256, last_modified);
+                file_analyzer.file_name = file_name;
+                (mem::take(url.inner_mut()), file_analyzer)
+            },
+        )
+        .collect::<HashMap<_, _>>()
+}
 
-pub async fn process_files(
-    files: &mut [DownloadedFile],
-) -> Result<HashMap<DecodedUrl, Analyzer<'_, impl Read + Seek>>> {
-    stream::iter(files.iter_mut().map(
-        |DownloadedFile {
-             file,
-             url,
-             sha_256,
-             file_name,
-             last_modified,
-             ..
-         }| async move {
-            let mut file_analyzer = Analyzer::new(file, file_name)?;
-            let architecture = url
-                .override_architecture()
-                .or_else(|| Architecture::from_url(url.as_str()));
-            for installer in &mut file_analyzer.installers {
-                if let Some(architecture) = architecture {
-                    installer.architecture = architecture;
-                }
-                debug!("{url}: {architecture:?}");
-                installer.url = url.inner().clone();
-                installer.sha_256 = sha_256.clone();
-                installer.release_date = *last_modified;
-            }
-            file_analyzer.file_name = mem::take(file_name);
-            Ok((mem::take(url.inner_mut()), file_analyzer))
-        },
-    ))
-    .buffer_unordered(num_cpus::get())
-    .try_collect::<HashMap<_, _>>()
-    .await
+/// Sets the values of the installers analyzed from a file that come from where it was downloaded
+/// from rather than from the file itself.
+pub fn process_installers(
+    url: &Url,
+    installers: &mut [Installer],
+    sha_256: &Sha256String,
+    last_modified: Option<NaiveDate>,
+) {
+    let architecture = url
+        .override_architecture()
+        .or_else(|| Architecture::from_url(url.as_str()));
+    for installer in installers {
+        if let Some(architecture) = architecture {
+            installer.architecture = architecture;
+        }
+        debug!("{url}: {architecture:?}");
+        installer.url = url.inner().clone();
+        installer.sha_256 = sha_256.clone();
+        installer.release_date = last_modified;
+    }
 }
@@ -0,0 +1,86 @@
+use reqwest::Request;
+use serde::{Deserialize, Serialize};
+use url::Url;
+
+/// A single recorded HTTP request and the response that was received for it.
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "PascalCase")]
+pub struct Exchange {
+    pub request: RecordedRequest,
+    pub response: RecordedResponse,
+}
+
+/// The parts of a request that are used to match it against a recording.
+///
+/// Request headers are deliberately not recorded so that tokens never end up in fixtures.
+#[derive(Clone, Debug, Eq, PartialEq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "PascalCase")]
+pub struct RecordedRequest {
+    pub method: String,
+    pub url: Url,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub body: Option<String>,
+}
+
+impl RecordedRequest {
+    /// Returns the GraphQL query of the request if its body is a GraphQL operation.
+    ///
+    /// GraphQL variables can contain values that differ between runs, such as randomly generated
+    /// branch names, so requests with the same query are matched when there is no exact match.
+    pub fn graphql_query(&self) -> Option<String> {
+        #[derive(Deserialize)]
+        struct Operation {
+            query: String,
+        }
+
+        serde_json::from_str::<Operation>(self.body.as_deref()?)
+            .ok()
+            .map(|operation| operation.query)
+    }
+
+    /// Returns `true` if both requests have the same method and URL and either the same GraphQL
+    /// query or the same body.
+    pub fn loosely_matches(&self, other: &Self) -> bool {
+        self.method == other.method
+            && self.url == other.url
+            && match (self.graphql_query(), other.graphql_query()) {
+                (Some(query), Some(other_query)) => query == other_query,
+                _ => self.body == other.body,
+            }
+    }
+}
+
+impl From<&Request> for RecordedRequest {
+    fn from(request: &Request) -> Self {
+        Self {
+            method: request.method().to_string(),
+            url: request.url().clone(),
+            body: request
+                .body()
+                .and_then(reqwest::Body::as_bytes)
+                .map(|bytes| String::from_utf8_lossy(bytes).into_owned()),
+        }
+    }
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+#[serde(rename_all = "PascalCase")]
+pub struct RecordedResponse {
+    pub status: u16,
+
+    /// The final URL of the response after any redirects.
+    pub url: Url,
+
+    pub headers: Vec<(String, String)>,
+
+    pub body: RecordedBody,
+}
+
+/// A response body stored inline if it is text, or in a separate file in the fixture directory
+/// otherwise.
+#[derive(Clone, Serialize, Deserialize)]
+#[serde(rename_all = "PascalCase")]
+pub enum RecordedBody {
+    Text(String),
+    File(String),
+}
@@ -0,0 +1,431 @@
+mod exchange;
+
+use std::{
+    env,
+    fs::{self, File, OpenOptions},
+    io,
+    io::{BufRead, BufReader, Write},
+    sync::{Mutex, OnceLock},
+};
+
+use bytes::Bytes;
+use camino::Utf8PathBuf;
+use cynic::{GraphQlResponse, Operation, http::CynicReqwestError};
+use exchange::{Exchange, RecordedBody, RecordedRequest, RecordedResponse};
+use reqwest::{
+    Client, Request, RequestBuilder, Response, ResponseBuilderExt, StatusCode,
+    header::{CONTENT_TYPE, SET_COOKIE},
+};
+use serde::{Serialize, de::DeserializeOwned};
+use sha2::{Digest, Sha256};
+use tracing::warn;
+use winget_types::Sha256String;
+
+/// The environment variable containing a directory to record HTTP exchanges to.
+pub const RECORD_FIXTURES: &str = "KOMAC_RECORD_FIXTURES";
+
+/// The environment variable containing a directory to replay recorded HTTP exchanges from.
+pub const REPLAY_FIXTURES: &str = "KOMAC_REPLAY_FIXTURES";
+
+const EXCHANGES_FILE: &str = "exchanges.jsonl";
+const BODIES_DIRECTORY: &str = "bodies";
+
+static FIXTURES: OnceLock<Fixtures> = OnceLock::new();
+
+/// Records HTTP exchanges to, or replays them from, a fixture directory.
+///
+/// All requests made to GitHub and to installer URLs go through [`Dispatch::dispatch`], so a run
+/// of a command can be recorded once with network access and then replayed deterministically
+/// without a network or a GitHub token.
+pub enum Fixtures {
+    Record(Recorder),
+    Replay(Replayer),
+}
+
+impl Fixtures {
+    /// Enables recording or replaying based on the [`RECORD_FIXTURES`] and [`REPLAY_FIXTURES`]
+    /// environment variables.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if both environment variables are set, or if the fixture directory could
+    /// not be created or read.
+    pub fn init() -> color_eyre::Result<()> {
+        let fixtures = match (env::var(RECORD_FIXTURES), env::var(REPLAY_FIXTURES)) {
+            (Ok(_), Ok(_)) => color_eyre::eyre::bail!(
+                "Only one of {RECORD_FIXTURES} and {REPLAY_FIXTURES} can be set"
+            ),
+            (Ok(directory), Err(_)) => Self::Record(Recorder::new(directory.into())?),
+            (Err(_), Ok(directory)) => Self::Replay(Replayer::new(directory.into())?),
+            (Err(_), Err(_)) => return Ok(()),
+        };
+
+        let _ = FIXTURES.set(fixtures);
+
+        Ok(())
+    }
+
+    /// Returns `true` if recorded exchanges are being replayed instead of using the network.
+    pub fn is_replaying() -> bool {
+        matches!(FIXTURES.get(), Some(Self::Replay(_)))
+    }
+
+    async fn dispatch(&self, client: Client, request: Request) -> reqwest::Result<Response> {
+        match self {
+            Self::Record(recorder) => recorder.record(client, request).await,
+            Self::Replay(replayer) => Ok(replayer.replay(&request)),
+        }
+    }
+}
+
+pub struct Recorder {
+    directory: Utf8PathBuf,
+    exchanges: Mutex<File>,
+}
+
+impl Recorder {
+    fn new(directory: Utf8PathBuf) -> io::Result<Self> {
+        fs::create_dir_all(directory.join(BODIES_DIRECTORY))?;
+
+        let exchanges = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(directory.join(EXCHANGES_FILE))?;
+
+        Ok(Self {
+            directory,
+            exchanges: Mutex::new(exchanges),
+        })
+    }
+
+    async fn record(&self, client: Client, request: Request) -> reqwest::Result<Response> {
+        let recorded_request = RecordedRequest::from(&request);
+
+        let response = client.execute(request).await?;
+
+        let status = response.status();
+        let url = response.url().clone();
+        let headers = response
+            .headers()
+            .iter()
+            .filter(|(name, _)| *name != SET_COOKIE)
+            .filter_map(|(name, value)| Some((name.to_string(), value.to_str().ok()?.to_owned())))
+            .collect::<Vec<_>>();
+        let body = response.bytes().await?;
+
+        let recorded_response = RecordedResponse {
+            status: status.as_u16(),
+            url,
+            headers,
+            body: self.store_body(&body),
+        };
+
+        if let Err(error) = self.append(&Exchange {
+            request: recorded_request,
+            response: recorded_response.clone(),
+        }) {
+            warn!(%error, "Failed to record HTTP exchange");
+        }
+
+        Ok(recorded_response.into_response(body))
+    }
+
+    /// Stores a body inline if it is text, or in a file named after its hash otherwise.
+    fn store_body(&self, body: &[u8]) -> RecordedBody {
+        if let Ok(text) = str::from_utf8(body) {
+            return RecordedBody::Text(text.to_owned());
+        }
+
+        let file_name = format!(
+            "{BODIES_DIRECTORY}/{}",
+            Sha256String::from_digest(&Sha256::digest(body))
+        );
+
+        if let Err(error) = fs::write(self.directory.join(&file_name), body) {
+            warn!(%error, "Failed to record HTTP response body");
+        }
+
+        RecordedBody::File(file_name)
+    }
+
+    fn append(&self, exchange: &Exchange) -> io::Result<()> {
+        let mut line = serde_json::to_vec(exchange)?;
+        line.push(b'\n');
+
+        self.exchanges
+            .lock()
+            .unwrap_or_else(std::sync::PoisonError::into_inner)
+            .write_all(&line)
+    }
+}
+
+pub struct Replayer {
+    directory: Utf8PathBuf,
+    exchanges: Mutex<Vec<(Exchange, bool)>>,
+}
+
+impl Replayer {
+    fn new(directory: Utf8PathBuf) -> io::Result<Self> {
+        let exchanges = BufReader::new(File::open(directory.join(EXCHANGES_FILE))?)
+            .lines()
+            .filter(|line| line.as_ref().is_ok_and(|line| !line.trim().is_empty()))
+            .map(|line| Ok((serde_json::from_str::<Exchange>(&line?)?, false)))
+            .collect::<io::Result<Vec<_>>>()?;
+
+        Ok(Self {
+            directory,
+            exchanges: Mutex::new(exchanges),
+        })
+    }
+
+    /// Finds the recorded response for a request.
+    ///
+    /// Recorded exchanges are replayed in the order they were recorded so that the same request
+    /// made several times can return different responses. An exact match is preferred over a
+    /// request with the same GraphQL query but different variables. Once all matching exchanges
+    /// have been used, the last one is repeated. If nothing matches, a `404 Not Found` response is
+    /// returned.
+    fn replay(&self, request: &Request) -> Response {
+        let request = RecordedRequest::from(request);
+
+        let mut exchanges = self
+            .exchanges
+            .lock()
+            .unwrap_or_else(std::sync::PoisonError::into_inner);
+
+        let position = exchanges
+            .iter()
+            .position(|(exchange, used)| !used && exchange.request == request)
+            .or_else(|| {
+                exchanges.iter().position(|(exchange, used)| {
+                    !used && exchange.request.loosely_matches(&request)
+                })
+            })
+            .or_else(|| {
+                exchanges
+                    .iter()
+                    .rposition(|(exchange, _)| exchange.request.loosely_matches(&request))
+            });
+
+        let Some(position) = position else {
+            warn!("No recorded fixture for {} {}", request.method, request.url);
+            return RecordedResponse {
+                status: StatusCode::NOT_FOUND.as_u16(),
+                url: request.url.clone(),
+                headers: Vec::new(),
+                body: RecordedBody::Text(format!(
+                    "No recorded fixture for {} {}",
+                    request.method, request.url
+                )),
+            }
+            .into_response(Bytes::new());
+        };
+
+        exchanges[position].1 = true;
+        let response = exchanges[position].0.response.clone();
+        drop(exchanges);
+
+        let body = match &response.body {
+            RecordedBody::Text(text) => Bytes::from(text.clone()),
+            RecordedBody::File(path) => fs::read(self.directory.join(path))
+                .map(Bytes::from)
+                .unwrap_or_else(|error| {
+                    warn!(%error, "Failed to read recorded HTTP response body {path}");
+                    Bytes::new()
+                }),
+        };
+
+        response.into_response(body)
+    }
+}
+
+impl RecordedResponse {
+    fn into_response(self, body: Bytes) -> Response {
+        let body = match self.body {
+            RecordedBody::Text(text) if body.is_empty() => Bytes::from(text),
+            _ => body,
+        };
+
+        let mut builder = http::Response::builder().status(self.status).url(self.url);
+        for (name, value) in &self.headers {
+            builder = builder.header(name, value);
+        }
+
+        Response::from(builder.body(body).unwrap_or_else(|_| {
+            http::Response::builder()
+                .status(StatusCode::INTERNAL_SERVER_ERROR)
+                .header(CONTENT_TYPE, "text/plain")
+                .body(Bytes::from_static(b"Invalid recorded response"))
+                .unwrap_or_else(|_| unreachable!())
+        }))
+    }
+}
+
+/// Sends requests through the fixture recorder or replayer when one is enabled.
+pub trait Dispatch {
+    /// Sends the request, recording the exchange or replaying a recorded one if fixtures are
+    /// enabled.
+    fn dispatch(self) -> impl Future<Output = reqwest::Result<Response>> + Send;
+}
+
+impl Dispatch for RequestBuilder {
+    async fn dispatch(self) -> reqwest::Result<Response> {
+        let (client, request) = self.build_split();
+        let request = request?;
+
+        match FIXTURES.get() {
+            Some(fixtures) => fixtures.dispatch(client, request).await,
+            None => client.execute(request).await,
+        }
+    }
+}
+
+/// A replacement for [`cynic::http::ReqwestExt`] that sends GraphQL operations through
+/// [`Dispatch::dispatch`].
+pub trait ReqwestExt {
+    fn run_graphql<ResponseData, Vars>(
+        self,
+        operation: impl AsRef<Operation<ResponseData, Vars>>,
+    ) -> impl Future<Output = Result<GraphQlResponse<ResponseData>, CynicReqwestError>> + Send
+    where
+        Vars: Serialize,
+        ResponseData: DeserializeOwned + 'static;
+}
+
+impl ReqwestExt for RequestBuilder {
+    fn run_graphql<ResponseData, Vars>(
+        self,
+        operation: impl AsRef<Operation<ResponseData, Vars>>,
+    ) -> impl Future<Output = Result<GraphQlResponse<ResponseData>, CynicReqwestError>> + Send
+    where
+        Vars: Serialize,
+        ResponseData: DeserializeOwned + 'static,
+    {
+        let builder = self.json(operation.as_ref());
+
+        async move { graphql_response(builder.dispatch().await?).await }
+    }
+}
+
+/// Reads the response to a GraphQL operation, including the errors of an unsuccessful response.
+pub async fn graphql_response<ResponseData: DeserializeOwned>(
+    response: Response,
+) -> Result<GraphQlResponse<ResponseData>, CynicReqwestError> {
+    let status = response.status();
+    if status.is_success() {
+        Ok(response.json().await?)
+    } else {
+        let text = response.text().await?;
+        serde_json::from_str(&text).map_err(|_| CynicReqwestError::ErrorResponse(status, text))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use camino::Utf8PathBuf;
+    use reqwest::{Client, Request, StatusCode};
+    use serde_json::json;
+
+    use super::{
+        Recorder, Replayer,
+        exchange::{Exchange, RecordedBody, RecordedRequest, RecordedResponse},
+    };
+
+    const GRAPHQL_URL: &str = "https://api.github.com/graphql";
+
+    fn graphql_request(branch: &str) -> Request {
+        Client::new()
+            .post(GRAPHQL_URL)
+            .json(&json!({ "query": "mutation CreateRef", "variables": { "name": branch } }))
+            .build()
+            .unwrap()
+    }
+
+    fn exchange(request: &Request, body: RecordedBody) -> Exchange {
+        Exchange {
+            request: RecordedRequest::from(request),
+            response: RecordedResponse {
+                status: StatusCode::OK.as_u16(),
+                url: request.url().clone(),
+                headers: Vec::new(),
+                body,
+            },
+        }
+    }
+
+    fn replayer(exchanges: &[Exchange]) -> (tempfile::TempDir, Replayer) {
+        let directory = tempfile::tempdir().unwrap();
+        let path = Utf8PathBuf::from_path_buf(directory.path().to_path_buf()).unwrap();
+
+        let recorder = Recorder::new(path.clone()).unwrap();
+        for exchange in exchanges {
+            recorder.append(exchange).unwrap();
+        }
+
+        (directory, Replayer::new(path).unwrap())
+    }
+
+    #[tokio::test]
+    async fn replays_in_recorded_order() {
+        let request = Client::new()
+            .get("https://example.com/file.txt")
+            .build()
+            .unwrap();
+        let (_directory, replayer) = replayer(&[
+            exchange(&request, RecordedBody::Text("first".to_owned())),
+            exchange(&request, RecordedBody::Text("second".to_owned())),
+        ]);
+
+        assert_eq!(replayer.replay(&request).text().await.unwrap(), "first");
+        assert_eq!(replayer.replay(&request).text().await.unwrap(), "second");
+        assert_eq!(replayer.replay(&request).text().await.unwrap(), "second");
+    }
+
+    #[tokio::test]
+    async fn matches_graphql_query_with_different_variables() {
+        let (_directory, replayer) = replayer(&[exchange(
+            &graphql_request("komac-recorded"),
+            RecordedBody::Text("created".to_owned()),
+        )]);
+
+        let response = replayer.replay(&graphql_request("komac-replayed"));
+
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_eq!(response.text().await.unwrap(), "created");
+    }
+
+    #[tokio::test]
+    async fn binary_body_roundtrip() {
+        let request = Client::new()
+            .get("https://example.com/installer.exe")
+            .build()
+            .unwrap();
+        let binary = [0x4D, 0x5A, 0x90, 0x00, 0xFF, 0xFE];
+
+        let directory = tempfile::tempdir().unwrap();
+        let path = Utf8PathBuf::from_path_buf(directory.path().to_path_buf()).unwrap();
+        let recorder = Recorder::new(path.clone()).unwrap();
+        let body = recorder.store_body(&binary);
+        assert!(matches!(body, RecordedBody::File(_)));
+        recorder.append(&exchange(&request, body)).unwrap();
+
+        let replayer = Replayer::new(path).unwrap();
+
+        assert_eq!(
+            replayer.replay(&request).bytes().await.unwrap().as_ref(),
+            binary
+        );
+    }
+
+    #[test]
+    fn unrecorded_request_is_not_found() {
+        let (_directory, replayer) = replayer(&[]);
+
+        let request = Client::new()
+            .get("https://example.com/missing")
+            .build()
+            .unwrap();
+
+        assert_eq!(replayer.replay(&request).status(), StatusCode::NOT_FOUND);
+    }
+}
@@ -1,31 +1,39 @@
-use std::{borrow::Cow, collections::BTreeSet, num::NonZeroU32, str::FromStr};
+use std::{
+    borrow::{Borrow, Cow},
+    collections::{BTreeMap, BTreeSet},
+    num::NonZeroU32,
+    str::FromStr,
+    sync::{Arc, Mutex, PoisonError, RwLock, Weak},
+    time::Duration,
+};
 
 use bon::bon;
+use chrono::{DateTime, Utc};
 use color_eyre::eyre::eyre;
-use cynic::{GraphQlResponse, Id, MutationBuilder, QueryBuilder, http::ReqwestExt};
+use cynic::{GraphQlResponse, Id, MutationBuilder, QueryBuilder};
 use futures_util::future::OptionFuture;
 use indexmap::IndexMap;
 use indicatif::ProgressBar;
 use itertools::Itertools;
-use reqwest::Client;
+use reqwest::{Client, IntoUrl};
 use secrecy::SecretString;
 use serde::de::DeserializeOwned;
 use url::Url;
 use winget_types::{
-    Manifest, ManifestType, ManifestTypeWithLocale, PackageIdentifier, PackageVersion,
-    installer::InstallerManifest,
-    locale::{DefaultLocaleManifest, License, LocaleManifest, Publisher, ReleaseNotes, Tag},
+    Manifest, ManifestTypeWithLocale, PackageIdentifier, PackageVersion,
+    locale::{License, Publisher, ReleaseNotes, Tag},
     url::{DecodedUrl, LicenseUrl, PackageUrl, PublisherSupportUrl, PublisherUrl, ReleaseNotesUrl},
-    version::VersionManifest,
 };
 
-use super::{GitHubError, graphql::create_pull_request};
+use super::{
+    GitHubError,
+    graphql::{create_pull_request, get_pull_request},
+};
 use crate::{
     commands::{cleanup::MergeState, utils::SPINNER_TICK_RATE},
+    fixtures::ReqwestExt,
     github::{
-        MICROSOFT, WINGET_PKGS, WINGET_PKGS_FULL_NAME,
         graphql::{
-            GRAPHQL_URL,
             create_commit::{FileAddition, FileDeletion},
             create_ref::{CreateRef, CreateRefVariables, Ref as CreateBranchRef},
             get_all_values::{GetAllValues, GetAllValuesGitObject, GetAllValuesVariables, Tree},
@@ -33,35 +41,153 @@
             get_directory_content::GetDirectoryContentVariables,
             get_directory_content_with_text::{GetDirectoryContentWithText, TreeEntry},
             get_repository_info::{GetRepositoryInfo, RepositoryVariables, TargetGitObject},
+            graphql_url,
             types::GitObjectId,
             update_refs::{RefUpdate, UpdateRefs, UpdateRefsInput},
         },
+        host::GitHubHost,
+        request::GitHubRequest,
+        upstream::Upstream,
         utils::{
-            CommitTitle, PackagePath, branch_name, commit_title, is_manifest_file,
-            pull_request_body,
+            Amendment, CommitTitle, ManifestSnapshot, PackagePath, branch_name, commit_title,
+            edit_title, pull_request_body,
         },
     },
     manifests::Manifests,
-    token::default_headers,
+    repository::parse_manifests,
+    token::{TokenManager, app::AppInstallation, default_headers},
     traits::FromHtml,
     update_state::UpdateState,
 };
 
+/// An HTTP client for GitHub's APIs.
+///
+/// When authenticated as a GitHub App installation, the client is replaced in the background with
+/// one that uses a new installation access token before the current one expires.
+#[derive(Clone)]
+pub struct GitHubClient {
+    client: Arc<RwLock<Client>>,
+    installation: Option<Arc<AppInstallation>>,
+    /// Clients for hosts other than the upstream repository's, by their API URL, which are
+    /// authenticated with the token stored for each host.
+    hosts: Arc<Mutex<BTreeMap<String, Client>>>,
+}
+
+impl GitHubClient {
+    fn build_client(token: &SecretString) -> Result<Client, reqwest::Error> {
+        Client::builder()
+            .default_headers(default_headers(Some(token)))
+            .build()
+    }
+
+    fn current(&self) -> Client {
+        self.client
+            .read()
+            .unwrap_or_else(PoisonError::into_inner)
+            .clone()
+    }
+
+    /// Returns the client for a host, which is built with the token stored for the host the first
+    /// time a request is made to it unless it is the upstream repository's host.
+    fn for_host(&self, host: &GitHubHost) -> Result<Client, reqwest::Error> {
+        if host == Upstream::get().host() {
+            return Ok(self.current());
+        }
+
+        let mut hosts = self.hosts.lock().unwrap_or_else(PoisonError::into_inner);
+        if let Some(client) = hosts.get(host.graphql_url()) {
+            return Ok(client.clone());
+        }
+
+        let client = Client::builder()
+            .default_headers(default_headers(TokenManager::stored_token(host).as_ref()))
+            .build()?;
+        hosts.insert(host.graphql_url().to_owned(), client.clone());
+        Ok(client)
+    }
+
+    #[inline]
+    pub fn get<U: IntoUrl>(&self, url: U) -> GitHubRequest {
+        self.current().get(url).into()
+    }
+
+    #[inline]
+    pub fn post<U: IntoUrl>(&self, url: U) -> GitHubRequest {
+        self.current().post(url).into()
+    }
+
+    /// Returns the login of the user or organization that the GitHub App is installed on, if
+    /// authenticated as a GitHub App installation.
+    pub fn installation_account(&self) -> Option<&str> {
+        self.installation.as_deref().map(AppInstallation::account)
+    }
+
+    /// Replaces the client with one that uses a new installation access token shortly before each
+    /// token expires, until every clone of the client has been dropped.
+    async fn refresh(
+        client: Weak<RwLock<Client>>,
+        installation: Arc<AppInstallation>,
+        mut refresh_at: DateTime<Utc>,
+    ) {
+        const RETRY_INTERVAL: Duration = Duration::from_secs(30);
+
+        loop {
+            if let Ok(duration) = (refresh_at - Utc::now()).to_std() {
+                tokio::time::sleep(duration).await;
+            }
+
+            if client.strong_count() == 0 {
+                break;
+            }
+
+            let refreshed = installation.create_token().await.ok().and_then(|token| {
+                Some((Self::build_client(&token.token).ok()?, token.refresh_at()))
+            });
+
+            let Some((new_client, next_refresh_at)) = refreshed else {
+                tokio::time::sleep(RETRY_INTERVAL).await;
+                continue;
+            };
+
+            let Some(client) = client.upgrade() else {
+                break;
+            };
+
+            *client.write().unwrap_or_else(PoisonError::into_inner) = new_client;
+            refresh_at = next_refresh_at;
+        }
+    }
+}
+
 #[derive(Clone)]
 #[repr(transparent)]
-pub struct GitHub(pub(super) Client);
+pub struct GitHub(pub(super) GitHubClient);
 
 #[bon]
 impl GitHub {
-    pub fn new<T>(token: T) -> Result<Self, GitHubError>
+    pub fn new<T>(token_manager: T) -> Result<Self, GitHubError>
     where
-        T: AsRef<SecretString>,
+        T: Borrow<TokenManager>,
     {
-        Ok(Self(
-            Client::builder()
-                .default_headers(default_headers(Some(token.as_ref())))
-                .build()?,
-        ))
+        let token_manager = token_manager.borrow();
+        let client = Arc::new(RwLock::new(GitHubClient::build_client(
+            token_manager.as_ref(),
+        )?));
+
+        let installation = token_manager.app().map(|app| {
+            tokio::spawn(GitHubClient::refresh(
+                Arc::downgrade(&client),
+                Arc::clone(&app.installation),
+                app.refresh_at,
+            ));
+            Arc::clone(&app.installation)
+        });
+
+        Ok(Self(GitHubClient {
+            client,
+            installation,
+            hosts: Arc::default(),
+        }))
     }
 
     pub async fn get_manifests(
@@ -71,74 +197,28 @@ pub async fn get_manifests(
     ) -> Result<Manifests, GitHubError> {
         let full_package_path = PackagePath::new(identifier, Some(latest_version), None);
         let content = self
-            .get_directory_content_with_text(MICROSOFT, WINGET_PKGS, &full_package_path)
+            .get_directory_content_with_text()
+            .path(&full_package_path)
+            .call()
             .await?
             .collect::<Vec<_>>();
 
-        let version_manifest = content
-            .iter()
-            .find(|file| is_manifest_file::<VersionManifest>(&file.name, identifier, None))
-            .map(|file| serde_yaml::from_str::<VersionManifest>(&file.text))
-            .ok_or_else(|| GitHubError::ManifestNotFound {
-                r#type: ManifestType::Version,
-                path: full_package_path.clone(),
-            })??;
-
-        let locale_manifests = content
-            .iter()
-            .filter(|file| {
-                is_manifest_file::<LocaleManifest>(
-                    &file.name,
-                    identifier,
-                    Some(&version_manifest.default_locale),
-                )
-            })
-            .map(|file| serde_yaml::from_str::<LocaleManifest>(&file.text))
-            .collect::<serde_yaml::Result<_>>()?;
-
-        let default_locale_manifest = content
-            .iter()
-            .find(|file| {
-                is_manifest_file::<DefaultLocaleManifest>(
-                    &file.name,
-                    identifier,
-                    Some(&version_manifest.default_locale),
-                )
-            })
-            .map(|file| serde_yaml::from_str::<DefaultLocaleManifest>(&file.text))
-            .ok_or_else(|| GitHubError::ManifestNotFound {
-                r#type: ManifestType::DefaultLocale,
-                path: full_package_path.clone(),
-            })??;
-
-        let installer_manifest = content
-            .into_iter()
-            .find(|file| is_manifest_file::<InstallerManifest>(&file.name, identifier, None))
-            .map(|file| serde_yaml::from_str::<InstallerManifest>(&file.text))
-            .ok_or_else(|| GitHubError::ManifestNotFound {
-                r#type: ManifestType::Installer,
-                path: full_package_path.clone(),
-            })??;
-
-        Ok(Manifests {
-            installer: installer_manifest,
-            default_locale: default_locale_manifest,
-            locales: locale_manifests,
-            version: version_manifest,
-        })
+        parse_manifests(identifier, &full_package_path, &content)
     }
 
-    async fn get_directory_content_with_text(
+    #[builder]
+    pub async fn get_directory_content_with_text(
         &self,
-        owner: &str,
-        repo: &str,
+        #[builder(default = Upstream::get().owner())] owner: &str,
+        #[builder(default = Upstream::get().repo())] repo: &str,
+        #[builder(default = Upstream::get().revision())] branch_name: &str,
         path: &PackagePath,
     ) -> Result<impl Iterator<Item = GitHubFile>, GitHubError> {
         let GraphQlResponse { data, errors } = self
             .0
-            .post(GRAPHQL_URL)
+            .post(graphql_url())
             .run_graphql(GetDirectoryContentWithText::build(
-                GetDirectoryContentVariables::new(&owner, &repo, &format!("HEAD:{path}")),
+                GetDirectoryContentVariables::new(&owner, &repo, &format!("{branch_name}:{path}")),
             ))
             .await?;
 
@@ -164,7 +244,10 @@ pub async fn get_manifest<T: Manifest + DeserializeOwned>(
         manifest_type: ManifestTypeWithLocale,
     ) -> Result<T, GitHubError> {
         let path = PackagePath::new(identifier, Some(version), Some(&manifest_type));
-        let content = self.get_file_content(MICROSOFT, WINGET_PKGS, &path).await?;
+        let upstream = Upstream::get();
+        let content = self
+            .get_file_content(upstream.owner(), upstream.repo(), &path)
+            .await?;
         let manifest = serde_yaml::from_str::<T>(&content)?;
         Ok(manifest)
     }
@@ -174,8 +257,24 @@ pub async fn get_winget_pkgs(
         &self,
         #[builder(into)] owner: Option<Cow<'_, str>>,
     ) -> Result<RepositoryData, GitHubError> {
-        self.get_repository_info(owner.as_deref().unwrap_or(MICROSOFT), WINGET_PKGS)
-            .await
+        let upstream = Upstream::get();
+        let mut repository = self
+            .get_repository_info(
+                owner.as_deref().unwrap_or(upstream.owner()),
+                upstream.repo(),
+            )
+            .await?;
+
+        // Use the configured branch instead of the default branch, in both upstream and forks
+        if let Some(branch) = upstream.branch() {
+            let (name, oid) = self
+                .get_branch(&repository.owner, upstream.repo(), branch)
+                .await?;
+            repository.default_branch_name = name;
+            repository.default_branch_oid = oid;
+        }
+
+        Ok(repository)
     }
 
     async fn get_repository_info(
@@ -185,7 +284,7 @@ async fn get_repository_info(
     ) -> Result<RepositoryData, GitHubError> {
         let GraphQlResponse { data, errors } = self
             .0
-            .post(GRAPHQL_URL)
+            .post(graphql_url())
             .run_graphql(GetRepositoryInfo::build(RepositoryVariables::new(
                 owner, name,
             )))
@@ -233,7 +332,7 @@ pub async fn create_branch(
     ) -> Result<CreateBranchRef, GitHubError> {
         let GraphQlResponse { data, errors } = self
             .0
-            .post(GRAPHQL_URL)
+            .post(graphql_url())
             .run_graphql(CreateRef::build(
                 CreateRefVariables::builder()
                     .name(&format!("refs/heads/{branch_name}"))
@@ -255,16 +354,17 @@ pub async fn get_branches<T: AsRef<str>>(
     ) -> Result<(IndexMap<PullRequest, String>, Id), GitHubError> {
         let user = user.as_ref();
 
+        let upstream = Upstream::get().to_string();
         let mut pr_branch_map = IndexMap::new();
         let mut cursor = None;
 
         loop {
             let GraphQlResponse { data, errors } = self
                 .0
-                .post(GRAPHQL_URL)
+                .post(graphql_url())
                 .run_graphql(GetBranches::build(GetBranchesVariables {
                     owner: user,
-                    name: WINGET_PKGS,
+                    name: Upstream::get().repo(),
                     cursor: cursor.as_deref(),
                 }))
                 .await?;
@@ -305,7 +405,10 @@ pub async fn get_branches<T: AsRef<str>>(
                         _ => !pull_request.state.is_open(),
                     })
                     .find(|pull_request| {
-                        pull_request.repository.name_with_owner == WINGET_PKGS_FULL_NAME
+                        pull_request
+                            .repository
+                            .name_with_owner
+                            .eq_ignore_ascii_case(&upstream)
                     })
                 {
                     pr_branch_map.insert(pull_request, branch.name);
@@ -331,7 +434,7 @@ pub async fn delete_branches<I, T>(
     {
         let GraphQlResponse { data, errors } = self
             .0
-            .post(GRAPHQL_URL)
+            .post(graphql_url())
             .run_graphql(UpdateRefs::build(UpdateRefsInput::new(
                 RefUpdate::delete_branches(branch_names),
                 repository_id,
@@ -352,30 +455,38 @@ pub fn get_all_values_from_url(
         &self,
         url: DecodedUrl,
     ) -> OptionFuture<impl Future<Output = Result<GitHubValues, GitHubError>> + Sized> {
-        url.path_segments()
-            .and_then(|mut parts| {
-                let _file_name = parts.next_back()?;
-                let builder = self
-                    .get_all_values()
-                    .owner(parts.next()?.to_owned())
-                    .repo(parts.next()?.to_owned());
-                let _releases = parts.next()?;
-                let _download = parts.next()?;
-                Some(builder.tag_name(parts.join("/")).send())
+        GitHubHost::from_url(&url)
+            .zip(ReleaseAsset::from_url(&url))
+            .map(|(host, asset)| {
+                self.get_all_values()
+                    .host(host)
+                    .owner(asset.owner)
+                    .repo(asset.repo)
+                    .tag_name(asset.tag_name)
+                    .send()
             })
             .into()
     }
 
+    /// Returns a request to the GraphQL API of a GitHub instance.
+    ///
+    /// Requests to the upstream repository's host are authenticated with the client's token.
+    /// Requests to another host, such as github.com when the upstream repository is on a GitHub
+    /// Enterprise Server, are authenticated with the token stored for that host if there is one.
+    pub(super) fn graphql_request(&self, host: &GitHubHost) -> Result<GitHubRequest, GitHubError> {
+        Ok(self.0.for_host(host)?.post(host.graphql_url()).into())
+    }
+
     #[builder(finish_fn = send)]
     pub async fn get_all_values<'a>(
         &self,
+        host: GitHubHost,
         #[builder(into)] owner: Cow<'a, str>,
         #[builder(into)] repo: Cow<'a, str>,
         #[builder(into)] tag_name: Cow<'a, str>,
     ) -> Result<GitHubValues, GitHubError> {
         let GraphQlResponse { data, errors } = self
-            .0
-            .post(GRAPHQL_URL)
+            .graphql_request(&host)?
             .run_graphql(GetAllValues::build(GetAllValuesVariables {
                 name: &repo,
                 owner: &owner,
@@ -408,7 +519,7 @@ pub async fn get_all_values<'a>(
                     ) == "license"
                 })
                 .and_then(|name| {
-                    format!("https://github.com/{owner}/{repo}/blob/HEAD/{name}")
+                    host.web_url(format_args!("{owner}/{repo}/blob/HEAD/{name}"))
                         .parse::<LicenseUrl>()
                         .ok()
                 }),
@@ -425,7 +536,7 @@ pub async fn get_all_values<'a>(
             .collect::<BTreeSet<_>>();
 
         let issues_url = if repository.has_issues_enabled {
-            format!("https://github.com/{owner}/{repo}/issues")
+            host.web_url(format_args!("{owner}/{repo}/issues"))
                 .parse::<PublisherSupportUrl>()
                 .ok()
         } else {
@@ -435,7 +546,7 @@ pub async fn get_all_values<'a>(
         Ok(GitHubValues {
             description: repository.description,
             publisher: Publisher::from_str(repository.owner.login.as_str()).ok(),
-            publisher_url: PublisherUrl::from_str(repository.owner.url.as_str())?,
+            publisher_url: Some(PublisherUrl::from_str(repository.owner.url.as_str())?),
             issues_url,
             license: repository
                 .license_info
@@ -528,6 +639,7 @@ pub async fn add_version(
         issue_resolves: &[NonZeroU32],
         created_with: Option<&str>,
         created_with_url: Option<&DecodedUrl>,
+        previous: Option<&ManifestSnapshot>,
     ) -> Result<create_pull_request::PullRequest, GitHubError> {
         let (current_user, winget_pkgs) =
             tokio::try_join!(self.get_username(), self.get_winget_pkgs().send())?;
@@ -572,16 +684,143 @@ pub async fn add_version(
                 .issue_resolves(issue_resolves)
                 .maybe_created_with(created_with)
                 .maybe_created_with_url(created_with_url)
+                .changes(&changes)
+                .maybe_previous(previous)
                 .build(),
         )
         .await
     }
+
+    /// Commits changes to the manifests of existing versions of a package on a new branch of the
+    /// fork and opens one pull request for all of them.
+    #[builder(finish_fn = send)]
+    pub async fn edit_versions(
+        &self,
+        identifier: &PackageIdentifier,
+        versions: &[PackageVersion],
+        changes: Vec<(String, String)>,
+        summary: &str,
+        issue_resolves: &[NonZeroU32],
+    ) -> Result<create_pull_request::PullRequest, GitHubError> {
+        let (current_user, winget_pkgs) =
+            tokio::try_join!(self.get_username(), self.get_winget_pkgs().send())?;
+        let fork = self.get_winget_pkgs().owner(&current_user).send().await?;
+        let latest = versions.last().unwrap_or_else(|| unreachable!());
+        let pull_request_branch = self
+            .create_branch(
+                &fork.id,
+                &branch_name(identifier, latest),
+                winget_pkgs.default_branch_oid,
+            )
+            .await?;
+        let title = edit_title(identifier, versions);
+        let additions = changes
+            .iter()
+            .map(|(path, content)| FileAddition::new(path, content))
+            .collect::<Vec<_>>();
+        let _commit_url = self
+            .commit()
+            .branch_id(&pull_request_branch.id)
+            .head_sha(pull_request_branch.target.unwrap())
+            .message(&title)
+            .additions(additions)
+            .create()
+            .await?;
+        self.create_pull_request(
+            &winget_pkgs.id,
+            &fork.id,
+            &format!("{current_user}:{}", pull_request_branch.name),
+            &winget_pkgs.default_branch_name,
+            &title,
+            &pull_request_body()
+                .issue_resolves(issue_resolves)
+                .alternative_text(summary)
+                .build(),
+        )
+        .await
+    }
+
+    /// Commits the regenerated manifests of a package version onto the branch of an existing pull
+    /// request, updates its body, and comments on it with a summary of the files that changed.
+    ///
+    /// Returns `None` without amending anything if the manifests are already identical to those in
+    /// the pull request.
+    #[builder(finish_fn = send)]
+    pub async fn amend_pull_request(
+        &self,
+        pull_request: &get_pull_request::PullRequest,
+        fork_owner: &str,
+        identifier: &PackageIdentifier,
+        version: &PackageVersion,
+        changes: Vec<(String, String)>,
+        #[builder(default)] issue_resolves: &[NonZeroU32],
+        created_with: Option<&str>,
+        created_with_url: Option<&DecodedUrl>,
+        previous: Option<&ManifestSnapshot>,
+    ) -> Result<Option<create_pull_request::PullRequest>, GitHubError> {
+        let head_ref = pull_request.head_ref.as_ref().ok_or_else(|| {
+            GitHubError::GraphQL(eyre!(
+                "the branch of pull request #{} no longer exists",
+                pull_request.number
+            ))
+        })?;
+        let package_path = PackagePath::new(identifier, Some(version), None);
+        let existing = self
+            .get_directory_content_with_text()
+            .owner(fork_owner)
+            .branch_name(&head_ref.name)
+            .path(&package_path)
+            .call()
+            .await?
+            .collect::<Vec<_>>();
+        let amendment = Amendment::new(&existing, &changes);
+        if amendment.is_empty() {
+            return Ok(None);
+        }
+
+        let additions = changes
+            .iter()
+            .map(|(path, content)| FileAddition::new(path, content))
+            .collect::<Vec<_>>();
+        let deletions = amendment
+            .removed()
+            .map(|name| FileDeletion::new(format!("{package_path}/{name}")))
+            .collect::<Vec<_>>();
+        let _commit_url = self
+            .commit()
+            .branch_id(&head_ref.id)
+            .head_sha(pull_request.head_ref_oid.clone())
+            .message(&pull_request.title)
+            .additions(additions)
+            .deletions(deletions)
+            .create()
+            .await?;
+        let amended_pull_request = self
+            .update_pull_request_body(
+                &pull_request.id,
+                &pull_request_body()
+                    .issue_resolves(issue_resolves)
+                    .maybe_created_with(created_with)
+                    .maybe_created_with_url(created_with_url)
+                    .changes(&changes)
+                    .maybe_previous(previous)
+                    .build(),
+            )
+            .await?;
+        let _comment_url = self
+            .add_comment(&pull_request.id, &amendment.comment())
+            .await?;
+
+        Ok(Some(amended_pull_request))
+    }
 }
 
+/// The metadata of a package read from its repository and release on GitHub, or on another forge
+/// by [`metadata::get_values`](crate::metadata::get_values).
 pub struct GitHubValues {
     pub description: Option<String>,
     pub publisher: Option<Publisher>,
-    pub publisher_url: PublisherUrl,
+    pub publisher_url: Option<PublisherUrl>,
     pub issues_url: Option<PublisherSupportUrl>,
     pub license: Option<License>,
     pub license_url: Option<LicenseUrl>,
@@ -591,6 +830,31 @@ pub struct GitHubValues {
     pub topics: BTreeSet<Tag>,
 }
 
+/// The repository and release of a release asset's URL, such as
+/// `https://github.com/owner/repo/releases/download/v1.2.3/installer.msi`.
+#[derive(Debug, Eq, PartialEq)]
+pub struct ReleaseAsset {
+    pub owner: String,
+    pub repo: String,
+    pub tag_name: String,
+}
+
+impl ReleaseAsset {
+    pub fn from_url(url: &Url) -> Option<Self> {
+        let mut parts = url.path_segments()?;
+        let _file_name = parts.next_back()?;
+        let owner = parts.next()?.to_owned();
+        let repo = parts.next()?.to_owned();
+        let _releases = parts.next()?;
+        let _download = parts.next()?;
+        Some(Self {
+            owner,
+            repo,
+            tag_name: parts.join("/"),
+        })
+    }
+}
+
 pub struct GitHubFile {
     pub name: String,
     pub text: String,
@@ -3,7 +3,7 @@
 use thiserror::Error;
 use winget_types::{ManifestType, PackageIdentifier};
 
-use super::{WINGET_PKGS_FULL_NAME, utils::PackagePath};
+use super::{upstream::Upstream, utils::PackagePath};
 
 #[derive(Debug, Error)]
 pub enum GitHubError {
@@ -11,7 +11,7 @@ pub enum GitHubError {
     GraphQL(eyre::Error),
     #[error(transparent)]
     Rest(eyre::Error),
-    #[error("{0} does not exist in {WINGET_PKGS_FULL_NAME}")]
+    #[error("{0} does not exist in {upstream}", upstream = Upstream::get())]
     PackageNonExistent(PackageIdentifier),
     #[error("No {type} manifest was found in {path}")]
     ManifestNotFound {
@@ -28,6 +28,10 @@ pub enum GitHubError {
     YamlError(#[from] serde_yaml::Error),
     #[error(transparent)]
     UrlParseError(#[from] url::ParseError),
+    #[error(transparent)]
+    Git(eyre::Error),
+    #[error(transparent)]
+    Io(#[from] std::io::Error),
 }
 
 impl GitHubError {
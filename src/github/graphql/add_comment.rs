@@ -0,0 +1,95 @@
+use color_eyre::eyre::eyre;
+use cynic::{GraphQlResponse, Id, MutationBuilder};
+use url::Url;
+
+use super::{
+    super::{GitHubError, client::GitHub},
+    github_schema as schema, graphql_url,
+};
+use crate::fixtures::ReqwestExt;
+
+#[derive(cynic::QueryVariables)]
+pub struct AddCommentVariables<'a> {
+    pub input: AddCommentInput<'a>,
+}
+
+#[derive(cynic::QueryFragment)]
+#[cynic(graphql_type = "Mutation", variables = "AddCommentVariables")]
+pub struct AddComment {
+    #[arguments(input: $input)]
+    pub add_comment: Option<AddCommentPayload>,
+}
+
+/// <https://docs.github.com/graphql/reference/mutations#addcomment>
+#[derive(cynic::QueryFragment)]
+pub struct AddCommentPayload {
+    pub comment_edge: Option<IssueCommentEdge>,
+}
+
+#[derive(cynic::QueryFragment)]
+pub struct IssueCommentEdge {
+    pub node: Option<IssueComment>,
+}
+
+/// <https://docs.github.com/graphql/reference/objects#issuecomment>
+#[derive(cynic::QueryFragment)]
+pub struct IssueComment {
+    pub url: Url,
+}
+
+/// <https://docs.github.com/graphql/reference/input-objects#addcommentinput>
+#[derive(cynic::InputObject)]
+pub struct AddCommentInput<'a> {
+    pub body: &'a str,
+    pub subject_id: &'a Id,
+}
+
+impl GitHub {
+    /// Comments on an issue or pull request, returning the URL of the comment.
+    pub async fn add_comment(&self, subject_id: &Id, body: &str) -> Result<Url, GitHubError> {
+        let GraphQlResponse { data, errors } = self
+            .0
+            .post(graphql_url())
+            .run_graphql(AddComment::build(AddCommentVariables {
+                input: AddCommentInput { body, subject_id },
+            }))
+            .await?;
+
+        data.and_then(|data| data.add_comment?.comment_edge?.node)
+            .map(|comment| comment.url)
+            .ok_or_else(|| GitHubError::graphql_errors(eyre!("failed to add comment"), errors))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use cynic::{Id, MutationBuilder};
+    use indoc::indoc;
+
+    use super::{AddComment, AddCommentInput, AddCommentVariables};
+
+    #[test]
+    fn add_comment_output() {
+        const ADD_COMMENT_MUTATION: &str = indoc! {"
+            mutation AddComment($input: AddCommentInput!) {
+              addComment(input: $input) {
+                commentEdge {
+                  node {
+                    url
+                  }
+                }
+              }
+            }
+        "};
+
+        let id = Id::new("");
+        let operation = AddComment::build(AddCommentVariables {
+            input: AddCommentInput {
+                body: "",
+                subject_id: &id,
+            },
+        });
+
+        assert_eq!(operation.query, ADD_COMMENT_MUTATION);
+    }
+}
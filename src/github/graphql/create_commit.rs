@@ -2,14 +2,15 @@
 
 use bon::{Builder, bon};
 use color_eyre::eyre::eyre;
-use cynic::{GraphQlResponse, Id, MutationBuilder, http::ReqwestExt};
+use cynic::{GraphQlResponse, Id, MutationBuilder};
 use url::Url;
 
 use super::{
     super::{GitHubError, client::GitHub},
-    GRAPHQL_URL, github_schema as schema,
+    github_schema as schema, graphql_url,
     types::{Base64String, GitObjectId},
 };
+use crate::fixtures::ReqwestExt;
 
 #[derive(cynic::QueryVariables)]
 pub struct CreateCommitVariables<'a> {
@@ -141,7 +142,7 @@ pub async fn commit(
     ) -> Result<Url, GitHubError> {
         let GraphQlResponse { data, errors } = self
             .0
-            .post(GRAPHQL_URL)
+            .post(graphql_url())
             .run_graphql(CreateCommit::build(CreateCommitVariables {
                 input: CreateCommitOnBranchInput::builder()
                     .branch(CommittableBranch::new(branch_id))
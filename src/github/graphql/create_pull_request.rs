@@ -2,15 +2,18 @@
 
 use bon::Builder;
 use color_eyre::eyre::eyre;
-use cynic::{GraphQlResponse, MutationBuilder, http::ReqwestExt};
+use cynic::{GraphQlResponse, MutationBuilder};
 use owo_colors::OwoColorize;
 use url::Url;
 
 use super::{
-    super::{GitHubError, client::GitHub},
-    GRAPHQL_URL, github_schema as schema,
+    super::{GitHubError, client::GitHub, utils::PullRequestSettings},
+    github_schema as schema, graphql_url,
+};
+use crate::{
+    fixtures::ReqwestExt,
+    terminal::{Hyperlinkable, SUPPORTS_HYPERLINKS},
 };
-use crate::terminal::{Hyperlinkable, SUPPORTS_HYPERLINKS};
 
 #[derive(cynic::QueryVariables)]
 pub struct CreatePullRequestVariables<'a> {
@@ -54,11 +57,19 @@ pub const fn url(&self) -> &Url {
     }
 
     pub fn print_success(&self) {
+        self.print_action("created");
+    }
+
+    pub fn print_updated(&self) {
+        self.print_action("updated");
+    }
+
+    fn print_action(&self, action: &str) {
         let mut stdout = anstream::stdout();
 
         let _ = writeln!(
             stdout,
-            "{} created {}",
+            "{} {action} {}",
             "Successfully".green(),
             format_args!(
                 "{repository_name_with_owner}#{number}",
@@ -111,6 +122,7 @@ pub async fn create_pull_request(
             input: CreatePullRequestInput::builder()
                 .base_ref_name(branch_name)
                 .body(body)
+                .maybe_draft(PullRequestSettings::get().draft.then_some(true))
                 .head_ref_name(fork_ref_name)
                 .head_repository_id(fork_id)
                 .repository_id(repository_id)
@@ -119,7 +131,7 @@ pub async fn create_pull_request(
         });
 
         let GraphQlResponse { data, errors } =
-            self.0.post(GRAPHQL_URL).run_graphql(operation).await?;
+            self.0.post(graphql_url()).run_graphql(operation).await?;
 
         data.and_then(|data| data.create_pull_request?.pull_request)
             .ok_or_else(|| {
@@ -0,0 +1,233 @@
+use chrono::{DateTime, NaiveDate, Utc};
+use color_eyre::eyre::eyre;
+use cynic::{GraphQlResponse, QueryBuilder};
+use url::Url;
+
+use super::{
+    super::{GitHubError, client::GitHub, upstream::Upstream},
+    github_schema as schema, graphql_url,
+    types::{
+        CheckConclusionState, MergeableState, PullRequestReviewDecision, PullRequestState,
+        StatusState,
+    },
+};
+use crate::fixtures::ReqwestExt;
+
+#[derive(cynic::QueryVariables)]
+pub struct GetAuthoredPullRequestsVariables<'a> {
+    pub query: &'a str,
+}
+
+#[derive(cynic::QueryFragment)]
+#[cynic(graphql_type = "Query", variables = "GetAuthoredPullRequestsVariables")]
+pub struct GetAuthoredPullRequests {
+    #[arguments(first: 100, type: ISSUE, query: $query)]
+    pub search: SearchResultItemConnection,
+}
+
+#[derive(cynic::QueryFragment)]
+pub struct SearchResultItemConnection {
+    #[cynic(flatten)]
+    pub nodes: Vec<SearchResultItem>,
+}
+
+/// <https://docs.github.com/graphql/reference/objects#pullrequest>
+#[derive(cynic::QueryFragment)]
+pub struct PullRequest {
+    pub number: i32,
+    pub title: String,
+    pub url: Url,
+    pub state: PullRequestState,
+    pub is_draft: bool,
+    pub mergeable: MergeableState,
+    pub review_decision: Option<PullRequestReviewDecision>,
+    pub total_comments_count: Option<i32>,
+    #[arguments(first: 20)]
+    pub labels: Option<LabelConnection>,
+    pub status_check_rollup: Option<StatusCheckRollup>,
+    pub updated_at: DateTime<Utc>,
+}
+
+#[derive(cynic::QueryFragment)]
+pub struct LabelConnection {
+    #[cynic(flatten)]
+    pub nodes: Vec<Label>,
+}
+
+/// <https://docs.github.com/graphql/reference/objects#label>
+#[derive(cynic::QueryFragment)]
+pub struct Label {
+    pub name: String,
+}
+
+/// <https://docs.github.com/graphql/reference/objects#statuscheckrollup>
+#[derive(cynic::QueryFragment)]
+pub struct StatusCheckRollup {
+    pub state: StatusState,
+    #[arguments(first: 50)]
+    pub contexts: StatusCheckRollupContextConnection,
+}
+
+#[derive(cynic::QueryFragment)]
+pub struct StatusCheckRollupContextConnection {
+    #[cynic(flatten)]
+    pub nodes: Vec<StatusCheckRollupContext>,
+}
+
+/// <https://docs.github.com/graphql/reference/objects#checkrun>
+#[derive(cynic::QueryFragment)]
+pub struct CheckRun {
+    pub name: String,
+    pub conclusion: Option<CheckConclusionState>,
+}
+
+/// <https://docs.github.com/graphql/reference/objects#statuscontext>
+#[derive(cynic::QueryFragment)]
+pub struct StatusContext {
+    pub context: String,
+    pub state: StatusState,
+}
+
+/// <https://docs.github.com/graphql/reference/unions#statuscheckrollupcontext>
+#[derive(cynic::InlineFragments)]
+pub enum StatusCheckRollupContext {
+    CheckRun(CheckRun),
+    StatusContext(StatusContext),
+    #[cynic(fallback)]
+    Unknown,
+}
+
+impl StatusCheckRollupContext {
+    /// Returns the name of the check if it has failed.
+    pub fn failed_name(&self) -> Option<&str> {
+        match self {
+            Self::CheckRun(check_run) => check_run
+                .conclusion
+                .is_some_and(CheckConclusionState::is_failure)
+                .then_some(check_run.name.as_str()),
+            Self::StatusContext(status_context) => status_context
+                .state
+                .is_failure()
+                .then_some(status_context.context.as_str()),
+            Self::Unknown => None,
+        }
+    }
+}
+
+#[derive(cynic::InlineFragments)]
+pub enum SearchResultItem {
+    PullRequest(PullRequest),
+    #[cynic(fallback)]
+    Unknown,
+}
+
+impl SearchResultItem {
+    pub fn into_pull_request(self) -> Option<PullRequest> {
+        match self {
+            Self::PullRequest(pull_request) => Some(pull_request),
+            Self::Unknown => None,
+        }
+    }
+}
+
+impl GitHub {
+    /// Returns the open pull requests an author has made to the upstream repository, followed by
+    /// those that have been closed or merged since the given date, most recently updated first.
+    pub async fn get_authored_pull_requests(
+        &self,
+        author: &str,
+        closed_since: NaiveDate,
+    ) -> Result<Vec<PullRequest>, GitHubError> {
+        let query = format!(
+            "repo:{} is:pr author:{author} sort:updated-desc",
+            Upstream::get()
+        );
+        let open_query = format!("{query} is:open");
+        let closed_query = format!("{query} is:closed updated:>={closed_since}");
+        let (open, closed) = tokio::try_join!(
+            self.search_pull_requests(&open_query),
+            self.search_pull_requests(&closed_query),
+        )?;
+
+        Ok(open.into_iter().chain(closed).collect())
+    }
+
+    async fn search_pull_requests(&self, query: &str) -> Result<Vec<PullRequest>, GitHubError> {
+        let GraphQlResponse { data, errors } = self
+            .0
+            .post(graphql_url())
+            .run_graphql(GetAuthoredPullRequests::build(
+                GetAuthoredPullRequestsVariables { query },
+            ))
+            .await?;
+
+        data.map(|data| {
+            data.search
+                .nodes
+                .into_iter()
+                .filter_map(SearchResultItem::into_pull_request)
+                .collect()
+        })
+        .ok_or_else(|| {
+            GitHubError::graphql_errors(eyre!("failed to search for pull requests"), errors)
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use cynic::QueryBuilder;
+    use indoc::indoc;
+
+    use super::{GetAuthoredPullRequests, GetAuthoredPullRequestsVariables};
+
+    #[test]
+    fn get_authored_pull_requests_output() {
+        const GET_AUTHORED_PULL_REQUESTS_QUERY: &str = indoc! {r#"
+            query GetAuthoredPullRequests($query: String!) {
+              search(first: 100, type: ISSUE, query: $query) {
+                nodes {
+                  __typename
+                  ... on PullRequest {
+                    number
+                    title
+                    url
+                    state
+                    isDraft
+                    mergeable
+                    reviewDecision
+                    totalCommentsCount
+                    labels(first: 20) {
+                      nodes {
+                        name
+                      }
+                    }
+                    statusCheckRollup {
+                      state
+                      contexts(first: 50) {
+                        nodes {
+                          __typename
+                          ... on CheckRun {
+                            name
+                            conclusion
+                          }
+                          ... on StatusContext {
+                            context
+                            state
+                          }
+                        }
+                      }
+                    }
+                    updatedAt
+                  }
+                }
+              }
+            }
+        "#};
+
+        let operation =
+            GetAuthoredPullRequests::build(GetAuthoredPullRequestsVariables { query: "" });
+
+        assert_eq!(operation.query, GET_AUTHORED_PULL_REQUESTS_QUERY);
+    }
+}
@@ -0,0 +1,102 @@
+use color_eyre::eyre::eyre;
+use cynic::{GraphQlResponse, QueryBuilder};
+
+use super::{
+    super::{GitHubError, client::GitHub},
+    get_repository_info::{Ref, TargetGitObject},
+    github_schema as schema, graphql_url,
+    types::GitObjectId,
+};
+use crate::fixtures::ReqwestExt;
+
+#[derive(cynic::QueryVariables)]
+pub struct GetBranchVariables<'a> {
+    pub owner: &'a str,
+    pub name: &'a str,
+    pub qualified_name: &'a str,
+}
+
+/// <https://docs.github.com/graphql/reference/objects#repository>
+#[derive(cynic::QueryFragment)]
+#[cynic(graphql_type = "Query", variables = "GetBranchVariables")]
+pub struct GetBranch {
+    #[arguments(owner: $owner, name: $name)]
+    pub repository: Option<Repository>,
+}
+
+#[derive(cynic::QueryFragment)]
+#[cynic(variables = "GetBranchVariables")]
+pub struct Repository {
+    /// Fetch a given ref from the repository.
+    #[arguments(qualifiedName: $qualified_name)]
+    #[cynic(rename = "ref")]
+    pub ref_: Option<Ref>,
+}
+
+impl GitHub {
+    /// Gets the name and head commit of a branch.
+    pub async fn get_branch(
+        &self,
+        owner: &str,
+        repo: &str,
+        branch: &str,
+    ) -> Result<(String, GitObjectId), GitHubError> {
+        let GraphQlResponse { data, errors } = self
+            .0
+            .post(graphql_url())
+            .run_graphql(GetBranch::build(GetBranchVariables {
+                owner,
+                name: repo,
+                qualified_name: &format!("refs/heads/{branch}"),
+            }))
+            .await?;
+
+        data.and_then(|data| data.repository?.ref_)
+            .and_then(|branch_ref| {
+                let commit = branch_ref.target.and_then(TargetGitObject::into_commit)?;
+                Some((branch_ref.name, commit.oid))
+            })
+            .ok_or_else(|| {
+                GitHubError::graphql_errors(
+                    eyre!("failed to get branch {branch} from {owner}/{repo}"),
+                    errors,
+                )
+            })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use cynic::QueryBuilder;
+    use indoc::indoc;
+
+    use super::{GetBranch, GetBranchVariables};
+    use crate::github::{MICROSOFT, WINGET_PKGS};
+
+    #[test]
+    fn get_branch_output() {
+        const GET_BRANCH_QUERY: &str = indoc! {r#"
+            query GetBranch($owner: String!, $name: String!, $qualifiedName: String!) {
+              repository(owner: $owner, name: $name) {
+                ref(qualifiedName: $qualifiedName) {
+                  name
+                  target {
+                    __typename
+                    ... on Commit {
+                      oid
+                    }
+                  }
+                }
+              }
+            }
+        "#};
+
+        let operation = GetBranch::build(GetBranchVariables {
+            owner: MICROSOFT,
+            name: WINGET_PKGS,
+            qualified_name: "refs/heads/master",
+        });
+
+        assert_eq!(operation.query, GET_BRANCH_QUERY);
+    }
+}
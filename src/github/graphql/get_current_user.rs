@@ -1,12 +1,13 @@
 use std::env;
 
 use color_eyre::eyre::eyre;
-use cynic::{GraphQlResponse, QueryBuilder, http::ReqwestExt};
+use cynic::{GraphQlResponse, QueryBuilder};
 
 use super::{
     super::{GitHubError, client::GitHub},
-    GRAPHQL_URL, github_schema as schema,
+    github_schema as schema, graphql_url,
 };
+use crate::fixtures::ReqwestExt;
 
 /// <https://docs.github.com/graphql/reference/queries#viewer>
 #[derive(cynic::QueryFragment)]
@@ -39,10 +40,13 @@ pub async fn get_username(&self) -> Result<String, GitHubError> {
 
         if let Ok(login) = env::var(KOMAC_FORK_OWNER) {
             Ok(login)
+        } else if let Some(account) = self.0.installation_account() {
+            // Installation access tokens have no user, so the fork belongs to the app's account
+            Ok(account.to_owned())
         } else {
             let GraphQlResponse { data, errors } = self
                 .0
-                .post(GRAPHQL_URL)
+                .post(graphql_url())
                 .run_graphql(GetCurrentUserLogin::build(()))
                 .await?;
 
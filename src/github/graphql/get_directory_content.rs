@@ -2,12 +2,15 @@
 
 use bon::bon;
 use color_eyre::eyre::eyre;
-use cynic::{GraphQlResponse, QueryBuilder, http::ReqwestExt};
+use cynic::{GraphQlResponse, QueryBuilder};
 
 use super::{
-    super::{GitHubError, MICROSOFT, WINGET_PKGS, client::GitHub, utils::PackagePath},
-    GRAPHQL_URL, GetFileContent, github_schema as schema,
+    super::{
+        GitHubError, client::GitHub, host::GitHubHost, upstream::Upstream, utils::PackagePath,
+    },
+    GetFileContent, github_schema as schema, graphql_url,
 };
+use crate::fixtures::ReqwestExt;
 
 #[derive(cynic::QueryVariables)]
 pub struct GetDirectoryContentVariables<'a> {
@@ -85,32 +88,56 @@ pub async fn get_file_content<O, R, P>(
         O: AsRef<str>,
         R: AsRef<str>,
         P: fmt::Display,
+    {
+        self.get_file_content_at(
+            Upstream::get().host(),
+            owner,
+            repo,
+            Upstream::get().expression(&path),
+        )
+        .await
+    }
+
+    /// Gets the text of a file at a Git object expression, such as `v1.2.3:CHANGELOG.md`, in a
+    /// repository on a GitHub instance.
+    pub async fn get_file_content_at<O, R, E>(
+        &self,
+        host: &GitHubHost,
+        owner: O,
+        repo: R,
+        expression: E,
+    ) -> Result<String, GitHubError>
+    where
+        O: AsRef<str>,
+        R: AsRef<str>,
+        E: AsRef<str>,
     {
         let GraphQlResponse { data, errors } = self
-            .0
-            .post(GRAPHQL_URL)
+            .graphql_request(host)?
             .run_graphql(GetFileContent::build(GetDirectoryContentVariables::new(
                 &owner,
                 &repo,
-                &format!("HEAD:{path}"),
+                &expression,
             )))
             .await?;
 
         data.and_then(|data| data.repository?.object?.into_blob_text())
-            .ok_or_else(|| GitHubError::graphql_errors(eyre!("failed to get {path}"), errors))
+            .ok_or_else(|| {
+                GitHubError::graphql_errors(eyre!("failed to get {}", expression.as_ref()), errors)
+            })
     }
 
     #[builder]
     pub async fn get_directory_content(
         &self,
-        #[builder(default = MICROSOFT)] owner: &str,
-        #[builder(default = WINGET_PKGS)] repo: &str,
-        #[builder(default = "HEAD")] branch_name: &str,
+        #[builder(default = Upstream::get().owner())] owner: &str,
+        #[builder(default = Upstream::get().repo())] repo: &str,
+        #[builder(default = Upstream::get().revision())] branch_name: &str,
         path: &PackagePath,
     ) -> Result<impl Iterator<Item = String>, GitHubError> {
         let GraphQlResponse { data, errors } = self
             .0
-            .post(GRAPHQL_URL)
+            .post(graphql_url())
             .run_graphql(GetDirectoryContent::build(
                 GetDirectoryContentVariables::new(&owner, &repo, &format!("{branch_name}:{path}")),
             ))
@@ -1,13 +1,14 @@
 use chrono::{DateTime, Utc};
-use cynic::{QueryBuilder, http::ReqwestExt};
+use cynic::QueryBuilder;
 use url::Url;
 use winget_types::{PackageIdentifier, PackageVersion};
 
 use super::{
-    super::{GitHubError, WINGET_PKGS_FULL_NAME, client::GitHub},
-    GRAPHQL_URL, github_schema as schema,
+    super::{GitHubError, client::GitHub, upstream::Upstream},
+    github_schema as schema, graphql_url,
     types::PullRequestState,
 };
+use crate::fixtures::ReqwestExt;
 
 #[derive(cynic::QueryVariables)]
 pub struct GetExistingPullRequestVariables<'a> {
@@ -38,6 +39,7 @@ pub struct SearchResultItemConnection {
 
 #[derive(cynic::QueryFragment)]
 pub struct PullRequest {
+    pub number: i32,
     pub title: String,
     pub url: Url,
     pub state: PullRequestState,
@@ -88,37 +90,38 @@ pub async fn get_existing_pull_request(
         identifier: &PackageIdentifier,
         version: &PackageVersion,
     ) -> Result<Option<PullRequest>, GitHubError> {
-        self
-            .0
-            .post(GRAPHQL_URL)
-            .run_graphql(GetExistingPullRequest::build(GetExistingPullRequestVariables {
-                query: &format!("repo:{WINGET_PKGS_FULL_NAME} is:pull-request in:title {identifier} {version}"),
-            }))
+        self.0
+            .post(graphql_url())
+            .run_graphql(GetExistingPullRequest::build(
+                GetExistingPullRequestVariables {
+                    query: &format!(
+                        "repo:{} is:pull-request in:title {identifier} {version}",
+                        Upstream::get()
+                    ),
+                },
+            ))
             .await
             .map(|response| {
-                response
-                    .data?
-                    .into_pull_requests()
-                    .find(|pull_request| {
-                        let title = &*pull_request.title;
-                        // Check that the identifier and version are used in their entirety and not
-                        // part of another package identifier or version. For example, ensuring we
-                        // match against "Microsoft.Excel" not "Microsoft.Excel.Beta", or "1.2.3"
-                        // and not "1.2.3-beta" as `in:title` in the query only does a 'contains'
-                        // rather than a word boundary match.
-                        [identifier.as_str(), version.as_str()]
-                            .into_iter()
-                            .all(|needle| {
-                                title.match_indices(needle).any(|(index, matched)| {
-                                    let before = title[..index].chars().next_back();
-                                    let after = title[index + matched.len()..].chars().next();
-                                    // Check whether the characters before and after the identifier
-                                    // are either None (at the boundary of the title) or whitespace
-                                    before.is_none_or(char::is_whitespace)
-                                        && after.is_none_or(char::is_whitespace)
-                                })
+                response.data?.into_pull_requests().find(|pull_request| {
+                    let title = &*pull_request.title;
+                    // Check that the identifier and version are used in their entirety and not
+                    // part of another package identifier or version. For example, ensuring we
+                    // match against "Microsoft.Excel" not "Microsoft.Excel.Beta", or "1.2.3"
+                    // and not "1.2.3-beta" as `in:title` in the query only does a 'contains'
+                    // rather than a word boundary match.
+                    [identifier.as_str(), version.as_str()]
+                        .into_iter()
+                        .all(|needle| {
+                            title.match_indices(needle).any(|(index, matched)| {
+                                let before = title[..index].chars().next_back();
+                                let after = title[index + matched.len()..].chars().next();
+                                // Check whether the characters before and after the identifier
+                                // are either None (at the boundary of the title) or whitespace
+                                before.is_none_or(char::is_whitespace)
+                                    && after.is_none_or(char::is_whitespace)
                             })
-                    })
+                        })
+                })
             })
             .map_err(GitHubError::CynicRequest)
     }
@@ -139,6 +142,7 @@ fn get_existing_pull_request_output() {
                 nodes {
                   __typename
                   ... on PullRequest {
+                    number
                     title
                     url
                     state
@@ -0,0 +1,130 @@
+use color_eyre::eyre::eyre;
+use cynic::{GraphQlResponse, Id, QueryBuilder};
+use url::Url;
+
+use super::{
+    super::{GitHubError, client::GitHub, upstream::Upstream},
+    github_schema as schema, graphql_url,
+    types::{GitObjectId, PullRequestState},
+};
+use crate::fixtures::ReqwestExt;
+
+#[derive(cynic::QueryVariables)]
+pub struct GetPullRequestVariables<'a> {
+    pub owner: &'a str,
+    pub name: &'a str,
+    pub number: i32,
+}
+
+#[derive(cynic::QueryFragment)]
+#[cynic(graphql_type = "Query", variables = "GetPullRequestVariables")]
+pub struct GetPullRequest {
+    #[arguments(owner: $owner, name: $name)]
+    pub repository: Option<Repository>,
+}
+
+#[derive(cynic::QueryFragment)]
+#[cynic(variables = "GetPullRequestVariables")]
+pub struct Repository {
+    #[arguments(number: $number)]
+    pub pull_request: Option<PullRequest>,
+}
+
+/// <https://docs.github.com/graphql/reference/objects#pullrequest>
+#[derive(cynic::QueryFragment)]
+pub struct PullRequest {
+    pub id: Id,
+    pub number: i32,
+    pub title: String,
+    pub url: Url,
+    pub state: PullRequestState,
+    pub head_ref: Option<Ref>,
+    pub head_ref_oid: GitObjectId,
+    pub head_repository_owner: Option<RepositoryOwner>,
+}
+
+impl PullRequest {
+    /// Returns `true` if the pull request is still open.
+    #[inline]
+    pub const fn is_open(&self) -> bool {
+        self.state.is_open()
+    }
+}
+
+/// <https://docs.github.com/graphql/reference/objects#ref>
+#[derive(cynic::QueryFragment)]
+pub struct Ref {
+    pub id: Id,
+    pub name: String,
+}
+
+/// <https://docs.github.com/graphql/reference/interfaces#repositoryowner>
+#[derive(cynic::QueryFragment)]
+pub struct RepositoryOwner {
+    pub login: String,
+}
+
+impl GitHub {
+    /// Returns a pull request in the upstream repository by its number.
+    pub async fn get_pull_request(&self, number: i32) -> Result<PullRequest, GitHubError> {
+        let upstream = Upstream::get();
+        let GraphQlResponse { data, errors } = self
+            .0
+            .post(graphql_url())
+            .run_graphql(GetPullRequest::build(GetPullRequestVariables {
+                owner: upstream.owner(),
+                name: upstream.repo(),
+                number,
+            }))
+            .await?;
+
+        data.and_then(|data| data.repository?.pull_request)
+            .ok_or_else(|| {
+                GitHubError::graphql_errors(
+                    eyre!("failed to get pull request #{number} in {upstream}"),
+                    errors,
+                )
+            })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use cynic::QueryBuilder;
+    use indoc::indoc;
+
+    use super::{GetPullRequest, GetPullRequestVariables};
+
+    #[test]
+    fn get_pull_request_output() {
+        const GET_PULL_REQUEST_QUERY: &str = indoc! {"
+            query GetPullRequest($owner: String!, $name: String!, $number: Int!) {
+              repository(owner: $owner, name: $name) {
+                pullRequest(number: $number) {
+                  id
+                  number
+                  title
+                  url
+                  state
+                  headRef {
+                    id
+                    name
+                  }
+                  headRefOid
+                  headRepositoryOwner {
+                    login
+                  }
+                }
+              }
+            }
+        "};
+
+        let operation = GetPullRequest::build(GetPullRequestVariables {
+            owner: "",
+            name: "",
+            number: 0,
+        });
+
+        assert_eq!(operation.query, GET_PULL_REQUEST_QUERY);
+    }
+}
@@ -0,0 +1,184 @@
+use color_eyre::eyre::eyre;
+use cynic::{GraphQlResponse, QueryBuilder};
+use url::Url;
+
+use super::{
+    super::{GitHubError, client::GitHub, upstream::Upstream},
+    get_authored_pull_requests::LabelConnection,
+    github_schema as schema, graphql_url,
+};
+use crate::fixtures::ReqwestExt;
+
+#[derive(cynic::QueryVariables)]
+pub struct GetPullRequestFeedbackVariables<'a> {
+    pub owner: &'a str,
+    pub name: &'a str,
+    pub number: i32,
+}
+
+#[derive(cynic::QueryFragment)]
+#[cynic(graphql_type = "Query", variables = "GetPullRequestFeedbackVariables")]
+pub struct GetPullRequestFeedback {
+    #[arguments(owner: $owner, name: $name)]
+    pub repository: Option<Repository>,
+}
+
+#[derive(cynic::QueryFragment)]
+#[cynic(variables = "GetPullRequestFeedbackVariables")]
+pub struct Repository {
+    #[arguments(number: $number)]
+    pub pull_request: Option<PullRequest>,
+}
+
+/// <https://docs.github.com/graphql/reference/objects#pullrequest>
+#[derive(cynic::QueryFragment)]
+pub struct PullRequest {
+    pub title: String,
+    pub url: Url,
+    pub author: Option<Actor>,
+    #[arguments(first: 20)]
+    pub labels: Option<LabelConnection>,
+    #[arguments(last: 50)]
+    pub comments: IssueCommentConnection,
+    #[arguments(first: 100)]
+    pub files: Option<PullRequestChangedFileConnection>,
+}
+
+impl PullRequest {
+    /// Returns the names of the labels on the pull request.
+    pub fn label_names(&self) -> impl Iterator<Item = &str> {
+        self.labels
+            .iter()
+            .flat_map(|labels| &labels.nodes)
+            .map(|label| label.name.as_str())
+    }
+
+    /// Returns the bodies of the comments on the pull request that were not written by its author,
+    /// such as those from the validation pipeline's bot and moderators.
+    pub fn feedback(&self) -> impl Iterator<Item = &str> {
+        self.comments
+            .nodes
+            .iter()
+            .filter(|comment| {
+                comment.author.as_ref().map(|author| &author.login)
+                    != self.author.as_ref().map(|author| &author.login)
+            })
+            .map(|comment| comment.body.as_str())
+    }
+
+    /// Returns the paths of the files changed by the pull request.
+    pub fn file_paths(&self) -> impl Iterator<Item = &str> {
+        self.files
+            .iter()
+            .flat_map(|files| &files.nodes)
+            .map(|file| file.path.as_str())
+    }
+}
+
+/// <https://docs.github.com/graphql/reference/interfaces#actor>
+#[derive(cynic::QueryFragment)]
+pub struct Actor {
+    pub login: String,
+}
+
+#[derive(cynic::QueryFragment)]
+pub struct IssueCommentConnection {
+    #[cynic(flatten)]
+    pub nodes: Vec<IssueComment>,
+}
+
+/// <https://docs.github.com/graphql/reference/objects#issuecomment>
+#[derive(cynic::QueryFragment)]
+pub struct IssueComment {
+    pub author: Option<Actor>,
+    pub body: String,
+}
+
+#[derive(cynic::QueryFragment)]
+pub struct PullRequestChangedFileConnection {
+    #[cynic(flatten)]
+    pub nodes: Vec<PullRequestChangedFile>,
+}
+
+/// <https://docs.github.com/graphql/reference/objects#pullrequestchangedfile>
+#[derive(cynic::QueryFragment)]
+pub struct PullRequestChangedFile {
+    pub path: String,
+}
+
+impl GitHub {
+    /// Returns the labels, comments and changed files of a pull request in the upstream repository.
+    pub async fn get_pull_request_feedback(&self, number: i32) -> Result<PullRequest, GitHubError> {
+        let upstream = Upstream::get();
+        let GraphQlResponse { data, errors } = self
+            .0
+            .post(graphql_url())
+            .run_graphql(GetPullRequestFeedback::build(
+                GetPullRequestFeedbackVariables {
+                    owner: upstream.owner(),
+                    name: upstream.repo(),
+                    number,
+                },
+            ))
+            .await?;
+
+        data.and_then(|data| data.repository?.pull_request)
+            .ok_or_else(|| {
+                GitHubError::graphql_errors(
+                    eyre!("failed to get pull request #{number} in {upstream}"),
+                    errors,
+                )
+            })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use cynic::QueryBuilder;
+    use indoc::indoc;
+
+    use super::{GetPullRequestFeedback, GetPullRequestFeedbackVariables};
+
+    #[test]
+    fn get_pull_request_feedback_output() {
+        const GET_PULL_REQUEST_FEEDBACK_QUERY: &str = indoc! {"
+            query GetPullRequestFeedback($owner: String!, $name: String!, $number: Int!) {
+              repository(owner: $owner, name: $name) {
+                pullRequest(number: $number) {
+                  title
+                  url
+                  author {
+                    login
+                  }
+                  labels(first: 20) {
+                    nodes {
+                      name
+                    }
+                  }
+                  comments(last: 50) {
+                    nodes {
+                      author {
+                        login
+                      }
+                      body
+                    }
+                  }
+                  files(first: 100) {
+                    nodes {
+                      path
+                    }
+                  }
+                }
+              }
+            }
+        "};
+
+        let operation = GetPullRequestFeedback::build(GetPullRequestFeedbackVariables {
+            owner: "",
+            name: "",
+            number: 0,
+        });
+
+        assert_eq!(operation.query, GET_PULL_REQUEST_FEEDBACK_QUERY);
+    }
+}
@@ -1,19 +1,30 @@
+pub mod add_comment;
 pub mod create_commit;
 pub mod create_pull_request;
 pub mod create_ref;
 pub mod get_all_values;
+pub mod get_authored_pull_requests;
+pub mod get_branch;
 pub mod get_branches;
 pub mod get_current_user;
 pub mod get_directory_content;
 pub mod get_directory_content_with_text;
 pub mod get_existing_pull_request;
 mod get_file_content;
+pub mod get_pull_request;
+pub mod get_pull_request_feedback;
 pub mod get_repository_info;
 mod schema;
 pub mod types;
+pub mod update_pull_request;
 pub mod update_refs;
 
 pub use get_file_content::GetFileContent;
 pub use schema::github_schema;
 
-pub const GRAPHQL_URL: &str = "https://api.github.com/graphql";
+use super::upstream::Upstream;
+
+/// Returns the GraphQL endpoint of the GitHub instance that the upstream repository is on.
+pub fn graphql_url() -> &'static str {
+    Upstream::get().host().graphql_url()
+}
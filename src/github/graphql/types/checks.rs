@@ -0,0 +1,70 @@
+use std::fmt::{Display, Formatter};
+
+use super::super::github_schema as schema;
+
+/// The combined state of the statuses and check runs on a commit.
+///
+/// See <https://docs.github.com/graphql/reference/enums#statusstate>.
+#[derive(cynic::Enum, Clone, Copy, Debug, Hash, PartialEq, Eq)]
+pub enum StatusState {
+    Error,
+    Expected,
+    Failure,
+    Pending,
+    Success,
+}
+
+impl StatusState {
+    /// Returns `true` if the status has failed or errored.
+    #[inline]
+    pub const fn is_failure(self) -> bool {
+        matches!(self, Self::Error | Self::Failure)
+    }
+
+    pub const fn as_str(self) -> &'static str {
+        match self {
+            Self::Error => "error",
+            Self::Expected => "expected",
+            Self::Failure => "failure",
+            Self::Pending => "pending",
+            Self::Success => "success",
+        }
+    }
+}
+
+impl Display for StatusState {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        self.as_str().fmt(f)
+    }
+}
+
+/// The result of a completed check run.
+///
+/// See <https://docs.github.com/graphql/reference/enums#checkconclusionstate>.
+#[derive(cynic::Enum, Clone, Copy, Debug, Hash, PartialEq, Eq)]
+pub enum CheckConclusionState {
+    ActionRequired,
+    Cancelled,
+    Failure,
+    Neutral,
+    Skipped,
+    Stale,
+    StartupFailure,
+    Success,
+    TimedOut,
+}
+
+impl CheckConclusionState {
+    /// Returns `true` if the check run did not complete successfully.
+    #[inline]
+    pub const fn is_failure(self) -> bool {
+        matches!(
+            self,
+            Self::ActionRequired
+                | Self::Cancelled
+                | Self::Failure
+                | Self::StartupFailure
+                | Self::TimedOut
+        )
+    }
+}
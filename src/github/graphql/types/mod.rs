@@ -1,10 +1,12 @@
 mod base64_string;
+mod checks;
 mod git_ref_name;
 mod pull_request;
 
 pub use base64_string::Base64String;
+pub use checks::{CheckConclusionState, StatusState};
 pub use git_ref_name::GitRefName;
-pub use pull_request::PullRequestState;
+pub use pull_request::{MergeableState, PullRequestReviewDecision, PullRequestState};
 
 use super::github_schema as schema;
 
@@ -5,7 +5,7 @@
 /// The possible states of a pull request.
 ///
 /// See <https://docs.github.com/graphql/reference/enums#pullrequeststate>.
-#[derive(cynic::Enum, Clone, Copy, Hash, PartialEq, Eq)]
+#[derive(cynic::Enum, Clone, Copy, Debug, Hash, PartialEq, Eq)]
 pub enum PullRequestState {
     /// A pull request that has been closed without being merged.
     Closed,
@@ -50,3 +50,65 @@ fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
         self.as_str().fmt(f)
     }
 }
+
+/// Whether a pull request can be merged.
+///
+/// See <https://docs.github.com/graphql/reference/enums#mergeablestate>.
+#[derive(cynic::Enum, Clone, Copy, Debug, Hash, PartialEq, Eq)]
+pub enum MergeableState {
+    /// The pull request cannot be merged due to merge conflicts.
+    Conflicting,
+
+    /// The pull request can be merged.
+    Mergeable,
+
+    /// The mergeability of the pull request is still being calculated.
+    Unknown,
+}
+
+impl MergeableState {
+    pub const fn as_str(self) -> &'static str {
+        match self {
+            Self::Conflicting => "conflicting",
+            Self::Mergeable => "mergeable",
+            Self::Unknown => "unknown",
+        }
+    }
+}
+
+impl Display for MergeableState {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        self.as_str().fmt(f)
+    }
+}
+
+/// The review status of a pull request.
+///
+/// See <https://docs.github.com/graphql/reference/enums#pullrequestreviewdecision>.
+#[derive(cynic::Enum, Clone, Copy, Debug, Hash, PartialEq, Eq)]
+pub enum PullRequestReviewDecision {
+    /// The pull request has received an approving review.
+    Approved,
+
+    /// Changes have been requested on the pull request.
+    ChangesRequested,
+
+    /// A review is required before the pull request can be merged.
+    ReviewRequired,
+}
+
+impl PullRequestReviewDecision {
+    pub const fn as_str(self) -> &'static str {
+        match self {
+            Self::Approved => "approved",
+            Self::ChangesRequested => "changes requested",
+            Self::ReviewRequired => "review required",
+        }
+    }
+}
+
+impl Display for PullRequestReviewDecision {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        self.as_str().fmt(f)
+    }
+}
@@ -0,0 +1,93 @@
+use bon::Builder;
+use color_eyre::eyre::eyre;
+use cynic::{GraphQlResponse, Id, MutationBuilder};
+
+use super::{
+    super::{GitHubError, client::GitHub},
+    create_pull_request::PullRequest,
+    github_schema as schema, graphql_url,
+};
+use crate::fixtures::ReqwestExt;
+
+#[derive(cynic::QueryVariables)]
+pub struct UpdatePullRequestVariables<'a> {
+    pub input: UpdatePullRequestInput<'a>,
+}
+
+#[derive(cynic::QueryFragment)]
+#[cynic(graphql_type = "Mutation", variables = "UpdatePullRequestVariables")]
+pub struct UpdatePullRequest {
+    #[arguments(input: $input)]
+    pub update_pull_request: Option<UpdatePullRequestPayload>,
+}
+
+/// <https://docs.github.com/graphql/reference/mutations#updatepullrequest>
+#[derive(cynic::QueryFragment)]
+pub struct UpdatePullRequestPayload {
+    pub pull_request: Option<PullRequest>,
+}
+
+/// <https://docs.github.com/graphql/reference/input-objects#updatepullrequestinput>
+#[derive(Builder, cynic::InputObject)]
+pub struct UpdatePullRequestInput<'a> {
+    #[cynic(skip_serializing_if = "Option::is_none")]
+    pub body: Option<&'a str>,
+    pub pull_request_id: &'a Id,
+}
+
+impl GitHub {
+    pub async fn update_pull_request_body(
+        &self,
+        pull_request_id: &Id,
+        body: &str,
+    ) -> Result<PullRequest, GitHubError> {
+        let operation = UpdatePullRequest::build(UpdatePullRequestVariables {
+            input: UpdatePullRequestInput::builder()
+                .pull_request_id(pull_request_id)
+                .body(body)
+                .build(),
+        });
+
+        let GraphQlResponse { data, errors } =
+            self.0.post(graphql_url()).run_graphql(operation).await?;
+
+        data.and_then(|data| data.update_pull_request?.pull_request)
+            .ok_or_else(|| {
+                GitHubError::graphql_errors(eyre!("failed to update pull request"), errors)
+            })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use cynic::{Id, MutationBuilder};
+    use indoc::indoc;
+
+    use super::{UpdatePullRequest, UpdatePullRequestInput, UpdatePullRequestVariables};
+
+    #[test]
+    fn update_pull_request_output() {
+        const UPDATE_PULL_REQUEST_MUTATION: &str = indoc! {"
+            mutation UpdatePullRequest($input: UpdatePullRequestInput!) {
+              updatePullRequest(input: $input) {
+                pullRequest {
+                  number
+                  repository {
+                    nameWithOwner
+                  }
+                  url
+                }
+              }
+            }
+        "};
+
+        let id = Id::new("");
+        let operation = UpdatePullRequest::build(UpdatePullRequestVariables {
+            input: UpdatePullRequestInput::builder()
+                .pull_request_id(&id)
+                .build(),
+        });
+
+        assert_eq!(operation.query, UPDATE_PULL_REQUEST_MUTATION);
+    }
+}
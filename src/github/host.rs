@@ -0,0 +1,180 @@
+use std::fmt;
+
+use url::Url;
+
+use super::{GITHUB_HOST, upstream::Upstream};
+
+/// A GitHub instance, either github.com or a GitHub Enterprise Server.
+///
+/// github.com serves its APIs from `api.github.com`, whereas GitHub Enterprise Server serves them
+/// from `/api/graphql` and `/api/v3` on the same host.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct GitHubHost {
+    name: String,
+    graphql_url: String,
+    rest_api_url: String,
+}
+
+impl GitHubHost {
+    /// Creates a host from its name, such as `ghe.example.com`.
+    ///
+    /// A URL such as `https://ghe.example.com/` is also accepted, in which case only its host and
+    /// port are used.
+    pub fn new<T: AsRef<str>>(name: T) -> Self {
+        let name = name.as_ref().trim();
+        let name = Url::parse(name)
+            .ok()
+            .filter(|url| url.has_host())
+            .map_or_else(
+                || name.trim_end_matches('/').to_ascii_lowercase(),
+                |url| url[url::Position::BeforeHost..url::Position::AfterPort].to_owned(),
+            );
+
+        let (graphql_url, rest_api_url) = if name == GITHUB_HOST {
+            (
+                String::from("https://api.github.com/graphql"),
+                String::from("https://api.github.com"),
+            )
+        } else {
+            (
+                format!("https://{name}/api/graphql"),
+                format!("https://{name}/api/v3"),
+            )
+        };
+
+        Self {
+            name,
+            graphql_url,
+            rest_api_url,
+        }
+    }
+
+    /// Returns true if this is github.com rather than a GitHub Enterprise Server.
+    #[inline]
+    pub fn is_github_com(&self) -> bool {
+        self.name == GITHUB_HOST
+    }
+
+    #[inline]
+    pub const fn graphql_url(&self) -> &str {
+        self.graphql_url.as_str()
+    }
+
+    #[inline]
+    pub const fn rest_api_url(&self) -> &str {
+        self.rest_api_url.as_str()
+    }
+
+    /// Returns the URL of a page on this host, such as `https://github.com/owner/repo/issues`.
+    pub fn web_url<T: fmt::Display>(&self, path: T) -> String {
+        format!("https://{}/{path}", self.name)
+    }
+
+    /// Returns true if the URL points to this host, such as a release asset on it.
+    pub fn hosts(&self, url: &Url) -> bool {
+        url.host_str()
+            .is_some_and(|host| match (url.port(), self.name.rsplit_once(':')) {
+                (Some(port), Some((name, expected_port))) => {
+                    host.eq_ignore_ascii_case(name) && port.to_string() == expected_port
+                }
+                (None, None) => host.eq_ignore_ascii_case(&self.name),
+                _ => false,
+            })
+    }
+
+    /// Returns the GitHub instance that a URL is on if it is on github.com or the upstream
+    /// repository's host.
+    ///
+    /// Packages in a repository on a GitHub Enterprise Server may still be released on github.com,
+    /// so github.com is always recognised.
+    pub fn from_url(url: &Url) -> Option<Self> {
+        let upstream = Upstream::get().host();
+        if upstream.hosts(url) {
+            Some(upstream.clone())
+        } else {
+            Some(Self::default()).filter(|github| github.hosts(url))
+        }
+    }
+}
+
+impl Default for GitHubHost {
+    fn default() -> Self {
+        Self::new(GITHUB_HOST)
+    }
+}
+
+impl fmt::Display for GitHubHost {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.name)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rstest::rstest;
+    use url::Url;
+
+    use super::GitHubHost;
+
+    #[rstest]
+    #[case(
+        "github.com",
+        "https://api.github.com/graphql",
+        "https://api.github.com"
+    )]
+    #[case(
+        "ghe.example.com",
+        "https://ghe.example.com/api/graphql",
+        "https://ghe.example.com/api/v3"
+    )]
+    #[case(
+        "https://GHE.example.com/",
+        "https://ghe.example.com/api/graphql",
+        "https://ghe.example.com/api/v3"
+    )]
+    #[case(
+        "ghe.example.com:8443",
+        "https://ghe.example.com:8443/api/graphql",
+        "https://ghe.example.com:8443/api/v3"
+    )]
+    fn api_urls(#[case] name: &str, #[case] graphql_url: &str, #[case] rest_api_url: &str) {
+        let host = GitHubHost::new(name);
+
+        assert_eq!(host.graphql_url(), graphql_url);
+        assert_eq!(host.rest_api_url(), rest_api_url);
+    }
+
+    #[rstest]
+    #[case(
+        "github.com",
+        "https://github.com/owner/repo/releases/download/v1/setup.exe",
+        true
+    )]
+    #[case("github.com", "https://ghe.example.com/owner/repo", false)]
+    #[case("ghe.example.com", "https://GHE.example.com/owner/repo", true)]
+    #[case(
+        "ghe.example.com:8443",
+        "https://ghe.example.com:8443/owner/repo",
+        true
+    )]
+    #[case("ghe.example.com:8443", "https://ghe.example.com/owner/repo", false)]
+    fn hosts_url(#[case] name: &str, #[case] url: &str, #[case] expected: bool) {
+        assert_eq!(
+            GitHubHost::new(name).hosts(&Url::parse(url).unwrap()),
+            expected
+        );
+    }
+
+    #[rstest]
+    #[case(
+        "https://github.com/owner/repo/releases/download/v1/setup.exe",
+        Some("github.com")
+    )]
+    #[case("https://example.com/owner/repo/releases/download/v1/setup.exe", None)]
+    fn host_from_url(#[case] url: &str, #[case] expected: Option<&str>) {
+        assert_eq!(
+            GitHubHost::from_url(&Url::parse(url).unwrap()),
+            expected.map(GitHubHost::new)
+        );
+    }
+}
@@ -0,0 +1,493 @@
+//! Keeps track of GitHub's rate limits and waits for them to reset when they are exceeded.
+//!
+//! Every response from GitHub's APIs includes `X-RateLimit-*` headers for the rate limit resource
+//! that the request counted against, such as `core` for the REST API or `graphql` for the points of
+//! the GraphQL API. A request is held back until its resource resets if its budget has been used
+//! up, and retried after waiting if it is rejected by a primary or secondary rate limit.
+//!
+//! A GraphQL query can cost more than one point, so GraphQL queries also select their `rateLimit`,
+//! which says how many points the query cost. The next query is held back until the resource
+//! resets if fewer points than that remain.
+//!
+//! See <https://docs.github.com/rest/using-the-rest-api/rate-limits-for-the-rest-api> and
+//! <https://docs.github.com/graphql/overview/rate-limits-and-query-limits-for-the-graphql-api>.
+
+use std::{
+    collections::BTreeMap,
+    str::FromStr,
+    sync::{Mutex, PoisonError},
+    time::Duration,
+};
+
+use bytes::Bytes;
+use chrono::{DateTime, Utc};
+use indicatif::{HumanDuration, ProgressBar};
+use reqwest::{
+    Request, Response, ResponseBuilderExt, StatusCode,
+    header::{HeaderMap, HeaderName, RETRY_AFTER},
+};
+use serde::{Deserialize, Serialize};
+use url::{Position, Url};
+
+use crate::commands::utils::SPINNER_SLOW_TICK_RATE;
+
+const X_RATELIMIT_LIMIT: HeaderName = HeaderName::from_static("x-ratelimit-limit");
+const X_RATELIMIT_REMAINING: HeaderName = HeaderName::from_static("x-ratelimit-remaining");
+const X_RATELIMIT_USED: HeaderName = HeaderName::from_static("x-ratelimit-used");
+const X_RATELIMIT_RESET: HeaderName = HeaderName::from_static("x-ratelimit-reset");
+const X_RATELIMIT_RESOURCE: HeaderName = HeaderName::from_static("x-ratelimit-resource");
+
+/// The most times a request is retried after being rate limited.
+const MAX_RETRIES: u8 = 5;
+
+/// How long to wait after the first secondary rate limit that doesn't say how long to wait for,
+/// which doubles each time the request is rate limited again.
+///
+/// <https://docs.github.com/rest/using-the-rest-api/best-practices-for-using-the-rest-api#handle-rate-limit-errors-appropriately>
+const SECONDARY_RATE_LIMIT_DELAY: Duration = Duration::from_secs(60);
+
+const GRAPHQL: &str = "graphql";
+
+/// The fields that GraphQL queries select to find out how many points they cost.
+///
+/// <https://docs.github.com/graphql/overview/rate-limits-and-query-limits-for-the-graphql-api#returning-the-rate-limit-status-of-a-query>
+const RATE_LIMIT_SELECTION: &str = "rateLimit { cost limit remaining used resetAt }";
+
+/// The budgets that were last reported for each rate limit resource of each GitHub host.
+static BUDGETS: Mutex<BTreeMap<(String, String), Pace>> = Mutex::new(BTreeMap::new());
+
+/// The budget of a rate limit resource and how much the last request against it cost.
+struct Pace {
+    budget: Budget,
+    /// The requests or points that the next request is expected to cost, which is the cost of the
+    /// last GraphQL query or one request.
+    cost: u64,
+}
+
+/// The `rateLimit` of a GraphQL query.
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct QueryCost {
+    cost: u64,
+    limit: u64,
+    remaining: u64,
+    used: u64,
+    reset_at: DateTime<Utc>,
+}
+
+/// The budget of a rate limit resource.
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+pub struct Budget {
+    /// The maximum number of requests, or points for GraphQL, per hour.
+    pub limit: u64,
+    /// The number of requests or points remaining until the budget resets.
+    pub remaining: u64,
+    /// The number of requests or points used since the budget last reset.
+    pub used: u64,
+    /// When the budget resets.
+    #[serde(with = "chrono::serde::ts_seconds")]
+    pub reset: DateTime<Utc>,
+}
+
+impl Budget {
+    /// Reads a budget and the name of its resource from the `X-RateLimit-*` headers of a response.
+    fn from_headers(headers: &HeaderMap) -> Option<(String, Self)> {
+        fn header<T: FromStr>(headers: &HeaderMap, name: HeaderName) -> Option<T> {
+            headers.get(name)?.to_str().ok()?.parse().ok()
+        }
+
+        Some((
+            header(headers, X_RATELIMIT_RESOURCE)?,
+            Self {
+                limit: header(headers, X_RATELIMIT_LIMIT)?,
+                remaining: header(headers, X_RATELIMIT_REMAINING)?,
+                used: header(headers, X_RATELIMIT_USED)?,
+                reset: DateTime::from_timestamp(header(headers, X_RATELIMIT_RESET)?, 0)?,
+            },
+        ))
+    }
+
+    /// Returns how long until the budget resets if less of it remains than a request costs.
+    fn exhausted_for(&self, now: DateTime<Utc>, cost: u64) -> Option<Duration> {
+        if self.remaining < cost {
+            // Wait a second past the reset as it is rounded down to the second
+            (self.reset - now)
+                .to_std()
+                .ok()
+                .map(|wait| wait + Duration::from_secs(1))
+        } else {
+            None
+        }
+    }
+}
+
+/// Returns the host of a request and the rate limit resource that it counts against.
+///
+/// The GraphQL API is at `/graphql` on github.com and at `/api/graphql` on GitHub Enterprise
+/// Server.
+fn resource(url: &Url) -> (String, &'static str) {
+    let host = url[Position::BeforeHost..Position::AfterPort].to_owned();
+    if url.path().ends_with("/graphql") {
+        (host, GRAPHQL)
+    } else {
+        (host, "core")
+    }
+}
+
+/// Returns the budget of a rate limit resource, and how much the next request against it is
+/// expected to cost.
+fn pace(key: &(String, String)) -> Option<(Budget, u64)> {
+    BUDGETS
+        .lock()
+        .unwrap_or_else(PoisonError::into_inner)
+        .get(key)
+        .map(|pace| (pace.budget.clone(), pace.cost))
+}
+
+/// Returns a GraphQL query that also selects its `rateLimit`, or `None` if the operation is a
+/// mutation, as `rateLimit` is only a field of queries.
+pub fn select_rate_limit(query: &str) -> Option<String> {
+    if !query.trim_start().starts_with("query") {
+        return None;
+    }
+
+    // The first brace opens the selection set of the query, after any variable definitions
+    let (definition, selection) = query.split_once('{')?;
+    Some(format!(
+        "{definition}{{\n  {RATE_LIMIT_SELECTION}{selection}"
+    ))
+}
+
+/// Records the budget and cost that a GraphQL query's `rateLimit` reports, rebuilding the response
+/// from its body.
+///
+/// # Errors
+///
+/// Returns an error if the body of the response couldn't be read.
+pub async fn record_query_cost(response: Response) -> reqwest::Result<Response> {
+    #[derive(Deserialize)]
+    struct Body {
+        data: Option<Data>,
+    }
+
+    #[derive(Deserialize)]
+    struct Data {
+        #[serde(rename = "rateLimit")]
+        rate_limit: Option<QueryCost>,
+    }
+
+    if !response.status().is_success() {
+        return Ok(response);
+    }
+
+    let status = response.status();
+    let url = response.url().clone();
+    let headers = response.headers().clone();
+    let body = response.bytes().await?;
+
+    if let Ok(Body {
+        data: Some(Data {
+            rate_limit: Some(query_cost),
+        }),
+    }) = serde_json::from_slice(&body)
+    {
+        let (host, _) = resource(&url);
+        BUDGETS
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .insert(
+                (host, GRAPHQL.to_owned()),
+                Pace {
+                    budget: Budget {
+                        limit: query_cost.limit,
+                        remaining: query_cost.remaining,
+                        used: query_cost.used,
+                        reset: query_cost.reset_at,
+                    },
+                    cost: query_cost.cost.max(1),
+                },
+            );
+    }
+
+    Ok(rebuild(status, url, &headers, body))
+}
+
+/// Sends a request to GitHub with the given function, waiting for its rate limit to reset first
+/// if it has been used up and retrying the request if it is rate limited.
+pub async fn send<F, Fut>(mut request: Request, execute: F) -> reqwest::Result<Response>
+where
+    F: Fn(Request) -> Fut,
+    Fut: Future<Output = reqwest::Result<Response>>,
+{
+    let (host, resource) = resource(request.url());
+    let key = (host, resource.to_owned());
+    let mut secondary_delay = SECONDARY_RATE_LIMIT_DELAY;
+    let mut retries = 0;
+
+    loop {
+        let exhausted_for =
+            pace(&key).and_then(|(budget, cost)| budget.exhausted_for(Utc::now(), cost));
+        if let Some(wait) = exhausted_for {
+            wait_for(
+                wait,
+                format!(
+                    "GitHub's {resource} rate limit has been used up. Waiting for {} until it resets",
+                    HumanDuration(wait)
+                ),
+            )
+            .await;
+        }
+
+        let retry = (retries < MAX_RETRIES)
+            .then(|| request.try_clone())
+            .flatten();
+
+        let response = execute(request).await?;
+
+        let budget = Budget::from_headers(response.headers());
+        if let Some((resource, budget)) = &budget {
+            // The headers don't say what a GraphQL query cost, so the last known cost is kept
+            BUDGETS
+                .lock()
+                .unwrap_or_else(PoisonError::into_inner)
+                .entry((key.0.clone(), resource.clone()))
+                .and_modify(|pace| pace.budget = budget.clone())
+                .or_insert_with(|| Pace {
+                    budget: budget.clone(),
+                    cost: 1,
+                });
+        }
+
+        let cost = pace(&key).map_or(1, |(_, cost)| cost);
+        let (response, delay) = check(
+            response,
+            budget.map(|(_, budget)| budget),
+            cost,
+            secondary_delay,
+        )
+        .await?;
+
+        let (Some(delay), Some(retry)) = (delay, retry) else {
+            return Ok(response);
+        };
+
+        wait_for(
+            delay,
+            format!(
+                "Rate limited by GitHub. Retrying in {}",
+                HumanDuration(delay)
+            ),
+        )
+        .await;
+
+        request = retry;
+        retries += 1;
+        secondary_delay *= 2;
+    }
+}
+
+/// Checks whether a response was rejected by a rate limit, returning how long to wait before
+/// retrying if it was.
+///
+/// Secondary rate limits that don't say how long to wait for are waited out for the given delay.
+/// The body of the response is only read if the response could have been rate limited, in which
+/// case the response is rebuilt from it.
+async fn check(
+    response: Response,
+    budget: Option<Budget>,
+    cost: u64,
+    secondary_delay: Duration,
+) -> reqwest::Result<(Response, Option<Duration>)> {
+    let status = response.status();
+    let exhausted_for = budget.and_then(|budget| budget.exhausted_for(Utc::now(), cost));
+
+    if !matches!(
+        status,
+        StatusCode::FORBIDDEN | StatusCode::TOO_MANY_REQUESTS
+    ) && exhausted_for.is_none()
+    {
+        return Ok((response, None));
+    }
+
+    let retry_after = response
+        .headers()
+        .get(RETRY_AFTER)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.parse().ok())
+        .map(Duration::from_secs);
+
+    let url = response.url().clone();
+    let headers = response.headers().clone();
+    let body = response.bytes().await?;
+
+    let delay = is_rate_limited(status, &body)
+        .then(|| retry_after.or(exhausted_for).unwrap_or(secondary_delay));
+
+    Ok((rebuild(status, url, &headers, body), delay))
+}
+
+/// Returns `true` if a response says that the request was rate limited.
+///
+/// The REST API responds with `429 Too Many Requests`, or with `403 Forbidden` and a message about
+/// the rate limit. GraphQL requests that use up the budget of points respond with `200 OK` and an
+/// error of type `RATE_LIMITED`.
+fn is_rate_limited(status: StatusCode, body: &[u8]) -> bool {
+    #[derive(Deserialize)]
+    struct Errors {
+        #[serde(default)]
+        errors: Vec<Error>,
+    }
+
+    #[derive(Deserialize)]
+    struct Error {
+        r#type: Option<String>,
+    }
+
+    match status {
+        StatusCode::TOO_MANY_REQUESTS => true,
+        StatusCode::FORBIDDEN => String::from_utf8_lossy(body)
+            .to_ascii_lowercase()
+            .contains("rate limit"),
+        _ => serde_json::from_slice::<Errors>(body).is_ok_and(|body| {
+            body.errors
+                .iter()
+                .any(|error| error.r#type.as_deref() == Some("RATE_LIMITED"))
+        }),
+    }
+}
+
+fn rebuild(status: StatusCode, url: Url, headers: &HeaderMap, body: Bytes) -> Response {
+    let mut builder = http::Response::builder().status(status).url(url);
+    if let Some(response_headers) = builder.headers_mut() {
+        response_headers.extend(headers.clone());
+    }
+
+    Response::from(builder.body(body).unwrap_or_else(|_| unreachable!()))
+}
+
+async fn wait_for(duration: Duration, message: String) {
+    let pb = ProgressBar::new_spinner().with_message(message);
+    pb.enable_steady_tick(SPINNER_SLOW_TICK_RATE);
+    tokio::time::sleep(duration).await;
+    pb.finish_and_clear();
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use chrono::{DateTime, TimeDelta};
+    use reqwest::{
+        StatusCode,
+        header::{HeaderMap, HeaderValue},
+    };
+    use rstest::rstest;
+
+    use super::{
+        Budget, X_RATELIMIT_LIMIT, X_RATELIMIT_REMAINING, X_RATELIMIT_RESET, X_RATELIMIT_RESOURCE,
+        X_RATELIMIT_USED, is_rate_limited, select_rate_limit,
+    };
+
+    #[test]
+    fn budget_from_headers() {
+        let mut headers = HeaderMap::new();
+        headers.insert(X_RATELIMIT_LIMIT, HeaderValue::from(5000));
+        headers.insert(X_RATELIMIT_REMAINING, HeaderValue::from(4987));
+        headers.insert(X_RATELIMIT_USED, HeaderValue::from(13));
+        headers.insert(X_RATELIMIT_RESET, HeaderValue::from(1_700_000_000));
+        headers.insert(X_RATELIMIT_RESOURCE, HeaderValue::from_static("graphql"));
+
+        assert_eq!(
+            Budget::from_headers(&headers),
+            Some((
+                String::from("graphql"),
+                Budget {
+                    limit: 5000,
+                    remaining: 4987,
+                    used: 13,
+                    reset: DateTime::from_timestamp(1_700_000_000, 0).unwrap(),
+                }
+            ))
+        );
+    }
+
+    #[test]
+    fn exhausted_budget() {
+        let reset = DateTime::from_timestamp(1_700_000_000, 0).unwrap();
+        let budget = Budget {
+            limit: 5000,
+            remaining: 0,
+            used: 5000,
+            reset,
+        };
+
+        assert_eq!(
+            budget.exhausted_for(reset - TimeDelta::seconds(30), 1),
+            Some(Duration::from_secs(31))
+        );
+        assert_eq!(budget.exhausted_for(reset + TimeDelta::seconds(1), 1), None);
+
+        let budget = Budget {
+            remaining: 5,
+            ..budget
+        };
+        assert_eq!(
+            budget.exhausted_for(reset - TimeDelta::seconds(30), 1),
+            None
+        );
+        // A GraphQL query that costs more points than remain waits for the budget to reset
+        assert_eq!(
+            budget.exhausted_for(reset - TimeDelta::seconds(30), 6),
+            Some(Duration::from_secs(31))
+        );
+    }
+
+    #[test]
+    fn selects_rate_limit_of_queries() {
+        assert_eq!(
+            select_rate_limit("query GetBranch($owner: String!) {\n  repository(owner: $owner) {\n    id\n  }\n}\n")
+                .as_deref(),
+            Some(
+                "query GetBranch($owner: String!) {\n  rateLimit { cost limit remaining used resetAt }\n  repository(owner: $owner) {\n    id\n  }\n}\n"
+            )
+        );
+        assert_eq!(
+            select_rate_limit(
+                "mutation CreateRef($input: CreateRefInput!) {\n  createRef(input: $input) {\n    clientMutationId\n  }\n}\n"
+            ),
+            None
+        );
+    }
+
+    #[rstest]
+    #[case(
+        StatusCode::OK,
+        r#"{"errors":[{"type":"RATE_LIMITED","message":"API rate limit exceeded"}]}"#,
+        true
+    )]
+    #[case(
+        StatusCode::FORBIDDEN,
+        r#"{"message":"You have exceeded a secondary rate limit. Please wait a few minutes before you try again."}"#,
+        true
+    )]
+    #[case(
+        StatusCode::FORBIDDEN,
+        r#"{"message":"Resource not accessible by integration"}"#,
+        false
+    )]
+    #[case(StatusCode::OK, r#"{"data":{"viewer":{"login":"octocat"}}}"#, false)]
+    #[case(
+        StatusCode::OK,
+        r#"{"data":{"repository":{"object":{"text":"Retry when RATE_LIMITED"}}}}"#,
+        false
+    )]
+    #[case(
+        StatusCode::NOT_FOUND,
+        r#"{"message":"Not Found","documentation_url":"https://docs.github.com/rest/rate-limit"}"#,
+        false
+    )]
+    #[case(StatusCode::TOO_MANY_REQUESTS, "", true)]
+    fn rate_limited(#[case] status: StatusCode, #[case] body: &str, #[case] expected: bool) {
+        assert_eq!(is_rate_limited(status, body.as_bytes()), expected);
+    }
+}
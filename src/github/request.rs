@@ -0,0 +1,88 @@
+//! Requests to GitHub's APIs, which are paced by GitHub's rate limits.
+//!
+//! Requests made with [`GitHubClient`](super::client::GitHubClient) are sent through
+//! [`rate_limit::send`], which holds a request back until its rate limit resets if its budget has
+//! been used up and retries it if it is rejected by a rate limit. GraphQL queries also select their
+//! `rateLimit`, so that the points each query costs are known before the next one is sent.
+
+use cynic::{GraphQlResponse, Operation, http::CynicReqwestError};
+use reqwest::{
+    RequestBuilder, Response,
+    header::{HeaderName, HeaderValue},
+};
+use serde::{Serialize, de::DeserializeOwned};
+use serde_json::Value;
+
+use super::rate_limit;
+use crate::fixtures::{Dispatch, Fixtures, ReqwestExt, graphql_response};
+
+/// A request to one of GitHub's APIs.
+pub struct GitHubRequest(RequestBuilder);
+
+impl GitHubRequest {
+    pub fn header<K, V>(self, key: K, value: V) -> Self
+    where
+        HeaderName: TryFrom<K>,
+        <HeaderName as TryFrom<K>>::Error: Into<http::Error>,
+        HeaderValue: TryFrom<V>,
+        <HeaderValue as TryFrom<V>>::Error: Into<http::Error>,
+    {
+        Self(self.0.header(key, value))
+    }
+
+    pub fn json<T: Serialize + ?Sized>(self, json: &T) -> Self {
+        Self(self.0.json(json))
+    }
+}
+
+impl From<RequestBuilder> for GitHubRequest {
+    fn from(builder: RequestBuilder) -> Self {
+        Self(builder)
+    }
+}
+
+impl Dispatch for GitHubRequest {
+    async fn dispatch(self) -> reqwest::Result<Response> {
+        let (client, request) = self.0.build_split();
+        let request = request?;
+
+        // Recorded responses are replayed without waiting for the rate limits they report
+        if Fixtures::is_replaying() {
+            return RequestBuilder::from_parts(client, request).dispatch().await;
+        }
+
+        rate_limit::send(request, |request| {
+            RequestBuilder::from_parts(client.clone(), request).dispatch()
+        })
+        .await
+    }
+}
+
+impl ReqwestExt for GitHubRequest {
+    fn run_graphql<ResponseData, Vars>(
+        self,
+        operation: impl AsRef<Operation<ResponseData, Vars>>,
+    ) -> impl Future<Output = Result<GraphQlResponse<ResponseData>, CynicReqwestError>> + Send
+    where
+        Vars: Serialize,
+        ResponseData: DeserializeOwned + 'static,
+    {
+        let operation = operation.as_ref();
+        let builder = match serde_json::to_value(operation) {
+            Ok(mut body) => {
+                if let Some(Value::String(query)) = body.get_mut("query")
+                    && let Some(selected) = rate_limit::select_rate_limit(query)
+                {
+                    *query = selected;
+                }
+                self.json(&body)
+            }
+            Err(_) => self.json(operation),
+        };
+
+        async move {
+            let response = builder.dispatch().await?;
+            graphql_response(rate_limit::record_query_cost(response).await?).await
+        }
+    }
+}
@@ -5,9 +5,12 @@
 pub use status::Status;
 
 use super::{
-    GITHUB_JSON_MIME, REST_API_URL, REST_API_VERSION, X_GITHUB_API_VERSION, error::RestError,
+    GITHUB_JSON_MIME, REST_API_VERSION, X_GITHUB_API_VERSION, error::RestError, rest_api_url,
+};
+use crate::{
+    fixtures::Dispatch,
+    github::{GitHubError, client::GitHub, upstream::Upstream},
 };
-use crate::github::{GitHubError, MICROSOFT, WINGET_PKGS, client::GitHub};
 
 #[derive(Copy, Clone, Deserialize)]
 pub struct CompareCommits {
@@ -26,8 +29,13 @@ pub const fn is_identical(&self) -> bool {
 
 impl GitHub {
     pub async fn compare_upstream(&self, fork_owner: &str) -> Result<CompareCommits, GitHubError> {
+        let upstream = Upstream::get();
         let endpoint = format!(
-            "{REST_API_URL}/repos/{fork_owner}/{WINGET_PKGS}/compare/HEAD...{MICROSOFT}:HEAD"
+            "{api}/repos/{fork_owner}/{repo}/compare/{revision}...{owner}:{revision}",
+            api = rest_api_url(),
+            repo = upstream.repo(),
+            owner = upstream.owner(),
+            revision = upstream.revision(),
         );
 
         let response = self
@@ -35,7 +43,7 @@ pub async fn compare_upstream(&self, fork_owner: &str) -> Result<CompareCommits,
             .get(endpoint)
             .header(ACCEPT, GITHUB_JSON_MIME)
             .header(X_GITHUB_API_VERSION, REST_API_VERSION)
-            .send()
+            .dispatch()
             .await?;
 
         if response.status().is_success() {
@@ -9,10 +9,12 @@
 use serde::{Deserialize, Serialize};
 
 use super::{
-    super::{GitHubError, WINGET_PKGS, client::GitHub},
-    GITHUB_JSON_MIME, REST_API_URL, REST_API_VERSION, X_GITHUB_API_VERSION,
+    super::{GitHubError, client::GitHub, upstream::Upstream},
+    GITHUB_JSON_MIME, REST_API_VERSION, X_GITHUB_API_VERSION,
     error::RestError,
+    rest_api_url,
 };
+use crate::fixtures::Dispatch;
 
 #[derive(Serialize)]
 struct Body<'branch> {
@@ -39,7 +41,11 @@ impl GitHub {
     ///
     /// See <https://docs.github.com/rest/branches/branches#sync-a-fork-branch-with-the-upstream-repository>.
     pub async fn sync_fork(&self, fork_owner: &str, branch: &str) -> Result<Response, GitHubError> {
-        let endpoint = format!("{REST_API_URL}/repos/{fork_owner}/{WINGET_PKGS}/merge-upstream");
+        let endpoint = format!(
+            "{api}/repos/{fork_owner}/{repo}/merge-upstream",
+            api = rest_api_url(),
+            repo = Upstream::get().repo()
+        );
 
         let response = self
             .0
@@ -47,7 +53,7 @@ pub async fn sync_fork(&self, fork_owner: &str, branch: &str) -> Result<Response
             .header(ACCEPT, GITHUB_JSON_MIME)
             .header(X_GITHUB_API_VERSION, REST_API_VERSION)
             .json(&Body { branch })
-            .send()
+            .dispatch()
             .await?;
 
         if response.status().is_success() {
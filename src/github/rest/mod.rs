@@ -1,14 +1,21 @@
 mod compare_commits;
 mod error;
 mod merge_upstream;
+mod permissions;
+mod rate_limit;
 mod tree;
 
 use reqwest::header::{HeaderName, HeaderValue};
 
+use super::upstream::Upstream;
+
 pub const GITHUB_JSON_MIME: HeaderValue = HeaderValue::from_static("application/vnd.github+json");
 
 pub const X_GITHUB_API_VERSION: HeaderName = HeaderName::from_static("x-github-api-version");
 
-pub const REST_API_URL: &str = "https://api.github.com";
+/// Returns the REST API base URL of the GitHub instance that the upstream repository is on.
+pub fn rest_api_url() -> &'static str {
+    Upstream::get().host().rest_api_url()
+}
 
 pub const REST_API_VERSION: HeaderValue = HeaderValue::from_static("2026-03-10");
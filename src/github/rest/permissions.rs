@@ -0,0 +1,223 @@
+//! Checks what a token is allowed to do before any changes are made.
+//!
+//! Classic personal access tokens and OAuth tokens list their scopes in the `X-OAuth-Scopes`
+//! header. Fine-grained personal access tokens and GitHub App installation tokens don't, so the
+//! `permissions` that GitHub returns with a repository are read instead. Those only say whether the
+//! token can read or push to a repository, not whether it can open pull requests, so for these
+//! tokens only pushing to the fork is checked. Only `GET` requests are made, so checking doesn't
+//! count towards the limits on creating content.
+
+use std::fmt;
+
+use reqwest::{
+    Response, StatusCode,
+    header::{ACCEPT, HeaderName},
+};
+use serde::Deserialize;
+
+use super::{
+    super::{GitHubError, client::GitHub, request::GitHubRequest, upstream::Upstream},
+    GITHUB_JSON_MIME, REST_API_VERSION, X_GITHUB_API_VERSION, rest_api_url,
+};
+use crate::fixtures::Dispatch;
+
+const X_OAUTH_SCOPES: HeaderName = HeaderName::from_static("x-oauth-scopes");
+
+/// A permission needed to open pull requests that a token doesn't have.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum MissingPermission {
+    /// A classic token doesn't have a scope that allows it to push to repositories.
+    Scope { private: bool },
+    /// The upstream repository doesn't exist or the token can't read it.
+    Upstream,
+    /// The user has no fork of the upstream repository to push branches to.
+    Fork { owner: String },
+    /// A fine-grained token can't push to the user's fork.
+    ForkContents { owner: String },
+}
+
+impl fmt::Display for MissingPermission {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let upstream = Upstream::get();
+        match self {
+            Self::Scope { private: true } => {
+                write!(
+                    f,
+                    "The token needs the `repo` scope as {upstream} is private"
+                )
+            }
+            Self::Scope { private: false } => {
+                f.write_str("The token needs the `public_repo` or `repo` scope")
+            }
+            Self::Upstream => write!(
+                f,
+                "{upstream} doesn't exist or the token can't read it. Give it access to {upstream}"
+            ),
+            Self::Fork { owner } => write!(f, "{owner} has no fork of {upstream} to push to"),
+            Self::ForkContents { owner } => write!(
+                f,
+                "The token can't push to {owner}/{repo}. Give it read and write access to Contents on it",
+                repo = upstream.repo()
+            ),
+        }
+    }
+}
+
+/// <https://docs.github.com/rest/repos/repos#get-a-repository>
+#[derive(Deserialize)]
+struct Repository {
+    private: bool,
+    /// What the authenticated user or app can do in the repository. This is only present for
+    /// authenticated requests.
+    permissions: Option<RepositoryPermissions>,
+}
+
+#[derive(Deserialize)]
+struct RepositoryPermissions {
+    push: bool,
+}
+
+impl GitHub {
+    /// Returns the permissions that the token is missing to push a branch to the fork of a user and
+    /// open a pull request from it to the upstream repository.
+    ///
+    /// Only the fork and upstream repositories are read, so nothing is created and no write limits
+    /// are used up.
+    pub async fn missing_permissions(
+        &self,
+        owner: &str,
+    ) -> Result<Vec<MissingPermission>, GitHubError> {
+        let upstream = Upstream::get();
+        let fork_endpoint = format!(
+            "{api}/repos/{owner}/{repo}",
+            api = rest_api_url(),
+            repo = upstream.repo()
+        );
+        let upstream_endpoint = format!(
+            "{api}/repos/{owner}/{repo}",
+            api = rest_api_url(),
+            owner = upstream.owner(),
+            repo = upstream.repo()
+        );
+
+        let (fork, upstream_repository) = tokio::try_join!(
+            send(self.0.get(&fork_endpoint)),
+            send(self.0.get(&upstream_endpoint))
+        )?;
+
+        let mut missing = Vec::new();
+
+        let scopes = fork
+            .headers()
+            .get(X_OAUTH_SCOPES)
+            .map(|scopes| scopes.to_str().unwrap_or_default().to_owned());
+        let fork = repository(fork).await?;
+        let upstream_repository = repository(upstream_repository).await?;
+
+        let Some(upstream_repository) = upstream_repository else {
+            missing.push(MissingPermission::Upstream);
+            return Ok(missing);
+        };
+
+        if fork.is_none() {
+            missing.push(MissingPermission::Fork {
+                owner: owner.to_owned(),
+            });
+        }
+
+        if let Some(scopes) = scopes {
+            let private = upstream_repository.private;
+            if !has_push_scope(&scopes, private) {
+                missing.push(MissingPermission::Scope { private });
+            }
+            return Ok(missing);
+        }
+
+        if fork
+            .and_then(|fork| fork.permissions)
+            .is_some_and(|permissions| !permissions.push)
+        {
+            missing.push(MissingPermission::ForkContents {
+                owner: owner.to_owned(),
+            });
+        }
+
+        Ok(missing)
+    }
+}
+
+/// Reads a repository from its response, returning `None` if it doesn't exist or the token can't
+/// see it.
+async fn repository(response: Response) -> Result<Option<Repository>, GitHubError> {
+    if response.status() == StatusCode::NOT_FOUND {
+        return Ok(None);
+    }
+
+    Ok(Some(
+        response.error_for_status()?.json::<Repository>().await?,
+    ))
+}
+
+async fn send(request: GitHubRequest) -> Result<Response, GitHubError> {
+    request
+        .header(ACCEPT, GITHUB_JSON_MIME)
+        .header(X_GITHUB_API_VERSION, REST_API_VERSION)
+        .dispatch()
+        .await
+        .map_err(GitHubError::from)
+}
+
+/// Returns `true` if the scopes of a classic token allow it to push to the fork and open pull
+/// requests. Public repositories only need `public_repo`, which `repo` includes.
+fn has_push_scope(scopes: &str, private: bool) -> bool {
+    scopes
+        .split(',')
+        .map(str::trim)
+        .any(|scope| scope == "repo" || (!private && scope == "public_repo"))
+}
+
+#[cfg(test)]
+mod tests {
+    use reqwest::{Response, StatusCode};
+    use rstest::rstest;
+
+    use super::{has_push_scope, repository};
+
+    fn response(status: StatusCode, body: &'static str) -> Response {
+        Response::from(http::Response::builder().status(status).body(body).unwrap())
+    }
+
+    #[rstest]
+    #[case("public_repo, read:org", false, true)]
+    #[case("public_repo, read:org", true, false)]
+    #[case("repo, workflow", true, true)]
+    #[case("read:user", false, false)]
+    #[case("", false, false)]
+    fn push_scope(#[case] scopes: &str, #[case] private: bool, #[case] expected: bool) {
+        assert_eq!(has_push_scope(scopes, private), expected);
+    }
+
+    #[tokio::test]
+    async fn missing_repository_is_none() {
+        let response = response(StatusCode::NOT_FOUND, r#"{"message":"Not Found"}"#);
+
+        assert!(repository(response).await.unwrap().is_none());
+    }
+
+    #[tokio::test]
+    async fn repository_permissions() {
+        let response = response(
+            StatusCode::OK,
+            r#"{"private":false,"permissions":{"admin":false,"maintain":false,"push":true,"triage":false,"pull":true}}"#,
+        );
+
+        let repository = repository(response).await.unwrap().unwrap();
+
+        assert!(!repository.private);
+        assert!(
+            repository
+                .permissions
+                .is_some_and(|permissions| permissions.push)
+        );
+    }
+}
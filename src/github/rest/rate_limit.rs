@@ -0,0 +1,79 @@
+//! The rate limit status of the authenticated user.
+//!
+//! See <https://docs.github.com/rest/rate-limit/rate-limit#get-rate-limit-status-for-the-authenticated-user>.
+
+use std::collections::BTreeMap;
+
+use reqwest::header::ACCEPT;
+use serde::Deserialize;
+
+use super::{
+    super::{GitHubError, client::GitHub, rate_limit::Budget},
+    GITHUB_JSON_MIME, REST_API_VERSION, X_GITHUB_API_VERSION,
+    error::RestError,
+    rest_api_url,
+};
+use crate::fixtures::Dispatch;
+
+#[derive(Deserialize)]
+struct Response {
+    /// The budget of each rate limit resource, such as `core` and `graphql`.
+    resources: BTreeMap<String, Budget>,
+}
+
+impl GitHub {
+    /// Returns the budget of each rate limit resource. Getting these doesn't count against any of
+    /// them.
+    pub async fn get_rate_limits(&self) -> Result<BTreeMap<String, Budget>, GitHubError> {
+        let response = self
+            .0
+            .get(format!("{}/rate_limit", rest_api_url()))
+            .header(ACCEPT, GITHUB_JSON_MIME)
+            .header(X_GITHUB_API_VERSION, REST_API_VERSION)
+            .dispatch()
+            .await?;
+
+        if response.status().is_success() {
+            Ok(response.json::<Response>().await?.resources)
+        } else {
+            Err(response
+                .json::<RestError>()
+                .await
+                .map_err(GitHubError::from)?
+                .into())
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::DateTime;
+    use indoc::indoc;
+
+    use super::{super::super::rate_limit::Budget, Response};
+
+    #[test]
+    fn deserialize_rate_limits() {
+        let response = serde_json::from_str::<Response>(indoc! {r#"
+            {
+              "resources": {
+                "core": { "limit": 5000, "used": 1, "remaining": 4999, "reset": 1691591363 },
+                "graphql": { "limit": 5000, "used": 7, "remaining": 4993, "reset": 1691593228 }
+              },
+              "rate": { "limit": 5000, "used": 1, "remaining": 4999, "reset": 1372700873 }
+            }
+        "#})
+        .unwrap();
+
+        assert_eq!(
+            response.resources.get("graphql"),
+            Some(&Budget {
+                limit: 5000,
+                remaining: 4993,
+                used: 7,
+                reset: DateTime::from_timestamp(1_691_593_228, 0).unwrap(),
+            })
+        );
+        assert_eq!(response.resources.len(), 2);
+    }
+}
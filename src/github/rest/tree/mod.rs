@@ -13,9 +13,10 @@
 use winget_types::{PackageIdentifier, PackageVersion};
 
 use super::{
-    super::{GitHubError, MICROSOFT, WINGET_PKGS, client::GitHub, utils::PackagePath},
-    GITHUB_JSON_MIME, REST_API_URL, REST_API_VERSION, X_GITHUB_API_VERSION,
+    super::{GitHubError, client::GitHub, upstream::Upstream, utils::PackagePath},
+    GITHUB_JSON_MIME, REST_API_VERSION, X_GITHUB_API_VERSION, rest_api_url,
 };
+use crate::fixtures::Dispatch;
 
 /// A Git Tree which represents the hierarchy between files in a Git repository.
 #[derive(Serialize, Deserialize)]
@@ -31,9 +32,10 @@ pub async fn get_versions(
         &self,
         package_identifier: &PackageIdentifier,
     ) -> Result<BTreeSet<PackageVersion>, GitHubError> {
+        let upstream = Upstream::get();
         self.get_all_versions(
-            MICROSOFT,
-            WINGET_PKGS,
+            upstream.owner(),
+            upstream.repo(),
             PackagePath::new(package_identifier, None, None),
         )
         .await
@@ -54,7 +56,9 @@ async fn get_all_versions(
         const SEPARATOR: char = '/';
 
         let endpoint = format!(
-            "{REST_API_URL}/repos/{owner}/{repo}/git/trees/HEAD:{path}?recursive={recursive}",
+            "{api}/repos/{owner}/{repo}/git/trees/{expression}?recursive={recursive}",
+            api = rest_api_url(),
+            expression = Upstream::get().expression(&path),
             recursive = true
         );
 
@@ -63,7 +67,7 @@ async fn get_all_versions(
             .get(endpoint)
             .header(ACCEPT, GITHUB_JSON_MIME)
             .header(X_GITHUB_API_VERSION, REST_API_VERSION)
-            .send()
+            .dispatch()
             .await?
             .error_for_status()?;
 
@@ -0,0 +1,112 @@
+use std::{fmt, sync::OnceLock};
+
+use super::{MICROSOFT, WINGET_PKGS, host::GitHubHost};
+
+static UPSTREAM: OnceLock<Upstream> = OnceLock::new();
+
+/// The repository that manifests are read from and pull requests are made to.
+///
+/// This is [microsoft/winget-pkgs](https://github.com/microsoft/winget-pkgs) by default, but can
+/// be any repository with the same layout, such as an internal manifests repository feeding a
+/// private winget REST source, including one on a GitHub Enterprise Server. Forks are expected to
+/// have the same name as the upstream repository.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Upstream {
+    host: GitHubHost,
+    owner: String,
+    repo: String,
+    branch: Option<String>,
+    manifest_root: String,
+}
+
+impl Upstream {
+    /// The directory that manifests are stored in by default.
+    pub const DEFAULT_MANIFEST_ROOT: &'static str = "manifests";
+
+    pub fn new(
+        owner: impl Into<String>,
+        repo: impl Into<String>,
+        branch: Option<String>,
+        manifest_root: impl Into<String>,
+    ) -> Self {
+        Self {
+            host: GitHubHost::default(),
+            owner: owner.into(),
+            repo: repo.into(),
+            branch,
+            manifest_root: manifest_root.into().trim_matches('/').to_owned(),
+        }
+    }
+
+    /// Sets the GitHub instance that the repository is hosted on, which is github.com by default.
+    #[must_use]
+    pub fn with_host(mut self, host: GitHubHost) -> Self {
+        self.host = host;
+        self
+    }
+
+    /// Returns the upstream repository for this invocation.
+    ///
+    /// If [`Upstream::set`] has not been called, microsoft/winget-pkgs is used.
+    pub fn get() -> &'static Self {
+        UPSTREAM.get_or_init(Self::default)
+    }
+
+    /// Sets the upstream repository for this invocation. This has no effect if the upstream
+    /// repository has already been set or used.
+    pub fn set(self) {
+        let _ = UPSTREAM.set(self);
+    }
+
+    #[inline]
+    pub const fn host(&self) -> &GitHubHost {
+        &self.host
+    }
+
+    #[inline]
+    pub const fn owner(&self) -> &str {
+        self.owner.as_str()
+    }
+
+    #[inline]
+    pub const fn repo(&self) -> &str {
+        self.repo.as_str()
+    }
+
+    /// Returns the branch that was explicitly configured, if any. Otherwise, the repository's
+    /// default branch is used.
+    #[inline]
+    pub fn branch(&self) -> Option<&str> {
+        self.branch.as_deref()
+    }
+
+    #[inline]
+    pub const fn manifest_root(&self) -> &str {
+        self.manifest_root.as_str()
+    }
+
+    /// Returns the revision that manifests are read from, which is either the configured branch or
+    /// `HEAD` for the default branch.
+    #[inline]
+    pub fn revision(&self) -> &str {
+        self.branch().unwrap_or("HEAD")
+    }
+
+    /// Returns a Git object expression for a path in the configured branch, such as
+    /// `HEAD:manifests/p/Package/Identifier`.
+    pub fn expression<T: fmt::Display>(&self, path: T) -> String {
+        format!("{}:{path}", self.revision())
+    }
+}
+
+impl Default for Upstream {
+    fn default() -> Self {
+        Self::new(MICROSOFT, WINGET_PKGS, None, Self::DEFAULT_MANIFEST_ROOT)
+    }
+}
+
+impl fmt::Display for Upstream {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}/{}", self.owner, self.repo)
+    }
+}
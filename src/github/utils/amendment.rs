@@ -0,0 +1,180 @@
+use std::fmt::{self, Write};
+
+use clap::{crate_name, crate_version};
+
+use crate::github::client::GitHubFile;
+
+/// How a manifest file in a pull request changes when the pull request is amended.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum FileChange {
+    Added,
+    Modified,
+    Removed,
+}
+
+impl FileChange {
+    pub const fn as_str(self) -> &'static str {
+        match self {
+            Self::Added => "Added",
+            Self::Modified => "Modified",
+            Self::Removed => "Removed",
+        }
+    }
+}
+
+impl fmt::Display for FileChange {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.as_str().fmt(f)
+    }
+}
+
+/// The difference between the manifest files on a pull request's branch and the newly generated
+/// manifests that are committed on top of them.
+#[derive(Debug, Eq, PartialEq)]
+pub struct Amendment<'a> {
+    files: Vec<(&'a str, FileChange)>,
+}
+
+impl<'a> Amendment<'a> {
+    /// Compares the files currently in the package version's directory against the generated
+    /// changes, which are keyed by their full path.
+    pub fn new(existing: &'a [GitHubFile], changes: &'a [(String, String)]) -> Self {
+        let mut files = changes
+            .iter()
+            .filter_map(|(path, content)| {
+                let name = path
+                    .rsplit_once('/')
+                    .map_or(path.as_str(), |(_, name)| name);
+                match existing.iter().find(|file| file.name == name) {
+                    Some(file) if file.text == *content => None,
+                    Some(_) => Some((name, FileChange::Modified)),
+                    None => Some((name, FileChange::Added)),
+                }
+            })
+            .collect::<Vec<_>>();
+
+        files.extend(
+            existing
+                .iter()
+                .filter(|file| {
+                    !changes
+                        .iter()
+                        .any(|(path, _)| path.ends_with(&format!("/{}", file.name)))
+                })
+                .map(|file| (file.name.as_str(), FileChange::Removed)),
+        );
+
+        Self { files }
+    }
+
+    /// Returns `true` if the generated manifests are identical to those already in the pull
+    /// request.
+    #[inline]
+    pub const fn is_empty(&self) -> bool {
+        self.files.is_empty()
+    }
+
+    /// Returns the names of the files that are no longer generated and should be deleted.
+    pub fn removed(&self) -> impl Iterator<Item = &str> {
+        self.files
+            .iter()
+            .filter(|(_, change)| *change == FileChange::Removed)
+            .map(|&(name, _)| name)
+    }
+
+    /// Returns a comment summarizing the amendment to post on the pull request.
+    pub fn comment(&self) -> String {
+        let mut comment = format!(
+            "Updated the manifests with [{}]({}) v{}:\n\n",
+            crate_name!(),
+            env!("CARGO_PKG_REPOSITORY"),
+            crate_version!()
+        );
+
+        for (name, change) in &self.files {
+            let _ = writeln!(comment, "- {change} `{name}`");
+        }
+
+        comment
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use indoc::formatdoc;
+
+    use super::{Amendment, FileChange};
+    use crate::github::client::GitHubFile;
+
+    fn changes() -> Vec<(String, String)> {
+        vec![
+            (
+                String::from("manifests/p/Package/1.0.0/Package.installer.yaml"),
+                String::from("new installer"),
+            ),
+            (
+                String::from("manifests/p/Package/1.0.0/Package.locale.en-US.yaml"),
+                String::from("default locale"),
+            ),
+            (
+                String::from("manifests/p/Package/1.0.0/Package.locale.de-DE.yaml"),
+                String::from("locale"),
+            ),
+            (
+                String::from("manifests/p/Package/1.0.0/Package.yaml"),
+                String::from("version"),
+            ),
+        ]
+    }
+
+    #[test]
+    fn amendment_changes() {
+        let existing = [
+            GitHubFile::new("Package.installer.yaml", "old installer"),
+            GitHubFile::new("Package.locale.en-US.yaml", "default locale"),
+            GitHubFile::new("Package.locale.fr-FR.yaml", "locale"),
+            GitHubFile::new("Package.yaml", "version"),
+        ];
+        let changes = changes();
+
+        let amendment = Amendment::new(&existing, &changes);
+
+        assert_eq!(
+            amendment.files,
+            [
+                ("Package.installer.yaml", FileChange::Modified),
+                ("Package.locale.de-DE.yaml", FileChange::Added),
+                ("Package.locale.fr-FR.yaml", FileChange::Removed),
+            ]
+        );
+        assert_eq!(
+            amendment.removed().collect::<Vec<_>>(),
+            ["Package.locale.fr-FR.yaml"]
+        );
+        assert_eq!(
+            amendment.comment(),
+            formatdoc! {"
+                Updated the manifests with [{name}]({repository}) v{version}:
+
+                - Modified `Package.installer.yaml`
+                - Added `Package.locale.de-DE.yaml`
+                - Removed `Package.locale.fr-FR.yaml`
+                ",
+                name = env!("CARGO_PKG_NAME"),
+                repository = env!("CARGO_PKG_REPOSITORY"),
+                version = env!("CARGO_PKG_VERSION"),
+            }
+        );
+    }
+
+    #[test]
+    fn unchanged_amendment() {
+        let changes = changes();
+        let existing = changes
+            .iter()
+            .map(|(path, content)| GitHubFile::new(path.rsplit_once('/').unwrap().1, content))
+            .collect::<Vec<_>>();
+
+        assert!(Amendment::new(&existing, &changes).is_empty());
+    }
+}
@@ -1,19 +1,17 @@
+mod amendment;
 mod commit_title;
 mod package_path;
 pub mod pull_request;
+mod pull_request_body;
 
-use std::{env, fmt::Write, num::NonZeroU32};
-
-use bon::builder;
-use clap::{crate_name, crate_version};
+pub use amendment::Amendment;
 pub use commit_title::CommitTitle;
-use itertools::Itertools;
 pub use package_path::PackagePath;
-use rand::RngExt;
-use uuid::Uuid;
-use winget_types::{
-    LanguageTag, Manifest, ManifestType, PackageIdentifier, PackageVersion, url::DecodedUrl,
+pub use pull_request_body::{
+    BodySection, ManifestSnapshot, PullRequestSettings, pull_request_body,
 };
+use uuid::Uuid;
+use winget_types::{LanguageTag, Manifest, ManifestType, PackageIdentifier, PackageVersion};
 
 use crate::update_state::UpdateState;
 
@@ -68,71 +66,6 @@ pub fn is_manifest_file<M: Manifest>(
     }
 }
 
-#[builder(finish_fn = build)]
-pub fn pull_request_body(
-    #[builder(default)] issue_resolves: &[NonZeroU32],
-    alternative_text: Option<&str>,
-    created_with: Option<&str>,
-    created_with_url: Option<&DecodedUrl>,
-) -> String {
-    const FRUITS: [&str; 16] = [
-        "apple",
-        "banana",
-        "blueberries",
-        "cherries",
-        "grapes",
-        "green_apple",
-        "kiwi_fruit",
-        "lemon",
-        "mango",
-        "melon",
-        "peach",
-        "pear",
-        "pineapple",
-        "strawberry",
-        "tangerine",
-        "watermelon",
-    ];
-
-    let mut body = String::new();
-    if let Some(alternative_text) = alternative_text {
-        let _ = writeln!(body, "### {alternative_text}");
-    } else {
-        let mut rng = rand::rng();
-
-        let emoji = if rng.random_ratio(1, 50) {
-            FRUITS[rng.random_range(0..FRUITS.len())]
-        } else {
-            "rocket"
-        };
-
-        body.push_str("### Pull request has been created with ");
-
-        if let (Some(tool_name), Some(tool_url)) = (created_with, created_with_url) {
-            let _ = write!(body, "[{tool_name}]({tool_url})");
-        } else {
-            let _ = write!(
-                body,
-                "[{}]({}) v{}",
-                crate_name!(),
-                env!("CARGO_PKG_REPOSITORY"),
-                crate_version!()
-            );
-        }
-
-        let _ = writeln!(body, " :{emoji}:");
-    }
-
-    if !issue_resolves.is_empty() {
-        let _ = writeln!(body);
-        for issue in issue_resolves.iter().sorted_unstable() {
-            let _ = writeln!(body, "- Resolves #{issue}");
-        }
-    }
-
-    body
-}
-
 pub fn branch_name(
     package_identifier: &PackageIdentifier,
     package_version: &PackageVersion,
@@ -159,17 +92,51 @@ pub fn commit_title(
     format!("{update_state}: {package_identifier} version {package_version}")
 }
 
+/// Returns the title of a pull request that edits the manifests of existing versions of a package,
+/// such as `Update versions: Package.Identifier 1.0.0 to 1.2.0`. The versions are in ascending
+/// order.
+pub fn edit_title(
+    package_identifier: &PackageIdentifier,
+    package_versions: &[PackageVersion],
+) -> String {
+    match package_versions {
+        [package_version] => commit_title(
+            package_identifier,
+            package_version,
+            UpdateState::UpdateVersion,
+        ),
+        [first, .., last] => format!("Update versions: {package_identifier} {first} to {last}"),
+        [] => format!("Update versions: {package_identifier}"),
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use winget_types::{
-        LanguageTag, PackageIdentifier,
+        LanguageTag, PackageIdentifier, PackageVersion,
         icu_locale::langid,
         installer::InstallerManifest,
         locale::{DefaultLocaleManifest, LocaleManifest},
         version::VersionManifest,
     };
 
-    use super::is_manifest_file;
+    use super::{edit_title, is_manifest_file};
+
+    #[test]
+    fn edit_titles() {
+        let identifier = "Package.Identifier".parse::<PackageIdentifier>().unwrap();
+        let versions =
+            ["1.0.0", "1.1.0", "1.2.0"].map(|version| version.parse::<PackageVersion>().unwrap());
+
+        assert_eq!(
+            edit_title(&identifier, &versions),
+            "Update versions: Package.Identifier 1.0.0 to 1.2.0"
+        );
+        assert_eq!(
+            edit_title(&identifier, &versions[..1]),
+            "Update version: Package.Identifier version 1.0.0"
+        );
+    }
 
     #[test]
     fn valid_installer_manifest_file() {
@@ -3,6 +3,7 @@
 use winget_types::{ManifestTypeWithLocale, PackageIdentifier, PackageVersion};
 
 use super::{INSTALLER_PART, LOCALE_PART, YAML_EXTENSION};
+use crate::github::upstream::Upstream;
 
 #[derive(Clone, Debug, Eq, PartialEq, Ord, PartialOrd, Hash)]
 #[repr(transparent)]
@@ -23,7 +24,10 @@ pub fn new(
         );
 
         // manifests/p
-        let mut result = format!("manifests/{first_character}");
+        let mut result = match Upstream::get().manifest_root() {
+            "" => first_character.to_string(),
+            root => format!("{root}/{first_character}"),
+        };
 
         // manifests/p/Package/Identifier
         for part in identifier.as_str().split('.') {
@@ -3,28 +3,52 @@
 use winget_types::PackageIdentifier;
 
 use crate::{
-    github::utils::PackagePath,
-    manifests::{Manifests, build_manifest_string},
+    github::{client::GitHubFile, utils::PackagePath},
+    manifests::{Manifests, build_manifest_string, header},
 };
 
+/// Writes the manifests of a package version, returning the path and content of each file.
+///
+/// If the manifests were read from existing files, the comments at the top of each file are kept.
 #[builder(finish_fn = create)]
 pub fn pr_changes(
     package_identifier: &PackageIdentifier,
     manifests: &Manifests,
     package_path: &PackagePath,
     created_with: Option<&str>,
+    original: Option<&[GitHubFile]>,
 ) -> Result<Vec<(String, String)>> {
+    let header = |name: &str| {
+        original
+            .and_then(|files| files.iter().find(|file| file.name == name))
+            .map(|file| header(&file.text))
+            .unwrap_or_default()
+    };
+
     let mut path_content_map = vec![
         (
             format!("{package_path}/{package_identifier}.installer.yaml"),
-            build_manifest_string(&manifests.installer, created_with)?,
+            build_manifest_string(
+                &manifests.installer,
+                manifests.installer.manifest_version,
+                created_with,
+                &header(&format!("{package_identifier}.installer.yaml")),
+            )?,
         ),
         (
             format!(
                 "{}/{}.locale.{}.yaml",
                 package_path, package_identifier, manifests.version.default_locale
             ),
-            build_manifest_string(&manifests.default_locale, created_with)?,
+            build_manifest_string(
+                &manifests.default_locale,
+                manifests.default_locale.manifest_version,
+                created_with,
+                &header(&format!(
+                    "{package_identifier}.locale.{}.yaml",
+                    manifests.version.default_locale
+                )),
+            )?,
         ),
     ];
     for locale_manifest in &manifests.locales {
@@ -33,12 +57,25 @@ pub fn pr_changes(
                 "{package_path}/{package_identifier}.locale.{}.yaml",
                 locale_manifest.package_locale
             ),
-            build_manifest_string(locale_manifest, created_with)?,
+            build_manifest_string(
+                locale_manifest,
+                locale_manifest.manifest_version,
+                created_with,
+                &header(&format!(
+                    "{package_identifier}.locale.{}.yaml",
+                    locale_manifest.package_locale
+                )),
+            )?,
         ));
     }
     path_content_map.push((
         format!("{package_path}/{package_identifier}.yaml"),
-        build_manifest_string(&manifests.version, created_with)?,
+        build_manifest_string(
+            &manifests.version,
+            manifests.version.manifest_version,
+            created_with,
+            &header(&format!("{package_identifier}.yaml")),
+        )?,
     ));
     Ok(path_content_map)
 }
@@ -0,0 +1,685 @@
+use std::{
+    collections::BTreeSet,
+    fmt::Write,
+    num::NonZeroU32,
+    sync::{LazyLock, OnceLock},
+};
+
+use bon::builder;
+use clap::{ValueEnum, crate_name, crate_version};
+use itertools::{EitherOrBoth, Itertools};
+use rand::RngExt;
+use regex::{Captures, Regex};
+use serde::Deserialize;
+use serde_yaml::{Mapping, Value};
+use winget_types::{installer::InstallerManifest, url::DecodedUrl};
+
+use crate::manifests::{
+    Manifests,
+    diff::{Change, ManifestDiff, ManifestValues, summary},
+};
+
+static SETTINGS: OnceLock<PullRequestSettings> = OnceLock::new();
+
+/// How pull requests are opened and what their bodies contain.
+#[derive(Debug, Default)]
+pub struct PullRequestSettings {
+    /// Whether pull requests are opened as drafts.
+    pub draft: bool,
+
+    /// A template that replaces the default body of pull requests for new or updated versions.
+    ///
+    /// See [`Placeholder`] for the values that can be inserted into it.
+    pub template: Option<String>,
+
+    /// Extra sections added to the default body.
+    pub sections: BTreeSet<BodySection>,
+
+    /// GitHub users that are mentioned in the body.
+    pub reviewers: Vec<String>,
+}
+
+impl PullRequestSettings {
+    /// Returns the pull request settings for this invocation.
+    pub fn get() -> &'static Self {
+        SETTINGS.get_or_init(Self::default)
+    }
+
+    /// Sets the pull request settings for this invocation. This has no effect if the settings have
+    /// already been set or used.
+    pub fn set(self) {
+        let _ = SETTINGS.set(self);
+    }
+}
+
+/// An optional section of the default pull request body.
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Ord, PartialOrd, Hash, Deserialize, ValueEnum)]
+#[serde(rename_all = "kebab-case")]
+pub enum BodySection {
+    /// An excerpt of the default locale's release notes.
+    ReleaseNotes,
+    /// A table of the installers that were added and removed compared to the previous version.
+    Installers,
+    /// A table of every field that was added, removed or changed compared to the previous
+    /// version.
+    Diff,
+}
+
+/// A value that can be inserted into a pull request body template by writing its name in braces,
+/// such as `{identifier}`. Unknown names are left as they are.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Placeholder {
+    Identifier,
+    Version,
+    Urls,
+    Hashes,
+    ChangedFields,
+    Resolves,
+    CreatedWith,
+    ReleaseNotes,
+    Installers,
+    Diff,
+    Reviewers,
+}
+
+impl Placeholder {
+    fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "identifier" => Some(Self::Identifier),
+            "version" => Some(Self::Version),
+            "urls" => Some(Self::Urls),
+            "hashes" => Some(Self::Hashes),
+            "changed_fields" => Some(Self::ChangedFields),
+            "resolves" => Some(Self::Resolves),
+            "created_with" => Some(Self::CreatedWith),
+            "release_notes" => Some(Self::ReleaseNotes),
+            "installers" => Some(Self::Installers),
+            "diff" => Some(Self::Diff),
+            "reviewers" => Some(Self::Reviewers),
+            _ => None,
+        }
+    }
+}
+
+/// The parts of a package version's manifests that are described in a pull request body.
+pub struct ManifestSnapshot {
+    installer: InstallerManifest,
+    values: ManifestValues,
+}
+
+impl ManifestSnapshot {
+    pub fn new(manifests: &Manifests) -> Self {
+        Self {
+            installer: manifests.installer.clone(),
+            values: ManifestValues::new(manifests),
+        }
+    }
+
+    /// Reads the manifests from the paths and contents of the files in a pull request, returning
+    /// `None` if there is no installer manifest.
+    pub fn from_changes(changes: &[(String, String)]) -> Option<Self> {
+        let installer = changes
+            .iter()
+            .find(|(path, _)| path.ends_with(".installer.yaml"))
+            .and_then(|(_, content)| serde_yaml::from_str(content).ok())?;
+
+        Some(Self {
+            installer,
+            values: ManifestValues::from_changes(changes),
+        })
+    }
+
+    /// Compares the manifests of a previous version with these.
+    pub fn diff(&self, previous: &Self) -> ManifestDiff {
+        ManifestDiff::between(&previous.values, &self.values)
+    }
+
+    fn urls(&self) -> impl Iterator<Item = &DecodedUrl> {
+        self.installer
+            .installers
+            .iter()
+            .map(|installer| &installer.url)
+            .unique()
+    }
+
+    /// Returns the first lines of the release notes as a Markdown quote.
+    fn release_notes_excerpt(&self) -> Option<String> {
+        const MAX_LINES: usize = 10;
+
+        let release_notes = self.values.default_locale.get("ReleaseNotes")?.as_str()?;
+        let mut lines = release_notes.lines();
+        let mut excerpt = lines
+            .by_ref()
+            .take(MAX_LINES)
+            .map(|line| format!("> {line}").trim_end().to_owned())
+            .join("\n");
+        if lines.next().is_some() {
+            excerpt.push_str("\n> …");
+        }
+        Some(excerpt)
+    }
+
+    /// Returns the names of the fields that differ from a previous version, other than the
+    /// package version.
+    fn changed_fields(&self, previous: &Self) -> BTreeSet<String> {
+        let current_installer = serde_yaml::to_value(&self.installer).unwrap_or_default();
+        let previous_installer = serde_yaml::to_value(&previous.installer).unwrap_or_default();
+
+        let mut fields = BTreeSet::new();
+        changed_keys(&current_installer, &previous_installer, &mut fields);
+        changed_keys(
+            &self.values.default_locale,
+            &previous.values.default_locale,
+            &mut fields,
+        );
+
+        let installers = |manifest: &Value| {
+            manifest
+                .get("Installers")
+                .and_then(Value::as_sequence)
+                .cloned()
+                .unwrap_or_default()
+        };
+        for installers in installers(&current_installer)
+            .into_iter()
+            .zip_longest(installers(&previous_installer))
+        {
+            match installers {
+                EitherOrBoth::Both(current, previous) => {
+                    changed_keys(&current, &previous, &mut fields);
+                }
+                EitherOrBoth::Left(_) | EitherOrBoth::Right(_) => {
+                    fields.insert(String::from("Installers"));
+                }
+            }
+        }
+
+        fields
+    }
+
+    /// Returns a Markdown table of the installers, marking those that were added or removed
+    /// compared to a previous version.
+    fn installer_table(&self, previous: Option<&Self>) -> String {
+        let current = InstallerRow::all(&self.installer);
+        let previous =
+            previous.map_or_else(Vec::new, |previous| InstallerRow::all(&previous.installer));
+
+        let mut table = String::from(
+            "| | Architecture | Type | Scope | URL |\n|-|--------------|------|-------|-----|\n",
+        );
+        let added = current
+            .iter()
+            .map(|row| (if previous.contains(row) { " " } else { "+" }, row));
+        let removed = previous
+            .iter()
+            .filter(|row| !current.contains(row))
+            .map(|row| ("-", row));
+        for (marker, row) in added.chain(removed) {
+            let _ = writeln!(
+                table,
+                "| {marker} | {} | {} | {} | {} |",
+                row.architecture, row.r#type, row.scope, row.url
+            );
+        }
+        table
+    }
+
+    /// Returns a Markdown table of the fields that differ from a previous version, or `None` if
+    /// no fields changed.
+    fn diff_table(&self, previous: &Self) -> Option<String> {
+        let diff = self.diff(previous);
+        if diff.is_empty() {
+            return None;
+        }
+
+        let mut table = String::from(
+            "| | Manifest | Field | Previous | New |\n|-|----------|-------|----------|-----|\n",
+        );
+        for field in &diff.fields {
+            let (marker, from, to) = match &field.change {
+                Change::Added { value } => ("+", String::new(), summary(value)),
+                Change::Removed { value } => ("-", summary(value), String::new()),
+                Change::Changed { from, to } => ("~", summary(from), summary(to)),
+            };
+            let _ = writeln!(
+                table,
+                "| {marker} | {} | `{}` | {} | {} |",
+                field.manifest,
+                field.field,
+                from.replace('|', "\\|"),
+                to.replace('|', "\\|")
+            );
+        }
+        Some(table)
+    }
+}
+
+fn changed_keys(current: &Value, previous: &Value, fields: &mut BTreeSet<String>) {
+    const IGNORED: [&str; 2] = ["PackageVersion", "Installers"];
+
+    let empty = Mapping::new();
+    let current = current.as_mapping().unwrap_or(&empty);
+    let previous = previous.as_mapping().unwrap_or(&empty);
+    for key in current.keys().chain(previous.keys()) {
+        if let Some(name) = key.as_str()
+            && !IGNORED.contains(&name)
+            && current.get(key) != previous.get(key)
+        {
+            fields.insert(name.to_owned());
+        }
+    }
+}
+
+#[derive(Eq, PartialEq)]
+struct InstallerRow {
+    architecture: String,
+    r#type: String,
+    scope: String,
+    url: String,
+}
+
+impl InstallerRow {
+    fn all(manifest: &InstallerManifest) -> Vec<Self> {
+        manifest
+            .installers
+            .iter()
+            .map(|installer| Self {
+                architecture: installer.architecture.to_string(),
+                r#type: installer
+                    .r#type
+                    .or(manifest.r#type)
+                    .map(|r#type| r#type.to_string())
+                    .unwrap_or_default(),
+                scope: installer
+                    .scope
+                    .or(manifest.scope)
+                    .map(|scope| scope.to_string())
+                    .unwrap_or_default(),
+                url: installer.url.to_string(),
+            })
+            .collect()
+    }
+}
+
+/// Builds the body of a pull request.
+///
+/// Pull requests for new or updated versions use the configured template if there is one. Otherwise,
+/// the body says what created the pull request, followed by any configured sections, the issues
+/// it resolves and the reviewers it mentions.
+#[builder(finish_fn = build)]
+pub fn pull_request_body(
+    #[builder(default)] issue_resolves: &[NonZeroU32],
+    alternative_text: Option<&str>,
+    created_with: Option<&str>,
+    created_with_url: Option<&DecodedUrl>,
+    #[builder(default)] changes: &[(String, String)],
+    previous: Option<&ManifestSnapshot>,
+) -> String {
+    Body {
+        heading: alternative_text.map_or_else(
+            || created_with_heading(created_with, created_with_url),
+            str::to_owned,
+        ),
+        is_alternative: alternative_text.is_some(),
+        issue_resolves,
+        current: ManifestSnapshot::from_changes(changes),
+        previous,
+    }
+    .render(PullRequestSettings::get())
+}
+
+fn created_with_heading(
+    created_with: Option<&str>,
+    created_with_url: Option<&DecodedUrl>,
+) -> String {
+    const FRUITS: [&str; 16] = [
+        "apple",
+        "banana",
+        "blueberries",
+        "cherries",
+        "grapes",
+        "green_apple",
+        "kiwi_fruit",
+        "lemon",
+        "mango",
+        "melon",
+        "peach",
+        "pear",
+        "pineapple",
+        "strawberry",
+        "tangerine",
+        "watermelon",
+    ];
+
+    let mut rng = rand::rng();
+
+    let emoji = if rng.random_ratio(1, 50) {
+        FRUITS[rng.random_range(0..FRUITS.len())]
+    } else {
+        "rocket"
+    };
+
+    let mut heading = String::from("Pull request has been created with ");
+
+    if let (Some(tool_name), Some(tool_url)) = (created_with, created_with_url) {
+        let _ = write!(heading, "[{tool_name}]({tool_url})");
+    } else {
+        let _ = write!(
+            heading,
+            "[{}]({}) v{}",
+            crate_name!(),
+            env!("CARGO_PKG_REPOSITORY"),
+            crate_version!()
+        );
+    }
+
+    let _ = write!(heading, " :{emoji}:");
+
+    heading
+}
+
+struct Body<'a> {
+    heading: String,
+    is_alternative: bool,
+    issue_resolves: &'a [NonZeroU32],
+    current: Option<ManifestSnapshot>,
+    previous: Option<&'a ManifestSnapshot>,
+}
+
+impl Body<'_> {
+    fn render(&self, settings: &PullRequestSettings) -> String {
+        if let Some(template) = &settings.template
+            && !self.is_alternative
+            && self.current.is_some()
+        {
+            return self.render_template(template, settings);
+        }
+
+        let mut body = String::new();
+        let _ = writeln!(body, "### {}", self.heading);
+
+        if let Some(current) = &self.current {
+            if settings.sections.contains(&BodySection::ReleaseNotes)
+                && let Some(release_notes) = current.release_notes_excerpt()
+            {
+                let _ = write!(body, "\n#### Release notes\n\n{release_notes}\n");
+            }
+
+            if settings.sections.contains(&BodySection::Installers) {
+                let _ = write!(
+                    body,
+                    "\n#### Installers\n\n{}",
+                    current.installer_table(self.previous)
+                );
+            }
+
+            if settings.sections.contains(&BodySection::Diff)
+                && let Some(diff) = self
+                    .previous
+                    .and_then(|previous| current.diff_table(previous))
+            {
+                let _ = write!(body, "\n#### Changes\n\n{diff}");
+            }
+        }
+
+        if !self.issue_resolves.is_empty() {
+            let _ = write!(body, "\n{}", self.resolves());
+        }
+
+        if !settings.reviewers.is_empty() {
+            let _ = writeln!(body, "\ncc {}", mentions(&settings.reviewers));
+        }
+
+        body
+    }
+
+    fn render_template(&self, template: &str, settings: &PullRequestSettings) -> String {
+        static PLACEHOLDER: LazyLock<Regex> =
+            LazyLock::new(|| Regex::new(r"\{([a-z_]+)\}").unwrap_or_else(|_| unreachable!()));
+
+        PLACEHOLDER
+            .replace_all(template, |captures: &Captures| {
+                Placeholder::from_name(&captures[1]).map_or_else(
+                    || captures[0].to_owned(),
+                    |placeholder| self.value(placeholder, settings),
+                )
+            })
+            .into_owned()
+    }
+
+    fn value(&self, placeholder: Placeholder, settings: &PullRequestSettings) -> String {
+        let Some(current) = &self.current else {
+            return String::new();
+        };
+
+        match placeholder {
+            Placeholder::Identifier => current.installer.package_identifier.to_string(),
+            Placeholder::Version => current.installer.package_version.to_string(),
+            Placeholder::Urls => current.urls().map(|url| format!("- {url}")).join("\n"),
+            Placeholder::Hashes => current
+                .installer
+                .installers
+                .iter()
+                .unique_by(|installer| &installer.url)
+                .map(|installer| format!("- `{}` {}", installer.sha_256, installer.url))
+                .join("\n"),
+            Placeholder::ChangedFields => self
+                .previous
+                .map(|previous| {
+                    current
+                        .changed_fields(previous)
+                        .iter()
+                        .map(|field| format!("`{field}`"))
+                        .join(", ")
+                })
+                .unwrap_or_default(),
+            Placeholder::Resolves => self.resolves().trim_end().to_owned(),
+            Placeholder::CreatedWith => self.heading.clone(),
+            Placeholder::ReleaseNotes => current.release_notes_excerpt().unwrap_or_default(),
+            Placeholder::Installers => current.installer_table(self.previous).trim_end().to_owned(),
+            Placeholder::Diff => self
+                .previous
+                .and_then(|previous| current.diff_table(previous))
+                .map(|diff| diff.trim_end().to_owned())
+                .unwrap_or_default(),
+            Placeholder::Reviewers => mentions(&settings.reviewers),
+        }
+    }
+
+    fn resolves(&self) -> String {
+        let mut resolves = String::new();
+        for issue in self.issue_resolves.iter().sorted_unstable() {
+            let _ = writeln!(resolves, "- Resolves #{issue}");
+        }
+        resolves
+    }
+}
+
+fn mentions(reviewers: &[String]) -> String {
+    reviewers
+        .iter()
+        .map(|reviewer| format!("@{}", reviewer.trim_start_matches('@')))
+        .join(" ")
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{collections::BTreeSet, num::NonZeroU32};
+
+    use indoc::indoc;
+
+    use super::{Body, BodySection, ManifestSnapshot, PullRequestSettings};
+
+    fn changes(version: &str, url: &str, release_notes: &str) -> Vec<(String, String)> {
+        vec![
+            (
+                format!(
+                    "manifests/p/Package/Identifier/{version}/Package.Identifier.installer.yaml"
+                ),
+                format!(
+                    indoc! {"
+                        PackageIdentifier: Package.Identifier
+                        PackageVersion: {version}
+                        InstallerType: msi
+                        Installers:
+                        - Architecture: x64
+                          InstallerUrl: {url}
+                          InstallerSha256: {sha_256}
+                        ManifestType: installer
+                        ManifestVersion: 1.10.0
+                    "},
+                    version = version,
+                    url = url,
+                    sha_256 = "A".repeat(64),
+                ),
+            ),
+            (
+                format!(
+                    "manifests/p/Package/Identifier/{version}/Package.Identifier.locale.en-US.yaml"
+                ),
+                format!(
+                    indoc! {"
+                        PackageIdentifier: Package.Identifier
+                        PackageVersion: {version}
+                        PackageLocale: en-US
+                        Publisher: Publisher
+                        PackageName: Package
+                        License: MIT
+                        ShortDescription: A package
+                        ReleaseNotes: {release_notes}
+                        ManifestType: defaultLocale
+                        ManifestVersion: 1.10.0
+                    "},
+                    version = version,
+                    release_notes = release_notes,
+                ),
+            ),
+        ]
+    }
+
+    fn body<'a>(
+        current: &[(String, String)],
+        previous: Option<&'a ManifestSnapshot>,
+        issue_resolves: &'a [NonZeroU32],
+    ) -> Body<'a> {
+        Body {
+            heading: String::from("Pull request has been created with komac"),
+            is_alternative: false,
+            issue_resolves,
+            current: ManifestSnapshot::from_changes(current),
+            previous,
+        }
+    }
+
+    #[test]
+    fn default_body_with_sections() {
+        let previous = ManifestSnapshot::from_changes(&changes(
+            "1.0.0",
+            "https://example.com/1.0.0.msi",
+            "Fixes",
+        ))
+        .unwrap();
+        let current = changes("1.1.0", "https://example.com/1.1.0.msi", "Features");
+        let settings = PullRequestSettings {
+            sections: BTreeSet::from([BodySection::ReleaseNotes, BodySection::Installers]),
+            reviewers: vec![String::from("@octocat")],
+            ..PullRequestSettings::default()
+        };
+
+        assert_eq!(
+            body(&current, Some(&previous), &[NonZeroU32::new(5).unwrap()]).render(&settings),
+            indoc! {"
+                ### Pull request has been created with komac
+
+                #### Release notes
+
+                > Features
+
+                #### Installers
+
+                | | Architecture | Type | Scope | URL |
+                |-|--------------|------|-------|-----|
+                | + | x64 | msi |  | https://example.com/1.1.0.msi |
+                | - | x64 | msi |  | https://example.com/1.0.0.msi |
+
+                - Resolves #5
+
+                cc @octocat
+            "}
+        );
+    }
+
+    #[test]
+    fn default_body_with_diff() {
+        let previous = ManifestSnapshot::from_changes(&changes(
+            "1.0.0",
+            "https://example.com/1.0.0.msi",
+            "Fixes",
+        ))
+        .unwrap();
+        let current = changes("1.1.0", "https://example.com/1.1.0.msi", "Features");
+        let settings = PullRequestSettings {
+            sections: BTreeSet::from([BodySection::Diff]),
+            ..PullRequestSettings::default()
+        };
+
+        assert_eq!(
+            body(&current, Some(&previous), &[]).render(&settings),
+            indoc! {"
+                ### Pull request has been created with komac
+
+                #### Changes
+
+                | | Manifest | Field | Previous | New |
+                |-|----------|-------|----------|-----|
+                | ~ | installer | `Installers[x64, msi].InstallerUrl` | https://example.com/1.0.0.msi | https://example.com/1.1.0.msi |
+                | ~ | defaultLocale | `ReleaseNotes` | Fixes | Features |
+            "}
+        );
+    }
+
+    #[test]
+    fn template_body() {
+        let previous = ManifestSnapshot::from_changes(&changes(
+            "1.0.0",
+            "https://example.com/1.0.0.msi",
+            "Fixes",
+        ))
+        .unwrap();
+        let current = changes("1.1.0", "https://example.com/1.1.0.msi", "Features");
+        let settings = PullRequestSettings {
+            template: Some(String::from(indoc! {"
+                {identifier} {version} {unknown}
+                {urls}
+                {hashes}
+                Changed: {changed_fields}
+                {resolves}
+            "})),
+            ..PullRequestSettings::default()
+        };
+
+        assert_eq!(
+            body(&current, Some(&previous), &[NonZeroU32::new(5).unwrap()]).render(&settings),
+            format!(
+                indoc! {"
+                    Package.Identifier 1.1.0 {{unknown}}
+                    - https://example.com/1.1.0.msi
+                    - `{sha_256}` https://example.com/1.1.0.msi
+                    Changed: `InstallerUrl`, `ReleaseNotes`
+                    - Resolves #5
+                "},
+                sha_256 = "A".repeat(64)
+            )
+        );
+    }
+
+    #[test]
+    fn default_body_without_settings() {
+        let current = changes("1.1.0", "https://example.com/1.1.0.msi", "Features");
+
+        assert_eq!(
+            body(&current, None, &[]).render(&PullRequestSettings::default()),
+            "### Pull request has been created with komac\n"
+        );
+    }
+}
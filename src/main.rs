@@ -1,6 +1,7 @@
 #[cfg(all(feature = "openssl", feature = "rustls"))]
 compile_error!("`openssl` and `rustls` are mutually exclusive. Please enable only one.");
 
+use camino::Utf8PathBuf;
 use clap::{Parser, Subcommand, crate_name};
 use color_eyre::eyre::Result;
 use tracing::{Level, metadata::LevelFilter};
@@ -12,8 +13,15 @@
         analyze::Analyze,
         cleanup::Cleanup,
         complete::Complete,
+        diff::Diff,
+        edit::Edit,
+        fmt::Fmt,
+        lint::Lint,
         list_versions::ListVersions,
+        migrate::Migrate,
         new_version::NewVersion,
+        prs::PullRequests,
+        rate_limit::ShowRateLimit,
         remove_dead_versions::RemoveDeadVersions,
         remove_version::RemoveVersion,
         show_version::ShowVersion,
@@ -21,20 +29,30 @@
         sync_fork::SyncFork,
         token::commands::{TokenArgs, TokenCommands},
         update_version::UpdateVersion,
+        validate::Validate,
     },
+    config::{Config, PullRequestConfig, UpstreamConfig},
+    fixtures::Fixtures,
+    github::{upstream::Upstream, utils::PullRequestSettings},
+    manifests::lint::LintRules,
     token::TokenManager,
 };
 
 mod analysis;
 mod commands;
+mod config;
 mod download;
 mod download_file;
 mod editor;
+mod fixtures;
 mod github;
 mod manifests;
 mod match_installers;
+mod metadata;
 mod prompts;
 mod read;
+mod release_notes;
+mod repository;
 mod terminal;
 mod token;
 mod traits;
@@ -48,12 +66,22 @@ async fn main() -> Result<()> {
 
     setup_logging();
 
-    match Cli::parse().command {
+    Fixtures::init()?;
+
+    let cli = Cli::parse();
+
+    let config = Config::load(cli.config.as_deref())?;
+    Upstream::from(cli.upstream.or(config.upstream)).set();
+    PullRequestSettings::try_from(cli.pull_request.or(config.pull_request))?.set();
+    LintRules::from(config.lint).set();
+
+    match cli.command {
         Commands::New(new_version) => new_version.run().await,
         Commands::Update(update_version) => update_version.run().await,
         Commands::Cleanup(cleanup) => cleanup.run().await,
         Commands::Remove(remove_version) => remove_version.run().await,
         Commands::Token(token_args) => match token_args.command {
+            TokenCommands::Login(login) => login.run().await,
             TokenCommands::Remove(remove_token) => remove_token.run(),
             TokenCommands::Update(update_token) => update_token.run().await,
         },
@@ -61,9 +89,17 @@ async fn main() -> Result<()> {
         Commands::Show(show_version) => show_version.run().await,
         Commands::Sync(sync_fork) => sync_fork.run().await,
         Commands::Complete(complete) => complete.run(),
-        Commands::Analyze(analyse) => analyse.run(),
+        Commands::Analyze(analyse) => analyse.run().await,
         Commands::RemoveDeadVersions(remove_dead_versions) => remove_dead_versions.run().await,
         Commands::Submit(submit) => submit.run().await,
+        Commands::Prs(pull_requests) => pull_requests.run().await,
+        Commands::RateLimit(rate_limit) => rate_limit.run().await,
+        Commands::Validate(validate) => validate.run(),
+        Commands::Lint(lint) => lint.run(),
+        Commands::Migrate(migrate) => migrate.run().await,
+        Commands::Diff(diff) => diff.run().await,
+        Commands::Fmt(fmt) => fmt.run().await,
+        Commands::Edit(edit) => edit.run().await,
     }?;
 
     TokenManager::unset_default_store();
@@ -95,6 +131,17 @@ fn setup_logging() {
 struct Cli {
     #[arg(short = 'v', short_alias = 'V', long, action = clap::builder::ArgAction::Version)]
     version: (),
+
+    /// Path to a configuration file [default: komac/config.yaml in the user's config directory]
+    #[arg(long, global = true, env = "KOMAC_CONFIG", value_hint = clap::ValueHint::FilePath)]
+    config: Option<Utf8PathBuf>,
+
+    #[command(flatten)]
+    upstream: UpstreamConfig,
+
+    #[command(flatten)]
+    pull_request: PullRequestConfig,
+
     #[command(subcommand)]
     command: Commands,
 }
@@ -113,6 +160,14 @@ enum Commands {
     Analyze(Analyze),
     RemoveDeadVersions(RemoveDeadVersions),
     Submit(Submit),
+    Prs(PullRequests),
+    RateLimit(ShowRateLimit),
+    Validate(Validate),
+    Lint(Lint),
+    Migrate(Migrate),
+    Diff(Diff),
+    Fmt(Fmt),
+    Edit(Edit),
 }
 
 #[cfg(test)]
@@ -0,0 +1,302 @@
+use std::{
+    fmt,
+    io::{self, Write},
+};
+
+use camino::{Utf8Path, Utf8PathBuf};
+use owo_colors::OwoColorize;
+use serde::Serialize;
+
+/// How serious a problem found in a manifest is.
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Ord, PartialOrd, Hash, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Severity {
+    Warning,
+    Error,
+}
+
+impl Severity {
+    pub const fn as_str(self) -> &'static str {
+        match self {
+            Self::Warning => "warning",
+            Self::Error => "error",
+        }
+    }
+}
+
+impl fmt::Display for Severity {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Warning => write!(f, "{}", self.as_str().yellow()),
+            Self::Error => write!(f, "{}", self.as_str().red()),
+        }
+    }
+}
+
+/// A problem found in a manifest file, such as a schema violation or a broken semantic rule.
+#[derive(Clone, Debug, Eq, PartialEq, Serialize)]
+pub struct Diagnostic {
+    pub file: Utf8PathBuf,
+    /// The one-based line the problem is on, if it could be located.
+    pub line: Option<usize>,
+    /// The one-based column the problem is at, if it could be located.
+    pub column: Option<usize>,
+    pub severity: Severity,
+    /// A stable identifier for the kind of problem, such as `duplicate-installer`.
+    pub code: &'static str,
+    pub message: String,
+}
+
+impl Diagnostic {
+    pub fn error<P, M>(file: P, code: &'static str, message: M) -> Self
+    where
+        P: Into<Utf8PathBuf>,
+        M: Into<String>,
+    {
+        Self::new(file, Severity::Error, code, message)
+    }
+
+    pub fn warning<P, M>(file: P, code: &'static str, message: M) -> Self
+    where
+        P: Into<Utf8PathBuf>,
+        M: Into<String>,
+    {
+        Self::new(file, Severity::Warning, code, message)
+    }
+
+    pub fn new<P, M>(file: P, severity: Severity, code: &'static str, message: M) -> Self
+    where
+        P: Into<Utf8PathBuf>,
+        M: Into<String>,
+    {
+        Self {
+            file: file.into(),
+            line: None,
+            column: None,
+            severity,
+            code,
+            message: message.into(),
+        }
+    }
+
+    /// Sets the line that the problem is on.
+    #[must_use]
+    pub const fn at_line(mut self, line: Option<usize>) -> Self {
+        self.line = line;
+        self
+    }
+
+    /// Sets the line and column that the problem is at.
+    #[must_use]
+    pub const fn at(mut self, line: usize, column: usize) -> Self {
+        self.line = Some(line);
+        self.column = Some(column);
+        self
+    }
+
+    #[inline]
+    pub const fn is_error(&self) -> bool {
+        matches!(self.severity, Severity::Error)
+    }
+}
+
+impl fmt::Display for Diagnostic {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut location = self.file.to_string();
+        if let Some(line) = self.line {
+            location = format!("{location}:{line}");
+            if let Some(column) = self.column {
+                location = format!("{location}:{column}");
+            }
+        }
+
+        write!(
+            f,
+            "{}: {}[{}]: {}",
+            location.bold(),
+            self.severity,
+            self.code,
+            self.message
+        )
+    }
+}
+
+/// Writes diagnostics followed by a summary of how many errors and warnings there were.
+pub fn print_diagnostics<W: Write>(mut writer: W, diagnostics: &[Diagnostic]) -> io::Result<()> {
+    for diagnostic in diagnostics {
+        writeln!(writer, "{diagnostic}")?;
+    }
+
+    let errors = diagnostics
+        .iter()
+        .filter(|diagnostic| diagnostic.is_error())
+        .count();
+    let warnings = diagnostics.len() - errors;
+
+    writeln!(
+        writer,
+        "{errors} {}, {warnings} {}",
+        if errors == 1 { "error" } else { "errors" },
+        if warnings == 1 { "warning" } else { "warnings" },
+    )
+}
+
+/// Finds the lines of keys in the text of a manifest, so that problems found in a deserialized
+/// manifest can be reported at the line they came from.
+///
+/// Only the top-level keys and the keys of each entry in `Installers` are located, which is where
+/// nearly all problems are.
+pub struct KeyLocator<'text> {
+    lines: Vec<&'text str>,
+    /// The zero-based index of the first line of each installer.
+    installers: Vec<usize>,
+}
+
+impl<'text> KeyLocator<'text> {
+    pub fn new(text: &'text str) -> Self {
+        let lines = text.lines().collect::<Vec<_>>();
+
+        let mut installers = Vec::new();
+        let mut item_indent = None;
+        let mut in_installers = false;
+        for (index, line) in lines.iter().enumerate() {
+            let trimmed = line.trim_start();
+            if trimmed.is_empty() || trimmed.starts_with('#') {
+                continue;
+            }
+
+            let indent = line.len() - trimmed.len();
+            if indent == 0 && !trimmed.starts_with('-') {
+                in_installers = trimmed.starts_with("Installers:");
+                continue;
+            }
+
+            if in_installers
+                && trimmed.starts_with('-')
+                && *item_indent.get_or_insert(indent) == indent
+            {
+                installers.push(index);
+            }
+        }
+
+        Self { lines, installers }
+    }
+
+    /// Returns the one-based line of a top-level key.
+    pub fn key(&self, key: &str) -> Option<usize> {
+        self.lines
+            .iter()
+            .position(|line| is_key(line, key))
+            .map(|index| index + 1)
+    }
+
+    /// Returns the one-based line of a key in the installer at an index, or the first line of the
+    /// installer if it doesn't have the key.
+    pub fn installer_key(&self, index: usize, key: &str) -> Option<usize> {
+        let start = *self.installers.get(index)?;
+        let end = self
+            .installers
+            .get(index + 1)
+            .copied()
+            .unwrap_or(self.lines.len());
+
+        self.lines[start..end]
+            .iter()
+            .position(|line| is_key(line.trim_start().trim_start_matches("- "), key))
+            .map_or(Some(start + 1), |offset| Some(start + offset + 1))
+    }
+
+    /// Returns the one-based line of an item in a top-level block sequence, such as a tag in
+    /// `Tags`.
+    pub fn list_item(&self, key: &str, item: &str) -> Option<usize> {
+        let start = self.key(key)?;
+
+        self.lines[start..]
+            .iter()
+            .take_while(|line| line.is_empty() || line.starts_with([' ', '-', '#']))
+            .position(|line| {
+                line.trim_start()
+                    .strip_prefix('-')
+                    .map(|value| value.trim().trim_matches(['"', '\'']))
+                    == Some(item)
+            })
+            .map(|offset| start + offset + 1)
+    }
+
+    /// Returns the text of a one-based line, without its line ending.
+    pub fn line(&self, line: usize) -> Option<&'text str> {
+        self.lines.get(line.checked_sub(1)?).copied()
+    }
+}
+
+fn is_key(line: &str, key: &str) -> bool {
+    line.strip_prefix(key)
+        .is_some_and(|rest| rest.starts_with(':'))
+}
+
+/// Returns the file name of a path, or the whole path if it has none.
+pub fn file_name(path: &Utf8Path) -> &str {
+    path.file_name().unwrap_or(path.as_str())
+}
+
+#[cfg(test)]
+mod tests {
+    use indoc::indoc;
+
+    use super::KeyLocator;
+
+    const INSTALLER_MANIFEST: &str = indoc! {"
+        # Created with komac
+        PackageIdentifier: Package.Identifier
+        PackageVersion: 1.2.3
+        InstallerType: exe
+        Installers:
+        - Architecture: x86
+          InstallerUrl: https://example.com/installer-x86.exe
+          InstallerSha256: 0000000000000000000000000000000000000000000000000000000000000000
+        - Architecture: x64
+          InstallerUrl: https://example.com/installer-x64.exe
+          Scope: machine
+          InstallerSha256: 0000000000000000000000000000000000000000000000000000000000000000
+        ManifestType: installer
+        ManifestVersion: 1.12.0
+    "};
+
+    #[test]
+    fn locates_top_level_keys() {
+        let locator = KeyLocator::new(INSTALLER_MANIFEST);
+
+        assert_eq!(locator.key("PackageVersion"), Some(3));
+        assert_eq!(locator.key("ManifestVersion"), Some(14));
+        assert_eq!(locator.key("Scope"), None);
+    }
+
+    #[test]
+    fn locates_installer_keys() {
+        let locator = KeyLocator::new(INSTALLER_MANIFEST);
+
+        assert_eq!(locator.installer_key(0, "Architecture"), Some(6));
+        assert_eq!(locator.installer_key(1, "Architecture"), Some(9));
+        assert_eq!(locator.installer_key(2, "Architecture"), None);
+        assert_eq!(locator.installer_key(1, "Scope"), Some(11));
+        assert_eq!(locator.installer_key(0, "Scope"), Some(6));
+    }
+
+    #[test]
+    fn locates_list_items() {
+        let locator = KeyLocator::new(indoc! {"
+            PackageIdentifier: Package.Identifier
+            Tags:
+            - cli
+            # A comment
+            - 'Command Line'
+            ShortDescription: A package
+            Moniker: cli
+        "});
+
+        assert_eq!(locator.list_item("Tags", "cli"), Some(3));
+        assert_eq!(locator.list_item("Tags", "Command Line"), Some(5));
+        assert_eq!(locator.list_item("Tags", "A package"), None);
+        assert_eq!(locator.line(6), Some("ShortDescription: A package"));
+    }
+}
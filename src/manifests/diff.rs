@@ -0,0 +1,583 @@
+//! Semantic comparison of the manifests of two versions of a package.
+//!
+//! Manifests are compared field by field as YAML values rather than line by line. Installers are
+//! matched between the versions by their URL, architecture and scope, and each installer is
+//! compared together with the values it inherits from the root of the installer manifest, so a
+//! field moving between the root and the installers isn't reported as a change.
+
+use std::{fmt, io::Write};
+
+use itertools::Itertools;
+use owo_colors::OwoColorize;
+use serde::Serialize;
+use serde_yaml::{Mapping, Value};
+
+use super::Manifests;
+
+/// Fields that are only at the root of an installer manifest rather than being inherited by each
+/// installer.
+const ROOT_FIELDS: [&str; 5] = [
+    "PackageIdentifier",
+    "PackageVersion",
+    "ManifestType",
+    "ManifestVersion",
+    "Installers",
+];
+
+/// The fields that installers are matched by, from the most to the least specific. Installers that
+/// have the same URL, architecture and scope are matched first, then installers whose URL changed
+/// but that are otherwise the same, and finally installers that have only the same architecture
+/// and scope.
+const INSTALLER_MATCHES: [&[&str]; 3] = [
+    &["InstallerUrl", "Architecture", "Scope"],
+    &["Architecture", "Scope", "InstallerType", "InstallerLocale"],
+    &["Architecture", "Scope"],
+];
+
+/// The manifests of a package version as YAML values, which is how they are compared.
+#[derive(Debug, Default)]
+pub struct ManifestValues {
+    pub installer: Value,
+    pub default_locale: Value,
+    pub locales: Vec<Value>,
+    pub version: Value,
+}
+
+impl ManifestValues {
+    pub fn new(manifests: &Manifests) -> Self {
+        Self {
+            installer: serde_yaml::to_value(&manifests.installer).unwrap_or_default(),
+            default_locale: serde_yaml::to_value(&manifests.default_locale).unwrap_or_default(),
+            locales: manifests
+                .locales
+                .iter()
+                .filter_map(|locale| serde_yaml::to_value(locale).ok())
+                .collect(),
+            version: serde_yaml::to_value(&manifests.version).unwrap_or_default(),
+        }
+    }
+
+    /// Reads the manifests from the paths and contents of the files of a package version. Files
+    /// that aren't manifests are skipped.
+    pub fn from_changes(changes: &[(String, String)]) -> Self {
+        let mut values = Self::default();
+        for (_, content) in changes {
+            let Ok(manifest) = serde_yaml::from_str::<Value>(content) else {
+                continue;
+            };
+            match manifest.get("ManifestType").and_then(Value::as_str) {
+                Some("installer") => values.installer = manifest,
+                Some("defaultLocale") => values.default_locale = manifest,
+                Some("locale") => values.locales.push(manifest),
+                Some("version") => values.version = manifest,
+                _ => {}
+            }
+        }
+        values
+    }
+}
+
+/// How a field changed between two versions.
+#[derive(Debug, Eq, PartialEq, Serialize)]
+#[serde(tag = "change", rename_all = "lowercase")]
+pub enum Change {
+    Added { value: Value },
+    Removed { value: Value },
+    Changed { from: Value, to: Value },
+}
+
+/// A field of a manifest that differs between two versions.
+#[derive(Debug, Eq, PartialEq, Serialize)]
+pub struct FieldDiff {
+    /// The manifest the field is in, such as `installer` or `locale.de-DE`.
+    pub manifest: String,
+    /// The path of the field, such as `Installers[x64, msi, machine].InstallerUrl`.
+    pub field: String,
+    #[serde(flatten)]
+    pub change: Change,
+}
+
+impl fmt::Display for FieldDiff {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.change {
+            Change::Added { value } => {
+                write!(f, "{} {}: {}", "+".green(), self.field, summary(value))
+            }
+            Change::Removed { value } => {
+                write!(f, "{} {}: {}", "-".red(), self.field, summary(value))
+            }
+            Change::Changed { from, to } => write!(
+                f,
+                "{} {}: {} → {}",
+                "~".yellow(),
+                self.field,
+                summary(from),
+                summary(to)
+            ),
+        }
+    }
+}
+
+/// The fields that differ between the manifests of two versions of a package.
+#[derive(Debug, Default, Serialize)]
+#[serde(transparent)]
+pub struct ManifestDiff {
+    pub fields: Vec<FieldDiff>,
+}
+
+impl ManifestDiff {
+    pub fn new(previous: &Manifests, current: &Manifests) -> Self {
+        Self::between(
+            &ManifestValues::new(previous),
+            &ManifestValues::new(current),
+        )
+    }
+
+    /// Compares the manifests of a previous version with those of the current one.
+    pub fn between(previous: &ManifestValues, current: &ManifestValues) -> Self {
+        let mut diff = Self::default();
+
+        diff.compare(
+            "installer",
+            "",
+            &installer_root(&previous.installer),
+            &installer_root(&current.installer),
+        );
+        diff.compare_installers(&previous.installer, &current.installer);
+        diff.compare(
+            "defaultLocale",
+            "",
+            &previous.default_locale,
+            &current.default_locale,
+        );
+        diff.compare_locales(&previous.locales, &current.locales);
+        diff.compare("version", "", &previous.version, &current.version);
+
+        diff
+    }
+
+    #[inline]
+    pub const fn is_empty(&self) -> bool {
+        self.fields.is_empty()
+    }
+
+    /// Prints the changed fields of each manifest.
+    pub fn print(&self) {
+        let mut lock = anstream::stdout().lock();
+
+        let mut previous_manifest = None;
+        for field in &self.fields {
+            if previous_manifest != Some(&field.manifest) {
+                let _ = writeln!(lock, "{}", field.manifest.bold());
+                previous_manifest = Some(&field.manifest);
+            }
+            let _ = writeln!(lock, "  {field}");
+        }
+    }
+
+    fn compare(&mut self, manifest: &str, path: &str, previous: &Value, current: &Value) {
+        let join = |key: &str| {
+            if path.is_empty() {
+                key.to_owned()
+            } else {
+                format!("{path}.{key}")
+            }
+        };
+
+        let (Value::Mapping(previous), Value::Mapping(current)) = (previous, current) else {
+            if previous != current {
+                self.push(
+                    manifest,
+                    path,
+                    Change::Changed {
+                        from: previous.clone(),
+                        to: current.clone(),
+                    },
+                );
+            }
+            return;
+        };
+
+        for (key, value) in previous {
+            let Some(name) = key.as_str() else {
+                continue;
+            };
+            // The package version differs between every version, so it isn't worth reporting
+            if path.is_empty() && name == "PackageVersion" {
+                continue;
+            }
+            match current.get(key) {
+                Some(current) => self.compare(manifest, &join(name), value, current),
+                None => self.push(
+                    manifest,
+                    join(name),
+                    Change::Removed {
+                        value: value.clone(),
+                    },
+                ),
+            }
+        }
+        for (key, value) in current {
+            if let Some(name) = key.as_str()
+                && !previous.contains_key(key)
+                && !(path.is_empty() && name == "PackageVersion")
+            {
+                self.push(
+                    manifest,
+                    join(name),
+                    Change::Added {
+                        value: value.clone(),
+                    },
+                );
+            }
+        }
+    }
+
+    fn compare_installers(&mut self, previous: &Value, current: &Value) {
+        const MANIFEST: &str = "installer";
+
+        let previous = installers(previous);
+        let current = installers(current);
+
+        let mut unmatched_previous = (0..previous.len()).collect::<Vec<_>>();
+        let mut unmatched_current = (0..current.len()).collect::<Vec<_>>();
+        let mut pairs = Vec::new();
+        for fields in INSTALLER_MATCHES {
+            unmatched_previous.retain(|&previous_index| {
+                let Some(position) = unmatched_current.iter().position(|&current_index| {
+                    fields.iter().all(|&field| {
+                        previous[previous_index].get(field) == current[current_index].get(field)
+                    })
+                }) else {
+                    return true;
+                };
+                pairs.push((previous_index, unmatched_current.remove(position)));
+                false
+            });
+        }
+        pairs.sort_unstable_by_key(|&(_, current_index)| current_index);
+
+        for (previous_index, current_index) in pairs {
+            self.compare(
+                MANIFEST,
+                &installer_label(&current[current_index]),
+                &Value::Mapping(previous[previous_index].clone()),
+                &Value::Mapping(current[current_index].clone()),
+            );
+        }
+        for index in unmatched_previous {
+            self.push(
+                MANIFEST,
+                installer_label(&previous[index]),
+                Change::Removed {
+                    value: previous[index]
+                        .get("InstallerUrl")
+                        .cloned()
+                        .unwrap_or_default(),
+                },
+            );
+        }
+        for index in unmatched_current {
+            self.push(
+                MANIFEST,
+                installer_label(&current[index]),
+                Change::Added {
+                    value: current[index]
+                        .get("InstallerUrl")
+                        .cloned()
+                        .unwrap_or_default(),
+                },
+            );
+        }
+    }
+
+    fn compare_locales(&mut self, previous: &[Value], current: &[Value]) {
+        const PACKAGE_LOCALE: &str = "PackageLocale";
+
+        let locale_tag = |manifest: &Value| {
+            manifest
+                .get(PACKAGE_LOCALE)
+                .and_then(Value::as_str)
+                .unwrap_or_default()
+                .to_owned()
+        };
+
+        for manifest in current {
+            let tag = locale_tag(manifest);
+            match previous.iter().find(|previous| locale_tag(previous) == tag) {
+                Some(previous) => self.compare(&format!("locale.{tag}"), "", previous, manifest),
+                None => self.push(
+                    format!("locale.{tag}"),
+                    PACKAGE_LOCALE,
+                    Change::Added {
+                        value: Value::String(tag),
+                    },
+                ),
+            }
+        }
+        for manifest in previous {
+            let tag = locale_tag(manifest);
+            if !current.iter().any(|current| locale_tag(current) == tag) {
+                self.push(
+                    format!("locale.{tag}"),
+                    PACKAGE_LOCALE,
+                    Change::Removed {
+                        value: Value::String(tag),
+                    },
+                );
+            }
+        }
+    }
+
+    fn push<M, F>(&mut self, manifest: M, field: F, change: Change)
+    where
+        M: Into<String>,
+        F: Into<String>,
+    {
+        self.fields.push(FieldDiff {
+            manifest: manifest.into(),
+            field: field.into(),
+            change,
+        });
+    }
+}
+
+/// Returns the fields of an installer manifest that aren't inherited by its installers.
+fn installer_root(manifest: &Value) -> Value {
+    let mut root = manifest.as_mapping().cloned().unwrap_or_default();
+    root.retain(|key, _| {
+        key.as_str()
+            .is_some_and(|key| key != "Installers" && ROOT_FIELDS.contains(&key))
+    });
+    Value::Mapping(root)
+}
+
+/// Returns the installers of an installer manifest with the values they inherit from its root.
+fn installers(manifest: &Value) -> Vec<Mapping> {
+    let Some(root) = manifest.as_mapping() else {
+        return Vec::new();
+    };
+
+    let inherited = root
+        .iter()
+        .filter(|(key, _)| !key.as_str().is_some_and(|key| ROOT_FIELDS.contains(&key)))
+        .collect::<Vec<_>>();
+
+    root.get("Installers")
+        .and_then(Value::as_sequence)
+        .into_iter()
+        .flatten()
+        .filter_map(Value::as_mapping)
+        .map(|installer| {
+            let mut installer = installer.clone();
+            for &(key, value) in &inherited {
+                if !installer.contains_key(key) {
+                    installer.insert(key.clone(), value.clone());
+                }
+            }
+            installer
+        })
+        .collect()
+}
+
+/// Names an installer by the fields that usually tell installers apart, such as
+/// `Installers[x64, msi, machine]`.
+fn installer_label(installer: &Mapping) -> String {
+    format!(
+        "Installers[{}]",
+        ["Architecture", "InstallerType", "Scope", "InstallerLocale"]
+            .into_iter()
+            .filter_map(|field| installer.get(field).and_then(Value::as_str))
+            .join(", ")
+    )
+}
+
+/// Formats a value on a single line, so that values of different YAML types that are written the
+/// same way, such as the number `1.0` and the string `'1.0'`, compare as equal.
+pub(super) fn display(value: &Value) -> String {
+    match value {
+        Value::Null => String::from("null"),
+        Value::Bool(bool) => bool.to_string(),
+        Value::Number(number) => number.to_string(),
+        Value::String(string) => string.clone(),
+        _ => serde_json::to_string(value).unwrap_or_default(),
+    }
+}
+
+/// Formats a value on a single line, shortening long values such as release notes.
+pub fn summary(value: &Value) -> String {
+    const MAX_CHARS: usize = 60;
+
+    let display = display(value);
+    let mut lines = display.lines();
+    let first_line = lines.next().unwrap_or_default();
+    if first_line.chars().count() > MAX_CHARS || lines.next().is_some() {
+        format!(
+            "{}…",
+            first_line
+                .chars()
+                .take(MAX_CHARS)
+                .collect::<String>()
+                .trim_end()
+        )
+    } else {
+        first_line.to_owned()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use indoc::indoc;
+    use serde_yaml::Value;
+
+    use super::{Change, ManifestDiff, ManifestValues, summary};
+
+    fn values(installer: &str, locales: &[&str]) -> ManifestValues {
+        ManifestValues {
+            installer: serde_yaml::from_str(installer).unwrap(),
+            default_locale: serde_yaml::from_str(indoc! {"
+                PackageIdentifier: Package.Identifier
+                PackageVersion: 1.0.0
+                PackageLocale: en-US
+                ManifestType: defaultLocale
+            "})
+            .unwrap(),
+            locales: locales
+                .iter()
+                .map(|tag| serde_yaml::from_str(&format!("PackageLocale: {tag}")).unwrap())
+                .collect(),
+            version: Value::Null,
+        }
+    }
+
+    fn fields(diff: &ManifestDiff) -> Vec<(&str, &str, &Change)> {
+        diff.fields
+            .iter()
+            .map(|field| (field.manifest.as_str(), field.field.as_str(), &field.change))
+            .collect()
+    }
+
+    #[test]
+    fn matches_installers_by_architecture_and_scope() {
+        let previous = values(
+            indoc! {"
+                PackageVersion: 1.0.0
+                InstallerType: msi
+                Installers:
+                - Architecture: x86
+                  Scope: machine
+                  InstallerUrl: https://example.com/1.0.0-x86.msi
+                - Architecture: x64
+                  Scope: machine
+                  InstallerUrl: https://example.com/1.0.0-x64.msi
+                ManifestVersion: 1.10.0
+            "},
+            &[],
+        );
+        let current = values(
+            indoc! {"
+                PackageVersion: 1.1.0
+                Installers:
+                - Architecture: x64
+                  InstallerType: msi
+                  Scope: machine
+                  InstallerUrl: https://example.com/1.1.0-x64.msi
+                - Architecture: arm64
+                  InstallerType: msi
+                  Scope: machine
+                  InstallerUrl: https://example.com/1.1.0-arm64.msi
+                ManifestVersion: 1.12.0
+            "},
+            &[],
+        );
+
+        let diff = ManifestDiff::between(&previous, &current);
+
+        assert_eq!(
+            fields(&diff),
+            [
+                (
+                    "installer",
+                    "ManifestVersion",
+                    &Change::Changed {
+                        from: Value::from("1.10.0"),
+                        to: Value::from("1.12.0")
+                    }
+                ),
+                (
+                    "installer",
+                    "Installers[x64, msi, machine].InstallerUrl",
+                    &Change::Changed {
+                        from: Value::from("https://example.com/1.0.0-x64.msi"),
+                        to: Value::from("https://example.com/1.1.0-x64.msi")
+                    }
+                ),
+                (
+                    "installer",
+                    "Installers[x86, msi, machine]",
+                    &Change::Removed {
+                        value: Value::from("https://example.com/1.0.0-x86.msi")
+                    }
+                ),
+                (
+                    "installer",
+                    "Installers[arm64, msi, machine]",
+                    &Change::Added {
+                        value: Value::from("https://example.com/1.1.0-arm64.msi")
+                    }
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn matches_locales_by_tag() {
+        let installer = "PackageVersion: 1.0.0";
+        let diff = ManifestDiff::between(
+            &values(installer, &["de-DE", "fr-FR"]),
+            &values(installer, &["fr-FR", "en-GB"]),
+        );
+
+        assert_eq!(
+            fields(&diff),
+            [
+                (
+                    "locale.en-GB",
+                    "PackageLocale",
+                    &Change::Added {
+                        value: Value::from("en-GB")
+                    }
+                ),
+                (
+                    "locale.de-DE",
+                    "PackageLocale",
+                    &Change::Removed {
+                        value: Value::from("de-DE")
+                    }
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn unchanged_manifests_have_no_diff() {
+        let installer = indoc! {"
+            PackageVersion: 1.0.0
+            Installers:
+            - Architecture: x64
+              InstallerUrl: https://example.com/installer.exe
+        "};
+
+        assert!(ManifestDiff::between(&values(installer, &[]), &values(installer, &[])).is_empty());
+    }
+
+    #[test]
+    fn summarises_long_values() {
+        assert_eq!(summary(&Value::from("Fixes\n- A bug")), "Fixes…");
+        assert_eq!(
+            summary(&Value::from("a".repeat(70))),
+            format!("{}…", "a".repeat(60))
+        );
+        assert_eq!(summary(&Value::from("Short")), "Short");
+    }
+}
@@ -0,0 +1,396 @@
+//! Setting fields of a package version's manifests by name.
+//!
+//! Fields are set on the YAML values of the manifests, which are then read back into winget-types'
+//! model of the schema, so a value is checked in the same way as one in a manifest file would be.
+//! Locale fields are set in the default locale manifest, and in the other locale manifests only
+//! where they repeated the default locale's previous value, so translations are kept. Installer
+//! fields are set at the root of the installer manifest and in any installer that overrides them.
+//! A field can only be set in manifests whose manifest version has it.
+
+use std::str::FromStr;
+
+use serde::{Serialize, de::DeserializeOwned};
+use serde_yaml::Value;
+use thiserror::Error;
+use winget_types::ManifestVersion;
+
+use super::{Manifests, validation::FIELD_VERSIONS};
+
+/// Fields that identify a package version and its manifests rather than describing the package.
+const PROTECTED_FIELDS: [&str; 6] = [
+    "PackageIdentifier",
+    "PackageVersion",
+    "PackageLocale",
+    "DefaultLocale",
+    "ManifestType",
+    "ManifestVersion",
+];
+
+#[derive(Debug, Error)]
+pub enum EditError {
+    #[error("`{0}` is not in the form FIELD=VALUE")]
+    InvalidEdit(String),
+    #[error("`{0}` identifies the package version, so it can't be edited")]
+    Protected(String),
+    #[error("`{0}` is not a field of the installer or locale manifests")]
+    UnknownField(String),
+    #[error("`{value}` is not a valid value for `{field}`: {source}")]
+    InvalidValue {
+        field: String,
+        value: String,
+        source: serde_yaml::Error,
+    },
+    #[error(
+        "`{field}` was added in manifest version {added}, so it can't be set in manifests that use \
+         the {manifest_version} schema"
+    )]
+    FieldVersion {
+        field: String,
+        added: ManifestVersion,
+        manifest_version: ManifestVersion,
+    },
+    #[error(transparent)]
+    Yaml(#[from] serde_yaml::Error),
+}
+
+/// A field to set, from a `FIELD=VALUE` argument.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct FieldEdit {
+    pub field: String,
+    /// The value to set, or `None` to remove the field.
+    ///
+    /// Values are read as YAML where the field expects something other than a string, so lists
+    /// such as `[cli, tool]` can be given.
+    pub value: Option<String>,
+}
+
+impl FieldEdit {
+    /// Returns the values to try setting the field to, from the most to the least specific.
+    fn candidates(&self) -> Vec<Value> {
+        let Some(value) = &self.value else {
+            return vec![Value::Null];
+        };
+
+        let string = Value::String(value.clone());
+        match serde_yaml::from_str::<Value>(value) {
+            Ok(parsed) if !parsed.is_string() && !parsed.is_null() => vec![parsed, string],
+            _ => vec![string],
+        }
+    }
+}
+
+impl FromStr for FieldEdit {
+    type Err = EditError;
+
+    fn from_str(edit: &str) -> Result<Self, Self::Err> {
+        let (field, value) = edit
+            .split_once('=')
+            .map(|(field, value)| (field.trim(), value.trim()))
+            .filter(|(field, _)| !field.is_empty())
+            .ok_or_else(|| EditError::InvalidEdit(edit.to_owned()))?;
+
+        if PROTECTED_FIELDS.contains(&field) {
+            return Err(EditError::Protected(field.to_owned()));
+        }
+
+        Ok(Self {
+            field: field.to_owned(),
+            value: (!value.is_empty()).then(|| value.to_owned()),
+        })
+    }
+}
+
+/// Applies edits to the manifests of a package version.
+///
+/// # Errors
+///
+/// Returns an error if a field isn't part of the installer or locale manifests, if its value isn't
+/// valid for the field, or if it was added in a later manifest version than the manifests use. The
+/// manifests may have been partly edited when an error is returned.
+pub fn apply(manifests: &mut Manifests, edits: &[FieldEdit]) -> Result<(), EditError> {
+    for edit in edits {
+        let previous = get(&manifests.default_locale, &edit.field);
+        if set(&mut manifests.default_locale, edit)? {
+            check_field_version(edit, manifests.default_locale.manifest_version)?;
+            for locale in &mut manifests.locales {
+                if previous.is_some() && get(locale, &edit.field) == previous {
+                    set(locale, edit)?;
+                }
+            }
+            continue;
+        }
+
+        let mut is_installer_field = set(&mut manifests.installer, edit)?;
+        for installer in &mut manifests.installer.installers {
+            if get(installer, &edit.field).is_some() {
+                is_installer_field |= set(installer, edit)?;
+            }
+        }
+        if is_installer_field {
+            check_field_version(edit, manifests.installer.manifest_version)?;
+        }
+
+        // A field that no manifest has is already removed
+        if !is_installer_field && edit.value.is_some() {
+            return Err(EditError::UnknownField(edit.field.clone()));
+        }
+    }
+
+    Ok(())
+}
+
+/// Checks that a field being set is part of the schema of the manifest version that its manifest
+/// uses. Fields can always be removed.
+fn check_field_version(
+    edit: &FieldEdit,
+    manifest_version: ManifestVersion,
+) -> Result<(), EditError> {
+    if edit.value.is_none() {
+        return Ok(());
+    }
+
+    match FIELD_VERSIONS
+        .iter()
+        .find(|&&(field, added)| field == edit.field && added > manifest_version)
+    {
+        Some(&(_, added)) => Err(EditError::FieldVersion {
+            field: edit.field.clone(),
+            added,
+            manifest_version,
+        }),
+        None => Ok(()),
+    }
+}
+
+fn get<T: Serialize>(manifest: &T, field: &str) -> Option<Value> {
+    serde_yaml::to_value(manifest).ok()?.get(field).cloned()
+}
+
+/// Sets or removes a field of a manifest, returning `false` without changing the manifest if the
+/// field isn't part of its schema, or if the field is being removed and the manifest doesn't have
+/// it.
+fn set<T>(manifest: &mut T, edit: &FieldEdit) -> Result<bool, EditError>
+where
+    T: Serialize + DeserializeOwned,
+{
+    let Value::Mapping(fields) = serde_yaml::to_value(&*manifest)? else {
+        return Ok(false);
+    };
+    let key = Value::from(edit.field.as_str());
+
+    let mut error = None;
+    for value in edit.candidates() {
+        let mut fields = fields.clone();
+        if value.is_null() {
+            if fields.remove(&key).is_none() {
+                return Ok(false);
+            }
+        } else {
+            fields.insert(key.clone(), value);
+        }
+
+        // Some types in the schema borrow from the text they're read from, so the manifest is read
+        // from text rather than from the value
+        match serde_yaml::from_str::<T>(&serde_yaml::to_string(&fields)?) {
+            // Fields that aren't part of the schema are dropped when the manifest is read
+            Ok(edited) if edit.value.is_some() && get(&edited, &edit.field).is_none() => {
+                return Ok(false);
+            }
+            Ok(edited) => {
+                *manifest = edited;
+                return Ok(true);
+            }
+            Err(source) => error = error.or(Some(source)),
+        }
+    }
+
+    Err(error.map_or_else(
+        || EditError::UnknownField(edit.field.clone()),
+        |source| EditError::InvalidValue {
+            field: edit.field.clone(),
+            value: edit.value.clone().unwrap_or_default(),
+            source,
+        },
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use indoc::indoc;
+    use rstest::rstest;
+    use winget_types::ManifestVersion;
+
+    use super::{EditError, FieldEdit, apply};
+    use crate::manifests::Manifests;
+
+    fn manifests() -> Manifests {
+        Manifests {
+            installer: serde_yaml::from_str(indoc! {"
+                PackageIdentifier: Package.Identifier
+                PackageVersion: 1.0.0
+                Installers:
+                - Architecture: x64
+                  InstallerType: msi
+                  InstallerUrl: https://example.com/installer.msi
+                  InstallerSha256: 0000000000000000000000000000000000000000000000000000000000000000
+                - Architecture: arm64
+                  InstallerType: msi
+                  Scope: machine
+                  InstallerUrl: https://example.com/installer-arm64.msi
+                  InstallerSha256: 1111111111111111111111111111111111111111111111111111111111111111
+                ManifestType: installer
+                ManifestVersion: 1.10.0
+            "})
+            .unwrap(),
+            default_locale: serde_yaml::from_str(indoc! {"
+                PackageIdentifier: Package.Identifier
+                PackageVersion: 1.0.0
+                PackageLocale: en-US
+                Publisher: Publisher
+                PackageName: Package
+                License: GPL-3.0
+                ShortDescription: A package
+                ManifestType: defaultLocale
+                ManifestVersion: 1.10.0
+            "})
+            .unwrap(),
+            locales: vec![
+                serde_yaml::from_str(indoc! {"
+                    PackageIdentifier: Package.Identifier
+                    PackageVersion: 1.0.0
+                    PackageLocale: de-DE
+                    License: GPL-3.0
+                    ShortDescription: Ein Paket
+                    ManifestType: locale
+                    ManifestVersion: 1.10.0
+                "})
+                .unwrap(),
+                serde_yaml::from_str(indoc! {"
+                    PackageIdentifier: Package.Identifier
+                    PackageVersion: 1.0.0
+                    PackageLocale: fr-FR
+                    License: GPL-3.0 (Licence publique générale GNU)
+                    ManifestType: locale
+                    ManifestVersion: 1.10.0
+                "})
+                .unwrap(),
+            ],
+            version: serde_yaml::from_str(indoc! {"
+                PackageIdentifier: Package.Identifier
+                PackageVersion: 1.0.0
+                DefaultLocale: en-US
+                ManifestType: version
+                ManifestVersion: 1.10.0
+            "})
+            .unwrap(),
+        }
+    }
+
+    fn edit(edit: &str) -> FieldEdit {
+        edit.parse().unwrap()
+    }
+
+    #[rstest]
+    #[case("License=MIT", "License", Some("MIT"))]
+    #[case("Tags = [cli, tool]", "Tags", Some("[cli, tool]"))]
+    #[case(
+        "PublisherUrl=https://example.com/?a=b",
+        "PublisherUrl",
+        Some("https://example.com/?a=b")
+    )]
+    #[case("Moniker=", "Moniker", None)]
+    fn parses_edit(#[case] input: &str, #[case] field: &str, #[case] value: Option<&str>) {
+        assert_eq!(
+            edit(input),
+            FieldEdit {
+                field: field.to_owned(),
+                value: value.map(str::to_owned),
+            }
+        );
+    }
+
+    #[rstest]
+    #[case("License")]
+    #[case("=MIT")]
+    #[case("PackageVersion=1.2.3")]
+    fn rejects_edit(#[case] input: &str) {
+        assert!(input.parse::<FieldEdit>().is_err());
+    }
+
+    #[test]
+    fn sets_locale_fields() {
+        let mut manifests = manifests();
+
+        apply(
+            &mut manifests,
+            &[
+                edit("License=MIT"),
+                edit("Tags=[cli, tool]"),
+                edit("Moniker="),
+            ],
+        )
+        .unwrap();
+
+        assert_eq!(manifests.default_locale.license.as_str(), "MIT");
+        assert_eq!(manifests.default_locale.tags.len(), 2);
+        // The German locale repeated the previous license, but the French one translated it
+        assert_eq!(
+            manifests.locales[0]
+                .license
+                .as_ref()
+                .map(|license| license.as_str()),
+            Some("MIT")
+        );
+        assert_eq!(
+            manifests.locales[1]
+                .license
+                .as_ref()
+                .map(|license| license.as_str()),
+            Some("GPL-3.0 (Licence publique générale GNU)")
+        );
+    }
+
+    #[test]
+    fn sets_installer_fields() {
+        let mut manifests = manifests();
+
+        apply(
+            &mut manifests,
+            &[edit("Scope=user"), edit("MinimumOSVersion=10.0.17763.0")],
+        )
+        .unwrap();
+
+        let installer = serde_yaml::to_value(&manifests.installer).unwrap();
+        assert_eq!(installer["Scope"], "user");
+        assert_eq!(installer["MinimumOSVersion"], "10.0.17763.0");
+        assert_eq!(installer["Installers"][1]["Scope"], "user");
+    }
+
+    #[test]
+    fn rejects_unknown_field_and_invalid_value() {
+        assert!(matches!(
+            apply(&mut manifests(), &[edit("Licence=MIT")]),
+            Err(EditError::UnknownField(field)) if field == "Licence"
+        ));
+        assert!(matches!(
+            apply(&mut manifests(), &[edit("Scope=everyone")]),
+            Err(EditError::InvalidValue { field, .. }) if field == "Scope"
+        ));
+    }
+
+    #[rstest]
+    #[case("ReleaseNotesUrl=https://example.com/releases")]
+    #[case("ReleaseDate=2024-01-01")]
+    fn rejects_fields_newer_than_manifest_version(#[case] input: &str) {
+        let mut manifests = manifests();
+        manifests.installer.manifest_version = ManifestVersion::new(1, 0, 0);
+        manifests.default_locale.manifest_version = ManifestVersion::new(1, 0, 0);
+
+        assert!(matches!(
+            apply(&mut manifests, &[edit(input)]),
+            Err(EditError::FieldVersion { added, .. }) if added == ManifestVersion::new(1, 1, 0)
+        ));
+        // Fields can still be removed
+        assert!(apply(&mut manifests, &[edit("ReleaseNotesUrl=")]).is_ok());
+    }
+}
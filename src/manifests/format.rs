@@ -0,0 +1,151 @@
+//! Rewriting of manifests into the layout that Komac writes them in.
+//!
+//! Manifests are read into winget-types' model of the schema and written back out, which orders
+//! their fields, normalises quoting and line endings and hoists values that are the same for every
+//! installer to the root of the installer manifest. Comments at the top of a manifest, such as the
+//! tool it was created with, are kept, but comments anywhere else are removed.
+
+use super::{
+    build_manifest_string,
+    diagnostic::Diagnostic,
+    header,
+    manifest::Manifest,
+    validation::{ManifestFile, parse},
+};
+
+/// Returns the text of a manifest file in the canonical layout.
+///
+/// # Errors
+///
+/// Returns a diagnostic if the file isn't a valid manifest, or if it has a field that isn't in the
+/// schema, as formatting would remove it.
+pub fn format(file: &ManifestFile) -> Result<String, Diagnostic> {
+    let (manifest, unknown_fields) = parse(file)?;
+    if let Some((field, line)) = unknown_fields.into_iter().next() {
+        return Err(Diagnostic::error(
+            &file.path,
+            "unknown-field",
+            format!("`{field}` is not a field of the schema, so formatting would remove it"),
+        )
+        .at_line(line));
+    }
+
+    let header = header(&file.text);
+    match manifest {
+        Manifest::Installer(mut manifest) => {
+            // Optimizing also resets the manifest version, so it is restored afterwards
+            let manifest_version = manifest.manifest_version;
+            manifest.optimize();
+            manifest.manifest_version = manifest_version;
+            build_manifest_string(&manifest, manifest_version, None, &header)
+        }
+        Manifest::DefaultLocale(manifest) => {
+            build_manifest_string(&manifest, manifest.manifest_version, None, &header)
+        }
+        Manifest::Locale(manifest) => {
+            build_manifest_string(&manifest, manifest.manifest_version, None, &header)
+        }
+        Manifest::Version(manifest) => {
+            build_manifest_string(&manifest, manifest.manifest_version, None, &header)
+        }
+    }
+    .map_err(|error| Diagnostic::error(&file.path, "schema", error.to_string()))
+}
+
+#[cfg(test)]
+mod tests {
+    use camino::Utf8PathBuf;
+    use indoc::indoc;
+
+    use super::format;
+    use crate::manifests::validation::ManifestFile;
+
+    fn file(text: &str) -> ManifestFile {
+        ManifestFile {
+            path: Utf8PathBuf::from("Package.Identifier.installer.yaml"),
+            text: text.to_owned(),
+        }
+    }
+
+    #[test]
+    fn formats_hand_written_manifest() {
+        let manifest = file(indoc! {r#"
+            # Created by hand
+            PackageVersion: "1.2.3"
+            PackageIdentifier: 'Package.Identifier'
+            Installers:
+            - Architecture: x86
+              InstallerType: msi
+              InstallerUrl: https://example.com/installer-x86.msi
+              InstallerSha256: 0123456789ABCDEF0123456789ABCDEF0123456789ABCDEF0123456789ABCDEF
+            # The 64-bit installer
+            - Architecture: x64
+              InstallerType: msi
+              InstallerUrl: https://example.com/installer-x64.msi
+              InstallerSha256: 0123456789ABCDEF0123456789ABCDEF0123456789ABCDEF0123456789ABCDEF
+            ManifestType: installer
+            ManifestVersion: 1.10.0
+        "#});
+
+        assert_eq!(
+            format(&manifest).unwrap(),
+            indoc! {"
+                # Created by hand
+                # yaml-language-server: $schema=https://aka.ms/winget-manifest.installer.1.10.0.schema.json
+
+                PackageIdentifier: Package.Identifier
+                PackageVersion: 1.2.3
+                InstallerType: msi
+                Installers:
+                - Architecture: x86
+                  InstallerUrl: https://example.com/installer-x86.msi
+                  InstallerSha256: 0123456789ABCDEF0123456789ABCDEF0123456789ABCDEF0123456789ABCDEF
+                - Architecture: x64
+                  InstallerUrl: https://example.com/installer-x64.msi
+                  InstallerSha256: 0123456789ABCDEF0123456789ABCDEF0123456789ABCDEF0123456789ABCDEF
+                ManifestType: installer
+                ManifestVersion: 1.10.0
+            "}
+            .replace('\n', "\r\n")
+        );
+    }
+
+    #[test]
+    fn formatting_is_idempotent() {
+        let manifest = file(indoc! {"
+            PackageIdentifier: Package.Identifier
+            PackageVersion: 1.2.3
+            Installers:
+            - Architecture: x64
+              InstallerType: exe
+              InstallerUrl: https://example.com/installer.exe
+              InstallerSha256: 0000000000000000000000000000000000000000000000000000000000000000
+            ManifestType: installer
+            ManifestVersion: 1.12.0
+        "});
+
+        let formatted = format(&manifest).unwrap();
+        assert!(formatted.starts_with("# Created with komac"));
+        assert_eq!(format(&file(&formatted)).unwrap(), formatted);
+    }
+
+    #[test]
+    fn refuses_to_remove_unknown_fields() {
+        let manifest = file(indoc! {"
+            PackageIdentifier: Package.Identifier
+            PackageVersion: 1.2.3
+            Installers:
+            - Architecture: x64
+              InstallerType: exe
+              InstallerUrl: https://example.com/installer.exe
+              InstallerSha256: 0000000000000000000000000000000000000000000000000000000000000000
+            Unknown: true
+            ManifestType: installer
+            ManifestVersion: 1.12.0
+        "});
+
+        let diagnostic = format(&manifest).unwrap_err();
+        assert_eq!(diagnostic.code, "unknown-field");
+        assert_eq!(diagnostic.line, Some(8));
+    }
+}
@@ -0,0 +1,169 @@
+//! JSON manifests, which winget reads in the same way as YAML manifests.
+//!
+//! Manifests are written as YAML with comments saying what created them and which schema they
+//! follow. JSON has no comments, so these are kept in the `$comment` and `$schema` keys instead,
+//! which go before the fields of the manifest. Fields keep the order they have in the YAML.
+
+use std::fmt::Write;
+
+use camino::Utf8Path;
+use clap::ValueEnum;
+use color_eyre::eyre::Result;
+use serde_yaml::{Mapping, Value};
+
+use super::convert_to_crlf;
+
+const SCHEMA: &str = "$schema";
+const COMMENT: &str = "$comment";
+const SCHEMA_COMMENT: &str = "yaml-language-server: $schema=";
+
+/// The file format to write manifests in.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq, ValueEnum)]
+pub enum ManifestFormat {
+    #[default]
+    Yaml,
+    Json,
+}
+
+impl ManifestFormat {
+    pub const fn extension(self) -> &'static str {
+        match self {
+            Self::Yaml => "yaml",
+            Self::Json => "json",
+        }
+    }
+
+    /// Converts a YAML manifest to this format, with CRLF line endings as winget-pkgs requires.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the manifest is converted to JSON and isn't valid YAML.
+    pub fn convert(self, manifest: &str) -> Result<String> {
+        match self {
+            Self::Yaml => Ok(manifest.to_owned()),
+            Self::Json => Ok(convert_to_crlf(&to_json(manifest)?).into_owned()),
+        }
+    }
+}
+
+/// Returns `true` if a path has a JSON extension.
+pub fn is_json(path: &Utf8Path) -> bool {
+    path.extension()
+        .is_some_and(|extension| extension.eq_ignore_ascii_case("json"))
+}
+
+/// Converts a YAML manifest to JSON, keeping its leading comments in the `$comment` key and the
+/// schema in the `$schema` key. YAML that isn't a mapping, such as a list of installers, is
+/// converted as it is.
+///
+/// # Errors
+///
+/// Returns an error if the manifest isn't valid YAML.
+pub fn to_json(manifest: &str) -> Result<String> {
+    let mut schema = None;
+    let mut comments = Vec::new();
+    for line in manifest.lines().map(str::trim) {
+        let Some(comment) = line.strip_prefix('#') else {
+            if line.is_empty() {
+                continue;
+            }
+            break;
+        };
+        let comment = comment.trim();
+        match comment.strip_prefix(SCHEMA_COMMENT) {
+            Some(url) => schema = Some(url),
+            None => comments.push(comment),
+        }
+    }
+
+    let fields = match serde_yaml::from_str::<Value>(manifest)? {
+        Value::Mapping(fields) => fields,
+        value => return Ok(serde_json::to_string_pretty(&value)? + "\n"),
+    };
+
+    let mut json = Mapping::new();
+    if let Some(schema) = schema {
+        json.insert(Value::from(SCHEMA), Value::from(schema));
+    }
+    if !comments.is_empty() {
+        json.insert(Value::from(COMMENT), Value::from(comments.join("\n")));
+    }
+    json.extend(fields);
+
+    Ok(serde_json::to_string_pretty(&json)? + "\n")
+}
+
+/// Converts a JSON manifest to YAML, writing its `$comment` and `$schema` keys as the leading
+/// comments of the manifest.
+///
+/// # Errors
+///
+/// Returns an error if the manifest isn't valid JSON.
+pub fn from_json(manifest: &str) -> Result<String> {
+    let mut fields = match serde_json::from_str::<Value>(manifest)? {
+        Value::Mapping(fields) => fields,
+        value => return Ok(serde_yaml::to_string(&value)?),
+    };
+
+    let mut result = String::new();
+    if let Some(Value::String(comment)) = fields.shift_remove(COMMENT) {
+        for line in comment.lines() {
+            let _ = writeln!(result, "# {line}");
+        }
+    }
+    if let Some(Value::String(schema)) = fields.shift_remove(SCHEMA) {
+        let _ = writeln!(result, "# {SCHEMA_COMMENT}{schema}");
+    }
+    if !result.is_empty() {
+        result.push('\n');
+    }
+    result.push_str(&serde_yaml::to_string(&fields)?);
+
+    Ok(result)
+}
+
+#[cfg(test)]
+mod tests {
+    use indoc::indoc;
+    use serde_json::json;
+
+    use super::{from_json, to_json};
+
+    const MANIFEST: &str = indoc! {"
+        # Created with komac v2.16.0
+        # yaml-language-server: $schema=https://aka.ms/winget-manifest.version.1.12.0.schema.json
+
+        PackageIdentifier: Foo.Bar
+        PackageVersion: 1.2.3
+        DefaultLocale: en-US
+        ManifestType: version
+        ManifestVersion: 1.12.0
+    "};
+
+    #[test]
+    fn converts_to_json() {
+        let json = to_json(MANIFEST).unwrap();
+
+        assert_eq!(
+            serde_json::from_str::<serde_json::Value>(&json).unwrap(),
+            json!({
+                "$schema": "https://aka.ms/winget-manifest.version.1.12.0.schema.json",
+                "$comment": "Created with komac v2.16.0",
+                "PackageIdentifier": "Foo.Bar",
+                "PackageVersion": "1.2.3",
+                "DefaultLocale": "en-US",
+                "ManifestType": "version",
+                "ManifestVersion": "1.12.0",
+            })
+        );
+        assert!(json.find("PackageIdentifier") < json.find("ManifestVersion"));
+        assert!(json.find("$schema") < json.find("PackageIdentifier"));
+    }
+
+    #[test]
+    fn round_trips() {
+        let yaml = from_json(&to_json(MANIFEST).unwrap()).unwrap();
+
+        assert_eq!(yaml, MANIFEST);
+    }
+}
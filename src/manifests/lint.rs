@@ -0,0 +1,599 @@
+//! Style rules that winget-pkgs moderators enforce beyond schema validity.
+//!
+//! Each rule has a stable ID whose level can be changed or turned off in the configuration file or
+//! with `--rule`. Rules whose problems can be fixed without changing the meaning of a manifest
+//! provide a fix, which replaces the line the problem is on.
+
+use std::{
+    collections::BTreeMap,
+    fmt,
+    sync::{LazyLock, OnceLock},
+};
+
+use clap::ValueEnum;
+use itertools::Itertools;
+use regex::Regex;
+use serde::Deserialize;
+use winget_types::{
+    installer::{InstallerManifest, InstallerType},
+    locale::{PackageName, ReleaseNotes, ShortDescription, Tag},
+};
+
+use super::{
+    diagnostic::{Diagnostic, KeyLocator, Severity},
+    manifest::Manifest,
+    validation::{ManifestFile, parse},
+};
+
+static RULES: OnceLock<LintRules> = OnceLock::new();
+
+/// A style rule, identified by its kebab-case ID.
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Ord, PartialOrd, Hash, Deserialize, ValueEnum)]
+#[serde(rename_all = "kebab-case")]
+pub enum Rule {
+    /// `ShortDescription` ends with a period
+    ShortDescriptionPeriod,
+    /// A tag has uppercase letters or spaces
+    TagFormat,
+    /// `PackageName` contains the package version
+    PackageNameVersion,
+    /// `ReleaseNotes` contains Markdown, which winget shows as plain text
+    ReleaseNotesMarkdown,
+    /// An `InstallerUrl` uses http instead of https
+    InsecureUrl,
+    /// An MSI or WiX installer has no `ProductCode`
+    MsiProductCode,
+}
+
+impl Rule {
+    pub const fn id(self) -> &'static str {
+        match self {
+            Self::ShortDescriptionPeriod => "short-description-period",
+            Self::TagFormat => "tag-format",
+            Self::PackageNameVersion => "package-name-version",
+            Self::ReleaseNotesMarkdown => "release-notes-markdown",
+            Self::InsecureUrl => "insecure-url",
+            Self::MsiProductCode => "msi-product-code",
+        }
+    }
+
+    pub const fn description(self) -> &'static str {
+        match self {
+            Self::ShortDescriptionPeriod => "ShortDescription ends with a period",
+            Self::TagFormat => "A tag has uppercase letters or spaces",
+            Self::PackageNameVersion => "PackageName contains the package version",
+            Self::ReleaseNotesMarkdown => "ReleaseNotes contains leftover Markdown",
+            Self::InsecureUrl => "An InstallerUrl uses http instead of https",
+            Self::MsiProductCode => "An MSI or WiX installer has no ProductCode",
+        }
+    }
+
+    /// Returns `true` if problems found by the rule can be fixed automatically.
+    pub const fn is_fixable(self) -> bool {
+        matches!(self, Self::ShortDescriptionPeriod | Self::TagFormat)
+    }
+
+    /// Returns an array of all the rules.
+    #[inline]
+    pub const fn all() -> [Self; 6] {
+        [
+            Self::ShortDescriptionPeriod,
+            Self::TagFormat,
+            Self::PackageNameVersion,
+            Self::ReleaseNotesMarkdown,
+            Self::InsecureUrl,
+            Self::MsiProductCode,
+        ]
+    }
+}
+
+impl fmt::Display for Rule {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.id().fmt(f)
+    }
+}
+
+/// How problems found by a rule are reported.
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Deserialize, ValueEnum)]
+#[serde(rename_all = "lowercase")]
+pub enum RuleLevel {
+    Off,
+    Warning,
+    Error,
+}
+
+impl RuleLevel {
+    const fn severity(self) -> Option<Severity> {
+        match self {
+            Self::Off => None,
+            Self::Warning => Some(Severity::Warning),
+            Self::Error => Some(Severity::Error),
+        }
+    }
+}
+
+impl fmt::Display for RuleLevel {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Off => f.write_str("off"),
+            Self::Warning => f.write_str("warning"),
+            Self::Error => f.write_str("error"),
+        }
+    }
+}
+
+/// The level of each rule for this invocation. Rules that aren't set are warnings.
+#[derive(Clone, Debug, Default)]
+pub struct LintRules(BTreeMap<Rule, RuleLevel>);
+
+impl LintRules {
+    pub const fn new(levels: BTreeMap<Rule, RuleLevel>) -> Self {
+        Self(levels)
+    }
+
+    /// Returns the lint rules for this invocation.
+    pub fn get() -> &'static Self {
+        RULES.get_or_init(Self::default)
+    }
+
+    /// Sets the lint rules for this invocation. This has no effect if the rules have already been
+    /// set or used.
+    pub fn set(self) {
+        let _ = RULES.set(self);
+    }
+
+    /// Sets the level of a rule.
+    pub fn insert(&mut self, rule: Rule, level: RuleLevel) {
+        self.0.insert(rule, level);
+    }
+
+    pub fn level(&self, rule: Rule) -> RuleLevel {
+        self.0.get(&rule).copied().unwrap_or(RuleLevel::Warning)
+    }
+}
+
+/// Parses a rule level override in the form `<RULE>=<LEVEL>`.
+pub fn parse_rule_level(value: &str) -> Result<(Rule, RuleLevel), String> {
+    let (rule, level) = value
+        .split_once('=')
+        .ok_or_else(|| format!("expected <RULE>=<LEVEL> but got `{value}`"))?;
+
+    Ok((
+        Rule::from_str(rule.trim(), true)?,
+        RuleLevel::from_str(level.trim(), true)?,
+    ))
+}
+
+/// A problem found by a rule, and how to fix it if it can be fixed automatically.
+pub struct Finding {
+    pub diagnostic: Diagnostic,
+    pub fix: Option<Fix>,
+}
+
+/// A replacement for a line of a manifest.
+pub struct Fix {
+    /// The one-based line to replace.
+    line: usize,
+    /// The new contents of the line, without its line ending.
+    replacement: String,
+}
+
+/// Checks a manifest file against the lint rules.
+///
+/// Files that can't be deserialized aren't linted, as they are reported by validation instead.
+pub fn lint(file: &ManifestFile, rules: &LintRules) -> Vec<Finding> {
+    let Ok((manifest, _)) = parse(file) else {
+        return Vec::new();
+    };
+
+    let mut linter = Linter {
+        file,
+        locator: KeyLocator::new(&file.text),
+        rules,
+        findings: Vec::new(),
+    };
+
+    match &manifest {
+        Manifest::Installer(installer_manifest) => linter.installers(installer_manifest),
+        Manifest::DefaultLocale(manifest) => {
+            linter.short_description(&manifest.short_description);
+            linter.tags(manifest.tags.iter());
+            linter.package_name(&manifest.package_name, manifest.package_version.as_str());
+            linter.release_notes(manifest.release_notes.as_ref());
+        }
+        Manifest::Locale(manifest) => {
+            if let Some(short_description) = &manifest.short_description {
+                linter.short_description(short_description);
+            }
+            linter.tags(manifest.tags.iter());
+            if let Some(package_name) = &manifest.package_name {
+                linter.package_name(package_name, manifest.package_version.as_str());
+            }
+            linter.release_notes(manifest.release_notes.as_ref());
+        }
+        Manifest::Version(_) => {}
+    }
+
+    linter.findings
+}
+
+/// Applies fixes to the text of a manifest, preserving its line endings.
+///
+/// Returns `None` if the fixed manifest can no longer be deserialized, in which case the fixes
+/// weren't safe to apply.
+pub fn apply_fixes<'fix, I>(file: &ManifestFile, fixes: I) -> Option<String>
+where
+    I: IntoIterator<Item = &'fix Fix>,
+{
+    let replacements = fixes
+        .into_iter()
+        .map(|fix| (fix.line, fix.replacement.as_str()))
+        .collect::<BTreeMap<_, _>>();
+
+    let text = file
+        .text
+        .split_inclusive('\n')
+        .enumerate()
+        .map(|(index, line)| match replacements.get(&(index + 1)) {
+            Some(replacement) => {
+                let content = line.trim_end_matches(['\r', '\n']);
+                format!("{replacement}{}", &line[content.len()..])
+            }
+            None => line.to_owned(),
+        })
+        .collect::<String>();
+
+    let fixed = ManifestFile {
+        path: file.path.clone(),
+        text,
+    };
+    parse(&fixed).ok().map(|_| fixed.text)
+}
+
+struct Linter<'file> {
+    file: &'file ManifestFile,
+    locator: KeyLocator<'file>,
+    rules: &'file LintRules,
+    findings: Vec<Finding>,
+}
+
+impl Linter<'_> {
+    fn report(&mut self, rule: Rule, line: Option<usize>, message: String, fix: Option<Fix>) {
+        let Some(severity) = self.rules.level(rule).severity() else {
+            return;
+        };
+
+        self.findings.push(Finding {
+            diagnostic: Diagnostic::new(&self.file.path, severity, rule.id(), message)
+                .at_line(line),
+            fix,
+        });
+    }
+
+    fn short_description(&mut self, short_description: &ShortDescription) {
+        let short_description = short_description.as_str().trim_end();
+        if !short_description.ends_with('.') || short_description.ends_with("..") {
+            return;
+        }
+
+        let line = self.locator.key("ShortDescription");
+        let fix = line.and_then(|line| {
+            let text = self.locator.line(line)?;
+            let value = text["ShortDescription:".len()..].trim();
+            if value.starts_with(['|', '>']) {
+                return None;
+            }
+
+            // Remove the period, keeping any closing quote after it
+            let period = text.rfind('.')?;
+            text[period + 1..]
+                .trim_end()
+                .chars()
+                .all(|char| matches!(char, '"' | '\''))
+                .then(|| Fix {
+                    line,
+                    replacement: format!("{}{}", &text[..period], &text[period + 1..]),
+                })
+        });
+
+        self.report(
+            Rule::ShortDescriptionPeriod,
+            line,
+            String::from("ShortDescription should not end with a period"),
+            fix,
+        );
+    }
+
+    fn tags<'tag, I>(&mut self, tags: I)
+    where
+        I: Iterator<Item = &'tag Tag>,
+    {
+        for tag in tags {
+            let tag = tag.as_str();
+            if !tag
+                .chars()
+                .any(|char| char.is_whitespace() || char.is_uppercase())
+            {
+                continue;
+            }
+
+            let fixed = tag.to_lowercase().split_whitespace().join("-");
+            let item = self.locator.list_item("Tags", tag);
+            let fix = item.and_then(|line| {
+                Some(Fix {
+                    line,
+                    replacement: self.locator.line(line)?.replacen(tag, &fixed, 1),
+                })
+            });
+
+            self.report(
+                Rule::TagFormat,
+                item.or_else(|| self.locator.key("Tags")),
+                format!(
+                    "Tag `{tag}` should be lowercase with hyphens instead of spaces, like `{fixed}`"
+                ),
+                fix,
+            );
+        }
+    }
+
+    fn package_name(&mut self, package_name: &PackageName, version: &str) {
+        if contains_version(package_name.as_str(), version) {
+            self.report(
+                Rule::PackageNameVersion,
+                self.locator.key("PackageName"),
+                format!("PackageName should not contain the package version {version}"),
+                None,
+            );
+        }
+    }
+
+    fn release_notes(&mut self, release_notes: Option<&ReleaseNotes>) {
+        /// Headings, emphasis, links, images, code fences and HTML tags.
+        static MARKDOWN: LazyLock<Regex> = LazyLock::new(|| {
+            Regex::new(r"(?m)^ {0,3}#{1,6} |\*\*\S|__\S|!?\[[^\]]*\]\([^)]*\)|^ {0,3}```|</?(?:br|p|ul|ol|li|h[1-6]|a)\b").unwrap()
+        });
+
+        if release_notes.is_some_and(|release_notes| MARKDOWN.is_match(release_notes.as_str())) {
+            self.report(
+                Rule::ReleaseNotesMarkdown,
+                self.locator.key("ReleaseNotes"),
+                String::from("ReleaseNotes contains Markdown, which winget shows as plain text"),
+                None,
+            );
+        }
+    }
+
+    fn installers(&mut self, manifest: &InstallerManifest) {
+        for (index, installer) in manifest.installers.iter().enumerate() {
+            let number = index + 1;
+
+            if installer.url.scheme() == "http" {
+                self.report(
+                    Rule::InsecureUrl,
+                    self.locator.installer_key(index, "InstallerUrl"),
+                    format!("Installer {number} is downloaded over http instead of https"),
+                    None,
+                );
+            }
+
+            let is_msi = matches!(
+                installer.r#type.or(manifest.r#type),
+                Some(InstallerType::Msi | InstallerType::Wix)
+            );
+            let has_product_code = installer
+                .product_code
+                .as_ref()
+                .or(manifest.product_code.as_ref())
+                .is_some()
+                || installer
+                    .apps_and_features_entries
+                    .iter()
+                    .chain(manifest.apps_and_features_entries.iter())
+                    .any(|entry| entry.product_code().is_some());
+            if is_msi && !has_product_code {
+                self.report(
+                    Rule::MsiProductCode,
+                    self.locator.installer_key(index, "ProductCode"),
+                    format!(
+                        "Installer {number} is an MSI but has no ProductCode, so winget can't tell whether it is installed"
+                    ),
+                    None,
+                );
+            }
+        }
+    }
+}
+
+/// Returns `true` if a package name contains a dotted version as a whole word, optionally
+/// prefixed with `v`.
+fn contains_version(name: &str, version: &str) -> bool {
+    let is_word = |char: char| char.is_alphanumeric() || char == '.';
+
+    version.contains('.')
+        && name.match_indices(version).any(|(index, _)| {
+            let before = name[..index].trim_end_matches(['v', 'V']);
+            let after = &name[index + version.len()..];
+            !before.chars().next_back().is_some_and(is_word)
+                && !after.chars().next().is_some_and(is_word)
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use camino::Utf8PathBuf;
+    use indoc::{formatdoc, indoc};
+    use rstest::rstest;
+
+    use super::{
+        LintRules, Rule, RuleLevel, apply_fixes, contains_version, lint, parse_rule_level,
+    };
+    use crate::manifests::{diagnostic::Severity, validation::ManifestFile};
+
+    fn default_locale(fields: &str) -> ManifestFile {
+        ManifestFile {
+            path: Utf8PathBuf::from("Foo.Bar.locale.en-US.yaml"),
+            text: formatdoc! {"
+                PackageIdentifier: Foo.Bar
+                PackageVersion: 1.2.3
+                PackageLocale: en-US
+                Publisher: Foo
+                License: MIT
+                {fields}
+                ManifestType: defaultLocale
+                ManifestVersion: 1.12.0
+            "},
+        }
+    }
+
+    fn codes(file: &ManifestFile, rules: &LintRules) -> Vec<&'static str> {
+        lint(file, rules)
+            .into_iter()
+            .map(|finding| finding.diagnostic.code)
+            .collect()
+    }
+
+    #[test]
+    fn clean_manifest() {
+        let file = default_locale(indoc! {"
+            PackageName: Bar
+            ShortDescription: A package...
+            Tags:
+            - command-line
+            ReleaseNotes: |-
+              - Fixed a crash
+        "});
+
+        assert!(lint(&file, &LintRules::default()).is_empty());
+    }
+
+    #[test]
+    fn finds_problems() {
+        let file = default_locale(indoc! {"
+            PackageName: Bar v1.2.3
+            ShortDescription: A package.
+            Tags:
+            - Command Line
+            ReleaseNotes: |-
+              ## What's changed
+              - Fixed a crash
+        "});
+
+        assert_eq!(
+            codes(&file, &LintRules::default()),
+            [
+                "short-description-period",
+                "tag-format",
+                "package-name-version",
+                "release-notes-markdown",
+            ]
+        );
+    }
+
+    #[test]
+    fn configured_levels() {
+        let file = default_locale(indoc! {"
+            PackageName: Bar
+            ShortDescription: A package.
+            Tags:
+            - Command Line
+        "});
+        let mut rules = LintRules::default();
+        rules.insert(Rule::TagFormat, RuleLevel::Off);
+        rules.insert(Rule::ShortDescriptionPeriod, RuleLevel::Error);
+
+        let findings = lint(&file, &rules);
+
+        assert_eq!(findings.len(), 1);
+        assert_eq!(findings[0].diagnostic.code, "short-description-period");
+        assert_eq!(findings[0].diagnostic.severity, Severity::Error);
+    }
+
+    #[test]
+    fn fixes_problems() {
+        let file = default_locale(indoc! {r#"
+            PackageName: Bar
+            ShortDescription: "A package."
+            Tags:
+            - Command Line
+            - cli
+        "#});
+        let file = ManifestFile {
+            text: file.text.replace('\n', "\r\n"),
+            ..file
+        };
+
+        let findings = lint(&file, &LintRules::default());
+        let fixed = apply_fixes(
+            &file,
+            findings.iter().filter_map(|finding| finding.fix.as_ref()),
+        )
+        .unwrap();
+
+        assert_eq!(
+            fixed,
+            default_locale(indoc! {r#"
+                PackageName: Bar
+                ShortDescription: "A package"
+                Tags:
+                - command-line
+                - cli
+            "#})
+            .text
+            .replace('\n', "\r\n")
+        );
+    }
+
+    #[test]
+    fn installer_problems() {
+        let file = ManifestFile {
+            path: Utf8PathBuf::from("Foo.Bar.installer.yaml"),
+            text: String::from(indoc! {"
+                PackageIdentifier: Foo.Bar
+                PackageVersion: 1.2.3
+                InstallerType: msi
+                Installers:
+                - Architecture: x86
+                  InstallerUrl: http://example.com/installer-x86.msi
+                  InstallerSha256: 0000000000000000000000000000000000000000000000000000000000000000
+                  ProductCode: '{00000000-0000-0000-0000-000000000000}'
+                - Architecture: x64
+                  InstallerUrl: https://example.com/installer-x64.msi
+                  InstallerSha256: 0000000000000000000000000000000000000000000000000000000000000000
+                ManifestType: installer
+                ManifestVersion: 1.12.0
+            "}),
+        };
+
+        let lines = lint(&file, &LintRules::default())
+            .into_iter()
+            .map(|finding| (finding.diagnostic.code, finding.diagnostic.line))
+            .collect::<Vec<_>>();
+
+        assert_eq!(
+            lines,
+            [("insecure-url", Some(6)), ("msi-product-code", Some(9))]
+        );
+    }
+
+    #[rstest]
+    #[case("App 1.2.3", "1.2.3", true)]
+    #[case("App v1.2.3", "1.2.3", true)]
+    #[case("App (1.2.3)", "1.2.3", true)]
+    #[case("App 11.2.3", "1.2.3", false)]
+    #[case("App 1.2.34", "1.2.3", false)]
+    #[case("7-Zip", "7", false)]
+    fn version_in_package_name(#[case] name: &str, #[case] version: &str, #[case] expected: bool) {
+        assert_eq!(contains_version(name, version), expected);
+    }
+
+    #[test]
+    fn rule_level_override() {
+        assert_eq!(
+            parse_rule_level("tag-format=off"),
+            Ok((Rule::TagFormat, RuleLevel::Off))
+        );
+        assert!(parse_rule_level("tag-format").is_err());
+        assert!(parse_rule_level("unknown=error").is_err());
+    }
+}
@@ -1,10 +1,13 @@
+use serde::Serialize;
 use winget_types::{
-    PackageIdentifier, PackageVersion,
+    ManifestVersion, PackageIdentifier, PackageVersion,
     installer::InstallerManifest,
     locale::{DefaultLocaleManifest, LocaleManifest},
     version::VersionManifest,
 };
 
+#[derive(Serialize)]
+#[serde(untagged)]
 pub enum Manifest {
     Installer(InstallerManifest),
     DefaultLocale(DefaultLocaleManifest),
@@ -30,4 +33,13 @@ pub const fn package_version(&self) -> &PackageVersion {
             Self::Version(version) => &version.package_version,
         }
     }
+
+    pub const fn manifest_version(&self) -> ManifestVersion {
+        match self {
+            Self::Installer(installer) => installer.manifest_version,
+            Self::DefaultLocale(default_locale) => default_locale.manifest_version,
+            Self::Locale(locale) => locale.manifest_version,
+            Self::Version(version) => version.manifest_version,
+        }
+    }
 }
@@ -0,0 +1,638 @@
+//! Migration of the manifests of a package version to another manifest version.
+//!
+//! Fields that were renamed between manifest versions are renamed first, following
+//! [`RENAMED_FIELDS`]. Manifests are then read into winget-types' model of the schema, which
+//! converts fields that kept their name and meaning between manifest versions. A field that neither
+//! has is refused rather than dropped. The migrated manifests are compared with the originals field
+//! by field, so that every renamed, moved or converted field is reported.
+
+use std::{fmt, io::Write};
+
+use color_eyre::eyre::{Result, bail, eyre};
+use owo_colors::OwoColorize;
+use serde_yaml::Value;
+use winget_types::{ManifestVersion, PackageIdentifier, PackageVersion};
+
+use super::{
+    Manifests,
+    diagnostic::file_name,
+    diff::display,
+    manifest::Manifest,
+    singleton::is_singleton,
+    validation::{ManifestFile, SCHEMA_VERSIONS, parse, parse_singleton},
+};
+use crate::github::{
+    client::GitHubFile,
+    utils::{PackagePath, pull_request::pr_changes},
+};
+
+/// The manifest version that manifests written for winget's preview are read as.
+///
+/// Preview manifests hold a whole package version in one file and have no `ManifestVersion`.
+const PREVIEW: ManifestVersion = ManifestVersion::new(0, 1, 0);
+
+const MANIFEST_VERSION: &str = "ManifestVersion";
+
+/// Fields that were renamed, and the manifest version whose schema first used the new name.
+///
+/// A field is renamed when a manifest is migrated from an older manifest version to this one or a
+/// newer one. Nested fields are renamed in each installer too.
+const RENAMED_FIELDS: [(ManifestVersion, &str, &str); 10] = [
+    (ManifestVersion::new(1, 0, 0), "Id", "PackageIdentifier"),
+    (ManifestVersion::new(1, 0, 0), "Version", "PackageVersion"),
+    (ManifestVersion::new(1, 0, 0), "Name", "PackageName"),
+    (ManifestVersion::new(1, 0, 0), "AppMoniker", "Moniker"),
+    (
+        ManifestVersion::new(1, 0, 0),
+        "Description",
+        "ShortDescription",
+    ),
+    (ManifestVersion::new(1, 0, 0), "Homepage", "PackageUrl"),
+    (ManifestVersion::new(1, 0, 0), "Arch", "Architecture"),
+    (ManifestVersion::new(1, 0, 0), "Url", "InstallerUrl"),
+    (ManifestVersion::new(1, 0, 0), "Sha256", "InstallerSha256"),
+    (
+        ManifestVersion::new(1, 0, 0),
+        "Switches",
+        "InstallerSwitches",
+    ),
+];
+
+/// How a field changed when a manifest was migrated.
+#[derive(Debug, Eq, PartialEq)]
+pub enum FieldChange {
+    Added(String),
+    Removed(String),
+    Changed {
+        from: String,
+        to: String,
+    },
+    /// The field was moved to another path with the same value, such as from every installer to
+    /// the root of the installer manifest.
+    Moved(String),
+    /// The field was renamed in a newer manifest version.
+    Renamed(String),
+}
+
+/// A field of a manifest file that changed when it was migrated.
+#[derive(Debug, Eq, PartialEq)]
+pub struct FieldReport {
+    pub file: String,
+    /// The path of the field, such as `Installers[0].Scope`.
+    pub field: String,
+    pub change: FieldChange,
+}
+
+impl fmt::Display for FieldReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.change {
+            FieldChange::Added(value) => write!(f, "{} {}: {value}", "+".green(), self.field),
+            FieldChange::Removed(value) => write!(f, "{} {}: {value}", "-".red(), self.field),
+            FieldChange::Changed { from, to } => {
+                write!(f, "{} {}: {from} → {to}", "~".yellow(), self.field)
+            }
+            FieldChange::Moved(to) => write!(f, "{} {} moved to {to}", "→".blue(), self.field),
+            FieldChange::Renamed(to) => {
+                write!(f, "{} {} renamed to {to}", "→".blue(), self.field)
+            }
+        }
+    }
+}
+
+/// The manifests of a package version after they were migrated.
+pub struct Migration {
+    pub identifier: PackageIdentifier,
+    pub version: PackageVersion,
+    /// The paths and contents of the migrated manifests.
+    pub changes: Vec<(String, String)>,
+    pub report: Vec<FieldReport>,
+}
+
+impl Migration {
+    /// Prints the changed fields of each file.
+    pub fn print_report(&self) {
+        let mut lock = anstream::stdout().lock();
+
+        let mut previous_file = None;
+        for report in &self.report {
+            if previous_file != Some(&report.file) {
+                let _ = writeln!(lock, "{}", report.file.bold());
+                previous_file = Some(&report.file);
+            }
+            let _ = writeln!(lock, "  {report}");
+        }
+    }
+}
+
+/// Migrates the manifest files of a single package version to a manifest version.
+///
+/// # Errors
+///
+/// Returns an error if the manifest version has no published schema, if any of the files use a
+/// newer manifest version or have a field that isn't part of the schema, or if the files don't
+/// make up a complete package version.
+pub fn migrate(files: &[ManifestFile], to: ManifestVersion) -> Result<Migration> {
+    if !SCHEMA_VERSIONS.contains(&to) {
+        bail!(
+            "{to} is not a manifest version with a published schema. Valid versions are {}",
+            SCHEMA_VERSIONS
+                .map(|version| version.to_string())
+                .join(", ")
+        );
+    }
+
+    let mut installer = None;
+    let mut default_locale = None;
+    let mut locales = Vec::new();
+    let mut version = None;
+    let mut renamed = Vec::new();
+    let mut before = Vec::new();
+    for file in files {
+        let name = file_name(&file.path);
+        let (converted, renames) = rename_fields(file, to)?;
+        renamed.extend(renames.into_iter().map(|(field, to)| FieldReport {
+            file: name.to_owned(),
+            field,
+            change: FieldChange::Renamed(to),
+        }));
+
+        let (manifests, unknown_fields) = if is_singleton(&converted.text) {
+            parse_singleton(&converted).map(|(manifests, unknown)| (Vec::from(manifests), unknown))
+        } else {
+            parse(&converted).map(|(manifest, unknown)| (vec![manifest], unknown))
+        }
+        .map_err(|diagnostic| eyre!("{name}: {}", diagnostic.message))?;
+
+        if let Some((field, _)) = unknown_fields.first() {
+            bail!("{name}: `{field}` is not a field of the schema, so migrating would remove it");
+        }
+        before.push((name, converted.text));
+
+        for manifest in manifests {
+            let current = manifest.manifest_version();
+            if current > to {
+                bail!("{name} uses manifest version {current}, which is newer than {to}");
+            }
+            match manifest {
+                Manifest::Installer(mut manifest) => {
+                    // Hoist values that are the same for every installer to the root. This also
+                    // resets the manifest version, so it is set afterwards
+                    manifest.optimize();
+                    manifest.manifest_version = to;
+                    installer = Some(manifest);
+                }
+                Manifest::DefaultLocale(mut manifest) => {
+                    manifest.manifest_version = to;
+                    default_locale = Some(manifest);
+                }
+                Manifest::Locale(mut manifest) => {
+                    manifest.manifest_version = to;
+                    locales.push(manifest);
+                }
+                Manifest::Version(mut manifest) => {
+                    manifest.manifest_version = to;
+                    version = Some(manifest);
+                }
+            }
+        }
+    }
+
+    let (Some(installer), Some(default_locale), Some(version)) =
+        (installer, default_locale, version)
+    else {
+        bail!("A package version needs a version, installer and default locale manifest");
+    };
+
+    let manifests = Manifests {
+        installer,
+        default_locale,
+        locales,
+        version,
+    };
+
+    let identifier = manifests.version.package_identifier.clone();
+    let package_version = manifests.version.package_version.clone();
+    let package_path = PackagePath::new(&identifier, Some(&package_version), None);
+
+    let original = files
+        .iter()
+        .map(|file| GitHubFile::new(file_name(&file.path), file.text.as_str()))
+        .collect::<Vec<_>>();
+    let changes = pr_changes()
+        .package_identifier(&identifier)
+        .manifests(&manifests)
+        .package_path(&package_path)
+        .original(&original)
+        .create()?;
+
+    // Renamed fields are compared under their new name, so they are only reported as renamed
+    let mut report = renamed;
+    for (name, text) in before {
+        let Some((_, content)) = changes
+            .iter()
+            .find(|(path, _)| path.rsplit('/').next() == Some(name))
+        else {
+            continue;
+        };
+
+        let (Ok(before), Ok(after)) = (
+            serde_yaml::from_str::<Value>(&text),
+            serde_yaml::from_str::<Value>(content),
+        ) else {
+            continue;
+        };
+
+        report.extend(
+            compare(&before, &after)
+                .into_iter()
+                .map(|(field, change)| FieldReport {
+                    file: name.to_owned(),
+                    field,
+                    change,
+                }),
+        );
+    }
+
+    Ok(Migration {
+        identifier,
+        version: package_version,
+        changes,
+        report,
+    })
+}
+
+/// Renames the fields of a manifest file that were renamed after its manifest version, up to the
+/// manifest version being migrated to, returning the renamed file and the paths of the fields that
+/// were renamed along with their new names.
+///
+/// A preview manifest is also given the manifest type and version of a 1.0.0 singleton manifest.
+/// Preview manifests have no locale, so they are read as `en-US`, which winget-pkgs' preview
+/// manifests were written in.
+fn rename_fields(
+    file: &ManifestFile,
+    to: ManifestVersion,
+) -> Result<(ManifestFile, Vec<(String, String)>)> {
+    fn rename(
+        fields: &mut Value,
+        path: &str,
+        from: &str,
+        to: &str,
+        renames: &mut Vec<(String, String)>,
+    ) {
+        let Value::Mapping(fields) = fields else {
+            return;
+        };
+        if let Some(value) = fields.remove(from) {
+            fields.insert(Value::from(to), value);
+            renames.push((format!("{path}{from}"), format!("{path}{to}")));
+        }
+    }
+
+    let name = file_name(&file.path);
+    let mut fields =
+        serde_yaml::from_str::<Value>(&file.text).map_err(|error| eyre!("{name}: {error}"))?;
+    let current = match fields.get(MANIFEST_VERSION).and_then(Value::as_str) {
+        Some(version) => version
+            .parse::<ManifestVersion>()
+            .map_err(|error| eyre!("{name}: {error}"))?,
+        None => PREVIEW,
+    };
+
+    let mut renames = Vec::new();
+    for &(version, from, new) in &RENAMED_FIELDS {
+        if current < version && version <= to {
+            rename(&mut fields, "", from, new, &mut renames);
+            if let Some(Value::Sequence(installers)) = fields.get_mut("Installers") {
+                for (index, installer) in installers.iter_mut().enumerate() {
+                    rename(
+                        installer,
+                        &format!("Installers[{index}]."),
+                        from,
+                        new,
+                        &mut renames,
+                    );
+                }
+            }
+        }
+    }
+
+    if current == PREVIEW
+        && let Value::Mapping(fields) = &mut fields
+    {
+        if !fields.contains_key("PackageLocale") {
+            fields.insert(Value::from("PackageLocale"), Value::from("en-US"));
+        }
+        fields.insert(Value::from("ManifestType"), Value::from("singleton"));
+        fields.insert(
+            Value::from(MANIFEST_VERSION),
+            Value::from(ManifestVersion::new(1, 0, 0).to_string()),
+        );
+    }
+
+    // Manifests without renamed fields are read from their own text, as writing the fields back
+    // out would change values such as unquoted versions like `1.10`
+    let text = if renames.is_empty() && current != PREVIEW {
+        file.text.clone()
+    } else {
+        serde_yaml::to_string(&fields)?
+    };
+
+    Ok((
+        ManifestFile {
+            path: file.path.clone(),
+            text,
+        },
+        renames,
+    ))
+}
+
+/// Compares two manifests field by field, returning the path of each field that changed.
+fn compare(before: &Value, after: &Value) -> Vec<(String, FieldChange)> {
+    fn walk(before: &Value, after: &Value, path: &str, changes: &mut Vec<(String, FieldChange)>) {
+        let join = |key: &str| {
+            if path.is_empty() {
+                key.to_owned()
+            } else {
+                format!("{path}.{key}")
+            }
+        };
+
+        match (before, after) {
+            (Value::Mapping(before), Value::Mapping(after)) => {
+                for (key, value) in before {
+                    let Some(name) = key.as_str() else {
+                        continue;
+                    };
+                    match after.get(key) {
+                        Some(after_value) => walk(value, after_value, &join(name), changes),
+                        None => changes.push((join(name), FieldChange::Removed(display(value)))),
+                    }
+                }
+                for (key, value) in after {
+                    if let Some(name) = key.as_str()
+                        && !before.contains_key(key)
+                    {
+                        changes.push((join(name), FieldChange::Added(display(value))));
+                    }
+                }
+            }
+            (Value::Sequence(before), Value::Sequence(after)) if before.len() == after.len() => {
+                for (index, (before, after)) in before.iter().zip(after).enumerate() {
+                    walk(before, after, &format!("{path}[{index}]"), changes);
+                }
+            }
+            _ if display(before) != display(after) => changes.push((
+                path.to_owned(),
+                FieldChange::Changed {
+                    from: display(before),
+                    to: display(after),
+                },
+            )),
+            _ => {}
+        }
+    }
+
+    let mut changes = Vec::new();
+    walk(before, after, "", &mut changes);
+
+    // A field that was removed from one path and added at another with the same name and value
+    // was moved there
+    let added = changes
+        .iter()
+        .filter_map(|(field, change)| match change {
+            FieldChange::Added(value) => Some((field.clone(), value.clone())),
+            _ => None,
+        })
+        .collect::<Vec<_>>();
+    let mut moved = Vec::new();
+    for (field, change) in &mut changes {
+        if let FieldChange::Removed(value) = change
+            && let Some((to, _)) = added
+                .iter()
+                .find(|(to, added)| added == value && key(to) == key(field))
+        {
+            moved.push(to.clone());
+            *change = FieldChange::Moved(to.clone());
+        }
+    }
+    changes.retain(|(field, change)| {
+        !(matches!(change, FieldChange::Added(_)) && moved.contains(field))
+    });
+
+    changes
+}
+
+/// Returns the last key of a field's path.
+fn key(field: &str) -> &str {
+    field.rsplit('.').next().unwrap_or(field)
+}
+
+#[cfg(test)]
+mod tests {
+    use camino::Utf8PathBuf;
+    use indoc::indoc;
+    use winget_types::ManifestVersion;
+
+    use super::{FieldChange, migrate};
+    use crate::manifests::validation::ManifestFile;
+
+    fn files() -> Vec<ManifestFile> {
+        [
+            (
+                "Foo.Bar.yaml",
+                indoc! {"
+                    PackageIdentifier: Foo.Bar
+                    PackageVersion: 1.2.3
+                    DefaultLocale: en-US
+                    ManifestType: version
+                    ManifestVersion: 1.4.0
+                "},
+            ),
+            (
+                "Foo.Bar.locale.en-US.yaml",
+                indoc! {"
+                    PackageIdentifier: Foo.Bar
+                    PackageVersion: 1.2.3
+                    PackageLocale: en-US
+                    Publisher: Foo
+                    PackageName: Bar
+                    License: MIT
+                    ShortDescription: A package
+                    ManifestType: defaultLocale
+                    ManifestVersion: 1.4.0
+                "},
+            ),
+            (
+                "Foo.Bar.installer.yaml",
+                indoc! {"
+                    PackageIdentifier: Foo.Bar
+                    PackageVersion: 1.2.3
+                    Installers:
+                    - Architecture: x86
+                      InstallerType: msi
+                      InstallerUrl: https://example.com/installer-x86.msi
+                      InstallerSha256: 0000000000000000000000000000000000000000000000000000000000000000
+                    - Architecture: x64
+                      InstallerType: msi
+                      InstallerUrl: https://example.com/installer-x64.msi
+                      InstallerSha256: 0000000000000000000000000000000000000000000000000000000000000000
+                    ManifestType: installer
+                    ManifestVersion: 1.4.0
+                "},
+            ),
+        ]
+        .into_iter()
+        .map(|(name, text)| ManifestFile {
+            path: Utf8PathBuf::from(name),
+            text: text.to_owned(),
+        })
+        .collect()
+    }
+
+    #[test]
+    fn reports_field_changes() {
+        let migration = migrate(&files(), ManifestVersion::DEFAULT).unwrap();
+
+        let installer = migration
+            .report
+            .iter()
+            .filter(|report| report.file == "Foo.Bar.installer.yaml")
+            .map(|report| (report.field.as_str(), &report.change))
+            .collect::<Vec<_>>();
+
+        assert_eq!(
+            installer,
+            [
+                (
+                    "Installers[0].InstallerType",
+                    &FieldChange::Moved(String::from("InstallerType"))
+                ),
+                (
+                    "Installers[1].InstallerType",
+                    &FieldChange::Moved(String::from("InstallerType"))
+                ),
+                (
+                    "ManifestVersion",
+                    &FieldChange::Changed {
+                        from: String::from("1.4.0"),
+                        to: String::from("1.12.0")
+                    }
+                ),
+            ]
+        );
+        assert_eq!(
+            migration
+                .report
+                .iter()
+                .filter(|report| report.file == "Foo.Bar.yaml")
+                .count(),
+            1
+        );
+    }
+
+    #[test]
+    fn writes_target_schema() {
+        let migration = migrate(&files(), ManifestVersion::new(1, 10, 0)).unwrap();
+
+        for (_, content) in &migration.changes {
+            assert!(content.starts_with("# Created with komac"));
+            assert!(content.contains(".1.10.0.schema.json"));
+            assert!(content.contains("ManifestVersion: 1.10.0"));
+        }
+    }
+
+    #[test]
+    fn keeps_header() {
+        let mut files = files();
+        files[0]
+            .text
+            .insert_str(0, "# Created with WinGetCreate v1.6.1.0\n");
+
+        let migration = migrate(&files, ManifestVersion::DEFAULT).unwrap();
+
+        let (_, version) = migration
+            .changes
+            .iter()
+            .find(|(path, _)| path.ends_with("/Foo.Bar.yaml"))
+            .unwrap();
+        assert!(version.starts_with(
+            "# Created with WinGetCreate v1.6.1.0\r\n# yaml-language-server: \
+             $schema=https://aka.ms/winget-manifest.version.1.12.0.schema.json\r\n"
+        ));
+    }
+
+    #[test]
+    fn refuses_to_remove_unknown_fields() {
+        let mut files = files();
+        files[2].text = files[2].text.replace(
+            "ManifestType: installer",
+            "Obsolete: true\nManifestType: installer",
+        );
+
+        let Err(error) = migrate(&files, ManifestVersion::DEFAULT) else {
+            panic!("migrating should fail");
+        };
+        assert!(error.to_string().contains("`Obsolete`"));
+    }
+
+    #[test]
+    fn renames_preview_fields() {
+        let preview = ManifestFile {
+            path: Utf8PathBuf::from("1.2.3.yaml"),
+            text: String::from(indoc! {"
+                Id: Foo.Bar
+                Version: 1.2.3
+                Name: Bar
+                Publisher: Foo
+                License: MIT
+                AppMoniker: bar
+                Description: A package
+                Homepage: https://example.com
+                InstallerType: msi
+                Installers:
+                - Arch: x64
+                  Url: https://example.com/installer.msi
+                  Sha256: 0000000000000000000000000000000000000000000000000000000000000000
+            "}),
+        };
+
+        let migration = migrate(&[preview], ManifestVersion::DEFAULT).unwrap();
+
+        let renamed = migration
+            .report
+            .iter()
+            .filter_map(|report| match &report.change {
+                FieldChange::Renamed(to) => Some((report.field.as_str(), to.as_str())),
+                _ => None,
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(
+            renamed,
+            [
+                ("Id", "PackageIdentifier"),
+                ("Version", "PackageVersion"),
+                ("Name", "PackageName"),
+                ("AppMoniker", "Moniker"),
+                ("Description", "ShortDescription"),
+                ("Homepage", "PackageUrl"),
+                ("Installers[0].Arch", "Installers[0].Architecture"),
+                ("Installers[0].Url", "Installers[0].InstallerUrl"),
+                ("Installers[0].Sha256", "Installers[0].InstallerSha256"),
+            ]
+        );
+        assert_eq!(migration.identifier.as_str(), "Foo.Bar");
+        assert_eq!(migration.changes.len(), 3);
+        let (_, installer) = migration
+            .changes
+            .iter()
+            .find(|(path, _)| path.ends_with(".installer.yaml"))
+            .unwrap();
+        assert!(installer.contains("InstallerUrl: https://example.com/installer.msi"));
+    }
+
+    #[test]
+    fn rejects_downgrades() {
+        assert!(migrate(&files(), ManifestVersion::new(1, 2, 0)).is_err());
+        assert!(migrate(&files(), ManifestVersion::new(1, 3, 0)).is_err());
+    }
+}
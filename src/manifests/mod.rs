@@ -14,14 +14,23 @@
 use tree_sitter_highlight::{Highlight, HighlightConfiguration, HighlightEvent, Highlighter};
 pub use url::Url;
 use winget_types::{
-    Manifest,
+    Manifest, ManifestVersion,
     installer::InstallerManifest,
     locale::{DefaultLocaleManifest, LocaleManifest},
     version::VersionManifest,
 };
 
+pub mod diagnostic;
+pub mod diff;
+pub mod edit;
+pub mod format;
+pub mod json;
+pub mod lint;
 pub mod manifest;
+pub mod migration;
+pub mod singleton;
 mod url;
+pub mod validation;
 
 pub struct Manifests {
     pub installer: InstallerManifest,
@@ -107,24 +116,66 @@ pub fn print_manifest(lock: &mut AutoStream<StdoutLock<'static>>, manifest: &str
     }
 }
 
+/// Writes a manifest that follows the schema of one of winget-types' manifest types.
+///
+/// See [`manifest_string`] for the comments that are written before it.
 pub fn build_manifest_string<T>(
     manifest: &T,
+    manifest_version: ManifestVersion,
     created_with: Option<&str>,
+    header: &[&str],
 ) -> serde_yaml::Result<String>
 where
     T: Manifest + Serialize,
 {
-    let mut result = String::from("# Created with ");
-    if let Some(created_with_tool) = created_with {
-        let _ = write!(result, "{created_with_tool} using ");
+    manifest_string(manifest, T::SCHEMA, manifest_version, created_with, header)
+}
+
+/// Writes a manifest after comments saying what created it and which schema it follows.
+///
+/// The schema is given for the latest manifest version and points at the schema of the manifest's
+/// own version instead, as a manifest that was read and written back keeps its manifest version.
+/// If the header, such as the comments at the top of the file the manifest was read from, isn't
+/// empty, it is written in place of the comment saying what created the manifest.
+pub fn manifest_string<T: Serialize>(
+    manifest: &T,
+    schema: &str,
+    manifest_version: ManifestVersion,
+    created_with: Option<&str>,
+    header: &[&str],
+) -> serde_yaml::Result<String> {
+    let mut result = String::new();
+    if header.is_empty() {
+        result.push_str("# Created with ");
+        if let Some(created_with_tool) = created_with {
+            let _ = write!(result, "{created_with_tool} using ");
+        }
+        let _ = writeln!(result, "{} v{}", crate_name!(), crate_version!());
     }
-    let _ = writeln!(result, "{} v{}", crate_name!(), crate_version!());
-    let _ = writeln!(result, "# yaml-language-server: $schema={}", T::SCHEMA);
+    for line in header {
+        let _ = writeln!(result, "{line}");
+    }
+
+    let schema = schema.replacen(
+        &format!(".{}.", ManifestVersion::DEFAULT),
+        &format!(".{manifest_version}."),
+        1,
+    );
+    let _ = writeln!(result, "# yaml-language-server: $schema={schema}");
     let _ = writeln!(result);
     let _ = write!(result, "{}", serde_yaml::to_string(manifest)?);
     Ok(convert_to_crlf(&result).into_owned())
 }
 
+/// Returns the comments at the top of a manifest, other than its schema comment.
+pub fn header(text: &str) -> Vec<&str> {
+    text.lines()
+        .map(str::trim_end)
+        .take_while(|line| line.is_empty() || line.starts_with('#'))
+        .filter(|line| line.starts_with('#') && !line.contains("yaml-language-server"))
+        .collect()
+}
+
 fn convert_to_crlf(input: &str) -> Cow<'_, str> {
     const CR: char = '\r';
     const LF: char = '\n';
@@ -0,0 +1,260 @@
+//! Singleton manifests, which hold the installer, default locale and version manifests of a
+//! package version with a single installer in one file.
+//!
+//! winget accepts singleton manifests, but winget-pkgs only accepts manifests split into separate
+//! files, so singleton manifests are split when they are read and are only written on request.
+
+use color_eyre::eyre::{Result, bail};
+use serde_yaml::{Mapping, Value};
+use winget_types::{
+    ManifestType, ManifestVersion, installer::InstallerManifest, locale::DefaultLocaleManifest,
+    version::VersionManifest,
+};
+
+use super::{diff::ManifestValues, manifest::Manifest, manifest_string};
+
+const MANIFEST_TYPE: &str = "ManifestType";
+const MANIFEST_VERSION: &str = "ManifestVersion";
+const SINGLETON: &str = "singleton";
+
+/// Returns `true` if the text of a manifest is a singleton manifest.
+pub fn is_singleton(text: &str) -> bool {
+    serde_yaml::from_str::<Value>(text)
+        .ok()
+        .and_then(|manifest| {
+            manifest
+                .get(MANIFEST_TYPE)
+                .and_then(Value::as_str)
+                .map(|r#type| r#type == SINGLETON)
+        })
+        .unwrap_or_default()
+}
+
+/// Splits a singleton manifest into the installer, default locale and version manifests that it
+/// holds.
+///
+/// # Errors
+///
+/// Returns an error if the installer or default locale fields of the manifest don't match their
+/// schema. The error is located at the line of the singleton manifest it is on.
+pub fn split(text: &str) -> serde_yaml::Result<[Manifest; 3]> {
+    let installer =
+        serde_yaml::from_str::<InstallerManifest>(&with_manifest_type(text, "installer"))?;
+    let default_locale =
+        serde_yaml::from_str::<DefaultLocaleManifest>(&with_manifest_type(text, "defaultLocale"))?;
+    let version = VersionManifest {
+        package_identifier: default_locale.package_identifier.clone(),
+        package_version: default_locale.package_version.clone(),
+        default_locale: default_locale.package_locale.clone(),
+        manifest_type: ManifestType::Version,
+        manifest_version: default_locale.manifest_version,
+    };
+
+    Ok([
+        Manifest::Installer(installer),
+        Manifest::DefaultLocale(default_locale),
+        Manifest::Version(version),
+    ])
+}
+
+/// Replaces the manifest type of a singleton manifest so that it can be deserialized as another
+/// type of manifest, which ignores the fields that aren't part of that type. Every line stays where
+/// it is, so that errors are reported at the right line.
+fn with_manifest_type(text: &str, r#type: &str) -> String {
+    text.lines()
+        .map(|line| {
+            if line.starts_with(MANIFEST_TYPE) && line[MANIFEST_TYPE.len()..].starts_with(':') {
+                format!("{MANIFEST_TYPE}: {type}")
+            } else {
+                line.to_owned()
+            }
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// Writes the manifests of a package version as a singleton manifest.
+///
+/// # Errors
+///
+/// Returns an error if the package version has more than one installer or a locale other than its
+/// default locale, as a singleton manifest can't hold them.
+pub fn build_singleton_string(
+    manifests: &ManifestValues,
+    created_with: Option<&str>,
+) -> Result<String> {
+    const INSTALLER_ROOT_FIELDS: [&str; 4] = [
+        "PackageIdentifier",
+        "PackageVersion",
+        MANIFEST_TYPE,
+        MANIFEST_VERSION,
+    ];
+
+    let installers = manifests
+        .installer
+        .get("Installers")
+        .and_then(Value::as_sequence)
+        .map_or(0, Vec::len);
+    if installers != 1 {
+        bail!("A singleton manifest can only have one installer but there are {installers}");
+    }
+    if !manifests.locales.is_empty() {
+        bail!(
+            "A singleton manifest can only have the default locale but there are {} other locales",
+            manifests.locales.len()
+        );
+    }
+
+    let empty = Mapping::new();
+    let default_locale = manifests.default_locale.as_mapping().unwrap_or(&empty);
+    let installer = manifests.installer.as_mapping().unwrap_or(&empty);
+
+    let mut singleton = default_locale
+        .iter()
+        .filter(|(key, _)| {
+            !key.as_str()
+                .is_some_and(|key| [MANIFEST_TYPE, MANIFEST_VERSION].contains(&key))
+        })
+        .chain(installer.iter().filter(|(key, _)| {
+            !key.as_str()
+                .is_some_and(|key| INSTALLER_ROOT_FIELDS.contains(&key))
+        }))
+        .map(|(key, value)| (key.clone(), value.clone()))
+        .collect::<Mapping>();
+
+    let manifest_version = installer.get(MANIFEST_VERSION).cloned().unwrap_or_default();
+    let schema = format!(
+        "https://aka.ms/winget-manifest.singleton.{}.schema.json",
+        ManifestVersion::DEFAULT
+    );
+    let version = manifest_version
+        .as_str()
+        .and_then(|version| version.parse().ok())
+        .unwrap_or_default();
+    singleton.insert(Value::from(MANIFEST_TYPE), Value::from(SINGLETON));
+    singleton.insert(Value::from(MANIFEST_VERSION), manifest_version);
+
+    Ok(manifest_string(
+        &singleton,
+        &schema,
+        version,
+        created_with,
+        &[],
+    )?)
+}
+
+#[cfg(test)]
+mod tests {
+    use indoc::indoc;
+    use winget_types::LanguageTag;
+
+    use super::{build_singleton_string, is_singleton, split};
+    use crate::manifests::{diff::ManifestValues, manifest::Manifest};
+
+    const SINGLETON: &str = indoc! {"
+        PackageIdentifier: Package.Identifier
+        PackageVersion: 1.2.3
+        PackageLocale: en-GB
+        Publisher: Publisher
+        PackageName: Package
+        License: MIT
+        ShortDescription: A package
+        Installers:
+        - Architecture: x64
+          InstallerType: msi
+          InstallerUrl: https://example.com/installer.msi
+          InstallerSha256: 0123456789ABCDEF0123456789ABCDEF0123456789ABCDEF0123456789ABCDEF
+        ManifestType: singleton
+        ManifestVersion: 1.10.0
+    "};
+
+    #[test]
+    fn splits_singleton_manifest() {
+        assert!(is_singleton(SINGLETON));
+
+        let [
+            Manifest::Installer(installer),
+            Manifest::DefaultLocale(default_locale),
+            Manifest::Version(version),
+        ] = split(SINGLETON).unwrap()
+        else {
+            panic!("Expected an installer, default locale and version manifest");
+        };
+
+        assert_eq!(installer.installers.len(), 1);
+        assert_eq!(default_locale.package_name.as_str(), "Package");
+        assert_eq!(
+            version.default_locale,
+            "en-GB".parse::<LanguageTag>().unwrap()
+        );
+        assert_eq!(version.manifest_version, installer.manifest_version);
+    }
+
+    #[test]
+    fn builds_singleton_manifest() {
+        let values = ManifestValues::from_changes(&[
+            (
+                String::from("Package.Identifier.installer.yaml"),
+                String::from(indoc! {"
+                    PackageIdentifier: Package.Identifier
+                    PackageVersion: 1.2.3
+                    InstallerType: msi
+                    Installers:
+                    - Architecture: x64
+                      InstallerUrl: https://example.com/installer.msi
+                    ManifestType: installer
+                    ManifestVersion: 1.12.0
+                "}),
+            ),
+            (
+                String::from("Package.Identifier.locale.en-US.yaml"),
+                String::from(indoc! {"
+                    PackageIdentifier: Package.Identifier
+                    PackageVersion: 1.2.3
+                    PackageLocale: en-US
+                    Publisher: Publisher
+                    ManifestType: defaultLocale
+                    ManifestVersion: 1.12.0
+                "}),
+            ),
+        ]);
+
+        let singleton = build_singleton_string(&values, None).unwrap();
+        let (header, manifest) = singleton.split_once("\r\n\r\n").unwrap();
+
+        assert!(header.ends_with(
+            "# yaml-language-server: $schema=https://aka.ms/winget-manifest.singleton.1.12.0.schema.json"
+        ));
+        assert_eq!(
+            manifest,
+            indoc! {"
+                PackageIdentifier: Package.Identifier
+                PackageVersion: 1.2.3
+                PackageLocale: en-US
+                Publisher: Publisher
+                InstallerType: msi
+                Installers:
+                - Architecture: x64
+                  InstallerUrl: https://example.com/installer.msi
+                ManifestType: singleton
+                ManifestVersion: 1.12.0
+            "}
+            .replace('\n', "\r\n")
+        );
+    }
+
+    #[test]
+    fn rejects_multiple_installers() {
+        let values = ManifestValues::from_changes(&[(
+            String::from("Package.Identifier.installer.yaml"),
+            String::from(indoc! {"
+                Installers:
+                - Architecture: x86
+                - Architecture: x64
+                ManifestType: installer
+            "}),
+        )]);
+
+        assert!(build_singleton_string(&values, None).is_err());
+    }
+}
@@ -0,0 +1,1051 @@
+//! Offline validation of the manifests of a package version, mirroring `winget validate`.
+//!
+//! Each file is first checked against the schema of its manifest type by deserializing it, which
+//! enforces required fields, value types, patterns and lengths. The manifests are then checked
+//! against the semantic rules that winget applies across the files of a package version.
+
+use std::{
+    collections::{BTreeMap, HashMap},
+    iter,
+};
+
+use camino::{Utf8Path, Utf8PathBuf};
+use serde::{Serialize, de::DeserializeOwned};
+use serde_yaml::{Mapping, Value};
+use winget_types::{
+    GenericManifest, LanguageTag, ManifestType, ManifestVersion, PackageIdentifier, PackageVersion,
+    installer::{InstallerManifest, InstallerType},
+    locale::{DefaultLocaleManifest, LocaleManifest},
+    version::VersionManifest,
+};
+
+use super::{
+    diagnostic::{Diagnostic, KeyLocator, file_name},
+    manifest::Manifest,
+    singleton::{is_singleton, split},
+};
+use crate::github::utils::is_manifest_file;
+
+/// The manifest versions that winget has published schemas for.
+///
+/// <https://github.com/microsoft/winget-cli/tree/master/schemas/JSON/manifests>
+pub const SCHEMA_VERSIONS: [ManifestVersion; 10] = [
+    ManifestVersion::new(1, 0, 0),
+    ManifestVersion::new(1, 1, 0),
+    ManifestVersion::new(1, 2, 0),
+    ManifestVersion::new(1, 4, 0),
+    ManifestVersion::new(1, 5, 0),
+    ManifestVersion::new(1, 6, 0),
+    ManifestVersion::new(1, 7, 0),
+    ManifestVersion::new(1, 9, 0),
+    ManifestVersion::new(1, 10, 0),
+    ManifestVersion::new(1, 12, 0),
+];
+
+/// Fields that were added to the schema after manifest version 1.0.0, and the manifest version
+/// that added them.
+///
+/// winget-types only models the latest schema, so a field that is listed here is reported if a
+/// manifest declares an older manifest version. Fields that aren't listed are only checked against
+/// the latest schema. Fields within `InstallerSwitches` and `ExpectedReturnCodes` are listed by
+/// their path, such as `InstallerSwitches.Repair`.
+pub(super) const FIELD_VERSIONS: [(&str, ManifestVersion); 27] = [
+    ("Markets", ManifestVersion::new(1, 1, 0)),
+    ("InstallerAbortsTerminal", ManifestVersion::new(1, 1, 0)),
+    ("ReleaseDate", ManifestVersion::new(1, 1, 0)),
+    ("InstallLocationRequired", ManifestVersion::new(1, 1, 0)),
+    ("RequireExplicitUpgrade", ManifestVersion::new(1, 1, 0)),
+    ("UnsupportedOSArchitectures", ManifestVersion::new(1, 1, 0)),
+    ("AppsAndFeaturesEntries", ManifestVersion::new(1, 1, 0)),
+    ("ElevationRequirement", ManifestVersion::new(1, 1, 0)),
+    ("ExpectedReturnCodes", ManifestVersion::new(1, 1, 0)),
+    ("Agreements", ManifestVersion::new(1, 1, 0)),
+    ("ReleaseNotes", ManifestVersion::new(1, 1, 0)),
+    ("ReleaseNotesUrl", ManifestVersion::new(1, 1, 0)),
+    ("DisplayInstallWarnings", ManifestVersion::new(1, 2, 0)),
+    ("UnsupportedArguments", ManifestVersion::new(1, 2, 0)),
+    (
+        "ExpectedReturnCodes.ReturnResponseUrl",
+        ManifestVersion::new(1, 2, 0),
+    ),
+    ("PurchaseUrl", ManifestVersion::new(1, 2, 0)),
+    ("InstallationNotes", ManifestVersion::new(1, 2, 0)),
+    ("Documentations", ManifestVersion::new(1, 2, 0)),
+    ("NestedInstallerType", ManifestVersion::new(1, 4, 0)),
+    ("NestedInstallerFiles", ManifestVersion::new(1, 4, 0)),
+    ("InstallationMetadata", ManifestVersion::new(1, 4, 0)),
+    ("Icons", ManifestVersion::new(1, 5, 0)),
+    ("DownloadCommandProhibited", ManifestVersion::new(1, 6, 0)),
+    ("RepairBehavior", ManifestVersion::new(1, 7, 0)),
+    ("InstallerSwitches.Repair", ManifestVersion::new(1, 7, 0)),
+    ("ArchiveBinariesDependOnPath", ManifestVersion::new(1, 9, 0)),
+    ("Authentication", ManifestVersion::new(1, 9, 0)),
+];
+
+/// The fields of a manifest file that aren't part of its schema, and the lines they are on.
+pub(super) type UnknownFields = Vec<(String, Option<usize>)>;
+
+/// The text of a manifest file.
+pub struct ManifestFile {
+    pub path: Utf8PathBuf,
+    pub text: String,
+}
+
+/// A manifest file that was successfully deserialized.
+struct Parsed<'file> {
+    file: &'file ManifestFile,
+    locator: KeyLocator<'file>,
+    manifest: Manifest,
+    /// Whether the manifest was split from a singleton manifest, in which case its file holds the
+    /// installer, default locale and version manifests.
+    singleton: bool,
+}
+
+impl Parsed<'_> {
+    fn error<M: Into<String>>(&self, key: &str, code: &'static str, message: M) -> Diagnostic {
+        Diagnostic::error(&self.file.path, code, message).at_line(self.locator.key(key))
+    }
+}
+
+/// Validates the manifest files of a single package version.
+pub fn validate(files: &[ManifestFile]) -> Vec<Diagnostic> {
+    let mut diagnostics = Vec::new();
+
+    let parsed = files
+        .iter()
+        .flat_map(|file| {
+            let singleton = is_singleton(&file.text);
+            let result = if singleton {
+                parse_singleton(file).map(|(manifests, unknown)| (Vec::from(manifests), unknown))
+            } else {
+                parse(file).map(|(manifest, unknown)| (vec![manifest], unknown))
+            };
+
+            match result {
+                Ok((manifests, unknown_fields)) => {
+                    diagnostics.extend(unknown_fields.into_iter().map(|(field, line)| {
+                        Diagnostic::warning(
+                            &file.path,
+                            "unknown-field",
+                            format!("`{field}` is not a field of the schema and will be ignored"),
+                        )
+                        .at_line(line)
+                    }));
+                    manifests
+                        .into_iter()
+                        .map(|manifest| Parsed {
+                            file,
+                            locator: KeyLocator::new(&file.text),
+                            manifest,
+                            singleton,
+                        })
+                        .collect()
+                }
+                Err(diagnostic) => {
+                    diagnostics.push(diagnostic);
+                    Vec::new()
+                }
+            }
+        })
+        .collect::<Vec<_>>();
+
+    let Some(directory) = files.first().and_then(|file| file.path.parent()) else {
+        return diagnostics;
+    };
+
+    check_schema_versions(&parsed, &mut diagnostics);
+    check_field_versions(&parsed, &mut diagnostics);
+    check_package(directory, &parsed, &mut diagnostics);
+
+    for parsed in &parsed {
+        if let Manifest::Installer(installer_manifest) = &parsed.manifest {
+            if parsed.singleton && installer_manifest.installers.len() > 1 {
+                diagnostics.push(parsed.error(
+                    "Installers",
+                    "singleton-installers",
+                    "A singleton manifest can only have one installer",
+                ));
+            }
+            check_installers(parsed, installer_manifest, &mut diagnostics);
+        }
+    }
+
+    diagnostics
+}
+
+/// Deserializes a manifest file into the type given by its `ManifestType`, returning any fields
+/// that aren't part of the schema alongside it.
+pub(super) fn parse(file: &ManifestFile) -> Result<(Manifest, UnknownFields), Diagnostic> {
+    fn typed<T>(
+        file: &ManifestFile,
+        wrap: fn(T) -> Manifest,
+    ) -> Result<(Manifest, UnknownFields), Diagnostic>
+    where
+        T: DeserializeOwned + Serialize,
+    {
+        let manifest =
+            serde_yaml::from_str::<T>(&file.text).map_err(|error| schema_error(file, &error))?;
+        let unknown = unknown_fields(file, &serde_yaml::to_value(&manifest).unwrap_or_default());
+        Ok((wrap(manifest), unknown))
+    }
+
+    match serde_yaml::from_str::<GenericManifest>(&file.text)
+        .map_err(|error| schema_error(file, &error))?
+        .r#type
+    {
+        ManifestType::Installer => typed::<InstallerManifest>(file, Manifest::Installer),
+        ManifestType::DefaultLocale => {
+            typed::<DefaultLocaleManifest>(file, Manifest::DefaultLocale)
+        }
+        ManifestType::Locale => typed::<LocaleManifest>(file, Manifest::Locale),
+        ManifestType::Version => typed::<VersionManifest>(file, Manifest::Version),
+    }
+}
+
+/// Deserializes a singleton manifest into the installer, default locale and version manifests that
+/// it holds.
+pub(super) fn parse_singleton(
+    file: &ManifestFile,
+) -> Result<([Manifest; 3], UnknownFields), Diagnostic> {
+    let manifests = split(&file.text).map_err(|error| schema_error(file, &error))?;
+
+    // The fields of a singleton manifest are split between its installer and default locale
+    // manifests, so a field is only unknown if it is in neither
+    let mut known = Mapping::new();
+    for manifest in &manifests[..2] {
+        if let Ok(Value::Mapping(fields)) = serde_yaml::to_value(manifest) {
+            known.extend(fields);
+        }
+    }
+
+    let unknown = unknown_fields(file, &Value::Mapping(known));
+    Ok((manifests, unknown))
+}
+
+fn schema_error(file: &ManifestFile, error: &serde_yaml::Error) -> Diagnostic {
+    // The location is reported separately, so remove it from the end of the message
+    let message = error.to_string();
+    let message = message
+        .rsplit_once(" at line ")
+        .map_or(message.as_str(), |(message, _)| message);
+
+    let diagnostic = Diagnostic::error(&file.path, "schema", message);
+    match error.location() {
+        Some(location) => diagnostic.at(location.line(), location.column()),
+        None => diagnostic,
+    }
+}
+
+/// Returns the fields of a manifest file that were ignored when it was deserialized, by comparing
+/// its keys to those of the deserialized manifest.
+///
+/// Fields that are empty or `false` aren't reported as they are omitted when the manifest is
+/// serialized even if they are part of the schema.
+fn unknown_fields(file: &ManifestFile, known: &Value) -> UnknownFields {
+    fn compare(
+        raw: &Value,
+        known: &Value,
+        path: &str,
+        line: &dyn Fn(&str) -> Option<usize>,
+        unknown: &mut UnknownFields,
+    ) {
+        let (Value::Mapping(raw), Value::Mapping(known)) = (raw, known) else {
+            return;
+        };
+
+        for (key, value) in raw {
+            let Some(name) = key.as_str() else {
+                continue;
+            };
+            let field = if path.is_empty() {
+                name.to_owned()
+            } else {
+                format!("{path}.{name}")
+            };
+
+            match known.get(key) {
+                Some(known_value) => compare(value, known_value, &field, line, unknown),
+                None if !is_empty(value) => unknown.push((field, line(name))),
+                None => {}
+            }
+        }
+    }
+
+    fn is_empty(value: &Value) -> bool {
+        match value {
+            Value::Null | Value::Bool(false) => true,
+            Value::String(string) => string.is_empty(),
+            Value::Sequence(sequence) => sequence.is_empty(),
+            Value::Mapping(mapping) => mapping.is_empty(),
+            _ => false,
+        }
+    }
+
+    let Ok(raw) = serde_yaml::from_str::<Value>(&file.text) else {
+        return Vec::new();
+    };
+
+    let locator = KeyLocator::new(&file.text);
+    let mut unknown = Vec::new();
+
+    compare(&raw, known, "", &|key| locator.key(key), &mut unknown);
+
+    // Installers are a list, so compare each one with the installer at the same index
+    if let (Some(Value::Sequence(raw_installers)), Some(Value::Sequence(known_installers))) =
+        (raw.get("Installers"), known.get("Installers"))
+    {
+        for (index, (raw, known)) in raw_installers.iter().zip(known_installers).enumerate() {
+            compare(
+                raw,
+                known,
+                &format!("Installers[{index}]"),
+                &|key| locator.installer_key(index, key),
+                &mut unknown,
+            );
+        }
+    }
+
+    unknown
+}
+
+/// Checks that every file uses a manifest version that has a published schema, and that all the
+/// files use the same one.
+fn check_schema_versions(parsed: &[Parsed], diagnostics: &mut Vec<Diagnostic>) {
+    for manifest in parsed {
+        let manifest_version = manifest.manifest.manifest_version();
+        if !SCHEMA_VERSIONS.contains(&manifest_version) {
+            diagnostics.push(manifest.error(
+                "ManifestVersion",
+                "manifest-version",
+                format!("{manifest_version} is not a published manifest schema version"),
+            ));
+        }
+    }
+
+    let Some(version_manifest) = parsed
+        .iter()
+        .find(|parsed| matches!(parsed.manifest, Manifest::Version(_)))
+    else {
+        return;
+    };
+
+    let expected = version_manifest.manifest.manifest_version();
+    for manifest in parsed {
+        if manifest.manifest.manifest_version() != expected {
+            diagnostics.push(manifest.error(
+                "ManifestVersion",
+                "manifest-version",
+                format!(
+                    "ManifestVersion {} does not match {expected} in the version manifest",
+                    manifest.manifest.manifest_version()
+                ),
+            ));
+        }
+    }
+}
+
+/// Checks that every field of a file is part of the schema of the manifest version it declares.
+fn check_field_versions(parsed: &[Parsed], diagnostics: &mut Vec<Diagnostic>) {
+    let mut check = |parsed: &Parsed, fields: &Value, line: &dyn Fn(&str) -> Option<usize>| {
+        let Value::Mapping(fields) = fields else {
+            return;
+        };
+        let manifest_version = parsed.manifest.manifest_version();
+        for (key, value) in fields {
+            let Some(key) = key.as_str() else {
+                continue;
+            };
+            // Nested fields are reported on the line of the field that holds them
+            let nested = match value {
+                Value::Mapping(nested) => vec![nested],
+                Value::Sequence(items) => items.iter().filter_map(Value::as_mapping).collect(),
+                _ => Vec::new(),
+            };
+            let paths = nested
+                .into_iter()
+                .flat_map(Mapping::keys)
+                .filter_map(Value::as_str)
+                .map(|nested_key| format!("{key}.{nested_key}"));
+
+            for field in iter::once(key.to_owned()).chain(paths) {
+                if let Some((_, added)) = FIELD_VERSIONS
+                    .iter()
+                    .find(|&&(name, added)| name == field && added > manifest_version)
+                {
+                    diagnostics.push(
+                        Diagnostic::error(
+                            &parsed.file.path,
+                            "field-version",
+                            format!(
+                                "`{field}` was added in manifest version {added}, so it isn't \
+                                 part of the {manifest_version} schema"
+                            ),
+                        )
+                        .at_line(line(key)),
+                    );
+                }
+            }
+        }
+    };
+
+    for parsed in parsed {
+        // The manifests of a singleton manifest share its file, so it is only checked once
+        if parsed.singleton && !matches!(parsed.manifest, Manifest::Installer(_)) {
+            continue;
+        }
+        let Ok(raw) = serde_yaml::from_str::<Value>(&parsed.file.text) else {
+            continue;
+        };
+
+        check(parsed, &raw, &|key| parsed.locator.key(key));
+        if let Some(Value::Sequence(installers)) = raw.get("Installers") {
+            for (index, installer) in installers.iter().enumerate() {
+                check(parsed, installer, &|key| {
+                    parsed.locator.installer_key(index, key)
+                });
+            }
+        }
+    }
+}
+
+/// Checks the rules that span the files of a package version: that there is exactly one version,
+/// installer and default locale manifest, that each file is named after what it contains, and that
+/// the package identifier and version are the same in every file.
+fn check_package(directory: &Utf8Path, parsed: &[Parsed], diagnostics: &mut Vec<Diagnostic>) {
+    let mut by_type = BTreeMap::<ManifestType, Vec<&Parsed>>::new();
+    for manifest in parsed {
+        let r#type = match manifest.manifest {
+            Manifest::Installer(_) => ManifestType::Installer,
+            Manifest::DefaultLocale(_) => ManifestType::DefaultLocale,
+            Manifest::Locale(_) => ManifestType::Locale,
+            Manifest::Version(_) => ManifestType::Version,
+        };
+        by_type.entry(r#type).or_default().push(manifest);
+    }
+
+    for r#type in [
+        ManifestType::Version,
+        ManifestType::Installer,
+        ManifestType::DefaultLocale,
+    ] {
+        match by_type.get(&r#type).map(Vec::as_slice) {
+            None | Some([]) => diagnostics.push(Diagnostic::error(
+                directory,
+                "missing-manifest",
+                format!("There is no {type} manifest", type = type_name(r#type)),
+            )),
+            Some([_]) => {}
+            Some([_, duplicates @ ..]) => {
+                diagnostics.extend(duplicates.iter().map(|duplicate| {
+                    duplicate.error(
+                        "ManifestType",
+                        "multiple-manifests",
+                        format!("There is more than one {} manifest", type_name(r#type)),
+                    )
+                }));
+            }
+        }
+    }
+
+    let Some(Manifest::Version(version_manifest)) = by_type
+        .get(&ManifestType::Version)
+        .and_then(|manifests| manifests.first())
+        .map(|parsed| &parsed.manifest)
+    else {
+        return;
+    };
+
+    let identifier = &version_manifest.package_identifier;
+    let version = &version_manifest.package_version;
+    let default_locale = &version_manifest.default_locale;
+
+    let mut locales = HashMap::<&LanguageTag, &Utf8Path>::new();
+    for manifest in parsed {
+        check_identity(manifest, identifier, version, diagnostics);
+        check_file_name(manifest, identifier, default_locale, diagnostics);
+
+        match &manifest.manifest {
+            Manifest::DefaultLocale(default_locale_manifest)
+                if default_locale_manifest.package_locale != *default_locale =>
+            {
+                diagnostics.push(manifest.error(
+                    "PackageLocale",
+                    "default-locale",
+                    format!(
+                        "The default locale manifest is for {} but the version manifest's DefaultLocale is {default_locale}",
+                        default_locale_manifest.package_locale
+                    ),
+                ));
+            }
+            Manifest::Locale(locale_manifest) => {
+                let locale = &locale_manifest.package_locale;
+                if locale == default_locale {
+                    diagnostics.push(manifest.error(
+                        "PackageLocale",
+                        "default-locale",
+                        format!("{locale} is the default locale so it must be in a defaultLocale manifest"),
+                    ));
+                } else if let Some(other) = locales.insert(locale, &manifest.file.path) {
+                    diagnostics.push(manifest.error(
+                        "PackageLocale",
+                        "duplicate-locale",
+                        format!("{locale} is also in {}", file_name(other)),
+                    ));
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
+fn check_identity(
+    manifest: &Parsed,
+    identifier: &PackageIdentifier,
+    version: &PackageVersion,
+    diagnostics: &mut Vec<Diagnostic>,
+) {
+    if manifest.manifest.package_identifier() != identifier {
+        diagnostics.push(manifest.error(
+            "PackageIdentifier",
+            "identifier-mismatch",
+            format!(
+                "PackageIdentifier {} does not match {identifier} in the version manifest",
+                manifest.manifest.package_identifier()
+            ),
+        ));
+    }
+
+    if manifest.manifest.package_version() != version {
+        diagnostics.push(manifest.error(
+            "PackageVersion",
+            "version-mismatch",
+            format!(
+                "PackageVersion {} does not match {version} in the version manifest",
+                manifest.manifest.package_version()
+            ),
+        ));
+    }
+}
+
+fn check_file_name(
+    manifest: &Parsed,
+    identifier: &PackageIdentifier,
+    default_locale: &LanguageTag,
+    diagnostics: &mut Vec<Diagnostic>,
+) {
+    // A singleton manifest is named like a version manifest, which is checked with its version
+    // manifest
+    if manifest.singleton && !matches!(manifest.manifest, Manifest::Version(_)) {
+        return;
+    }
+
+    let name = file_name(&manifest.file.path);
+    let default_locale = Some(default_locale);
+
+    let (is_valid, expected) = match &manifest.manifest {
+        Manifest::Installer(_) => (
+            is_manifest_file::<InstallerManifest>(name, identifier, None),
+            format!("{identifier}.installer.yaml"),
+        ),
+        Manifest::DefaultLocale(default_locale_manifest) => (
+            is_manifest_file::<DefaultLocaleManifest>(name, identifier, default_locale),
+            format!(
+                "{identifier}.locale.{}.yaml",
+                default_locale_manifest.package_locale
+            ),
+        ),
+        Manifest::Locale(locale_manifest) => (
+            is_manifest_file::<LocaleManifest>(name, identifier, default_locale)
+                && name
+                    == format!(
+                        "{identifier}.locale.{}.yaml",
+                        locale_manifest.package_locale
+                    ),
+            format!(
+                "{identifier}.locale.{}.yaml",
+                locale_manifest.package_locale
+            ),
+        ),
+        Manifest::Version(_) => (
+            is_manifest_file::<VersionManifest>(name, identifier, None),
+            format!("{identifier}.yaml"),
+        ),
+    };
+
+    if !is_valid {
+        diagnostics.push(Diagnostic::error(
+            &manifest.file.path,
+            "file-name",
+            format!("The file should be named {expected}"),
+        ));
+    }
+}
+
+/// Checks that every installer has a type, that no two installers can be chosen for the same
+/// system, and that each installer has the fields its type requires.
+///
+/// Fields at the root of the installer manifest apply to every installer that doesn't set them.
+fn check_installers(
+    parsed: &Parsed,
+    manifest: &InstallerManifest,
+    diagnostics: &mut Vec<Diagnostic>,
+) {
+    let mut seen = HashMap::new();
+
+    for (index, installer) in manifest.installers.iter().enumerate() {
+        let number = index + 1;
+        let error = |key: &str, code: &'static str, message: String| {
+            Diagnostic::error(&parsed.file.path, code, message)
+                .at_line(parsed.locator.installer_key(index, key))
+        };
+
+        let r#type = installer.r#type.or(manifest.r#type);
+        let scope = installer.scope.or(manifest.scope);
+        let locale = installer.locale.as_ref().or(manifest.locale.as_ref());
+
+        if let Some(other) = seen.insert((installer.architecture, r#type, scope, locale), number) {
+            diagnostics.push(error(
+                "Architecture",
+                "duplicate-installer",
+                format!(
+                    "Installer {number} has the same architecture, installer type, scope and locale as installer {other}"
+                ),
+            ));
+        }
+
+        let Some(r#type) = r#type else {
+            diagnostics.push(error(
+                "InstallerType",
+                "installer-type",
+                format!("Installer {number} has no InstallerType"),
+            ));
+            continue;
+        };
+
+        let nested_type = installer
+            .nested_installer_type
+            .or(manifest.nested_installer_type)
+            .map(InstallerType::from);
+        let nested_files = if installer.nested_installer_files.is_empty() {
+            &manifest.nested_installer_files
+        } else {
+            &installer.nested_installer_files
+        };
+
+        if r#type == InstallerType::Zip {
+            match nested_type {
+                None => diagnostics.push(error(
+                    "NestedInstallerType",
+                    "nested-installer",
+                    format!("Installer {number} is a zip but has no NestedInstallerType"),
+                )),
+                Some(InstallerType::Portable) => {}
+                Some(_) if nested_files.len() != 1 => diagnostics.push(error(
+                    "NestedInstallerFiles",
+                    "nested-installer",
+                    format!(
+                        "Installer {number} must have exactly one NestedInstallerFiles entry as its nested installer isn't portable"
+                    ),
+                )),
+                Some(_) => {}
+            }
+
+            if nested_files.is_empty() {
+                diagnostics.push(error(
+                    "NestedInstallerFiles",
+                    "nested-installer",
+                    format!("Installer {number} is a zip but has no NestedInstallerFiles"),
+                ));
+            }
+
+            if nested_type != Some(InstallerType::Portable)
+                && nested_files
+                    .iter()
+                    .any(|file| file.portable_command_alias.is_some())
+            {
+                diagnostics.push(error(
+                    "NestedInstallerFiles",
+                    "nested-installer",
+                    format!(
+                        "Installer {number} has a PortableCommandAlias but its nested installer isn't portable"
+                    ),
+                ));
+            }
+        } else if !nested_files.is_empty() || installer.nested_installer_type.is_some() {
+            diagnostics.push(error(
+                "NestedInstallerType",
+                "nested-installer",
+                format!("Installer {number} is not a zip so it can't have a nested installer"),
+            ));
+        }
+
+        let is_exe = r#type == InstallerType::Exe
+            || (r#type == InstallerType::Zip && nested_type == Some(InstallerType::Exe));
+        let switches = (
+            installer.switches.silent().or(manifest.switches.silent()),
+            installer
+                .switches
+                .silent_with_progress()
+                .or(manifest.switches.silent_with_progress()),
+        );
+        if is_exe && matches!(switches, (None, _) | (_, None)) {
+            diagnostics.push(error(
+                "InstallerSwitches",
+                "silent-switches",
+                format!(
+                    "Installer {number} is an exe so it needs both Silent and SilentWithProgress switches"
+                ),
+            ));
+        }
+
+        let commands = if installer.commands.is_empty() {
+            &manifest.commands
+        } else {
+            &installer.commands
+        };
+        if r#type == InstallerType::Portable && commands.len() > 1 {
+            diagnostics.push(error(
+                "Commands",
+                "portable-commands",
+                format!("Installer {number} is portable so it can have at most one command"),
+            ));
+        }
+    }
+}
+
+const fn type_name(r#type: ManifestType) -> &'static str {
+    match r#type {
+        ManifestType::Installer => "installer",
+        ManifestType::DefaultLocale => "defaultLocale",
+        ManifestType::Locale => "locale",
+        ManifestType::Version => "version",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use camino::Utf8PathBuf;
+    use indoc::{formatdoc, indoc};
+    use rstest::rstest;
+
+    use super::{ManifestFile, validate};
+
+    const VERSION: &str = indoc! {"
+        PackageIdentifier: Foo.Bar
+        PackageVersion: 1.2.3
+        DefaultLocale: en-US
+        ManifestType: version
+        ManifestVersion: 1.12.0
+    "};
+
+    const DEFAULT_LOCALE: &str = indoc! {"
+        PackageIdentifier: Foo.Bar
+        PackageVersion: 1.2.3
+        PackageLocale: en-US
+        Publisher: Foo
+        PackageName: Bar
+        License: MIT
+        ShortDescription: A package
+        ManifestType: defaultLocale
+        ManifestVersion: 1.12.0
+    "};
+
+    fn file(name: &str, text: &str) -> ManifestFile {
+        ManifestFile {
+            path: Utf8PathBuf::from(format!("manifests/f/Foo/Bar/1.2.3/{name}")),
+            text: text.to_owned(),
+        }
+    }
+
+    fn package(installers: &str) -> Vec<ManifestFile> {
+        vec![
+            file("Foo.Bar.yaml", VERSION),
+            file("Foo.Bar.locale.en-US.yaml", DEFAULT_LOCALE),
+            file(
+                "Foo.Bar.installer.yaml",
+                &formatdoc! {"
+                    PackageIdentifier: Foo.Bar
+                    PackageVersion: 1.2.3
+                    {installers}
+                    ManifestType: installer
+                    ManifestVersion: 1.12.0
+                "},
+            ),
+        ]
+    }
+
+    fn codes(files: &[ManifestFile]) -> Vec<&'static str> {
+        validate(files)
+            .into_iter()
+            .map(|diagnostic| diagnostic.code)
+            .collect()
+    }
+
+    #[test]
+    fn valid_package() {
+        let files = package(indoc! {"
+            InstallerType: msi
+            Installers:
+            - Architecture: x86
+              InstallerUrl: https://example.com/installer-x86.msi
+              InstallerSha256: 0000000000000000000000000000000000000000000000000000000000000000
+            - Architecture: x64
+              InstallerUrl: https://example.com/installer-x64.msi
+              InstallerSha256: 0000000000000000000000000000000000000000000000000000000000000000
+        "});
+
+        assert_eq!(validate(&files), Vec::new());
+    }
+
+    #[rstest]
+    #[case::duplicate_installer(
+        indoc! {"
+            InstallerType: msi
+            Installers:
+            - Architecture: x64
+              InstallerUrl: https://example.com/installer.msi
+              InstallerSha256: 0000000000000000000000000000000000000000000000000000000000000000
+            - Architecture: x64
+              InstallerUrl: https://example.com/other.msi
+              InstallerSha256: 0000000000000000000000000000000000000000000000000000000000000000
+        "},
+        &["duplicate-installer"]
+    )]
+    #[case::missing_installer_type(
+        indoc! {"
+            Installers:
+            - Architecture: x64
+              InstallerUrl: https://example.com/installer.msi
+              InstallerSha256: 0000000000000000000000000000000000000000000000000000000000000000
+        "},
+        &["installer-type"]
+    )]
+    #[case::zip_without_nested_installer(
+        indoc! {"
+            Installers:
+            - Architecture: x64
+              InstallerType: zip
+              InstallerUrl: https://example.com/installer.zip
+              InstallerSha256: 0000000000000000000000000000000000000000000000000000000000000000
+        "},
+        &["nested-installer", "nested-installer"]
+    )]
+    #[case::exe_without_silent_switches(
+        indoc! {"
+            InstallerType: exe
+            Installers:
+            - Architecture: x64
+              InstallerUrl: https://example.com/installer.exe
+              InstallerSha256: 0000000000000000000000000000000000000000000000000000000000000000
+        "},
+        &["silent-switches"]
+    )]
+    #[case::root_switches_apply_to_installers(
+        indoc! {"
+            InstallerType: exe
+            InstallerSwitches:
+              Silent: /S
+              SilentWithProgress: /S
+            Installers:
+            - Architecture: x64
+              InstallerUrl: https://example.com/installer.exe
+              InstallerSha256: 0000000000000000000000000000000000000000000000000000000000000000
+        "},
+        &[]
+    )]
+    #[case::unknown_field(
+        indoc! {"
+            InstallerType: msi
+            Installers:
+            - Architecture: x64
+              InstallerUrl: https://example.com/installer.msi
+              InstallerSha256: 0000000000000000000000000000000000000000000000000000000000000000
+              InstallerHash: abc
+        "},
+        &["unknown-field"]
+    )]
+    fn installer_rules(#[case] installers: &str, #[case] expected: &[&str]) {
+        assert_eq!(codes(&package(installers)), expected);
+    }
+
+    #[test]
+    fn package_rules() {
+        let mut files = package(indoc! {"
+            InstallerType: msi
+            Installers:
+            - Architecture: x64
+              InstallerUrl: https://example.com/installer.msi
+              InstallerSha256: 0000000000000000000000000000000000000000000000000000000000000000
+        "});
+        files[0].text = VERSION.replace("1.2.3", "1.2.4");
+        files[1].path.set_file_name("Foo.Bar.locale.en-GB.yaml");
+        files[2].text = files[2].text.replace("1.12.0", "1.3.0");
+
+        let diagnostics = validate(&files);
+        let codes = diagnostics
+            .iter()
+            .map(|diagnostic| (diagnostic.code, diagnostic.line))
+            .collect::<Vec<_>>();
+
+        assert_eq!(
+            codes,
+            [
+                ("manifest-version", Some(10)),
+                ("manifest-version", Some(10)),
+                ("version-mismatch", Some(2)),
+                ("file-name", None),
+                ("version-mismatch", Some(2)),
+            ]
+        );
+    }
+
+    #[rstest]
+    #[case::added_later("1.0.0", &[("field-version", Some(8))])]
+    #[case::added_before("1.1.0", &[])]
+    fn field_versions(#[case] manifest_version: &str, #[case] expected: &[(&str, Option<usize>)]) {
+        let mut files = package(indoc! {"
+            InstallerType: msi
+            Installers:
+            - Architecture: x64
+              InstallerUrl: https://example.com/installer.msi
+              InstallerSha256: 0000000000000000000000000000000000000000000000000000000000000000
+              ElevationRequirement: elevatesSelf
+        "});
+        for file in &mut files {
+            file.text = file.text.replace("1.12.0", manifest_version);
+        }
+
+        let diagnostics = validate(&files);
+        let codes = diagnostics
+            .iter()
+            .map(|diagnostic| (diagnostic.code, diagnostic.line))
+            .collect::<Vec<_>>();
+
+        assert_eq!(codes, expected);
+    }
+
+    #[rstest]
+    #[case::v1_1("ReleaseDate: 2024-01-01", "", "1.0.0", "1.1.0")]
+    #[case::v1_2(
+        indoc! {"
+            ExpectedReturnCodes:
+            - InstallerReturnCode: 1
+              ReturnResponse: installInProgress
+              ReturnResponseUrl: https://example.com/help
+        "},
+        "",
+        "1.1.0",
+        "1.2.0"
+    )]
+    #[case::v1_4(
+        indoc! {"
+            InstallationMetadata:
+              DefaultInstallLocation: '%ProgramFiles%/Bar'
+        "},
+        "",
+        "1.2.0",
+        "1.4.0"
+    )]
+    #[case::v1_5(
+        "",
+        indoc! {"
+            Icons:
+            - IconUrl: https://example.com/icon.png
+              IconFileType: png
+        "},
+        "1.4.0",
+        "1.5.0"
+    )]
+    #[case::v1_6("DownloadCommandProhibited: true", "", "1.5.0", "1.6.0")]
+    #[case::v1_7_repair_behavior("RepairBehavior: modify", "", "1.6.0", "1.7.0")]
+    #[case::v1_7_repair_switch(
+        indoc! {"
+            InstallerSwitches:
+              Repair: /repair
+        "},
+        "",
+        "1.6.0",
+        "1.7.0"
+    )]
+    #[case::v1_9("ArchiveBinariesDependOnPath: true", "", "1.7.0", "1.9.0")]
+    fn fields_added_in_each_version(
+        #[case] installer_fields: &str,
+        #[case] locale_fields: &str,
+        #[case] before: &str,
+        #[case] added: &str,
+    ) {
+        let field_versions = |manifest_version: &str| {
+            let mut files = package(&formatdoc! {"
+                InstallerType: msi
+                {installer_fields}
+                Installers:
+                - Architecture: x64
+                  InstallerUrl: https://example.com/installer.msi
+                  InstallerSha256: 0000000000000000000000000000000000000000000000000000000000000000
+            "});
+            files[1].text = DEFAULT_LOCALE.replace(
+                "ManifestType: defaultLocale",
+                &format!("{locale_fields}\nManifestType: defaultLocale"),
+            );
+            for file in &mut files {
+                file.text = file.text.replace("1.12.0", manifest_version);
+            }
+            codes(&files)
+                .into_iter()
+                .filter(|&code| code == "field-version")
+                .count()
+        };
+
+        assert_eq!(field_versions(before), 1);
+        assert_eq!(field_versions(added), 0);
+    }
+
+    #[rstest]
+    #[case::valid(1, &[])]
+    #[case::multiple_installers(2, &["singleton-installers"])]
+    fn singleton_manifest(#[case] installers: usize, #[case] expected: &[&str]) {
+        let installers = (0..installers)
+            .map(|index| {
+                formatdoc! {"
+                    - Architecture: {}
+                      InstallerUrl: https://example.com/installer.msi
+                      InstallerSha256: 0000000000000000000000000000000000000000000000000000000000000000
+                ", ["x64", "arm64"][index]}
+            })
+            .collect::<String>();
+        let singleton = formatdoc! {"
+            PackageIdentifier: Foo.Bar
+            PackageVersion: 1.2.3
+            PackageLocale: en-US
+            Publisher: Foo
+            PackageName: Bar
+            License: MIT
+            ShortDescription: A package
+            InstallerType: msi
+            Installers:
+            {installers}ManifestType: singleton
+            ManifestVersion: 1.12.0
+        "};
+
+        assert_eq!(codes(&[file("Foo.Bar.yaml", &singleton)]), expected);
+    }
+
+    #[test]
+    fn schema_error_location() {
+        let mut files = package(indoc! {"
+            InstallerType: msi
+            Installers:
+            - Architecture: x64
+              InstallerUrl: https://example.com/installer.msi
+              InstallerSha256: 0000000000000000000000000000000000000000000000000000000000000000
+        "});
+        files[1].text = DEFAULT_LOCALE.replace("Publisher: Foo", "Publisher: [Foo]");
+
+        let diagnostics = validate(&files);
+
+        assert_eq!(diagnostics[0].code, "schema");
+        assert_eq!(diagnostics[0].line, Some(4));
+        assert!(!diagnostics[0].message.contains(" at line "));
+        assert_eq!(diagnostics[1].code, "missing-manifest");
+    }
+}
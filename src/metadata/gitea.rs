@@ -0,0 +1,180 @@
+use serde::Deserialize;
+use url::Url;
+use winget_types::{
+    PackageVersion,
+    locale::{License, Publisher, ReleaseNotes, Tag},
+};
+
+use super::{MetadataError, get_json};
+use crate::{
+    github::client::{GitHubValues, ReleaseAsset},
+    release_notes::{
+        forge::{ForgeRelease, gitea_release, gitea_releases_url},
+        get_text,
+    },
+    traits::FromMarkdown,
+};
+
+/// <https://docs.gitea.com/api#tag/repository/operation/repoGet>
+#[derive(Deserialize)]
+struct Repository {
+    #[serde(default)]
+    description: String,
+    html_url: Url,
+    #[serde(default)]
+    has_issues: bool,
+    external_tracker: Option<ExternalTracker>,
+    #[serde(default)]
+    topics: Vec<String>,
+    /// The SPDX identifiers of the licenses found in the repository, which older versions of Gitea
+    /// and Forgejo don't detect.
+    #[serde(default)]
+    licenses: Vec<String>,
+    owner: Owner,
+}
+
+#[derive(Deserialize)]
+struct ExternalTracker {
+    external_tracker_url: String,
+}
+
+#[derive(Deserialize)]
+struct Owner {
+    login: String,
+    #[serde(default)]
+    full_name: String,
+    html_url: Option<String>,
+}
+
+impl Repository {
+    fn into_values(self, release: Option<ForgeRelease>) -> Result<GitHubValues, url::ParseError> {
+        let html_url = self.html_url.as_str().trim_end_matches('/');
+        let publisher_url = self.owner.html_url.unwrap_or_else(|| {
+            format!(
+                "{}/{}",
+                self.html_url.origin().ascii_serialization(),
+                self.owner.login
+            )
+        });
+        let issues_url = match self.external_tracker {
+            Some(tracker) => Some(tracker.external_tracker_url),
+            None => self.has_issues.then(|| format!("{html_url}/issues")),
+        };
+
+        Ok(GitHubValues {
+            description: Some(self.description)
+                .filter(|description| !description.trim().is_empty()),
+            publisher: Publisher::new(if self.owner.full_name.trim().is_empty() {
+                self.owner.login
+            } else {
+                self.owner.full_name
+            })
+            .ok(),
+            publisher_url: publisher_url.parse().ok(),
+            issues_url: issues_url.and_then(|url| url.parse().ok()),
+            // A repository with several licenses is left for the license to be entered, as it
+            // isn't known whether they apply together or are alternatives
+            license: match <[String; 1]>::try_from(self.licenses) {
+                Ok([license]) => License::new(license).ok(),
+                Err(_) => None,
+            },
+            license_url: None,
+            package_url: html_url.parse()?,
+            release_notes: release
+                .as_ref()
+                .and_then(|release| ReleaseNotes::from_markdown(&release.notes)),
+            release_notes_url: release.and_then(|release| release.url?.parse().ok()),
+            topics: self.topics.into_iter().flat_map(Tag::new).collect(),
+        })
+    }
+}
+
+/// Returns the API URL of the repository that a release asset belongs to, such as
+/// `https://codeberg.org/api/v1/repos/owner/repo` for
+/// `https://codeberg.org/owner/repo/releases/download/v1.2.3/installer.exe`.
+fn repository_api_url(url: &Url) -> Option<Url> {
+    let asset = ReleaseAsset::from_url(url)?;
+    let mut api_url = url.clone();
+    api_url.set_path(&format!("api/v1/repos/{}/{}", asset.owner, asset.repo));
+    api_url.set_query(None);
+    api_url.set_fragment(None);
+    Some(api_url)
+}
+
+pub async fn get_values(
+    url: &Url,
+    version: &PackageVersion,
+) -> Result<Option<GitHubValues>, MetadataError> {
+    let Some(api_url) = repository_api_url(url) else {
+        return Ok(None);
+    };
+
+    let repository = get_json::<Repository>(api_url).await?;
+    let release = match gitea_releases_url(&repository.html_url) {
+        Some(releases_url) => gitea_release(&get_text(releases_url).await?, version)?,
+        None => None,
+    };
+
+    Ok(Some(repository.into_values(release)?))
+}
+
+#[cfg(test)]
+mod tests {
+    use indoc::indoc;
+    use url::Url;
+
+    use super::{Repository, repository_api_url};
+
+    #[test]
+    fn api_url_from_installer_url() {
+        let url =
+            Url::parse("https://codeberg.org/owner/repo/releases/download/v1.2.3/installer.exe")
+                .unwrap();
+
+        assert_eq!(
+            repository_api_url(&url).unwrap().as_str(),
+            "https://codeberg.org/api/v1/repos/owner/repo"
+        );
+    }
+
+    #[test]
+    fn maps_repository_to_values() {
+        const REPOSITORY: &str = indoc! {r#"
+            {
+              "id": 12345,
+              "description": "A tool that does things",
+              "html_url": "https://codeberg.org/owner/repo",
+              "has_issues": true,
+              "topics": ["cli"],
+              "licenses": ["MIT"],
+              "owner": {
+                "login": "owner",
+                "full_name": "",
+                "html_url": "https://codeberg.org/owner"
+              }
+            }
+        "#};
+
+        let values = serde_json::from_str::<Repository>(REPOSITORY)
+            .unwrap()
+            .into_values(None)
+            .unwrap();
+
+        assert_eq!(
+            values.description.as_deref(),
+            Some("A tool that does things")
+        );
+        assert_eq!(values.publisher.unwrap().as_str(), "owner");
+        assert_eq!(
+            values.publisher_url.unwrap().as_str(),
+            "https://codeberg.org/owner"
+        );
+        assert_eq!(values.license.unwrap().as_str(), "MIT");
+        assert_eq!(
+            values.issues_url.unwrap().as_str(),
+            "https://codeberg.org/owner/repo/issues"
+        );
+        assert_eq!(values.release_notes, None);
+        assert_eq!(values.topics.len(), 1);
+    }
+}
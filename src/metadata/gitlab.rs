@@ -0,0 +1,210 @@
+use percent_encoding::{NON_ALPHANUMERIC, percent_decode_str, utf8_percent_encode};
+use serde::Deserialize;
+use url::Url;
+use winget_types::{
+    PackageVersion,
+    locale::{License, Publisher, ReleaseNotes, Tag},
+};
+
+use super::{MetadataError, get_json, license::spdx_id};
+use crate::{
+    github::client::GitHubValues,
+    release_notes::{
+        forge::{ForgeRelease, gitlab_release, gitlab_releases_url},
+        get_text,
+    },
+    traits::FromMarkdown,
+};
+
+/// <https://docs.gitlab.com/api/projects/#get-a-single-project>
+#[derive(Deserialize)]
+struct Project {
+    description: Option<String>,
+    web_url: Url,
+    #[serde(default)]
+    topics: Vec<String>,
+    #[serde(default)]
+    issues_enabled: bool,
+    license_url: Option<String>,
+    license: Option<ProjectLicense>,
+    namespace: Namespace,
+}
+
+#[derive(Deserialize)]
+struct ProjectLicense {
+    key: String,
+    name: String,
+}
+
+#[derive(Deserialize)]
+struct Namespace {
+    name: String,
+    web_url: String,
+}
+
+impl Project {
+    fn into_values(self, release: Option<ForgeRelease>) -> Result<GitHubValues, url::ParseError> {
+        let web_url = self.web_url.as_str().trim_end_matches('/');
+        Ok(GitHubValues {
+            description: self
+                .description
+                .filter(|description| !description.trim().is_empty()),
+            publisher: Publisher::new(self.namespace.name).ok(),
+            publisher_url: self.namespace.web_url.parse().ok(),
+            issues_url: self
+                .issues_enabled
+                .then(|| format!("{web_url}/-/issues").parse().ok())
+                .flatten(),
+            license: self
+                .license
+                .filter(|license| license.key != "other")
+                .and_then(|license| {
+                    License::new(spdx_id(&license.key).map_or(license.name, str::to_owned)).ok()
+                }),
+            license_url: self.license_url.and_then(|url| url.parse().ok()),
+            package_url: web_url.parse()?,
+            release_notes: release
+                .as_ref()
+                .and_then(|release| ReleaseNotes::from_markdown(&release.notes)),
+            release_notes_url: release.and_then(|release| release.url?.parse().ok()),
+            topics: self.topics.into_iter().flat_map(Tag::new).collect(),
+        })
+    }
+}
+
+/// Returns the API URL of the project that an installer URL belongs to, such as
+/// `https://gitlab.com/api/v4/projects/group%2Fproject?license=true` for
+/// `https://gitlab.com/group/project/-/releases/v1.2.3/downloads/installer.exe`.
+///
+/// Packages in the generic package registry are already under the API URL of their project.
+fn project_api_url(url: &Url) -> Option<Url> {
+    let segments = url.path_segments()?.collect::<Vec<_>>();
+    let project = match segments.as_slice() {
+        ["api", "v4", "projects", project, ..] => (*project).to_owned(),
+        _ => {
+            let end = segments
+                .iter()
+                .position(|segment| matches!(*segment, "-" | "uploads"))?;
+            if end < 2 {
+                return None;
+            }
+            let path = percent_decode_str(&segments[..end].join("/"))
+                .decode_utf8_lossy()
+                .into_owned();
+            utf8_percent_encode(&path, NON_ALPHANUMERIC).to_string()
+        }
+    };
+
+    let mut api_url = url.clone();
+    api_url.set_path(&format!("api/v4/projects/{project}"));
+    api_url.set_query(Some("license=true"));
+    api_url.set_fragment(None);
+    Some(api_url)
+}
+
+pub async fn get_values(
+    url: &Url,
+    version: &PackageVersion,
+) -> Result<Option<GitHubValues>, MetadataError> {
+    let Some(api_url) = project_api_url(url) else {
+        return Ok(None);
+    };
+
+    let project = get_json::<Project>(api_url).await?;
+    let release = match gitlab_releases_url(&project.web_url) {
+        Some(releases_url) => gitlab_release(&get_text(releases_url).await?, version)?,
+        None => None,
+    };
+
+    Ok(Some(project.into_values(release)?))
+}
+
+#[cfg(test)]
+mod tests {
+    use indoc::indoc;
+    use rstest::rstest;
+    use url::Url;
+
+    use super::{Project, project_api_url};
+    use crate::release_notes::forge::ForgeRelease;
+
+    #[rstest]
+    #[case(
+        "https://gitlab.com/group/project/-/releases/v1.2.3/downloads/installer.exe",
+        Some("https://gitlab.com/api/v4/projects/group%2Fproject?license=true")
+    )]
+    #[case(
+        "https://gitlab.com/group/subgroup/project/uploads/0123abcd/installer.exe",
+        Some("https://gitlab.com/api/v4/projects/group%2Fsubgroup%2Fproject?license=true")
+    )]
+    #[case(
+        "https://gitlab.com/api/v4/projects/12345/packages/generic/app/1.2.3/installer.exe",
+        Some("https://gitlab.com/api/v4/projects/12345?license=true")
+    )]
+    #[case("https://gitlab.com/installer.exe", None)]
+    fn api_url_from_installer_url(#[case] url: &str, #[case] expected: Option<&str>) {
+        assert_eq!(
+            project_api_url(&Url::parse(url).unwrap())
+                .as_ref()
+                .map(Url::as_str),
+            expected
+        );
+    }
+
+    #[test]
+    fn maps_project_to_values() {
+        const PROJECT: &str = indoc! {r#"
+            {
+              "id": 12345,
+              "description": "A tool that does things",
+              "web_url": "https://gitlab.com/group/project",
+              "topics": ["cli", "windows"],
+              "issues_enabled": true,
+              "license_url": "https://gitlab.com/group/project/-/blob/main/LICENSE",
+              "license": {
+                "key": "apache-2.0",
+                "name": "Apache License 2.0",
+                "nickname": null
+              },
+              "namespace": {
+                "name": "Group",
+                "web_url": "https://gitlab.com/groups/group"
+              }
+            }
+        "#};
+
+        let values = serde_json::from_str::<Project>(PROJECT)
+            .unwrap()
+            .into_values(Some(ForgeRelease {
+                notes: String::from("- Fixed a crash"),
+                url: Some(String::from(
+                    "https://gitlab.com/group/project/-/releases/v1.2.3",
+                )),
+            }))
+            .unwrap();
+
+        assert_eq!(
+            values.description.as_deref(),
+            Some("A tool that does things")
+        );
+        assert_eq!(values.publisher.unwrap().as_str(), "Group");
+        assert_eq!(values.license.unwrap().as_str(), "Apache-2.0");
+        assert_eq!(
+            values.issues_url.unwrap().as_str(),
+            "https://gitlab.com/group/project/-/issues"
+        );
+        assert_eq!(
+            values.package_url.as_str(),
+            "https://gitlab.com/group/project"
+        );
+        assert_eq!(values.release_notes.unwrap().as_str(), "- Fixed a crash");
+        assert_eq!(
+            values
+                .topics
+                .iter()
+                .map(|tag| tag.as_str())
+                .collect::<Vec<_>>(),
+            ["cli", "windows"]
+        );
+    }
+}
@@ -0,0 +1,58 @@
+/// SPDX identifiers of common licenses, with the other keys that forges know them by.
+///
+/// GitLab identifies licenses by the lowercase keys of [Licensee], which match the SPDX
+/// identifier apart from case, while SourceForge uses short names of its own. The identifiers are
+/// those that GitHub reports, so a license reads the same whichever forge a package is on.
+///
+/// [Licensee]: https://github.com/licensee/licensee
+const LICENSES: &[(&str, &[&str])] = &[
+    ("AGPL-3.0", &["agplv3"]),
+    ("Apache-2.0", &["apache2"]),
+    ("BSD-2-Clause", &[]),
+    ("BSD-3-Clause", &[]),
+    ("BSL-1.0", &[]),
+    ("CC0-1.0", &[]),
+    ("EPL-2.0", &[]),
+    ("GPL-2.0", &["gpl", "gplv2"]),
+    ("GPL-3.0", &["gplv3"]),
+    ("ISC", &[]),
+    ("LGPL-2.0", &["lgpl", "lgplv2"]),
+    ("LGPL-2.1", &[]),
+    ("LGPL-3.0", &["lgplv3"]),
+    ("MIT", &[]),
+    ("MPL-1.1", &["mpl11"]),
+    ("MPL-2.0", &["mpl20"]),
+    ("Unlicense", &[]),
+    ("WTFPL", &[]),
+    ("Zlib", &[]),
+];
+
+/// Returns the SPDX identifier of a license from a forge's key for it, such as `Apache-2.0` for
+/// GitLab's `apache-2.0` or SourceForge's `apache2`.
+pub fn spdx_id(key: &str) -> Option<&'static str> {
+    LICENSES
+        .iter()
+        .find(|(id, aliases)| {
+            id.eq_ignore_ascii_case(key)
+                || aliases.iter().any(|alias| alias.eq_ignore_ascii_case(key))
+        })
+        .map(|(id, _)| *id)
+}
+
+#[cfg(test)]
+mod tests {
+    use rstest::rstest;
+
+    use super::spdx_id;
+
+    #[rstest]
+    #[case("mit", Some("MIT"))]
+    #[case("apache-2.0", Some("Apache-2.0"))]
+    #[case("apache2", Some("Apache-2.0"))]
+    #[case("gplv3", Some("GPL-3.0"))]
+    #[case("bsd-3-clause", Some("BSD-3-Clause"))]
+    #[case("other", None)]
+    fn maps_to_spdx_id(#[case] key: &str, #[case] expected: Option<&str>) {
+        assert_eq!(spdx_id(key), expected);
+    }
+}
@@ -0,0 +1,154 @@
+//! Package metadata from forges other than GitHub.
+//!
+//! When none of a package's installers are released on GitHub, its description, license, topics,
+//! issue tracker and release notes are read from the forge that hosts the installers instead. The
+//! forge is chosen by the host of the installer URLs, and its metadata is mapped into the same
+//! [`GitHubValues`] that a GitHub repository's metadata is, so it fills the locale manifests alike.
+
+mod gitea;
+mod gitlab;
+mod license;
+mod sourceforge;
+
+use serde::de::DeserializeOwned;
+use thiserror::Error;
+use tracing::warn;
+use url::Url;
+use winget_types::PackageVersion;
+
+use crate::{github::client::GitHubValues, release_notes::get_text};
+
+/// A forge that package metadata can be read from.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Provider {
+    /// GitLab.com or a self-hosted GitLab instance, such as `gitlab.gnome.org`.
+    GitLab,
+    /// Gitea or Forgejo, such as Codeberg.
+    Gitea,
+    SourceForge,
+}
+
+impl Provider {
+    /// Returns the forge that hosts a URL.
+    ///
+    /// Self-hosted GitLab, Gitea and Forgejo instances are only recognised when their host starts
+    /// with `gitlab.`, `gitea.` or `forgejo.`.
+    pub fn from_url(url: &Url) -> Option<Self> {
+        let host = url.host_str()?.to_ascii_lowercase();
+        if host == "sourceforge.net" || host.ends_with(".sourceforge.net") {
+            Some(Self::SourceForge)
+        } else if host == "gitlab.com" || host.starts_with("gitlab.") {
+            Some(Self::GitLab)
+        } else if matches!(host.as_str(), "codeberg.org" | "gitea.com")
+            || host.starts_with("gitea.")
+            || host.starts_with("forgejo.")
+        {
+            Some(Self::Gitea)
+        } else {
+            None
+        }
+    }
+}
+
+#[derive(Debug, Error)]
+pub enum MetadataError {
+    #[error(transparent)]
+    Reqwest(#[from] reqwest::Error),
+    #[error("Failed to read project metadata: {0}")]
+    Json(#[from] serde_json::Error),
+    #[error(transparent)]
+    UrlParse(#[from] url::ParseError),
+}
+
+/// Reads the metadata of a package from the forge that the first of its installer URLs on a
+/// recognised forge is hosted on, returning `None` if none of them are.
+///
+/// # Errors
+///
+/// Returns an error if the forge's API couldn't be read.
+pub async fn get_values(
+    installer_urls: &[&Url],
+    version: &PackageVersion,
+) -> Result<Option<GitHubValues>, MetadataError> {
+    let Some((provider, url)) = installer_urls
+        .iter()
+        .find_map(|url| Some((Provider::from_url(url)?, *url)))
+    else {
+        return Ok(None);
+    };
+
+    match provider {
+        Provider::GitLab => gitlab::get_values(url, version).await,
+        Provider::Gitea => gitea::get_values(url, version).await,
+        Provider::SourceForge => sourceforge::get_values(url).await,
+    }
+}
+
+/// Reads the metadata of a package like [`get_values`], but logs a warning and returns `None` if
+/// the forge couldn't be read.
+///
+/// Forge metadata only fills in optional locale fields, so a private self-hosted instance, a
+/// project that the API doesn't know about or an outage doesn't stop a manifest from being made.
+pub async fn get_values_or_warn(
+    installer_urls: &[&Url],
+    version: &PackageVersion,
+) -> Option<GitHubValues> {
+    warn_on_error(get_values(installer_urls, version).await)
+}
+
+/// Logs a warning and returns `None` if the metadata of a package couldn't be read.
+fn warn_on_error(values: Result<Option<GitHubValues>, MetadataError>) -> Option<GitHubValues> {
+    values
+        .inspect_err(|error| warn!(%error, "Failed to read package metadata from its forge"))
+        .ok()
+        .flatten()
+}
+
+async fn get_json<T: DeserializeOwned>(url: Url) -> Result<T, MetadataError> {
+    Ok(serde_json::from_str(&get_text(url).await?)?)
+}
+
+#[cfg(test)]
+mod tests {
+    use rstest::rstest;
+    use url::Url;
+
+    use super::{MetadataError, Provider, warn_on_error};
+
+    #[rstest]
+    #[case(
+        "https://gitlab.com/group/project/-/releases/v1.2.3/downloads/installer.exe",
+        Some(Provider::GitLab)
+    )]
+    #[case(
+        "https://gitlab.gnome.org/GNOME/project/-/releases/1.2.3/downloads/installer.exe",
+        Some(Provider::GitLab)
+    )]
+    #[case(
+        "https://codeberg.org/owner/repo/releases/download/v1.2.3/installer.exe",
+        Some(Provider::Gitea)
+    )]
+    #[case(
+        "https://downloads.sourceforge.net/project/name/1.2.3/installer.exe",
+        Some(Provider::SourceForge)
+    )]
+    #[case(
+        "https://sourceforge.net/projects/name/files/1.2.3/installer.exe/download",
+        Some(Provider::SourceForge)
+    )]
+    #[case(
+        "https://github.com/owner/repo/releases/download/v1.2.3/installer.exe",
+        None
+    )]
+    #[case("https://example.com/installer.exe", None)]
+    fn provider_from_url(#[case] url: &str, #[case] expected: Option<Provider>) {
+        assert_eq!(Provider::from_url(&Url::parse(url).unwrap()), expected);
+    }
+
+    #[test]
+    fn unreadable_forge_is_skipped() {
+        let error = serde_json::from_str::<serde_json::Value>("<html>").unwrap_err();
+
+        assert!(warn_on_error(Err(MetadataError::Json(error))).is_none());
+    }
+}
@@ -0,0 +1,176 @@
+use serde::Deserialize;
+use url::Url;
+use winget_types::locale::{License, Tag};
+
+use super::{MetadataError, get_json, license::spdx_id};
+use crate::github::client::GitHubValues;
+
+const SOURCEFORGE: &str = "https://sourceforge.net";
+
+/// <https://sourceforge.net/p/forge/documentation/Allura%20API/>
+#[derive(Deserialize)]
+struct Project {
+    shortname: String,
+    #[serde(default)]
+    summary: String,
+    #[serde(default)]
+    short_description: String,
+    #[serde(default)]
+    labels: Vec<String>,
+    #[serde(default)]
+    categories: Categories,
+    #[serde(default)]
+    tools: Vec<Tool>,
+}
+
+#[derive(Default, Deserialize)]
+struct Categories {
+    #[serde(default)]
+    license: Vec<Category>,
+}
+
+#[derive(Deserialize)]
+struct Category {
+    shortname: String,
+    fullname: String,
+}
+
+#[derive(Deserialize)]
+struct Tool {
+    name: String,
+    mount_point: String,
+    url: String,
+}
+
+impl Project {
+    /// SourceForge projects have neither an owner page nor release notes, so the publisher and
+    /// release notes are left to be entered.
+    fn into_values(self) -> Result<GitHubValues, url::ParseError> {
+        let base = Url::parse(SOURCEFORGE)?;
+        let issues_url = self
+            .tools
+            .iter()
+            .filter(|tool| tool.name == "tickets")
+            .min_by_key(|tool| tool.mount_point != "bugs")
+            .and_then(|tool| base.join(&tool.url).ok());
+
+        Ok(GitHubValues {
+            description: [self.summary, self.short_description]
+                .into_iter()
+                .find(|description| !description.trim().is_empty()),
+            publisher: None,
+            publisher_url: None,
+            issues_url: issues_url.and_then(|url| url.as_str().parse().ok()),
+            license: match <[Category; 1]>::try_from(self.categories.license) {
+                Ok([license]) => License::new(
+                    spdx_id(&license.shortname).map_or(license.fullname, str::to_owned),
+                )
+                .ok(),
+                Err(_) => None,
+            },
+            license_url: None,
+            package_url: format!("{SOURCEFORGE}/projects/{}/", self.shortname).parse()?,
+            release_notes: None,
+            release_notes_url: None,
+            topics: self.labels.into_iter().flat_map(Tag::new).collect(),
+        })
+    }
+}
+
+/// Returns the name of the project that a download URL belongs to, such as `name` for
+/// `https://downloads.sourceforge.net/project/name/1.2.3/installer.exe` or
+/// `https://sourceforge.net/projects/name/files/1.2.3/installer.exe/download`.
+fn project_name(url: &Url) -> Option<&str> {
+    let mut segments = url.path_segments()?;
+    match segments.next()? {
+        "project" | "projects" | "p" => segments.next(),
+        name if url.host_str() == Some("downloads.sourceforge.net") => Some(name),
+        _ => None,
+    }
+    .filter(|name| !name.is_empty())
+}
+
+pub async fn get_values(url: &Url) -> Result<Option<GitHubValues>, MetadataError> {
+    let Some(name) = project_name(url) else {
+        return Ok(None);
+    };
+
+    let project = get_json::<Project>(Url::parse(&format!("{SOURCEFORGE}/rest/p/{name}"))?).await?;
+
+    Ok(Some(project.into_values()?))
+}
+
+#[cfg(test)]
+mod tests {
+    use indoc::indoc;
+    use rstest::rstest;
+    use url::Url;
+
+    use super::{Project, project_name};
+
+    #[rstest]
+    #[case(
+        "https://downloads.sourceforge.net/project/name/1.2.3/installer.exe",
+        Some("name")
+    )]
+    #[case(
+        "https://sourceforge.net/projects/name/files/1.2.3/installer.exe/download",
+        Some("name")
+    )]
+    #[case("https://downloads.sourceforge.net/name/installer.exe", Some("name"))]
+    #[case(
+        "https://netix.dl.sourceforge.net/project/name/1.2.3/installer.exe",
+        Some("name")
+    )]
+    #[case("https://sourceforge.net/directory/", None)]
+    fn name_from_download_url(#[case] url: &str, #[case] expected: Option<&str>) {
+        assert_eq!(project_name(&Url::parse(url).unwrap()), expected);
+    }
+
+    #[test]
+    fn maps_project_to_values() {
+        const PROJECT: &str = indoc! {r#"
+            {
+              "shortname": "name",
+              "name": "Name",
+              "summary": "A tool that does things",
+              "short_description": "A longer description of the tool",
+              "labels": ["windows"],
+              "categories": {
+                "license": [
+                  {
+                    "id": 15,
+                    "shortname": "gplv3",
+                    "fullname": "GNU General Public License version 3.0 (GPLv3)"
+                  }
+                ]
+              },
+              "tools": [
+                { "name": "wiki", "mount_point": "wiki", "url": "/p/name/wiki/" },
+                { "name": "tickets", "mount_point": "feature-requests", "url": "/p/name/feature-requests/" },
+                { "name": "tickets", "mount_point": "bugs", "url": "/p/name/bugs/" }
+              ]
+            }
+        "#};
+
+        let values = serde_json::from_str::<Project>(PROJECT)
+            .unwrap()
+            .into_values()
+            .unwrap();
+
+        assert_eq!(
+            values.description.as_deref(),
+            Some("A tool that does things")
+        );
+        assert_eq!(values.license.unwrap().as_str(), "GPL-3.0");
+        assert_eq!(
+            values.issues_url.unwrap().as_str(),
+            "https://sourceforge.net/p/name/bugs/"
+        );
+        assert_eq!(
+            values.package_url.as_str(),
+            "https://sourceforge.net/projects/name/"
+        );
+        assert_eq!(values.topics.len(), 1);
+    }
+}
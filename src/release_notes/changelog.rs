@@ -0,0 +1,103 @@
+use winget_types::PackageVersion;
+
+use super::matches_version;
+
+/// Finds the section of a [Keep a Changelog] style Markdown file for a version, returning the
+/// Markdown under its heading.
+///
+/// The section is the first heading that mentions the version, such as `## [1.2.3] - 2024-01-01`
+/// or `## v1.2.3`, and runs until the next heading of the same or a higher level. Link reference
+/// definitions, which Keep a Changelog puts at the end of the file, are left out.
+///
+/// [Keep a Changelog]: https://keepachangelog.com
+pub fn section(changelog: &str, version: &PackageVersion) -> Option<String> {
+    let mut lines = changelog.lines();
+    let level = lines.by_ref().find_map(|line| {
+        let level = heading_level(line)?;
+        matches_version(&line[level..], version).then_some(level)
+    })?;
+
+    let section = lines
+        .take_while(|line| heading_level(line).is_none_or(|heading| heading > level))
+        .filter(|line| !is_link_reference(line))
+        .collect::<Vec<_>>()
+        .join("\n");
+
+    let section = section.trim();
+    (!section.is_empty()).then(|| section.to_owned())
+}
+
+/// Returns the level of a Markdown ATX heading, such as 2 for `## Heading`.
+fn heading_level(line: &str) -> Option<usize> {
+    let level = line.bytes().take_while(|&byte| byte == b'#').count();
+    ((1..=6).contains(&level) && line[level..].starts_with([' ', '\t'])).then_some(level)
+}
+
+/// Returns `true` if a line is a Markdown link reference definition, such as
+/// `[1.2.3]: https://github.com/owner/repo/compare/v1.2.2...v1.2.3`.
+fn is_link_reference(line: &str) -> bool {
+    line.strip_prefix('[')
+        .and_then(|rest| rest.split_once("]:"))
+        .is_some_and(|(label, _)| !label.is_empty() && !label.contains(']'))
+}
+
+#[cfg(test)]
+mod tests {
+    use indoc::indoc;
+    use rstest::rstest;
+    use winget_types::PackageVersion;
+
+    use super::section;
+
+    const CHANGELOG: &str = indoc! {"
+        # Changelog
+
+        All notable changes to this project will be documented in this file.
+
+        ## [Unreleased]
+
+        ### Added
+
+        - Something that isn't released yet
+
+        ## [1.2.10] - 2024-03-01
+
+        ### Fixed
+
+        - A crash on startup
+
+        ## [1.2.3] - 2024-01-01
+
+        ### Added
+
+        - A `--quiet` flag
+
+        ### Changed
+
+        - Sped up start-up
+
+        ## v1.2.2
+
+        - The first release
+
+        [unreleased]: https://github.com/owner/repo/compare/v1.2.10...HEAD
+        [1.2.10]: https://github.com/owner/repo/compare/v1.2.3...v1.2.10
+        [1.2.3]: https://github.com/owner/repo/compare/v1.2.2...v1.2.3
+    "};
+
+    #[rstest]
+    #[case(
+        "1.2.3",
+        Some("### Added\n\n- A `--quiet` flag\n\n### Changed\n\n- Sped up start-up")
+    )]
+    #[case("1.2.10", Some("### Fixed\n\n- A crash on startup"))]
+    #[case("1.2.2", Some("- The first release"))]
+    #[case("1.2", None)]
+    #[case("1.2.4", None)]
+    fn finds_version_section(#[case] version: &str, #[case] expected: Option<&str>) {
+        assert_eq!(
+            section(CHANGELOG, &version.parse::<PackageVersion>().unwrap()).as_deref(),
+            expected
+        );
+    }
+}
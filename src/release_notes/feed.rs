@@ -0,0 +1,195 @@
+use quick_xml::{DeError, de::from_str};
+use serde::Deserialize;
+use winget_types::PackageVersion;
+
+use super::matches_version;
+
+/// An entry of an Atom or RSS feed.
+#[derive(Debug, Eq, PartialEq)]
+pub struct FeedEntry {
+    /// The HTML content of the entry.
+    pub content: String,
+    pub link: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct Atom {
+    #[serde(rename = "entry", default)]
+    entries: Vec<AtomEntry>,
+}
+
+#[derive(Deserialize)]
+struct AtomEntry {
+    title: Text,
+    #[serde(default)]
+    link: Vec<AtomLink>,
+    content: Option<Text>,
+    summary: Option<Text>,
+}
+
+#[derive(Deserialize)]
+struct AtomLink {
+    #[serde(rename = "@href")]
+    href: String,
+    #[serde(rename = "@rel")]
+    rel: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct Rss {
+    channel: Channel,
+}
+
+#[derive(Deserialize)]
+struct Channel {
+    #[serde(rename = "item", default)]
+    items: Vec<RssItem>,
+}
+
+#[derive(Deserialize)]
+struct RssItem {
+    title: Option<Text>,
+    link: Option<Text>,
+    description: Option<Text>,
+    #[serde(rename = "encoded")]
+    content: Option<Text>,
+}
+
+/// The text of an element, which may also have attributes such as `type="html"`.
+#[derive(Deserialize)]
+struct Text {
+    #[serde(rename = "$text", default)]
+    value: String,
+}
+
+/// Finds the entry of an Atom or RSS feed whose title mentions a version.
+///
+/// # Errors
+///
+/// Returns an error if the feed is neither an Atom nor an RSS feed.
+pub fn entry(feed: &str, version: &PackageVersion) -> Result<Option<FeedEntry>, DeError> {
+    let atom = from_str::<Atom>(feed)?;
+    if !atom.entries.is_empty() {
+        return Ok(atom
+            .entries
+            .into_iter()
+            .find(|entry| matches_version(&entry.title.value, version))
+            .and_then(|entry| {
+                let link = entry
+                    .link
+                    .into_iter()
+                    .find(|link| link.rel.as_deref().is_none_or(|rel| rel == "alternate"))
+                    .map(|link| link.href);
+                Some(FeedEntry {
+                    content: entry.content.or(entry.summary)?.value,
+                    link,
+                })
+            }));
+    }
+
+    Ok(from_str::<Rss>(feed)?
+        .channel
+        .items
+        .into_iter()
+        .find(|item| {
+            item.title
+                .as_ref()
+                .is_some_and(|title| matches_version(&title.value, version))
+        })
+        .and_then(|item| {
+            Some(FeedEntry {
+                content: item.content.or(item.description)?.value,
+                link: item.link.map(|link| link.value),
+            })
+        }))
+}
+
+#[cfg(test)]
+mod tests {
+    use indoc::indoc;
+    use winget_types::PackageVersion;
+
+    use super::{FeedEntry, entry};
+
+    fn version(version: &str) -> PackageVersion {
+        version.parse().unwrap()
+    }
+
+    #[test]
+    fn atom_entry() {
+        const ATOM: &str = indoc! {r#"
+            <?xml version="1.0" encoding="utf-8"?>
+            <feed xmlns="http://www.w3.org/2005/Atom">
+              <title>Releases</title>
+              <entry>
+                <title>Version 1.2.4</title>
+                <link rel="alternate" href="https://example.com/releases/1.2.4"/>
+                <content type="html">&lt;p&gt;Newer&lt;/p&gt;</content>
+              </entry>
+              <entry>
+                <title type="text">Version 1.2.3</title>
+                <link rel="alternate" href="https://example.com/releases/1.2.3"/>
+                <content type="html"><![CDATA[<ul><li>Fixed a crash</li></ul>]]></content>
+              </entry>
+            </feed>
+        "#};
+
+        assert_eq!(
+            entry(ATOM, &version("1.2.3")).unwrap(),
+            Some(FeedEntry {
+                content: String::from("<ul><li>Fixed a crash</li></ul>"),
+                link: Some(String::from("https://example.com/releases/1.2.3")),
+            })
+        );
+        assert_eq!(entry(ATOM, &version("1.2")).unwrap(), None);
+    }
+
+    #[test]
+    fn rss_item() {
+        const RSS: &str = indoc! {r#"
+            <?xml version="1.0" encoding="UTF-8"?>
+            <rss version="2.0">
+              <channel>
+                <title>Releases</title>
+                <item>
+                  <title>v1.2.3</title>
+                  <link>https://example.com/releases/1.2.3</link>
+                  <description>&lt;p&gt;Fixed a crash&lt;/p&gt;</description>
+                </item>
+              </channel>
+            </rss>
+        "#};
+
+        assert_eq!(
+            entry(RSS, &version("1.2.3")).unwrap(),
+            Some(FeedEntry {
+                content: String::from("<p>Fixed a crash</p>"),
+                link: Some(String::from("https://example.com/releases/1.2.3")),
+            })
+        );
+    }
+
+    #[test]
+    fn rss_item_encoded_content() {
+        const RSS: &str = indoc! {r#"
+            <?xml version="1.0" encoding="UTF-8"?>
+            <rss version="2.0" xmlns:content="http://purl.org/rss/1.0/modules/content/">
+              <channel>
+                <item>
+                  <title>Release 1.2.3</title>
+                  <description>Summary</description>
+                  <content:encoded><![CDATA[<p>Fixed a crash</p>]]></content:encoded>
+                </item>
+              </channel>
+            </rss>
+        "#};
+
+        assert_eq!(
+            entry(RSS, &version("1.2.3")).unwrap(),
+            Some(FeedEntry {
+                content: String::from("<p>Fixed a crash</p>"),
+                link: None,
+            })
+        );
+    }
+}
@@ -0,0 +1,208 @@
+use percent_encoding::{NON_ALPHANUMERIC, utf8_percent_encode};
+use serde::Deserialize;
+use url::Url;
+use winget_types::PackageVersion;
+
+use super::matches_version;
+
+/// A release from the GitLab or Gitea releases API, with its notes in Markdown.
+#[derive(Debug, Eq, PartialEq)]
+pub struct ForgeRelease {
+    pub notes: String,
+    pub url: Option<String>,
+}
+
+/// <https://docs.gitlab.com/api/releases/#list-releases>
+#[derive(Deserialize)]
+struct GitLabRelease {
+    tag_name: String,
+    #[serde(default)]
+    name: Option<String>,
+    #[serde(default)]
+    description: Option<String>,
+    #[serde(rename = "_links")]
+    links: Option<GitLabLinks>,
+}
+
+#[derive(Deserialize)]
+struct GitLabLinks {
+    #[serde(rename = "self")]
+    this: Option<String>,
+}
+
+/// <https://docs.gitea.com/api#tag/repository/operation/repoListReleases>
+#[derive(Deserialize)]
+struct GiteaRelease {
+    tag_name: String,
+    #[serde(default)]
+    name: Option<String>,
+    #[serde(default)]
+    body: Option<String>,
+    html_url: Option<String>,
+}
+
+/// Returns the URL of the GitLab releases API for a project, such as
+/// `https://gitlab.com/api/v4/projects/group%2Fproject/releases` for
+/// `https://gitlab.com/group/project`.
+pub fn gitlab_releases_url(project: &Url) -> Option<Url> {
+    let path = project_path(project)?;
+    let mut url = api_base(project);
+    url.set_path(&format!(
+        "api/v4/projects/{}/releases",
+        utf8_percent_encode(&path, NON_ALPHANUMERIC)
+    ));
+    url.set_query(Some("per_page=100"));
+    Some(url)
+}
+
+/// Returns the URL of the Gitea releases API for a repository, such as
+/// `https://codeberg.org/api/v1/repos/owner/repo/releases` for `https://codeberg.org/owner/repo`.
+pub fn gitea_releases_url(repository: &Url) -> Option<Url> {
+    let path = project_path(repository)?;
+    let (owner, repo) = path.split_once('/')?;
+    let mut url = api_base(repository);
+    url.set_path(&format!("api/v1/repos/{owner}/{repo}/releases"));
+    url.set_query(Some("limit=50"));
+    Some(url)
+}
+
+fn api_base(url: &Url) -> Url {
+    let mut base = url.clone();
+    base.set_path("");
+    base.set_query(None);
+    base.set_fragment(None);
+    base
+}
+
+/// Returns the path of a project from its URL, without a trailing slash or `.git` suffix.
+fn project_path(url: &Url) -> Option<String> {
+    let path = url.path().trim_matches('/');
+    let path = path.strip_suffix(".git").unwrap_or(path);
+    path.contains('/').then(|| path.to_owned())
+}
+
+/// Finds the release for a version in a response from the GitLab releases API.
+pub fn gitlab_release(
+    releases: &str,
+    version: &PackageVersion,
+) -> serde_json::Result<Option<ForgeRelease>> {
+    Ok(serde_json::from_str::<Vec<GitLabRelease>>(releases)?
+        .into_iter()
+        .find(|release| is_release_for(&release.tag_name, release.name.as_deref(), version))
+        .and_then(|release| {
+            Some(ForgeRelease {
+                notes: release.description?,
+                url: release.links.and_then(|links| links.this),
+            })
+        }))
+}
+
+/// Finds the release for a version in a response from the Gitea releases API.
+pub fn gitea_release(
+    releases: &str,
+    version: &PackageVersion,
+) -> serde_json::Result<Option<ForgeRelease>> {
+    Ok(serde_json::from_str::<Vec<GiteaRelease>>(releases)?
+        .into_iter()
+        .find(|release| is_release_for(&release.tag_name, release.name.as_deref(), version))
+        .and_then(|release| {
+            Some(ForgeRelease {
+                notes: release.body?,
+                url: release.html_url,
+            })
+        }))
+}
+
+/// Releases are matched by their tag, or by their name if their tag doesn't match.
+fn is_release_for(tag_name: &str, name: Option<&str>, version: &PackageVersion) -> bool {
+    matches_version(tag_name, version) || name.is_some_and(|name| matches_version(name, version))
+}
+
+#[cfg(test)]
+mod tests {
+    use indoc::indoc;
+    use url::Url;
+    use winget_types::PackageVersion;
+
+    use super::{
+        ForgeRelease, gitea_release, gitea_releases_url, gitlab_release, gitlab_releases_url,
+    };
+
+    fn version(version: &str) -> PackageVersion {
+        version.parse().unwrap()
+    }
+
+    #[test]
+    fn api_urls() {
+        let gitlab = Url::parse("https://gitlab.com/group/subgroup/project.git").unwrap();
+        let gitea = Url::parse("https://codeberg.org/owner/repo/").unwrap();
+
+        assert_eq!(
+            gitlab_releases_url(&gitlab).unwrap().as_str(),
+            "https://gitlab.com/api/v4/projects/group%2Fsubgroup%2Fproject/releases?per_page=100"
+        );
+        assert_eq!(
+            gitea_releases_url(&gitea).unwrap().as_str(),
+            "https://codeberg.org/api/v1/repos/owner/repo/releases?limit=50"
+        );
+        assert_eq!(
+            gitlab_releases_url(&Url::parse("https://gitlab.com/").unwrap()),
+            None
+        );
+    }
+
+    #[test]
+    fn finds_gitlab_release() {
+        const RELEASES: &str = indoc! {r#"
+            [
+              {
+                "tag_name": "v1.2.4",
+                "name": "1.2.4",
+                "description": "Newer",
+                "_links": { "self": "https://gitlab.com/group/project/-/releases/v1.2.4" }
+              },
+              {
+                "tag_name": "v1.2.3",
+                "name": "Version 1.2.3",
+                "description": "- Fixed a crash",
+                "_links": { "self": "https://gitlab.com/group/project/-/releases/v1.2.3" }
+              }
+            ]
+        "#};
+
+        assert_eq!(
+            gitlab_release(RELEASES, &version("1.2.3")).unwrap(),
+            Some(ForgeRelease {
+                notes: String::from("- Fixed a crash"),
+                url: Some(String::from(
+                    "https://gitlab.com/group/project/-/releases/v1.2.3"
+                )),
+            })
+        );
+        assert_eq!(gitlab_release(RELEASES, &version("1.2")).unwrap(), None);
+    }
+
+    #[test]
+    fn finds_gitea_release() {
+        const RELEASES: &str = indoc! {r#"
+            [
+              {
+                "tag_name": "release-1.2.3",
+                "name": "",
+                "body": "Fixed a crash",
+                "html_url": "https://codeberg.org/owner/repo/releases/tag/release-1.2.3"
+              }
+            ]
+        "#};
+
+        assert_eq!(
+            gitea_release(RELEASES, &version("1.2.3")).unwrap(),
+            Some(ForgeRelease {
+                notes: String::from("Fixed a crash"),
+                url: Some(String::from(
+                    "https://codeberg.org/owner/repo/releases/tag/release-1.2.3"
+                )),
+            })
+        );
+    }
+}
@@ -0,0 +1,303 @@
+//! Release notes from sources other than GitHub releases.
+//!
+//! Many projects keep their release notes in a changelog in their repository, in releases on
+//! GitLab or Gitea, or in an Atom or RSS feed. The notes for a package version are found in a
+//! source by matching the version against the headings of a changelog, or the tags and titles of
+//! releases and feed entries. Markdown and HTML notes are converted to text in the same way as the
+//! notes of GitHub releases, so they are formatted and truncated alike.
+
+mod changelog;
+mod feed;
+pub mod forge;
+
+use std::{fmt, str::FromStr};
+
+use reqwest::Client;
+use thiserror::Error;
+use url::Url;
+use winget_types::{PackageVersion, locale::ReleaseNotes, url::ReleaseNotesUrl};
+
+use crate::{
+    fixtures::Dispatch,
+    github::{
+        GitHubError,
+        client::{GitHub, ReleaseAsset},
+        graphql::types::Html,
+        host::GitHubHost,
+    },
+    token::default_headers,
+    traits::{FromHtml, FromMarkdown},
+};
+
+const DEFAULT_CHANGELOG: &str = "CHANGELOG.md";
+
+/// Where to read a package version's release notes from.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum ReleaseNotesSource {
+    /// A changelog in the GitHub repository that the installers are released from, read at the
+    /// tag of the release.
+    Changelog(String),
+    /// A changelog at a URL, where `{version}` is replaced with the package version.
+    ChangelogUrl(String),
+    /// The releases of a GitLab project.
+    GitLab(Url),
+    /// The releases of a Gitea or Forgejo repository.
+    Gitea(Url),
+    /// An Atom or RSS feed.
+    Feed(Url),
+}
+
+#[derive(Debug, Error)]
+pub enum ReleaseNotesError {
+    #[error(
+        "`{0}` is not a release notes source. Expected `changelog`, `changelog:<PATH|URL>`, \
+         `gitlab:<URL>`, `gitea:<URL>` or `feed:<URL>`"
+    )]
+    InvalidSource(String),
+    #[error(
+        "None of the installers are released on GitHub, so the repository to read the changelog \
+         from is unknown. Pass the URL of the changelog with `changelog:<URL>` instead"
+    )]
+    NoRepository,
+    #[error("{0} is not the URL of a repository")]
+    NotRepository(Url),
+    #[error(transparent)]
+    GitHub(#[from] GitHubError),
+    #[error(transparent)]
+    Reqwest(#[from] reqwest::Error),
+    #[error("Failed to read releases: {0}")]
+    Json(#[from] serde_json::Error),
+    #[error("Failed to read feed: {0}")]
+    Feed(#[from] quick_xml::DeError),
+}
+
+/// Release notes read from a [`ReleaseNotesSource`].
+pub struct SourcedReleaseNotes {
+    pub notes: ReleaseNotes,
+    pub url: Option<ReleaseNotesUrl>,
+}
+
+impl ReleaseNotesSource {
+    /// Reads the release notes of a package version, returning `None` if the source has no notes
+    /// for the version.
+    ///
+    /// A changelog in a GitHub repository is read at the tag of the release that one of the
+    /// installer URLs is an asset of.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the source couldn't be read.
+    pub async fn fetch(
+        &self,
+        github: &GitHub,
+        installer_urls: &[&Url],
+        version: &PackageVersion,
+    ) -> Result<Option<SourcedReleaseNotes>, ReleaseNotesError> {
+        let notes = match self {
+            Self::Changelog(path) => {
+                let (host, asset) = installer_urls
+                    .iter()
+                    .find_map(|url| GitHubHost::from_url(url).zip(ReleaseAsset::from_url(url)))
+                    .ok_or(ReleaseNotesError::NoRepository)?;
+                let changelog = github
+                    .get_file_content_at(
+                        &host,
+                        &asset.owner,
+                        &asset.repo,
+                        format!("{}:{path}", asset.tag_name),
+                    )
+                    .await?;
+                changelog::section(&changelog, version).map(|section| {
+                    let url = host.web_url(format_args!(
+                        "{}/{}/blob/{}/{path}",
+                        asset.owner, asset.repo, asset.tag_name
+                    ));
+                    (Notes::Markdown(section), Some(url))
+                })
+            }
+            Self::ChangelogUrl(url) => {
+                let url = url.replace("{version}", &version.to_string());
+                let changelog = get_text(&url).await?;
+                changelog::section(&changelog, version)
+                    .map(|section| (Notes::Markdown(section), Some(url)))
+            }
+            Self::GitLab(project) => {
+                let url = forge::gitlab_releases_url(project)
+                    .ok_or_else(|| ReleaseNotesError::NotRepository(project.clone()))?;
+                forge::gitlab_release(&get_text(url).await?, version)?
+                    .map(|release| (Notes::Markdown(release.notes), release.url))
+            }
+            Self::Gitea(repository) => {
+                let url = forge::gitea_releases_url(repository)
+                    .ok_or_else(|| ReleaseNotesError::NotRepository(repository.clone()))?;
+                forge::gitea_release(&get_text(url).await?, version)?
+                    .map(|release| (Notes::Markdown(release.notes), release.url))
+            }
+            Self::Feed(url) => feed::entry(&get_text(url.clone()).await?, version)?
+                .map(|entry| (Notes::Html(entry.content), entry.link)),
+        };
+
+        Ok(notes.and_then(|(notes, url)| {
+            Some(SourcedReleaseNotes {
+                notes: notes.into_release_notes()?,
+                url: url.and_then(|url| url.parse::<ReleaseNotesUrl>().ok()),
+            })
+        }))
+    }
+}
+
+impl FromStr for ReleaseNotesSource {
+    type Err = ReleaseNotesError;
+
+    fn from_str(source: &str) -> Result<Self, Self::Err> {
+        let invalid = || ReleaseNotesError::InvalidSource(source.to_owned());
+        let (kind, location) = source
+            .split_once(':')
+            .map_or((source, None), |(kind, location)| (kind, Some(location)));
+        let url = || location.and_then(|location| Url::parse(location).ok());
+
+        match (kind.to_ascii_lowercase().as_str(), location) {
+            ("changelog", None) => Ok(Self::Changelog(DEFAULT_CHANGELOG.to_owned())),
+            ("changelog", Some(location)) if url().is_some() => {
+                Ok(Self::ChangelogUrl(location.to_owned()))
+            }
+            ("changelog", Some(path)) if !path.is_empty() => {
+                Ok(Self::Changelog(path.trim_start_matches('/').to_owned()))
+            }
+            ("gitlab", _) => url().map(Self::GitLab).ok_or_else(invalid),
+            ("gitea" | "forgejo", _) => url().map(Self::Gitea).ok_or_else(invalid),
+            ("feed" | "atom" | "rss", _) => url().map(Self::Feed).ok_or_else(invalid),
+            _ => Err(invalid()),
+        }
+    }
+}
+
+impl fmt::Display for ReleaseNotesSource {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Changelog(path) => write!(f, "changelog:{path}"),
+            Self::ChangelogUrl(url) => write!(f, "changelog:{url}"),
+            Self::GitLab(url) => write!(f, "gitlab:{url}"),
+            Self::Gitea(url) => write!(f, "gitea:{url}"),
+            Self::Feed(url) => write!(f, "feed:{url}"),
+        }
+    }
+}
+
+enum Notes {
+    Markdown(String),
+    Html(String),
+}
+
+impl Notes {
+    fn into_release_notes(self) -> Option<ReleaseNotes> {
+        match self {
+            Self::Markdown(markdown) => ReleaseNotes::from_markdown(&markdown),
+            Self::Html(html) => ReleaseNotes::from_html(&Html::new(html)),
+        }
+    }
+}
+
+pub async fn get_text<U: reqwest::IntoUrl>(url: U) -> Result<String, reqwest::Error> {
+    Client::builder()
+        .default_headers(default_headers(None))
+        .build()?
+        .get(url)
+        .dispatch()
+        .await?
+        .error_for_status()?
+        .text()
+        .await
+}
+
+/// Returns `true` if a version is mentioned in a tag, title or heading, such as `v1.2.3`,
+/// `release-1.2.3` or `[1.2.3] - 2024-01-01` for version 1.2.3.
+///
+/// The version mustn't be part of a longer version, so 1.2.3 doesn't match `1.2.30`, `11.2.3` or
+/// `1.2.3-beta`.
+fn matches_version(text: &str, version: &PackageVersion) -> bool {
+    let version = version.to_string();
+    text.match_indices(&version).any(|(index, _)| {
+        let before = text[..index].chars().next_back();
+        let mut after = text[index + version.len()..].chars();
+        let next = after.next();
+
+        let starts_version = before.is_none_or(|char| !char.is_ascii_digit() && char != '.');
+        let ends_version = match next {
+            None => true,
+            Some(char) if char.is_ascii_digit() => false,
+            // A dot, hyphen or plus followed by more of a version continues it
+            Some('.' | '-' | '+') => after.next().is_none_or(|char| !char.is_alphanumeric()),
+            Some(_) => true,
+        };
+
+        starts_version && ends_version
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use rstest::rstest;
+    use url::Url;
+    use winget_types::PackageVersion;
+
+    use super::{ReleaseNotesSource, matches_version};
+
+    #[rstest]
+    #[case("1.2.3", true)]
+    #[case("v1.2.3", true)]
+    #[case("release-1.2.3", true)]
+    #[case("[1.2.3] - 2024-01-01", true)]
+    #[case("Version 1.2.3.", true)]
+    #[case("1.2.30", false)]
+    #[case("11.2.3", false)]
+    #[case("1.2.3.4", false)]
+    #[case("1.2.3-beta", false)]
+    #[case("1.2", false)]
+    fn version_matching(#[case] text: &str, #[case] expected: bool) {
+        assert_eq!(
+            matches_version(text, &"1.2.3".parse::<PackageVersion>().unwrap()),
+            expected
+        );
+    }
+
+    #[rstest]
+    #[case(
+        "changelog",
+        ReleaseNotesSource::Changelog(String::from("CHANGELOG.md"))
+    )]
+    #[case(
+        "changelog:docs/CHANGES.md",
+        ReleaseNotesSource::Changelog(String::from("docs/CHANGES.md"))
+    )]
+    #[case(
+        "changelog:https://example.com/{version}/CHANGELOG.md",
+        ReleaseNotesSource::ChangelogUrl(String::from(
+            "https://example.com/{version}/CHANGELOG.md"
+        ))
+    )]
+    #[case(
+        "gitlab:https://gitlab.com/group/project",
+        ReleaseNotesSource::GitLab(Url::parse("https://gitlab.com/group/project").unwrap())
+    )]
+    #[case(
+        "gitea:https://codeberg.org/owner/repo",
+        ReleaseNotesSource::Gitea(Url::parse("https://codeberg.org/owner/repo").unwrap())
+    )]
+    #[case(
+        "feed:https://example.com/releases.atom",
+        ReleaseNotesSource::Feed(Url::parse("https://example.com/releases.atom").unwrap())
+    )]
+    fn parses_source(#[case] source: &str, #[case] expected: ReleaseNotesSource) {
+        assert_eq!(source.parse::<ReleaseNotesSource>().unwrap(), expected);
+    }
+
+    #[rstest]
+    #[case("github")]
+    #[case("gitlab")]
+    #[case("feed:releases.atom")]
+    #[case("changelog:")]
+    fn rejects_invalid_source(#[case] source: &str) {
+        assert!(source.parse::<ReleaseNotesSource>().is_err());
+    }
+}
@@ -0,0 +1,92 @@
+use std::collections::BTreeSet;
+
+use winget_types::{PackageIdentifier, PackageVersion};
+
+use super::{ManifestRepository, Submission, VersionAddition, VersionRemoval, VersionsEdit};
+use crate::{
+    github::{
+        GitHubError,
+        client::{GitHub, GitHubFile},
+        utils::PackagePath,
+    },
+    manifests::Manifests,
+};
+
+impl ManifestRepository for GitHub {
+    async fn get_versions(
+        &self,
+        identifier: &PackageIdentifier,
+    ) -> Result<BTreeSet<PackageVersion>, GitHubError> {
+        Self::get_versions(self, identifier).await
+    }
+
+    async fn get_manifests(
+        &self,
+        identifier: &PackageIdentifier,
+        version: &PackageVersion,
+    ) -> Result<Manifests, GitHubError> {
+        Self::get_manifests(self, identifier, version).await
+    }
+
+    async fn get_manifest_files(
+        &self,
+        identifier: &PackageIdentifier,
+        version: &PackageVersion,
+    ) -> Result<Vec<GitHubFile>, GitHubError> {
+        Ok(self
+            .get_directory_content_with_text()
+            .path(&PackagePath::new(identifier, Some(version), None))
+            .call()
+            .await?
+            .collect())
+    }
+
+    async fn add_version(&self, addition: VersionAddition<'_>) -> Result<Submission, GitHubError> {
+        Self::add_version(self)
+            .identifier(addition.identifier)
+            .version(addition.version)
+            .maybe_versions(addition.versions)
+            .changes(addition.changes)
+            .maybe_replace_version(addition.replace_version)
+            .issue_resolves(addition.issue_resolves)
+            .maybe_created_with(addition.created_with)
+            .maybe_created_with_url(addition.created_with_url)
+            .maybe_previous(addition.previous)
+            .send()
+            .await
+            .map(Submission::PullRequest)
+    }
+
+    async fn remove_version(&self, removal: VersionRemoval<'_>) -> Result<Submission, GitHubError> {
+        let (fork, winget_pkgs) = tokio::try_join!(
+            async {
+                let current_user = self.get_username().await?;
+                self.get_winget_pkgs().owner(current_user).send().await
+            },
+            self.get_winget_pkgs().send(),
+        )?;
+
+        Self::remove_version(self)
+            .identifier(removal.identifier)
+            .version(removal.version)
+            .reason(removal.reason)
+            .fork(&fork)
+            .winget_pkgs(&winget_pkgs)
+            .issue_resolves(removal.issue_resolves)
+            .send()
+            .await
+            .map(Submission::PullRequest)
+    }
+
+    async fn edit_versions(&self, edit: VersionsEdit<'_>) -> Result<Submission, GitHubError> {
+        Self::edit_versions(self)
+            .identifier(edit.identifier)
+            .versions(edit.versions)
+            .changes(edit.changes)
+            .summary(edit.summary)
+            .issue_resolves(edit.issue_resolves)
+            .send()
+            .await
+            .map(Submission::PullRequest)
+    }
+}
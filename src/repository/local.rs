@@ -0,0 +1,572 @@
+use std::{collections::BTreeSet, ffi::OsStr, io, path::Path, process::Stdio};
+
+use anstream::println;
+use camino::{Utf8Path, Utf8PathBuf};
+use clap::Args;
+use color_eyre::eyre::eyre;
+use owo_colors::OwoColorize;
+use tokio::{fs, io::AsyncWriteExt, process::Command};
+use winget_types::{PackageIdentifier, PackageVersion};
+
+use super::{
+    ManifestRepository, Submission, VersionAddition, VersionRemoval, VersionsEdit, parse_manifests,
+};
+use crate::{
+    github::{
+        GitHubError,
+        client::{GitHub, GitHubFile},
+        utils::{
+            CommitTitle, PackagePath, branch_name, commit_title, edit_title, pull_request_body,
+        },
+    },
+    manifests::Manifests,
+    update_state::UpdateState,
+};
+
+/// Options for working on a local clone of the upstream repository instead of through the GitHub
+/// API.
+#[derive(Args)]
+pub struct CheckoutArgs {
+    /// Local clone of the upstream repository to read manifests from and commit changes to,
+    /// instead of using the GitHub API
+    #[arg(long, env = "KOMAC_CHECKOUT", value_hint = clap::ValueHint::DirPath)]
+    checkout: Option<Utf8PathBuf>,
+
+    /// Push branches committed to the local clone to this remote, which should be your fork, and
+    /// open pull requests for them
+    #[arg(
+        long,
+        requires = "checkout",
+        value_name = "REMOTE",
+        num_args = 0..=1,
+        default_missing_value = "origin"
+    )]
+    push: Option<String>,
+}
+
+impl CheckoutArgs {
+    /// Opens the local checkout if one was given.
+    ///
+    /// The GitHub client is only used to open pull requests for pushed branches.
+    pub async fn open(&self, github: &GitHub) -> Result<Option<LocalCheckout>, GitHubError> {
+        let Some(path) = &self.checkout else {
+            return Ok(None);
+        };
+
+        let mut checkout = LocalCheckout::open(path).await?;
+        checkout.push = self.push.as_ref().map(|remote| Push {
+            remote: remote.clone(),
+            github: github.clone(),
+        });
+
+        Ok(Some(checkout))
+    }
+}
+
+/// A local clone of the upstream repository.
+///
+/// Manifests are read from the working tree. Changes are committed onto a new branch from `HEAD`
+/// without touching the working tree or the index, so that many changes can be made before
+/// pushing. Branches are only pushed and pull requests are only opened if a remote to push to was
+/// given.
+pub struct LocalCheckout {
+    root: Utf8PathBuf,
+    push: Option<Push>,
+}
+
+struct Push {
+    remote: String,
+    github: GitHub,
+}
+
+impl LocalCheckout {
+    /// Opens the Git repository that contains the given path without pushing any changes.
+    pub async fn open(path: &Utf8Path) -> Result<Self, GitHubError> {
+        let root = git(path, ["rev-parse", "--show-toplevel"], None, None).await?;
+
+        Ok(Self {
+            root: Utf8PathBuf::from(root),
+            push: None,
+        })
+    }
+
+    async fn git<I, S>(
+        &self,
+        args: I,
+        index: Option<&Path>,
+        stdin: Option<&str>,
+    ) -> Result<String, GitHubError>
+    where
+        I: IntoIterator<Item = S>,
+        S: AsRef<OsStr>,
+    {
+        git(&self.root, args, index, stdin).await
+    }
+
+    /// Commits the changes onto a new branch from `HEAD` using a temporary index.
+    async fn commit(
+        &self,
+        branch: String,
+        title: String,
+        additions: &[(String, String)],
+        deletion: Option<&PackagePath>,
+    ) -> Result<LocalCommit, GitHubError> {
+        let head = self
+            .git(["rev-parse", "--verify", "HEAD^{commit}"], None, None)
+            .await?;
+
+        let index_dir = tempfile::tempdir()?;
+        let index = index_dir.path().join("index");
+        let index = Some(index.as_path());
+
+        self.git(["read-tree", head.as_str()], index, None).await?;
+
+        if let Some(path) = deletion {
+            self.git(
+                [
+                    "rm",
+                    "-r",
+                    "--cached",
+                    "--quiet",
+                    "--ignore-unmatch",
+                    "--",
+                    path.as_str(),
+                ],
+                index,
+                None,
+            )
+            .await?;
+        }
+
+        for (path, content) in additions {
+            let blob = self
+                .git(["hash-object", "-w", "--stdin"], None, Some(content))
+                .await?;
+            self.git(
+                [
+                    "update-index",
+                    "--add",
+                    "--cacheinfo",
+                    &format!("100644,{blob},{path}"),
+                ],
+                index,
+                None,
+            )
+            .await?;
+        }
+
+        let tree = self.git(["write-tree"], index, None).await?;
+        let oid = self
+            .git(
+                ["commit-tree", &tree, "-p", &head, "-m", &title],
+                None,
+                None,
+            )
+            .await?;
+
+        // An empty old value makes this fail if the branch already exists
+        self.git(
+            ["update-ref", &format!("refs/heads/{branch}"), &oid, ""],
+            None,
+            None,
+        )
+        .await?;
+
+        Ok(LocalCommit { branch, title, oid })
+    }
+
+    /// Pushes the commit's branch and opens a pull request for it if a remote was given.
+    async fn submit(&self, commit: LocalCommit, body: &str) -> Result<Submission, GitHubError> {
+        let Some(Push { remote, github }) = &self.push else {
+            return Ok(Submission::Commit(commit));
+        };
+
+        let refspec = format!(
+            "refs/heads/{branch}:refs/heads/{branch}",
+            branch = commit.branch
+        );
+        self.git(
+            ["push", "--quiet", remote.as_str(), refspec.as_str()],
+            None,
+            None,
+        )
+        .await?;
+
+        let (current_user, winget_pkgs) =
+            tokio::try_join!(github.get_username(), github.get_winget_pkgs().send())?;
+        let fork = github.get_winget_pkgs().owner(&current_user).send().await?;
+
+        github
+            .create_pull_request(
+                &winget_pkgs.id,
+                &fork.id,
+                &format!("{current_user}:{}", commit.branch),
+                &winget_pkgs.default_branch_name,
+                &commit.title,
+                body,
+            )
+            .await
+            .map(Submission::PullRequest)
+    }
+}
+
+impl ManifestRepository for LocalCheckout {
+    async fn get_versions(
+        &self,
+        identifier: &PackageIdentifier,
+    ) -> Result<BTreeSet<PackageVersion>, GitHubError> {
+        let path = PackagePath::new(identifier, None, None);
+
+        let mut entries = match fs::read_dir(self.root.join(path.as_str())).await {
+            Ok(entries) => entries,
+            Err(error) if error.kind() == io::ErrorKind::NotFound => {
+                return Err(GitHubError::PackageNonExistent(identifier.clone()));
+            }
+            Err(error) => return Err(error.into()),
+        };
+
+        let mut versions = BTreeSet::new();
+        while let Some(entry) = entries.next_entry().await? {
+            if !entry.file_type().await?.is_dir() {
+                continue;
+            }
+
+            // Version directories only contain manifests, whereas directories with subdirectories
+            // belong to other packages, such as Package.Identifier.Beta
+            let mut children = fs::read_dir(entry.path()).await?;
+            let mut has_files = false;
+            let mut has_directories = false;
+            while let Some(child) = children.next_entry().await? {
+                if child.file_type().await?.is_dir() {
+                    has_directories = true;
+                    break;
+                }
+                has_files = true;
+            }
+
+            if has_files
+                && !has_directories
+                && let Some(version) = entry
+                    .file_name()
+                    .to_str()
+                    .and_then(|name| name.parse::<PackageVersion>().ok())
+            {
+                versions.insert(version);
+            }
+        }
+
+        if versions.is_empty() {
+            Err(GitHubError::NoValidFiles { path })
+        } else {
+            Ok(versions)
+        }
+    }
+
+    async fn get_manifests(
+        &self,
+        identifier: &PackageIdentifier,
+        version: &PackageVersion,
+    ) -> Result<Manifests, GitHubError> {
+        let files = self.get_manifest_files(identifier, version).await?;
+
+        parse_manifests(
+            identifier,
+            &PackagePath::new(identifier, Some(version), None),
+            &files,
+        )
+    }
+
+    async fn get_manifest_files(
+        &self,
+        identifier: &PackageIdentifier,
+        version: &PackageVersion,
+    ) -> Result<Vec<GitHubFile>, GitHubError> {
+        let path = PackagePath::new(identifier, Some(version), None);
+
+        let mut files = Vec::new();
+        let mut entries = fs::read_dir(self.root.join(path.as_str())).await?;
+        while let Some(entry) = entries.next_entry().await? {
+            if entry.file_type().await?.is_file()
+                && let Ok(name) = entry.file_name().into_string()
+            {
+                let text = fs::read_to_string(entry.path()).await?;
+                files.push(GitHubFile::new(name, text));
+            }
+        }
+
+        Ok(files)
+    }
+
+    async fn add_version(&self, addition: VersionAddition<'_>) -> Result<Submission, GitHubError> {
+        let VersionAddition {
+            identifier,
+            version,
+            versions,
+            changes,
+            replace_version,
+            issue_resolves,
+            created_with,
+            created_with_url,
+            previous,
+        } = addition;
+
+        let commit = self
+            .commit(
+                branch_name(identifier, version),
+                commit_title(identifier, version, UpdateState::get(version, versions)),
+                &changes,
+                replace_version
+                    .map(|replace_version| {
+                        PackagePath::new(identifier, Some(replace_version), None)
+                    })
+                    .as_ref(),
+            )
+            .await?;
+
+        self.submit(
+            commit,
+            &pull_request_body()
+                .issue_resolves(issue_resolves)
+                .maybe_created_with(created_with)
+                .maybe_created_with_url(created_with_url)
+                .changes(&changes)
+                .maybe_previous(previous)
+                .build(),
+        )
+        .await
+    }
+
+    async fn remove_version(&self, removal: VersionRemoval<'_>) -> Result<Submission, GitHubError> {
+        let VersionRemoval {
+            identifier,
+            version,
+            reason,
+            issue_resolves,
+        } = removal;
+
+        let commit = self
+            .commit(
+                branch_name(identifier, version),
+                CommitTitle::remove(identifier, version).to_string(),
+                &[],
+                Some(&PackagePath::new(identifier, Some(version), None)),
+            )
+            .await?;
+
+        let submission = self
+            .submit(
+                commit,
+                &pull_request_body()
+                    .issue_resolves(issue_resolves)
+                    .alternative_text(reason)
+                    .build(),
+            )
+            .await?;
+
+        submission.print_success();
+
+        Ok(submission)
+    }
+
+    async fn edit_versions(&self, edit: VersionsEdit<'_>) -> Result<Submission, GitHubError> {
+        let VersionsEdit {
+            identifier,
+            versions,
+            changes,
+            summary,
+            issue_resolves,
+        } = edit;
+        let latest = versions.last().unwrap_or_else(|| unreachable!());
+
+        let commit = self
+            .commit(
+                branch_name(identifier, latest),
+                edit_title(identifier, versions),
+                &changes,
+                None,
+            )
+            .await?;
+
+        self.submit(
+            commit,
+            &pull_request_body()
+                .issue_resolves(issue_resolves)
+                .alternative_text(summary)
+                .build(),
+        )
+        .await
+    }
+}
+
+/// A commit that was made on a new branch in a [`LocalCheckout`].
+pub struct LocalCommit {
+    branch: String,
+    title: String,
+    oid: String,
+}
+
+impl LocalCommit {
+    pub fn print_success(&self) {
+        println!(
+            "{} committed {} to {} ({})",
+            "Successfully".green(),
+            self.title,
+            self.branch.blue(),
+            self.oid.get(..7).unwrap_or(&self.oid)
+        );
+    }
+}
+
+/// Runs Git in a repository and returns its trimmed output.
+///
+/// If an index is given, it is used instead of the repository's own index.
+async fn git<I, S>(
+    directory: &Utf8Path,
+    args: I,
+    index: Option<&Path>,
+    stdin: Option<&str>,
+) -> Result<String, GitHubError>
+where
+    I: IntoIterator<Item = S>,
+    S: AsRef<OsStr>,
+{
+    let mut command = Command::new("git");
+    command
+        .arg("-C")
+        .arg(directory)
+        .args(args)
+        .stdin(if stdin.is_some() {
+            Stdio::piped()
+        } else {
+            Stdio::null()
+        })
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped());
+
+    if let Some(index) = index {
+        command.env("GIT_INDEX_FILE", index);
+    }
+
+    let mut child = command.spawn()?;
+
+    if let Some(input) = stdin
+        && let Some(mut child_stdin) = child.stdin.take()
+    {
+        child_stdin.write_all(input.as_bytes()).await?;
+    }
+
+    let output = child.wait_with_output().await?;
+
+    if output.status.success() {
+        Ok(String::from_utf8_lossy(&output.stdout).trim().to_owned())
+    } else {
+        Err(GitHubError::Git(eyre!(
+            "git failed in {directory}: {}",
+            String::from_utf8_lossy(&output.stderr).trim()
+        )))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{collections::BTreeSet, fs};
+
+    use camino::{Utf8Path, Utf8PathBuf};
+    use winget_types::{PackageIdentifier, PackageVersion};
+
+    use super::{LocalCheckout, git};
+    use crate::repository::{ManifestRepository, Submission, VersionAddition};
+
+    fn write_manifest(root: &Utf8Path, path: &str) {
+        let path = root.join(path);
+        fs::create_dir_all(path.parent().unwrap()).unwrap();
+        fs::write(path, "PackageIdentifier: Package.Identifier\n").unwrap();
+    }
+
+    #[tokio::test]
+    async fn versions_from_working_tree() {
+        let directory = tempfile::tempdir().unwrap();
+        let root = Utf8PathBuf::from_path_buf(directory.path().to_path_buf()).unwrap();
+        write_manifest(&root, "manifests/p/Package/Identifier/1.0.0/manifest.yaml");
+        write_manifest(&root, "manifests/p/Package/Identifier/1.2.0/manifest.yaml");
+        write_manifest(
+            &root,
+            "manifests/p/Package/Identifier/Beta/1.3.0/manifest.yaml",
+        );
+        fs::create_dir_all(root.join("manifests/p/Package/Identifier/2.0.0")).unwrap();
+
+        let checkout = LocalCheckout { root, push: None };
+
+        assert_eq!(
+            checkout
+                .get_versions(&"Package.Identifier".parse::<PackageIdentifier>().unwrap())
+                .await
+                .unwrap(),
+            ["1.0.0", "1.2.0"]
+                .into_iter()
+                .map(|version| version.parse::<PackageVersion>().unwrap())
+                .collect::<BTreeSet<_>>()
+        );
+    }
+
+    #[tokio::test]
+    async fn commits_onto_new_branch() {
+        let directory = tempfile::tempdir().unwrap();
+        let root = Utf8PathBuf::from_path_buf(directory.path().to_path_buf()).unwrap();
+        write_manifest(&root, "manifests/p/Package/Identifier/1.0.0/manifest.yaml");
+        for args in [
+            &["init", "--quiet"][..],
+            &["config", "user.name", "komac"],
+            &["config", "user.email", "komac@example.com"],
+            &["add", "."],
+            &["commit", "--quiet", "--message", "Initial commit"],
+        ] {
+            git(&root, args, None, None).await.unwrap();
+        }
+
+        let checkout = LocalCheckout::open(&root).await.unwrap();
+        let identifier = "Package.Identifier".parse::<PackageIdentifier>().unwrap();
+        let version = "1.1.0".parse::<PackageVersion>().unwrap();
+        let replace_version = "1.0.0".parse::<PackageVersion>().unwrap();
+
+        let Submission::Commit(commit) = checkout
+            .add_version(
+                VersionAddition::builder()
+                    .identifier(&identifier)
+                    .version(&version)
+                    .changes(vec![(
+                        String::from("manifests/p/Package/Identifier/1.1.0/manifest.yaml"),
+                        String::from("PackageVersion: 1.1.0\n"),
+                    )])
+                    .replace_version(&replace_version)
+                    .build(),
+            )
+            .await
+            .unwrap()
+        else {
+            panic!("a local checkout without a remote should not open a pull request");
+        };
+
+        let files = git(
+            &root,
+            ["ls-tree", "-r", "--name-only", commit.branch.as_str()],
+            None,
+            None,
+        )
+        .await
+        .unwrap();
+        assert_eq!(files, "manifests/p/Package/Identifier/1.1.0/manifest.yaml");
+
+        // The working tree and current branch are left untouched
+        assert!(
+            root.join("manifests/p/Package/Identifier/1.0.0/manifest.yaml")
+                .exists()
+        );
+        assert_eq!(
+            git(&root, ["status", "--porcelain"], None, None)
+                .await
+                .unwrap(),
+            ""
+        );
+    }
+}
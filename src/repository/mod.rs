@@ -0,0 +1,182 @@
+mod github;
+mod local;
+
+use std::{collections::BTreeSet, num::NonZeroU32};
+
+use bon::Builder;
+pub use local::{CheckoutArgs, LocalCheckout, LocalCommit};
+use url::Url;
+use winget_types::{
+    ManifestType, PackageIdentifier, PackageVersion,
+    installer::InstallerManifest,
+    locale::{DefaultLocaleManifest, LocaleManifest},
+    url::DecodedUrl,
+    version::VersionManifest,
+};
+
+use crate::{
+    github::{
+        GitHubError,
+        client::GitHubFile,
+        graphql::create_pull_request::PullRequest,
+        utils::{ManifestSnapshot, PackagePath, is_manifest_file},
+    },
+    manifests::Manifests,
+};
+
+/// A copy of the upstream repository that manifests can be read from and new or removed versions
+/// can be submitted to.
+///
+/// This is implemented by the GitHub API client, which reads from and submits pull requests to the
+/// upstream repository directly, and by [`LocalCheckout`], which works on a local clone of it.
+pub trait ManifestRepository {
+    /// Returns every version of a package.
+    async fn get_versions(
+        &self,
+        identifier: &PackageIdentifier,
+    ) -> Result<BTreeSet<PackageVersion>, GitHubError>;
+
+    /// Returns all the manifests of a package version.
+    async fn get_manifests(
+        &self,
+        identifier: &PackageIdentifier,
+        version: &PackageVersion,
+    ) -> Result<Manifests, GitHubError>;
+
+    /// Returns the files in the directory of a package version as they are, without parsing them.
+    async fn get_manifest_files(
+        &self,
+        identifier: &PackageIdentifier,
+        version: &PackageVersion,
+    ) -> Result<Vec<GitHubFile>, GitHubError>;
+
+    /// Commits a new version on its own branch and submits it.
+    async fn add_version(&self, addition: VersionAddition<'_>) -> Result<Submission, GitHubError>;
+
+    /// Commits the removal of a version on its own branch and submits it.
+    async fn remove_version(&self, removal: VersionRemoval<'_>) -> Result<Submission, GitHubError>;
+
+    /// Commits changes to the manifests of existing versions of a package on one branch and
+    /// submits them together.
+    async fn edit_versions(&self, edit: VersionsEdit<'_>) -> Result<Submission, GitHubError>;
+}
+
+#[derive(Builder)]
+pub struct VersionAddition<'a> {
+    pub identifier: &'a PackageIdentifier,
+    pub version: &'a PackageVersion,
+    pub versions: Option<&'a BTreeSet<PackageVersion>>,
+    pub changes: Vec<(String, String)>,
+    pub replace_version: Option<&'a PackageVersion>,
+    #[builder(default)]
+    pub issue_resolves: &'a [NonZeroU32],
+    pub created_with: Option<&'a str>,
+    pub created_with_url: Option<&'a DecodedUrl>,
+    /// The manifests of the version that this one was updated from, used to describe what changed.
+    pub previous: Option<&'a ManifestSnapshot>,
+}
+
+#[derive(Builder)]
+pub struct VersionRemoval<'a> {
+    pub identifier: &'a PackageIdentifier,
+    pub version: &'a PackageVersion,
+    pub reason: &'a str,
+    #[builder(default)]
+    pub issue_resolves: &'a [NonZeroU32],
+}
+
+#[derive(Builder)]
+pub struct VersionsEdit<'a> {
+    pub identifier: &'a PackageIdentifier,
+    /// The edited versions in ascending order, of which there must be at least one.
+    pub versions: &'a [PackageVersion],
+    pub changes: Vec<(String, String)>,
+    /// What was edited, which is used as the heading of the pull request body.
+    pub summary: &'a str,
+    #[builder(default)]
+    pub issue_resolves: &'a [NonZeroU32],
+}
+
+/// The result of submitting a change to a [`ManifestRepository`].
+pub enum Submission {
+    /// A pull request that was opened to the upstream repository.
+    PullRequest(PullRequest),
+    /// A commit that was made on a new branch in a local checkout without being pushed.
+    Commit(LocalCommit),
+}
+
+impl Submission {
+    /// Returns the URL of the pull request, if one was opened.
+    pub const fn url(&self) -> Option<&Url> {
+        match self {
+            Self::PullRequest(pull_request) => Some(pull_request.url()),
+            Self::Commit(_) => None,
+        }
+    }
+
+    pub fn print_success(&self) {
+        match self {
+            Self::PullRequest(pull_request) => pull_request.print_success(),
+            Self::Commit(commit) => commit.print_success(),
+        }
+    }
+}
+
+/// Parses the manifests of a package version from the files in its directory.
+pub fn parse_manifests(
+    identifier: &PackageIdentifier,
+    path: &PackagePath,
+    files: &[GitHubFile],
+) -> Result<Manifests, GitHubError> {
+    let version_manifest = files
+        .iter()
+        .find(|file| is_manifest_file::<VersionManifest>(&file.name, identifier, None))
+        .map(|file| serde_yaml::from_str::<VersionManifest>(&file.text))
+        .ok_or_else(|| GitHubError::ManifestNotFound {
+            r#type: ManifestType::Version,
+            path: path.clone(),
+        })??;
+
+    let locale_manifests = files
+        .iter()
+        .filter(|file| {
+            is_manifest_file::<LocaleManifest>(
+                &file.name,
+                identifier,
+                Some(&version_manifest.default_locale),
+            )
+        })
+        .map(|file| serde_yaml::from_str::<LocaleManifest>(&file.text))
+        .collect::<serde_yaml::Result<_>>()?;
+
+    let default_locale_manifest = files
+        .iter()
+        .find(|file| {
+            is_manifest_file::<DefaultLocaleManifest>(
+                &file.name,
+                identifier,
+                Some(&version_manifest.default_locale),
+            )
+        })
+        .map(|file| serde_yaml::from_str::<DefaultLocaleManifest>(&file.text))
+        .ok_or_else(|| GitHubError::ManifestNotFound {
+            r#type: ManifestType::DefaultLocale,
+            path: path.clone(),
+        })??;
+
+    let installer_manifest = files
+        .iter()
+        .find(|file| is_manifest_file::<InstallerManifest>(&file.name, identifier, None))
+        .map(|file| serde_yaml::from_str::<InstallerManifest>(&file.text))
+        .ok_or_else(|| GitHubError::ManifestNotFound {
+            r#type: ManifestType::Installer,
+            path: path.clone(),
+        })??;
+
+    Ok(Manifests {
+        installer: installer_manifest,
+        default_locale: default_locale_manifest,
+        locales: locale_manifests,
+        version: version_manifest,
+    })
+}
@@ -1,5 +1,14 @@
-use std::sync::atomic::{AtomicBool, Ordering};
+pub mod app;
 
+use std::{
+    env,
+    sync::{
+        Arc,
+        atomic::{AtomicBool, Ordering},
+    },
+};
+
+use app::{AppAuthentication, AppInstallation};
 use bon::bon;
 use color_eyre::eyre::Result;
 use inquire::{InquireError, Password, error::InquireResult, validator::Validation};
@@ -11,10 +20,16 @@
 use secrecy::{ExposeSecret, SecretString};
 use thiserror::Error;
 use tokio::runtime::Handle;
+use tracing::warn;
 
-use crate::{commands::utils::environment::CI, prompts::handle_inquire_error};
+use crate::{
+    commands::utils::environment::CI,
+    fixtures::{Dispatch, Fixtures},
+    github::{host::GitHubHost, upstream::Upstream},
+    prompts::handle_inquire_error,
+};
 
-const GITHUB_API_ENDPOINT: &str = "https://api.github.com/octocat";
+const GITHUB_TOKEN: &str = "GITHUB_TOKEN";
 
 static DEFAULT_STORE_SET: AtomicBool = AtomicBool::new(false);
 
@@ -28,6 +43,24 @@ pub enum TokenError {
     InvalidToken,
     #[error("Failed to connect to GitHub. Please check your internet connection.")]
     FailedToConnect,
+    #[error(
+        "A GitHub App ID was provided without a private key. Provide one with the `KOMAC_GITHUB_APP_PRIVATE_KEY` environment variable."
+    )]
+    MissingPrivateKey,
+    #[error("The GitHub App private key is not a PEM encoded RSA private key.")]
+    InvalidPrivateKey,
+    #[error("The GitHub App private key was rejected: {0}")]
+    KeyRejected(ring::error::KeyRejected),
+    #[error("Failed to sign a JSON Web Token with the GitHub App private key.")]
+    SigningFailed,
+    #[error("`{0}` is not a valid GitHub App installation ID.")]
+    InvalidInstallationId(String),
+    #[error(
+        "The GitHub App has {0} installations. Choose one with the `KOMAC_GITHUB_APP_INSTALLATION_ID` environment variable."
+    )]
+    AmbiguousInstallation(usize),
+    #[error(transparent)]
+    Io(#[from] std::io::Error),
     #[error(transparent)]
     Keyring(#[from] keyring_core::Error),
     #[error(transparent)]
@@ -38,6 +71,7 @@ pub enum TokenError {
 
 pub struct TokenManager {
     token: SecretString,
+    app: Option<AppAuthentication>,
 }
 
 #[bon]
@@ -48,6 +82,41 @@ pub async fn handle(token: Option<SecretString>) -> Result<Self, TokenError> {
         // - Otherwise try keyring:
         //     * In CI: if no token or if stored token is invalid -> error (never prompt).
         //     * Interactive: if no stored token or stored token is invalid -> prompt and store.
+        // - When replaying recorded fixtures, no requests reach GitHub so any token will do.
+        // - If GitHub App credentials are set, they take precedence over a stored token and over
+        //   `GITHUB_TOKEN`, which is often exported by CI runners, but not over `--token`.
+
+        if Fixtures::is_replaying() {
+            return Ok(Self {
+                token: token.unwrap_or_else(|| SecretString::from("replayed-fixture-token")),
+                app: None,
+            });
+        }
+
+        let token_from_env = token.as_ref().is_some_and(|token| {
+            env::var(GITHUB_TOKEN).is_ok_and(|env_token| env_token == token.expose_secret())
+        });
+
+        if AppInstallation::is_configured() {
+            if token_from_env {
+                warn!("Authenticating as the GitHub App instead of with `{GITHUB_TOKEN}`");
+            } else if token.is_some() {
+                warn!("Authenticating with `--token` instead of as the GitHub App");
+            }
+        }
+
+        if (token.is_none() || token_from_env)
+            && let Some(installation) = AppInstallation::from_env().await?
+        {
+            let installation_token = installation.create_token().await?;
+            return Ok(Self {
+                app: Some(AppAuthentication {
+                    installation: Arc::new(installation),
+                    refresh_at: installation_token.refresh_at(),
+                }),
+                token: installation_token.token,
+            });
+        }
 
         let client = Client::builder()
             .default_headers(default_headers(None))
@@ -76,7 +145,7 @@ pub async fn handle(token: Option<SecretString>) -> Result<Self, TokenError> {
 
         if let Some(token) = token {
             match Self::validate(&client, token.expose_secret()).await {
-                Ok(()) => return Ok(Self { token }),
+                Ok(()) => return Ok(Self { token, app: None }),
                 Err(TokenError::InvalidToken) if token_passed || *CI => {
                     return Err(TokenError::InvalidToken);
                 }
@@ -97,6 +166,7 @@ pub async fn handle(token: Option<SecretString>) -> Result<Self, TokenError> {
 
         Ok(Self {
             token: validated_token,
+            app: None,
         })
     }
 
@@ -126,9 +196,9 @@ pub fn prompt(
 
     pub async fn validate(client: &Client, token: &str) -> Result<(), TokenError> {
         match client
-            .get(GITHUB_API_ENDPOINT)
+            .get(format!("{}/octocat", Upstream::get().host().rest_api_url()))
             .bearer_auth(token)
-            .send()
+            .dispatch()
             .await
         {
             Ok(response) => match response.status() {
@@ -145,8 +215,18 @@ pub async fn validate(client: &Client, token: &str) -> Result<(), TokenError> {
         }
     }
 
-    /// Returns komac's named entry in a credential store.
+    /// Returns komac's named entry in a credential store for the upstream repository's GitHub
+    /// host.
+    #[inline]
     pub fn credential() -> keyring_core::Result<Entry> {
+        Self::credential_for(Upstream::get().host())
+    }
+
+    /// Returns komac's named entry in a credential store for a GitHub host.
+    ///
+    /// Tokens for github.com keep the original entry name so that existing tokens continue to work,
+    /// while tokens for a GitHub Enterprise Server are stored under an entry named after its host.
+    pub fn credential_for(host: &GitHubHost) -> keyring_core::Result<Entry> {
         const SERVICE: &str = "komac";
         const USERNAME: &str = "github-access-token";
 
@@ -162,7 +242,20 @@ pub fn credential() -> keyring_core::Result<Entry> {
             DEFAULT_STORE_SET.store(true, Ordering::Relaxed);
         }
 
-        Entry::new(SERVICE, USERNAME)
+        if host.is_github_com() {
+            Entry::new(SERVICE, USERNAME)
+        } else {
+            Entry::new(SERVICE, &format!("{USERNAME}@{host}"))
+        }
+    }
+
+    /// Returns the token stored for a GitHub host, if there is one, without validating it or
+    /// prompting for one.
+    pub fn stored_token(host: &GitHubHost) -> Option<SecretString> {
+        Self::credential_for(host)
+            .and_then(|credential| credential.get_password())
+            .ok()
+            .map(|token| SecretString::new(token.into_boxed_str()))
     }
 
     pub fn unset_default_store() {
@@ -171,6 +264,12 @@ pub fn unset_default_store() {
         }
     }
 
+    /// Returns the GitHub App installation that is authenticated as, if any.
+    #[inline]
+    pub const fn app(&self) -> Option<&AppAuthentication> {
+        self.app.as_ref()
+    }
+
     #[inline]
     pub fn into_token(self) -> SecretString {
         self.token
@@ -6,6 +6,7 @@
 pub use ascii_ext::AsciiExt;
 use html2text::render::{TaggedLine, TextDecorator};
 pub use name::Name;
+use pulldown_cmark::{Options, Parser};
 use regex::Regex;
 use winget_types::{
     Manifest, ManifestVersion, PackageVersion,
@@ -165,6 +166,27 @@ fn from_html(html: &Html) -> Option<Self> {
     }
 }
 
+pub trait FromMarkdown {
+    fn from_markdown(markdown: &str) -> Option<Self>
+    where
+        Self: Sized;
+}
+
+impl FromMarkdown for ReleaseNotes {
+    fn from_markdown(markdown: &str) -> Option<Self> {
+        // Markdown is rendered to HTML so that it's formatted in the same way as GitHub releases
+        let mut html = String::new();
+        pulldown_cmark::html::push_html(
+            &mut html,
+            Parser::new_ext(
+                markdown,
+                Options::ENABLE_STRIKETHROUGH | Options::ENABLE_TABLES | Options::ENABLE_TASKLISTS,
+            ),
+        );
+        Self::from_html(&Html::new(html))
+    }
+}
+
 pub trait LocaleExt {
     fn update(
         &mut self,
@@ -207,7 +229,7 @@ fn update(
         if self.publisher_url.is_none() {
             self.publisher_url = github_values
                 .as_mut()
-                .map(|values| mem::take(&mut values.publisher_url));
+                .and_then(|values| values.publisher_url.take());
         }
         if self.publisher_support_url.is_none() {
             self.publisher_support_url = github_values
@@ -0,0 +1,198 @@
+//! Runs commands end to end against the HTTP exchanges in `tests/fixtures`, which are replayed
+//! instead of using the network, so no GitHub token is needed.
+//!
+//! Nothing can be answered interactively here, so every value that a command would otherwise prompt
+//! for is passed as an argument.
+
+use std::{
+    env, fs,
+    path::{Path, PathBuf},
+    process::{Command, Output},
+};
+
+const PACKAGE_PATH: &str = "manifests/c/Contoso/Widget";
+
+fn fixture(name: &str) -> PathBuf {
+    Path::new(env!("CARGO_MANIFEST_DIR"))
+        .join("tests")
+        .join("fixtures")
+        .join(name)
+}
+
+/// Runs Komac with the exchanges of a fixture, asserting that it succeeded and that every request
+/// it made had a recorded response.
+fn komac(fixture_name: &str, args: &[&str]) -> Output {
+    let output = Command::new(env!("CARGO_BIN_EXE_komac"))
+        .args(args)
+        .env("KOMAC_REPLAY_FIXTURES", fixture(fixture_name))
+        .env_remove("KOMAC_RECORD_FIXTURES")
+        .env_remove("GITHUB_TOKEN")
+        .env_remove("CI")
+        .env("NO_COLOR", "1")
+        .output()
+        .unwrap();
+
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(output.status.success(), "komac failed:\n{stderr}");
+    assert!(
+        !stderr.contains("No recorded fixture"),
+        "komac made a request that wasn't recorded:\n{stderr}"
+    );
+
+    output
+}
+
+/// A directory for a command to write manifests to, which is removed when it is dropped.
+struct OutputDirectory(PathBuf);
+
+impl OutputDirectory {
+    fn new(name: &str) -> Self {
+        let path = env::temp_dir().join(format!("komac-replay-{name}-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&path);
+        Self(path)
+    }
+
+    fn as_str(&self) -> &str {
+        self.0.to_str().unwrap()
+    }
+
+    /// Asserts that the manifests written for a version match the fixture's expected manifests.
+    fn assert_manifests(&self, fixture_name: &str, version: &str) {
+        let expected_directory = fixture(fixture_name).join("expected");
+        let written_directory = self.0.join(PACKAGE_PATH).join(version);
+
+        let mut written = fs::read_dir(&written_directory)
+            .unwrap()
+            .map(|entry| entry.unwrap().file_name())
+            .collect::<Vec<_>>();
+        written.sort();
+        let mut expected = fs::read_dir(&expected_directory)
+            .unwrap()
+            .map(|entry| entry.unwrap().file_name())
+            .collect::<Vec<_>>();
+        expected.sort();
+        assert_eq!(written, expected);
+
+        for file_name in expected {
+            let expected = fs::read_to_string(expected_directory.join(&file_name))
+                .unwrap()
+                .replace("{version}", env!("CARGO_PKG_VERSION"));
+            let written = fs::read_to_string(written_directory.join(&file_name)).unwrap();
+            assert_eq!(
+                written.replace("\r\n", "\n"),
+                expected.replace("\r\n", "\n"),
+                "{}",
+                file_name.display()
+            );
+        }
+    }
+}
+
+impl Drop for OutputDirectory {
+    fn drop(&mut self) {
+        let _ = fs::remove_dir_all(&self.0);
+    }
+}
+
+#[test]
+fn update() {
+    let output = OutputDirectory::new("update");
+
+    komac(
+        "update",
+        &[
+            "update",
+            "Contoso.Widget",
+            "--version",
+            "1.1.0",
+            "--urls",
+            "https://example.com/downloads/Widget-1.1.0-x64.msix",
+            "--dry-run",
+            "--output",
+            output.as_str(),
+        ],
+    );
+
+    output.assert_manifests("update", "1.1.0");
+}
+
+#[test]
+fn new() {
+    let output = OutputDirectory::new("new");
+
+    komac(
+        "new",
+        &[
+            "new",
+            "Contoso.Widget",
+            "--version",
+            "1.0.0",
+            "--urls",
+            "https://example.com/downloads/Widget-1.0.0-x64.msix",
+            "--install-modes",
+            "interactive",
+            "silent",
+            "--success-codes",
+            "--upgrade-behavior",
+            "install",
+            "--commands",
+            "--protocols",
+            "--package-locale",
+            "en-US",
+            "--publisher",
+            "Contoso",
+            "--publisher-url",
+            "https://example.com",
+            "--publisher-support-url",
+            "https://example.com/support",
+            "--author",
+            "Contoso",
+            "--package-name",
+            "Widget",
+            "--package-url",
+            "https://example.com/widget",
+            "--license",
+            "MIT",
+            "--license-url",
+            "https://example.com/widget/license",
+            "--copyright",
+            "Copyright (c) Contoso",
+            "--copyright-url",
+            "https://example.com/widget/license",
+            "--short-description",
+            "A widget for opening .widget files",
+            "--description",
+            "Widget opens .widget files.",
+            "--moniker",
+            "widget",
+            "--tags",
+            "widget",
+            "files",
+            "--release-notes-url",
+            "https://example.com/widget/releases/1.0.0",
+            "--dry-run",
+            "--output",
+            output.as_str(),
+        ],
+    );
+
+    output.assert_manifests("new", "1.0.0");
+}
+
+#[test]
+fn remove_dead_versions() {
+    let output = komac(
+        "remove-dead-versions",
+        &["remove-dead-versions", "Contoso.Widget", "--auto"],
+    );
+
+    // Only 1.0.0 is removed, as the installer of 1.1.0 is still available
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert_eq!(
+        stdout
+            .matches("https://github.com/microsoft/winget-pkgs/pull/")
+            .collect::<Vec<_>>(),
+        ["https://github.com/microsoft/winget-pkgs/pull/"]
+    );
+    assert!(stdout.contains("https://github.com/microsoft/winget-pkgs/pull/301234"));
+}